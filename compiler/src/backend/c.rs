@@ -1,5 +1,5 @@
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, BTreeSet};
 
 use crate::backend::{
     ir::{IrSymbol, IrInstruction, IrVariable},
@@ -11,30 +11,208 @@ use crate::frontend::{
 };
 use crate::util::strings::{StringMap, StringIdx};
 
+fn sorted_by_name<'m, T>(map: &'m HashMap<StringIdx, T>, strings: &StringMap) -> Vec<(&'m StringIdx, &'m T)> {
+    let mut entries = map.iter().collect::<Vec<(&StringIdx, &T)>>();
+    entries.sort_by_key(|(name, _)| strings.get(**name));
+    entries
+}
+
 struct ConversionFunctions {
     declarations: String,
     bodies: String,
-    declared: HashSet<(TypeGroup, TypeGroup)>
+    declared: HashSet<String>
+}
+
+struct CSource {
+    types: String,
+    functions: String,
+    declarations: Vec<(NamespacePath, String)>,
+    constants: String,
+    shared: String,
+    modules: Vec<(NamespacePath, String)>
+}
+
+// marks generated headers, so that hand-written ones are never overwritten
+pub const GENERATED_HEADER_MARKER: &str = "/* Generated by gerac. Do not edit. */\n";
+
+impl CSource {
+    fn header(&self, guard: &str) -> String {
+        let mut declarations = String::new();
+        for (_, module_declarations) in &self.declarations {
+            declarations.push_str(module_declarations);
+        }
+        format!("{GENERATED_HEADER_MARKER}#ifndef {guard}\n#define {guard}\n\n{}{}{}\n#endif\n", self.types, self.functions, declarations)
+    }
+
+    fn into_single_file(self) -> String {
+        let mut output = self.types;
+        output.push_str(&self.functions);
+        for (_, module_declarations) in &self.declarations {
+            output.push_str(module_declarations);
+        }
+        output.push_str(&self.constants);
+        output.push_str(&self.shared);
+        for (_, module) in &self.modules {
+            output.push_str("\n");
+            output.push_str(module);
+        }
+        return output;
+    }
 }
 
 pub fn generate_c(
     symbols: Vec<IrSymbol>,
-    mut global_type_scope: TypeScope,
+    global_type_scope: TypeScope,
     main_procedure_path: NamespacePath,
     strings: &mut StringMap
 ) -> String {
+    generate_c_with_header(symbols, global_type_scope, main_procedure_path, strings).0
+}
+
+pub fn generate_c_with_header(
+    symbols: Vec<IrSymbol>,
+    global_type_scope: TypeScope,
+    main_procedure_path: NamespacePath,
+    strings: &mut StringMap
+) -> (String, String) {
+    let (source, _) = generate_c_source(symbols, global_type_scope, &[], strings);
+    let header = source.header("GERA_PROGRAM_H");
+    let mut output = source.into_single_file();
+    output.push_str("\n");
+    emit_main_function(&main_procedure_path, strings, &mut output);
+    return (output, header);
+}
+
+pub fn generate_c_split(
+    symbols: Vec<IrSymbol>,
+    global_type_scope: TypeScope,
+    main_procedure_path: NamespacePath,
+    strings: &mut StringMap
+) -> Vec<(String, String)> {
+    let mut referenced_modules: HashMap<NamespacePath, HashSet<NamespacePath>> = HashMap::new();
+    for symbol in &symbols {
+        if let IrSymbol::Procedure { path, body, .. } = symbol {
+            collect_referenced_modules(body, referenced_modules.entry(module_of(path)).or_default());
+        }
+    }
+    let (mut source, _) = generate_c_source(symbols, global_type_scope, &[], strings);
+    emit_main_function(&main_procedure_path, strings, &mut source.shared);
+    let source = source;
+    fn emit_header(guard: &str, includes: &str, content: &str) -> String {
+        format!("#ifndef {guard}\n#define {guard}\n\n{includes}{content}\n#endif\n")
+    }
+    let module_file_name = |module_path: &NamespacePath, extension: &str| {
+        let mut file_name = String::new();
+        emit_path(module_path, strings, &mut file_name);
+        file_name.push_str(extension);
+        file_name
+    };
+    let module_includes = |module_paths: &mut dyn Iterator<Item = &NamespacePath>| {
+        let mut headers = module_paths
+            .filter(|module_path| source.declarations.iter().any(|(m, _)| *m == **module_path))
+            .map(|module_path| module_file_name(module_path, ".h"))
+            .collect::<Vec<String>>();
+        headers.sort();
+        headers.dedup();
+        headers.iter()
+            .map(|header| format!("#include \"{header}\"\n"))
+            .collect::<String>()
+    };
+    let shared_includes = "#include \"gera-shared.h\"\n#include \"gera-constants.h\"\n";
+    let mut files = vec![
+        (String::from("gera-types.h"), emit_header("GERA_TYPES_H", "", &source.types)),
+        (String::from("gera-shared.h"), emit_header("GERA_SHARED_H", "#include \"gera-types.h\"\n", &source.functions)),
+        (String::from("gera-constants.h"), emit_header("GERA_CONSTANTS_H", "#include \"gera-types.h\"\n", &source.constants))
+    ];
+    for (module_path, module_declarations) in &source.declarations {
+        let file_name = module_file_name(module_path, ".h");
+        let guard = format!("GERA_MODULE_{}_H", file_name[..file_name.len() - 2].to_uppercase());
+        files.push((file_name, emit_header(&guard, "#include \"gera-types.h\"\n", module_declarations)));
+    }
+    let main_includes = module_includes(&mut source.declarations.iter().map(|(module_path, _)| module_path));
+    files.push((String::from("gera-shared.c"), format!("{shared_includes}{main_includes}{}", source.shared)));
+    for (module_path, module) in &source.modules {
+        let empty = HashSet::new();
+        let includes = module_includes(&mut [module_path].into_iter()
+            .chain(referenced_modules.get(module_path).unwrap_or(&empty)));
+        files.push((module_file_name(module_path, ".c"), format!("{shared_includes}{includes}{module}")));
+    }
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+    return files;
+}
+
+fn module_of(path: &NamespacePath) -> NamespacePath {
+    NamespacePath::new(path.get_segments()[..path.get_segments().len() - 1].to_vec())
+}
+
+fn collect_referenced_modules(instructions: &[IrInstruction], referenced: &mut HashSet<NamespacePath>) {
+    for instruction in instructions {
+        match instruction {
+            IrInstruction::Call { path, .. } |
+            IrInstruction::LoadGlobalVariable { path, .. } => { referenced.insert(module_of(path)); }
+            IrInstruction::LoadClosure { body, .. } => collect_referenced_modules(body, referenced),
+            IrInstruction::BranchOnValue { branches, else_branch, .. } => {
+                for (_, branch_body) in branches { collect_referenced_modules(branch_body, referenced); }
+                collect_referenced_modules(else_branch, referenced);
+            }
+            IrInstruction::BranchOnVariant { branches, else_branch, .. } => {
+                for (_, _, branch_body) in branches { collect_referenced_modules(branch_body, referenced); }
+                collect_referenced_modules(else_branch, referenced);
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn generate_c_library(
+    symbols: Vec<IrSymbol>,
+    global_type_scope: TypeScope,
+    exported: Vec<(NamespacePath, usize, Vec<StringIdx>)>,
+    strings: &mut StringMap
+) -> (String, String) {
+    let (source, exported_decls) = generate_c_source(symbols, global_type_scope, &exported, strings);
+    let mut output = source.into_single_file();
+    output.push_str("\n");
+    output.push_str("void gera_init(void) {\n");
+    output.push_str("    gera_init_constants();\n");
+    output.push_str("}\n");
+    let mut header = String::from(GENERATED_HEADER_MARKER);
+    header.push_str("#ifndef GERA_LIBRARY_H\n");
+    header.push_str("#define GERA_LIBRARY_H\n\n");
+    header.push_str("#include <geracoredeps.h>\n");
+    header.push_str("#include <gera.h>\n\n");
+    header.push_str("// Strings and arrays passed to exported procedures are only borrowed for the\n");
+    header.push_str("// duration of the call, while the ones they return are owned by the caller\n");
+    header.push_str("// and need to be released once done. Arrays are created using the '_new'\n");
+    header.push_str("// procedure of their type, zero-initialized, and own the elements stored in them.\n");
+    header.push_str("GeraString gera_string_new(const char* data);\n");
+    header.push_str("void gera_string_retain(GeraString string);\n");
+    header.push_str("void gera_string_release(GeraString string);\n");
+    header.push_str("void gera_array_retain(GeraArray array);\n");
+    header.push_str("void gera_array_release(GeraArray array);\n\n");
+    header.push_str(&exported_decls);
+    header.push_str("\nvoid gera_init(void);\n\n");
+    header.push_str("#endif\n");
+    return (output, header);
+}
+
+fn generate_c_source(
+    symbols: Vec<IrSymbol>,
+    mut global_type_scope: TypeScope,
+    exported: &[(NamespacePath, usize, Vec<StringIdx>)],
+    strings: &mut StringMap
+) -> (CSource, String) {
     let mut final_type_scope = TypeScope::new();
-    let mut output = String::new();
     let mut external = HashMap::new();
-    emit_core_library(&mut output);
-    output.push_str("\n");
     let mut constants = ConstantPool::new();
     let mut static_var_vals = HashMap::new();
-    let mut constant_dependants = String::new();
-    constant_dependants.push_str("\n");
+    let mut symbol_declarations = Vec::new();
+    let mut static_var_declarations = String::new();
+    let mut static_var_definitions = String::new();
     emit_symbol_declarations(
         &symbols, &global_type_scope, &mut final_type_scope, &mut constants, &mut static_var_vals,
-        strings, &mut external, &mut constant_dependants
+        strings, &mut external, &mut symbol_declarations, &mut static_var_declarations,
+        &mut static_var_definitions
     );
     let mut conversions = ConversionFunctions {
         declarations: String::new(),
@@ -42,42 +220,73 @@ pub fn generate_c(
         declared: HashSet::new()
     };
     let mut closure_bodies = Vec::new();
-    let mut procedure_impls = String::new();
+    let mut modules = Vec::new();
     emit_procedure_impls(
         &symbols, &mut global_type_scope, &mut final_type_scope, &mut constants, strings,
-        &mut closure_bodies, &mut conversions, &mut external, &mut procedure_impls
+        &mut closure_bodies, &mut conversions, &mut external, &mut modules
+    );
+    let mut exported_types = String::new();
+    let mut exported_prototypes = String::new();
+    let mut exported_impls = String::new();
+    emit_exported_procedures(
+        &symbols, exported, &mut final_type_scope, strings, &mut conversions,
+        &mut exported_types, &mut exported_prototypes, &mut exported_impls
     );
-    constant_dependants.push_str("\n");
-    constant_dependants.push_str(&conversions.declarations);
-    constant_dependants.push_str("\n");
-    constant_dependants.push_str(&conversions.bodies);
-    constant_dependants.push_str("\n");
-    for closure_body in &closure_bodies {
-        constant_dependants.push_str(&closure_body);
-    }
-    constant_dependants.push_str("\n");
-    constant_dependants.push_str(&procedure_impls);
     let mut constant_decls = String::new();
-    emit_constant_declarations(&constants, &mut final_type_scope, &mut constant_decls);
+    emit_constant_declarations(&constants, &mut final_type_scope, true, &mut constant_decls);
+    let mut constant_defs = String::new();
+    emit_constant_declarations(&constants, &mut final_type_scope, false, &mut constant_defs);
     let mut constant_inits = String::new();
     emit_constant_initializers(
         &constants, &static_var_vals, &mut final_type_scope, strings, &mut constant_inits
     );
-    emit_type_declarations(&final_type_scope, &mut output);
-    output.push_str("\n");
-    emit_type_members(&final_type_scope, strings, &mut output);
-    output.push_str("\n");
-    emit_free_handler_functions(&final_type_scope, strings, &mut output);
-    output.push_str("\n");
-    emit_comparison_functions(&final_type_scope, strings, &mut output);
-    output.push_str("\n");
-    output.push_str(&constant_decls);
-    output.push_str(&constant_dependants);
-    output.push_str("\n");
-    output.push_str(&constant_inits);
+    let mut types = String::new();
+    emit_core_declarations(&mut types);
+    types.push_str("\n");
+    emit_variant_tag_definitions(&final_type_scope, strings, &mut types);
+    types.push_str("\n");
+    emit_type_declarations(&final_type_scope, &mut types);
+    types.push_str("\n");
+    emit_type_members(&final_type_scope, strings, &mut types);
+    types.push_str("\n");
+    types.push_str(&exported_types);
+    let mut functions = String::new();
+    let mut shared = String::new();
+    emit_core_library(&mut shared);
+    shared.push_str("\n");
+    emit_free_handler_functions(&final_type_scope, strings, &mut functions, &mut shared);
+    functions.push_str("\n");
+    shared.push_str("\n");
+    emit_comparison_functions(&final_type_scope, strings, &mut functions, &mut shared);
+    functions.push_str("\n");
+    shared.push_str("\n");
+    emit_closure_erasure_functions(&final_type_scope, &mut functions, &mut shared);
+    functions.push_str("\n");
+    shared.push_str("\n");
+    emit_closure_call_shims(&final_type_scope, strings, &mut functions, &mut shared);
+    functions.push_str("\n");
+    shared.push_str("\n");
+    functions.push_str(&conversions.declarations);
+    let mut constants_header = String::new();
+    constants_header.push_str(&constant_decls);
+    constants_header.push_str(&static_var_declarations);
+    constants_header.push_str("void gera_init_constants(void);\n");
+    shared.push_str(&constant_defs);
+    shared.push_str(&static_var_definitions);
+    shared.push_str("\n");
+    shared.push_str(&conversions.bodies);
+    shared.push_str("\n");
+    shared.push_str(&constant_inits);
+    shared.push_str("\n");
+    shared.push_str(&exported_impls);
+    return (CSource {
+        types, functions, declarations: symbol_declarations, constants: constants_header, shared, modules
+    }, exported_types + &exported_prototypes);
+}
+
+fn emit_core_declarations(output: &mut String) {
+    output.push_str(include_str!("./core/core.h"));
     output.push_str("\n");
-    emit_main_function(&main_procedure_path, strings, &mut output);
-    return output;
 }
 
 fn emit_core_library(output: &mut String) {
@@ -105,6 +314,39 @@ fn emit_variants_name(index: usize, output: &mut String) {
     output.push_str(&index.to_string());
 } 
 
+fn variant_tag(variant_name: &str) -> u64 {
+    // FNV-1a, so that external code can match on tags without knowing the compiler's internals
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in variant_name.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn emit_variant_tag(variant_name: &str, output: &mut String) {
+    output.push_str(&format!("0x{:016X}ULL", variant_tag(variant_name)));
+}
+
+fn emit_variant_tag_definitions(final_type_scope: &TypeScope, strings: &StringMap, output: &mut String) {
+    let variant_names = final_type_scope.internal_variants().iter()
+        .flat_map(|(variant_types, _)| variant_types.keys())
+        .map(|variant_name| strings.get(*variant_name))
+        .collect::<BTreeSet<&str>>();
+    for variant_name in variant_names {
+        output.push_str("#define GERA_TAG_");
+        output.push_str(variant_name);
+        output.push_str(" ");
+        emit_variant_tag(variant_name, output);
+        output.push_str("\n");
+    }
+}
+
+fn emit_member_name(member_name: StringIdx, strings: &StringMap, output: &mut String) {
+    output.push_str("member_");
+    output.push_str(strings.get(member_name));
+}
+
 fn emit_closure_name(index: usize, output: &mut String) {
     output.push_str("GeraClosure");
     output.push_str(&index.to_string());
@@ -176,12 +418,12 @@ fn emit_type_members(final_type_scope: &TypeScope, strings: &StringMap, output:
         output.push_str("typedef struct ");
         emit_object_name(object_idx, output);
         output.push_str(" {\n    GeraAllocation* allocation;");
-        for (member_name, member_type) in &final_type_scope.internal_objects()[object_idx].0 {
+        for (member_name, member_type) in sorted_by_name(&final_type_scope.internal_objects()[object_idx].0, strings) {
             if let Type::Unit = final_type_scope.group_concrete(*member_type) { continue; }
             output.push_str("\n    ");
             emit_type(*member_type, final_type_scope, output);
-            output.push_str("* member");
-            output.push_str(&member_name.0.to_string());
+            output.push_str("* ");
+            emit_member_name(*member_name, strings, output);
             output.push_str(";");
         }
         output.push_str("\n} ");
@@ -212,7 +454,7 @@ fn emit_type_members(final_type_scope: &TypeScope, strings: &StringMap, output:
             output.push_str("typedef union ");
             emit_variants_name(variants_idx, output);
             output.push_str("Value {");
-            for (variant_name, variant_type) in &final_type_scope.internal_variants()[variants_idx].0 {
+            for (variant_name, variant_type) in sorted_by_name(&final_type_scope.internal_variants()[variants_idx].0, strings) {
                 if let Type::Unit = final_type_scope.group_concrete(*variant_type) { continue; }
                 output.push_str("\n    ");
                 emit_type(*variant_type, final_type_scope, output);
@@ -241,12 +483,12 @@ fn emit_type_members(final_type_scope: &TypeScope, strings: &StringMap, output:
         emit_object_alloc_name(object_idx, output);
         output.push_str(" {");
         let mut had_member = false;
-        for (member_name, member_type) in &final_type_scope.internal_objects()[object_idx].0 {
+        for (member_name, member_type) in sorted_by_name(&final_type_scope.internal_objects()[object_idx].0, strings) {
             if let Type::Unit = final_type_scope.group_concrete(*member_type) { continue; }
             output.push_str("\n    ");
             emit_type(*member_type, final_type_scope, output);
-            output.push_str(" member");
-            output.push_str(&member_name.0.to_string());
+            output.push_str(" ");
+            emit_member_name(*member_name, strings, output);
             output.push_str(";");
             had_member = true;
         }
@@ -286,10 +528,10 @@ fn emit_rc_incr(
             switch.push_str("switch(");
             switch.push_str(variable);
             switch.push_str(".tag) {");
-            for (variant_name, variant_type) in &final_type_scope.variants(variant_idx).0 {
+            for (variant_name, variant_type) in sorted_by_name(&final_type_scope.variants(variant_idx).0, strings) {
                 if let Type::Unit = final_type_scope.group_concrete(*variant_type) { continue; }
                 switch.push_str("\n    case ");
-                switch.push_str(&variant_name.0.to_string());
+                emit_variant_tag(strings.get(*variant_name), &mut switch);
                 switch.push_str(":\n");
                 let mut var_decr = String::new();
                 emit_rc_incr(
@@ -340,10 +582,10 @@ fn emit_rc_decr(
             output.push_str("switch(");
             output.push_str(variable);
             output.push_str(".tag) {");
-            for (variant_name, variant_type) in &final_type_scope.variants(variant_idx).0 {
+            for (variant_name, variant_type) in sorted_by_name(&final_type_scope.variants(variant_idx).0, strings) {
                 if let Type::Unit = final_type_scope.group_concrete(*variant_type) { continue; }
                 output.push_str("\n    case ");
-                output.push_str(&variant_name.0.to_string());
+                emit_variant_tag(strings.get(*variant_name), output);
                 output.push_str(":\n");
                 let mut var_decr = String::new();
                 emit_rc_decr(
@@ -375,10 +617,15 @@ fn emit_object_free_handler_name(object_idx: usize, output: &mut String) {
     output.push_str(&object_idx.to_string());
 }
 
-fn emit_free_handler_functions(final_type_scope: &TypeScope, strings: &StringMap, output: &mut String) {
+fn emit_free_handler_functions(
+    final_type_scope: &TypeScope, strings: &StringMap, declarations: &mut String, output: &mut String
+) {
     for array_idx in 0..final_type_scope.internal_arrays().len() {
         let element_type = final_type_scope.internal_arrays()[array_idx];
         if let Type::Unit = final_type_scope.group_concrete(element_type) {} else {
+            declarations.push_str("void ");
+            emit_array_free_handler_name(array_idx, declarations);
+            declarations.push_str("(char* data, size_t size);\n");
             output.push_str("void ");
             emit_array_free_handler_name(array_idx, output);
             output.push_str("(char* data, size_t size) {");
@@ -399,6 +646,9 @@ fn emit_free_handler_functions(final_type_scope: &TypeScope, strings: &StringMap
         }
     }
     for object_idx in 0..final_type_scope.internal_objects().len() {
+        declarations.push_str("void ");
+        emit_object_free_handler_name(object_idx, declarations);
+        declarations.push_str("(char* data, size_t size);\n");
         output.push_str("void ");
         emit_object_free_handler_name(object_idx, output);
         output.push_str("(char* data, size_t size) {");
@@ -407,10 +657,10 @@ fn emit_free_handler_functions(final_type_scope: &TypeScope, strings: &StringMap
         output.push_str("* object = (");
         emit_object_alloc_name(object_idx, output);
         output.push_str("*) data;\n");
-        for (member_name, member_type) in &final_type_scope.internal_objects()[object_idx].0 {
+        for (member_name, member_type) in sorted_by_name(&final_type_scope.internal_objects()[object_idx].0, strings) {
             let mut member_rc_decr = String::new();
             emit_rc_decr(
-                &format!("object->member{}", member_name.0.to_string()),
+                &format!("object->member_{}", strings.get(*member_name)),
                 *member_type, final_type_scope, strings, &mut member_rc_decr
             );
             indent(&member_rc_decr, output);
@@ -495,33 +745,208 @@ fn emit_equality(
     }
 }
 
+fn emit_closure_call_shim_type_name(
+    t: TypeGroup, final_type_scope: &TypeScope, strings: &StringMap, output: &mut String
+) {
+    fn emit_members(
+        kind: &str, mut members: Vec<(StringIdx, TypeGroup)>,
+        final_type_scope: &TypeScope, strings: &StringMap, output: &mut String
+    ) {
+        members.sort_by_key(|(member_name, _)| strings.get(*member_name));
+        output.push_str(kind);
+        output.push_str("_");
+        for (member_name, member_type) in members {
+            let member_name = strings.get(member_name);
+            output.push_str(&member_name.len().to_string());
+            output.push_str(member_name);
+            output.push_str("_");
+            emit_closure_call_shim_type_name(member_type, final_type_scope, strings, output);
+            output.push_str("_");
+        }
+        output.push_str("end");
+    }
+    match final_type_scope.group_concrete(t) {
+        Type::Any | Type::Unit => output.push_str("unit"),
+        Type::Boolean => output.push_str("bool"),
+        Type::Integer => output.push_str("int"),
+        Type::Float => output.push_str("float"),
+        Type::String => output.push_str("str"),
+        Type::Array(_) => output.push_str("arr"),
+        Type::Object(o) => emit_members(
+            "obj", final_type_scope.object(o).0.iter().map(|(n, t)| (*n, *t)).collect(),
+            final_type_scope, strings, output
+        ),
+        Type::ConcreteObject(o) => emit_members(
+            "cobj", final_type_scope.concrete_object(o).to_vec(),
+            final_type_scope, strings, output
+        ),
+        Type::Variants(v) => emit_members(
+            "var", final_type_scope.variants(v).0.iter().map(|(n, t)| (*n, *t)).collect(),
+            final_type_scope, strings, output
+        ),
+        Type::Closure(_) => output.push_str("closure")
+    }
+}
+
+fn emit_closure_erase_name(index: usize, output: &mut String) {
+    output.push_str("gera___erase_closure");
+    output.push_str(&index.to_string());
+}
+
+fn emit_closure_restore_name(index: usize, output: &mut String) {
+    output.push_str("gera___restore_closure");
+    output.push_str(&index.to_string());
+}
+
+fn emit_closure_procedure_type(closure_idx: usize, final_type_scope: &TypeScope, output: &mut String) {
+    let (parameter_types, return_type, _) = &final_type_scope.internal_closures()[closure_idx];
+    emit_type(*return_type, final_type_scope, output);
+    output.push_str(" (*)(GeraAllocation*");
+    for parameter_type in parameter_types {
+        if let Type::Unit = final_type_scope.group_concrete(*parameter_type) { continue; }
+        output.push_str(", ");
+        emit_type(*parameter_type, final_type_scope, output);
+    }
+    output.push_str(")");
+}
+
+// external C code only ever sees closures as 'GeraClosure'
+fn emit_erased_type(t: TypeGroup, final_type_scope: &TypeScope, output: &mut String) {
+    if let Type::Closure(_) = final_type_scope.group_concrete(t) {
+        output.push_str("GeraClosure");
+    } else {
+        emit_type(t, final_type_scope, output);
+    }
+}
+
+fn emit_erased_value(value: &str, t: TypeGroup, final_type_scope: &TypeScope, output: &mut String) {
+    if let Type::Closure(closure_idx) = final_type_scope.group_concrete(t) {
+        emit_closure_erase_name(closure_idx.get_internal_id(), output);
+        output.push_str("(");
+        output.push_str(value);
+        output.push_str(")");
+    } else {
+        output.push_str(value);
+    }
+}
+
+fn emit_restored_value(value: &str, t: TypeGroup, final_type_scope: &TypeScope, output: &mut String) {
+    if let Type::Closure(closure_idx) = final_type_scope.group_concrete(t) {
+        emit_closure_restore_name(closure_idx.get_internal_id(), output);
+        output.push_str("(");
+        output.push_str(value);
+        output.push_str(")");
+    } else {
+        output.push_str(value);
+    }
+}
+
+fn emit_closure_erasure_functions(final_type_scope: &TypeScope, declarations: &mut String, output: &mut String) {
+    for closure_idx in 0..final_type_scope.internal_closures().len() {
+        let mut erase_signature = String::from("GeraClosure ");
+        emit_closure_erase_name(closure_idx, &mut erase_signature);
+        erase_signature.push_str("(");
+        emit_closure_name(closure_idx, &mut erase_signature);
+        erase_signature.push_str(" closure)");
+        declarations.push_str(&erase_signature);
+        declarations.push_str(";\n");
+        output.push_str(&erase_signature);
+        output.push_str(" {\n    return (GeraClosure) { .allocation = closure.allocation, .procedure = (void (*)(void)) closure.procedure };\n}\n");
+        let mut restore_signature = String::new();
+        emit_closure_name(closure_idx, &mut restore_signature);
+        restore_signature.push_str(" ");
+        emit_closure_restore_name(closure_idx, &mut restore_signature);
+        restore_signature.push_str("(GeraClosure closure)");
+        declarations.push_str(&restore_signature);
+        declarations.push_str(";\n");
+        output.push_str(&restore_signature);
+        output.push_str(" {\n    return (");
+        emit_closure_name(closure_idx, output);
+        output.push_str(") { .allocation = closure.allocation, .procedure = (");
+        emit_closure_procedure_type(closure_idx, final_type_scope, output);
+        output.push_str(") closure.procedure };\n}\n");
+    }
+}
+
+fn emit_closure_call_shims(
+    final_type_scope: &TypeScope, strings: &StringMap, declarations: &mut String, output: &mut String
+) {
+    let mut emitted = HashSet::new();
+    for closure_idx in 0..final_type_scope.internal_closures().len() {
+        let (parameter_types, return_type, _) = &final_type_scope.internal_closures()[closure_idx];
+        let parameter_types = parameter_types.iter()
+            .filter(|p| if let Type::Unit = final_type_scope.group_concrete(**p) { false } else { true })
+            .collect::<Vec<&TypeGroup>>();
+        let mut shim_name = String::from("gera_closure_call_");
+        for parameter_type in &parameter_types {
+            emit_closure_call_shim_type_name(**parameter_type, final_type_scope, strings, &mut shim_name);
+            shim_name.push_str("_");
+        }
+        shim_name.push_str("to_");
+        emit_closure_call_shim_type_name(*return_type, final_type_scope, strings, &mut shim_name);
+        if !emitted.insert(shim_name.clone()) { continue; }
+        let mut signature = String::new();
+        emit_erased_type(*return_type, final_type_scope, &mut signature);
+        signature.push_str(" ");
+        signature.push_str(&shim_name);
+        signature.push_str("(GeraClosure closure");
+        for p in 0..parameter_types.len() {
+            signature.push_str(", ");
+            emit_erased_type(*parameter_types[p], final_type_scope, &mut signature);
+            signature.push_str(" param");
+            signature.push_str(&p.to_string());
+        }
+        signature.push_str(")");
+        declarations.push_str(&signature);
+        declarations.push_str(";\n");
+        output.push_str(&signature);
+        output.push_str(" {\n    ");
+        let mut call = String::from("(");
+        emit_closure_restore_name(closure_idx, &mut call);
+        call.push_str("(closure).procedure)(closure.allocation");
+        for p in 0..parameter_types.len() {
+            call.push_str(", ");
+            emit_restored_value(&format!("param{}", p), *parameter_types[p], final_type_scope, &mut call);
+        }
+        call.push_str(")");
+        if let Type::Unit = final_type_scope.group_concrete(*return_type) {
+            output.push_str(&call);
+        } else {
+            output.push_str("return ");
+            emit_erased_value(&call, *return_type, final_type_scope, output);
+        }
+        output.push_str(";\n}\n");
+    }
+}
+
 fn emit_comparison_functions(
     final_type_scope: &TypeScope,
     strings: &StringMap,
+    declarations: &mut String,
     output: &mut String
 ) {
     for array_idx in 0..final_type_scope.internal_arrays().len() {
-        output.push_str("char ");
-        emit_array_comparison_name(array_idx, output);
-        output.push_str("(GeraArray a, GeraArray b);\n");
+        declarations.push_str("char ");
+        emit_array_comparison_name(array_idx, declarations);
+        declarations.push_str("(GeraArray a, GeraArray b);\n");
     }
     for object_idx in 0..final_type_scope.internal_objects().len() {
-        output.push_str("char ");
-        emit_object_comparison_name(object_idx, output);
-        output.push_str("(");
-        emit_object_name(object_idx, output);
-        output.push_str(" a, ");
-        emit_object_name(object_idx, output);
-        output.push_str(" b);\n");
+        declarations.push_str("char ");
+        emit_object_comparison_name(object_idx, declarations);
+        declarations.push_str("(");
+        emit_object_name(object_idx, declarations);
+        declarations.push_str(" a, ");
+        emit_object_name(object_idx, declarations);
+        declarations.push_str(" b);\n");
     }
     for variant_idx in 0..final_type_scope.internal_variants().len() {
-        output.push_str("char ");
-        emit_variant_comparison_name(variant_idx, output);
-        output.push_str("(");
-        emit_variants_name(variant_idx, output);
-        output.push_str(" a, ");
-        emit_variants_name(variant_idx, output);
-        output.push_str(" b);\n");
+        declarations.push_str("char ");
+        emit_variant_comparison_name(variant_idx, declarations);
+        declarations.push_str("(");
+        emit_variants_name(variant_idx, declarations);
+        declarations.push_str(" a, ");
+        emit_variants_name(variant_idx, declarations);
+        declarations.push_str(" b);\n");
     }
     for array_idx in 0..final_type_scope.internal_arrays().len() {
         let element_type = final_type_scope.internal_arrays()[array_idx];
@@ -555,11 +980,11 @@ fn emit_comparison_functions(
         output.push_str(" a, ");
         emit_object_name(object_idx, output);
         output.push_str(" b) {");
-        for (member_name, member_type) in &final_type_scope.internal_objects()[object_idx].0 {
+        for (member_name, member_type) in sorted_by_name(&final_type_scope.internal_objects()[object_idx].0, strings) {
             output.push_str("\n    if(!(");
             emit_equality(
-                &format!("(*a.member{})", member_name.0),
-                &format!("(*b.member{})", member_name.0),
+                &format!("(*a.member_{})", strings.get(*member_name)),
+                &format!("(*b.member_{})", strings.get(*member_name)),
                 *member_type, final_type_scope, output
             );
             output.push_str(")) { return 0; }");
@@ -576,10 +1001,10 @@ fn emit_comparison_functions(
         output.push_str(" b) {\n");
         output.push_str("    if(a.tag != b.tag) { return 0; }\n");
         output.push_str("    switch(a.tag) {\n");
-        for(variant_name, variant_type) in &final_type_scope.internal_variants()[variant_idx].0 {
+        for (variant_name, variant_type) in sorted_by_name(&final_type_scope.internal_variants()[variant_idx].0, strings) {
             if let Type::Unit = final_type_scope.group_concrete(*variant_type) { continue; }
             output.push_str("        case ");
-            output.push_str(&variant_name.0.to_string());
+            emit_variant_tag(strings.get(*variant_name), output);
             output.push_str(":\n");
             output.push_str("            if(!(");
             emit_equality(
@@ -596,6 +1021,28 @@ fn emit_comparison_functions(
     }
 }
 
+fn emit_external_type(
+    t: TypeGroup,
+    variants_name: &str,
+    final_type_scope: &TypeScope,
+    declared: &mut HashSet<String>,
+    typedefs: &mut String,
+    output: &mut String
+) {
+    if let Type::Variants(variants_idx) = final_type_scope.group_concrete(t) {
+        if declared.insert(variants_name.to_string()) {
+            typedefs.push_str("typedef ");
+            emit_variants_name(variants_idx.get_internal_id(), typedefs);
+            typedefs.push_str(" ");
+            typedefs.push_str(variants_name);
+            typedefs.push_str(";\n");
+        }
+        output.push_str(variants_name);
+    } else {
+        emit_erased_type(t, final_type_scope, output);
+    }
+}
+
 fn emit_symbol_declarations(
     symbols: &Vec<IrSymbol>,
     global_type_scope: &TypeScope,
@@ -604,9 +1051,21 @@ fn emit_symbol_declarations(
     static_var_vals: &mut HashMap<NamespacePath, (ConstantValue, TypeGroup)>,
     strings: &StringMap,
     external: &mut HashMap<NamespacePath, StringIdx>,
-    output: &mut String
+    declarations: &mut Vec<(NamespacePath, String)>,
+    variable_declarations: &mut String,
+    variable_definitions: &mut String
 ) {
+    let mut declared_external_types = HashSet::new();
     for symbol in symbols {
+        let mut declaration = String::new();
+        let output = &mut declaration;
+        let path = match symbol {
+            IrSymbol::Procedure { path, .. } |
+            IrSymbol::ExternalProcedure { path, .. } |
+            IrSymbol::BuiltInProcedure { path, .. } |
+            IrSymbol::Variable { path, .. } |
+            IrSymbol::ExternalVariable { path, .. } => path
+        };
         match symbol {
             IrSymbol::Procedure { path, variant, parameter_types, return_type, type_scope, .. } => {
                 let return_type = type_scope.transfer_group(*return_type, final_type_scope);
@@ -632,24 +1091,32 @@ fn emit_symbol_declarations(
             }
             IrSymbol::ExternalProcedure { path, backing, parameter_types, return_type, type_scope } => {
                 external.insert(path.clone(), *backing);
-                output.push_str("extern ");
+                let backing_name = strings.get(*backing);
+                let mut declaration = String::from("extern ");
                 let return_type = type_scope.transfer_group(*return_type, final_type_scope);
                 final_type_scope.replace_any_with_unit();
                 final_type_scope.deduplicate();
-                emit_type(return_type, final_type_scope, output);
-                output.push_str(" ");
-                output.push_str(strings.get(*backing));
-                output.push_str("(");
+                emit_external_type(
+                    return_type, &format!("{}_result", backing_name), final_type_scope,
+                    &mut declared_external_types, output, &mut declaration
+                );
+                declaration.push_str(" ");
+                declaration.push_str(backing_name);
+                declaration.push_str("(");
                 for p in 0..parameter_types.len() {
                     let param_type = type_scope.transfer_group(parameter_types[p], final_type_scope);
                     final_type_scope.replace_any_with_unit();
                     final_type_scope.deduplicate();
-                    if p > 0 { output.push_str(", "); }
-                    emit_type(param_type, final_type_scope, output);
-                    output.push_str(" param");
-                    output.push_str(&p.to_string());
+                    if p > 0 { declaration.push_str(", "); }
+                    emit_external_type(
+                        param_type, &format!("{}_param{}", backing_name, p), final_type_scope,
+                        &mut declared_external_types, output, &mut declaration
+                    );
+                    declaration.push_str(" param");
+                    declaration.push_str(&p.to_string());
                 }
-                output.push_str(");\n");
+                declaration.push_str(");\n");
+                output.push_str(&declaration);
             }
             IrSymbol::BuiltInProcedure { path, variant, parameter_types, return_type, type_scope } => {
                 let return_type = type_scope.transfer_group(*return_type, final_type_scope);
@@ -678,10 +1145,14 @@ fn emit_symbol_declarations(
                 final_type_scope.replace_any_with_unit();
                 final_type_scope.deduplicate();
                 if let Type::Unit = final_type_scope.group_concrete(value_type) { continue; }
-                emit_type(value_type, final_type_scope, output);
-                output.push_str(" ");
-                emit_path(path, strings, output);
-                output.push_str(";\n");
+                let mut declaration = String::new();
+                emit_type(value_type, final_type_scope, &mut declaration);
+                declaration.push_str(" ");
+                emit_path(path, strings, &mut declaration);
+                declaration.push_str(";\n");
+                variable_declarations.push_str("extern ");
+                variable_declarations.push_str(&declaration);
+                variable_definitions.push_str(&declaration);
                 let value = constants.insert(value, value_type, final_type_scope);
                 static_var_vals.insert(path.clone(), (value, value_type));
             }
@@ -691,13 +1162,24 @@ fn emit_symbol_declarations(
                 final_type_scope.deduplicate();
                 if let Type::Unit = final_type_scope.group_concrete(value_type) { continue; }
                 external.insert(path.clone(), *backing);
-                output.push_str("extern ");
-                emit_type(value_type, final_type_scope, output);  
-                output.push_str(" ");
-                output.push_str(strings.get(*backing));
-                output.push_str(";\n");
+                let backing_name = strings.get(*backing);
+                let mut declaration = String::from("extern ");
+                emit_external_type(
+                    value_type, &format!("{}_type", backing_name), final_type_scope,
+                    &mut declared_external_types, output, &mut declaration
+                );
+                declaration.push_str(" ");
+                declaration.push_str(backing_name);
+                declaration.push_str(";\n");
+                output.push_str(&declaration);
             }
         }
+        if declaration.len() == 0 { continue; }
+        let module_path = module_of(path);
+        match declarations.iter_mut().find(|(m, _)| *m == module_path) {
+            Some((_, module_declarations)) => module_declarations.push_str(&declaration),
+            None => declarations.push((module_path, declaration))
+        }
     }
 }
 
@@ -756,8 +1238,7 @@ fn emit_implicit_conversion(
         output.push_str(variable);
         return;
     }
-    if !conversions.declared.contains(&(from_type, to_type)) {
-        conversions.declared.insert((from_type, to_type));
+    if conversions.declared.insert(conversion_function_name.clone()) {
         let mut conversion_signature = String::new();
         emit_type(to_type, final_type_scope, &mut conversion_signature);
         conversion_signature.push_str(" ");
@@ -780,17 +1261,18 @@ fn emit_implicit_conversion(
                     conversion_function_str.push_str("    return (");
                     emit_type(to_type, final_type_scope, &mut conversion_function_str);
                     conversion_function_str.push_str(") {\n");
-                    for (member_name, to_member_type) in final_type_scope.object(to_object_idx).0.clone() {
-                        conversion_function_str.push_str("        .member");
-                        conversion_function_str.push_str(&member_name.0.to_string());
+                    let to_members = final_type_scope.object(to_object_idx).0.clone();
+                    for (member_name, to_member_type) in sorted_by_name(&to_members, strings) {
+                        conversion_function_str.push_str("        .");
+                        emit_member_name(*member_name, strings, &mut conversion_function_str);
                         conversion_function_str.push_str(" = ");
                         let mut member_value = String::new();
-                        member_value.push_str("from.member");
-                        member_value.push_str(&member_name.0.to_string());
-                        let from_member_type = final_type_scope.object(from_object_idx).0.get(&member_name)
-                            .unwrap_or_else(|| panic!("Object conversion is invalid! '{}' does not exist?", strings.get(member_name)));
+                        member_value.push_str("from.");
+                        emit_member_name(*member_name, strings, &mut member_value);
+                        let from_member_type = final_type_scope.object(from_object_idx).0.get(member_name)
+                            .unwrap_or_else(|| panic!("Object conversion is invalid! '{}' does not exist?", strings.get(*member_name)));
                         emit_implicit_conversion(
-                            &member_value, *from_member_type, to_member_type, conversions, final_type_scope,
+                            &member_value, *from_member_type, *to_member_type, conversions, final_type_scope,
                             strings, &mut conversion_function_str
                         );
                         conversion_function_str.push_str(",\n");
@@ -812,9 +1294,9 @@ fn emit_implicit_conversion(
                     conversion_function_str.push_str("* object = (");
                     emit_object_alloc_name(to_object_idx.get_internal_id(), &mut conversion_function_str);
                     conversion_function_str.push_str("*) allocation->data;\n");
-                    for (member_name, member_type) in &final_type_scope.object(to_object_idx).0 {
-                        conversion_function_str.push_str("    object->member");
-                        conversion_function_str.push_str(&member_name.0.to_string());
+                    for (member_name, member_type) in sorted_by_name(&final_type_scope.object(to_object_idx).0, strings) {
+                        conversion_function_str.push_str("    object->");
+                        emit_member_name(*member_name, strings, &mut conversion_function_str);
                         conversion_function_str.push_str(" = from.");
                         conversion_function_str.push_str(strings.get(*member_name));
                         conversion_function_str.push_str(";\n");
@@ -825,10 +1307,10 @@ fn emit_implicit_conversion(
                         );
                         indent(&member_value_incr_str, &mut conversion_function_str);
                         conversion_function_str.push_str("    ");
-                        conversion_function_str.push_str("result.member");
-                        conversion_function_str.push_str(&member_name.0.to_string());
-                        conversion_function_str.push_str(" = &object->member");
-                        conversion_function_str.push_str(&member_name.0.to_string());
+                        conversion_function_str.push_str("result.");
+                        emit_member_name(*member_name, strings, &mut conversion_function_str);
+                        conversion_function_str.push_str(" = &object->");
+                        emit_member_name(*member_name, strings, &mut conversion_function_str);
                         conversion_function_str.push_str(";\n");
                     }
                     conversion_function_str.push_str("    return result;\n");
@@ -845,8 +1327,8 @@ fn emit_implicit_conversion(
                         conversion_function_str.push_str(strings.get(*member_name));
                         conversion_function_str.push_str(" = *((");
                         emit_type(*member_type, final_type_scope, &mut conversion_function_str);
-                        conversion_function_str.push_str("*) from.member");
-                        conversion_function_str.push_str(&member_name.0.to_string());
+                        conversion_function_str.push_str("*) from.");
+                        emit_member_name(*member_name, strings, &mut conversion_function_str);
                         conversion_function_str.push_str("),\n");
                     }
                     conversion_function_str.push_str("    };\n");
@@ -898,6 +1380,8 @@ fn emit_scope_decrements(
     strings: &StringMap,
     output: &mut String
 ) {
+    let mut free = free.iter().collect::<Vec<&usize>>();
+    free.sort();
     for variable_idx in free {
         let mut variable_str = String::new();
         emit_variable(IrVariable { index: *variable_idx, version: 0 }, &mut variable_str);
@@ -975,10 +1459,12 @@ if(param1 < 0) {
         result.push_str("return result;\n");
         result
     });
-    builtins.insert(path_from(&["core", "exhaust"], strings), |_, _, _, strings| {
+    builtins.insert(path_from(&["core", "exhaust"], strings), |_, _, _, _| {
+        let mut next_tag = String::new();
+        emit_variant_tag("next", &mut next_tag);
         format!("
 while(((param0.procedure)(param0.allocation)).tag == {}) {{}}
-", strings.insert("next").0)
+", next_tag)
     });
     builtins.insert(path_from(&["core", "panic"], strings), |_, _, _, _| {
         String::from(r#"
@@ -1042,9 +1528,9 @@ return gera___wrap_static_string("<object>");
             Type::Variants(variant_idx) => {
                 let variant_types = &types.variants(variant_idx).0;
                 let mut result = String::from("switch(param0.tag) {\n");
-                for (variant_name, _) in variant_types {
+                for (variant_name, _) in sorted_by_name(variant_types, strings) {
                     result.push_str("    case ");
-                    result.push_str(&variant_name.0.to_string());
+                    emit_variant_tag(strings.get(*variant_name), &mut result);
                     let variant_str = format!("#{} <...>", strings.get(*variant_name));
                     result.push_str(": return gera___wrap_static_string(\"");
                     result.push_str(&variant_str);
@@ -1145,7 +1631,7 @@ return gera___substring(param0, start_idx, end_idx);
 return gera___concat(param0, param1);
 "#)
     });
-    builtins.insert(path_from(&["core", "parse_flt"], strings), |_, return_type, types, strings| {
+    builtins.insert(path_from(&["core", "parse_flt"], strings), |_, return_type, types, _| {
         let variant_idx = if let Type::Variants(v) = types.group_concrete(return_type) { v }
         else { panic!("should be variants"); };
         let mut result = String::new();
@@ -1154,16 +1640,16 @@ return gera___concat(param0, param1);
         result.push_str("if(geracoredeps_parse_success) { return (");
         emit_variants_name(variant_idx.get_internal_id(), &mut result);
         result.push_str(") { .tag = ");
-        result.push_str(&strings.insert("some").0.to_string());
+        emit_variant_tag("some", &mut result);
         result.push_str(", .value = { .some = value } }; }\n");
         result.push_str("return (");
         emit_variants_name(variant_idx.get_internal_id(), &mut result);
         result.push_str(") { .tag = ");
-        result.push_str(&strings.insert("none").0.to_string());
+        emit_variant_tag("none", &mut result);
         result.push_str(" };\n");
         result
     });
-    builtins.insert(path_from(&["core", "parse_int"], strings), |_, return_type, types, strings| {
+    builtins.insert(path_from(&["core", "parse_int"], strings), |_, return_type, types, _| {
         let variant_idx = if let Type::Variants(v) = types.group_concrete(return_type) { v }
         else { panic!("should be variants"); };
         let mut result = String::new();
@@ -1172,12 +1658,12 @@ return gera___concat(param0, param1);
         result.push_str("if(geracoredeps_parse_success) { return (");
         emit_variants_name(variant_idx.get_internal_id(), &mut result);
         result.push_str(") { .tag = ");
-        result.push_str(&strings.insert("some").0.to_string());
+        emit_variant_tag("some", &mut result);
         result.push_str(", .value = { .some = value } }; }\n");
         result.push_str("return (");
         emit_variants_name(variant_idx.get_internal_id(), &mut result);
         result.push_str(") { .tag = ");
-        result.push_str(&strings.insert("none").0.to_string());
+        emit_variant_tag("none", &mut result);
         result.push_str(" };\n");
         result
     });
@@ -1270,10 +1756,18 @@ fn emit_procedure_impls(
     closure_bodies: &mut Vec<String>,
     conversions: &mut ConversionFunctions,
     external: &mut HashMap<NamespacePath, StringIdx>,
-    output: &mut String
+    modules: &mut Vec<(NamespacePath, String)>
 ) {
     let builtin_bodies = get_builtin_bodies(strings);
     for symbol in symbols {
+        let path = match symbol {
+            IrSymbol::Procedure { path, .. } |
+            IrSymbol::BuiltInProcedure { path, .. } => path,
+            _ => continue
+        };
+        let closure_count = closure_bodies.len();
+        let mut procedure_impl = String::new();
+        let output = &mut procedure_impl;
         match symbol {
             IrSymbol::Procedure { path, variant, parameter_types, return_type, variables, body, type_scope } => {
                 let return_type = type_scope.transfer_group(*return_type, final_type_scope);
@@ -1365,6 +1859,18 @@ fn emit_procedure_impls(
             }
             _ => {}
         }
+        let module_path = module_of(path);
+        let module_idx = match modules.iter().position(|(m, _)| *m == module_path) {
+            Some(module_idx) => module_idx,
+            None => {
+                modules.push((module_path, String::new()));
+                modules.len() - 1
+            }
+        };
+        for closure_body in &closure_bodies[closure_count..] {
+            modules[module_idx].1.push_str(closure_body);
+        }
+        modules[module_idx].1.push_str(&procedure_impl);
     }
 }
 
@@ -1373,9 +1879,21 @@ fn emit_constant_name(idx: usize, output: &mut String) {
     output.push_str(&idx.to_string());
 }
 
-fn emit_constant_declarations(constants: &ConstantPool, final_type_scope: &mut TypeScope, output: &mut String) {
+fn emit_constant_declarations(
+    constants: &ConstantPool, final_type_scope: &mut TypeScope, is_extern: bool, output: &mut String
+) {
     for vi in 0..constants.get_value_count() {
+        if is_extern {
+            if let ConstantPoolValue::Variant(_, _, _) = constants.get_value(vi) {} else {
+                output.push_str("extern ");
+            }
+        }
         match constants.get_value(vi) {
+            ConstantPoolValue::String(_) if is_extern => {
+                output.push_str("const GeraString ");
+                emit_constant_name(vi, output);
+                output.push_str(";\n");
+            }
             ConstantPoolValue::String(v) => {
                 output.push_str("const GeraString ");
                 emit_constant_name(vi, output);
@@ -1407,6 +1925,7 @@ fn emit_constant_declarations(constants: &ConstantPool, final_type_scope: &mut T
                     output.push_str(&values.len().to_string());
                 }
                 output.push_str("];\n");
+                if is_extern { output.push_str("extern "); }
                 let constant_tidx = final_type_scope.insert_dedup_array(element_type);
                 let constant_type = final_type_scope.insert_group(&[Type::Array(constant_tidx)]);
                 emit_type(constant_type, final_type_scope, output);
@@ -1424,6 +1943,7 @@ fn emit_constant_declarations(constants: &ConstantPool, final_type_scope: &mut T
                 output.push_str(" ");
                 emit_constant_name(vi, output);
                 output.push_str("values;\n");
+                if is_extern { output.push_str("extern "); }
                 emit_type(constant_type, final_type_scope, output);
                 output.push_str(" ");
                 emit_constant_name(vi, output);
@@ -1480,9 +2000,9 @@ fn emit_constant_initializers(
                 cinit.push_str("values = (");
                 emit_object_alloc_name(constant_object_idx.get_internal_id(), &mut cinit);
                 cinit.push_str(") {\n");
-                for (member_name, (member_value, member_type)) in members {
-                    cinit.push_str("    .member");
-                    cinit.push_str(&member_name.0.to_string());
+                for (member_name, (member_value, member_type)) in sorted_by_name(members, strings) {
+                    cinit.push_str("    .");
+                    emit_member_name(*member_name, strings, &mut cinit);
                     cinit.push_str(" = ");
                     emit_value(*member_value, *member_type, final_type_scope, constants, strings, &mut cinit);
                     cinit.push_str(",\n");
@@ -1494,13 +2014,13 @@ fn emit_constant_initializers(
                 emit_type(constant_type, final_type_scope, &mut cinit);
                 cinit.push_str(") {\n");
                 cinit.push_str("    .allocation = NULL,\n");
-                for (member_name, _) in members {
-                    cinit.push_str("    .member");
-                    cinit.push_str(&member_name.0.to_string());
+                for (member_name, _) in sorted_by_name(members, strings) {
+                    cinit.push_str("    .");
+                    emit_member_name(*member_name, strings, &mut cinit);
                     cinit.push_str(" = &");
                     emit_constant_name(vi, &mut cinit);
-                    cinit.push_str("values.member");
-                    cinit.push_str(&member_name.0.to_string());
+                    cinit.push_str("values.");
+                    emit_member_name(*member_name, strings, &mut cinit);
                     cinit.push_str(",\n");
                 }
                 cinit.push_str("};\n");
@@ -1508,6 +2028,8 @@ fn emit_constant_initializers(
             ConstantPoolValue::Variant(_, _, _) => {}
         }
     }
+    let mut static_var_vals = static_var_vals.iter().collect::<Vec<(&NamespacePath, &(ConstantValue, TypeGroup))>>();
+    static_var_vals.sort_by_key(|(path, _)| path.display(strings));
     for (path, (value, value_type)) in static_var_vals {
         emit_path(path, strings, &mut cinit);
         cinit.push_str(" = ");
@@ -1518,6 +2040,246 @@ fn emit_constant_initializers(
     output.push_str("}\n");
 }
 
+fn emit_exported_type(
+    t: TypeGroup,
+    fallback_name: &str,
+    final_type_scope: &mut TypeScope,
+    strings: &StringMap,
+    declared: &mut HashSet<String>,
+    output: &mut String,
+    impls_output: &mut String
+) -> (String, Option<TypeGroup>) {
+    match final_type_scope.group_concrete(t) {
+        Type::Array(array_idx) => {
+            if declared.insert(fallback_name.to_string()) {
+                let element_type = final_type_scope.array(array_idx);
+                output.push_str("typedef GeraArray ");
+                output.push_str(fallback_name);
+                output.push_str("; // elements are of type '");
+                emit_type(element_type, final_type_scope, output);
+                output.push_str("'\n");
+                let signature = format!("{fallback_name} {fallback_name}_new(size_t length)");
+                output.push_str(&signature);
+                output.push_str(";\n");
+                impls_output.push_str(&signature);
+                impls_output.push_str(" {\n");
+                impls_output.push_str("    GeraAllocation* allocation = gera___rc_alloc(");
+                if let Type::Unit = final_type_scope.group_concrete(element_type) {
+                    impls_output.push_str("1, &gera___free_nothing");
+                } else {
+                    impls_output.push_str("length == 0? 1 : sizeof(");
+                    emit_type(element_type, final_type_scope, impls_output);
+                    impls_output.push_str(") * length, &");
+                    emit_array_free_handler_name(array_idx.get_internal_id(), impls_output);
+                }
+                impls_output.push_str(");\n");
+                impls_output.push_str("    for(size_t i = 0; i < allocation->size; i += 1) { allocation->data[i] = 0; }\n");
+                impls_output.push_str("    return (");
+                impls_output.push_str(fallback_name);
+                impls_output.push_str(") { .allocation = allocation, .length = length, .data = allocation->data };\n");
+                impls_output.push_str("}\n");
+            }
+            (fallback_name.to_string(), None)
+        }
+        Type::Object(object_idx) => {
+            let name = final_type_scope.object_name(object_idx).as_ref()
+                .map(|(path, _)| path.get_segments().iter()
+                    .map(|s| strings.get(*s))
+                    .collect::<Vec<&str>>()
+                    .join("_")
+                )
+                .unwrap_or_else(|| fallback_name.to_string());
+            let mut members = final_type_scope.object(object_idx).0.iter()
+                .map(|(member_name, member_type)| (*member_name, *member_type))
+                .collect::<Vec<(StringIdx, TypeGroup)>>();
+            members.sort_by_key(|(member_name, _)| strings.get(*member_name));
+            let concrete_object_idx = final_type_scope.insert_dedup_concrete_object(members.clone());
+            let concrete_object = final_type_scope.insert_group(&[Type::ConcreteObject(concrete_object_idx)]);
+            if declared.insert(name.clone()) {
+                output.push_str("typedef struct {");
+                for (member_name, member_type) in &members {
+                    output.push_str("\n    ");
+                    emit_type(*member_type, final_type_scope, output);
+                    output.push_str(" ");
+                    output.push_str(strings.get(*member_name));
+                    output.push_str(";");
+                }
+                if members.len() == 0 {
+                    output.push_str("\n    char empty;");
+                }
+                output.push_str("\n} ");
+                output.push_str(&name);
+                output.push_str(";\n");
+            }
+            (name, Some(concrete_object))
+        }
+        _ => {
+            let mut name = String::new();
+            emit_type(t, final_type_scope, &mut name);
+            (name, None)
+        }
+    }
+}
+
+fn emit_exported_procedures(
+    symbols: &Vec<IrSymbol>,
+    exported: &[(NamespacePath, usize, Vec<StringIdx>)],
+    final_type_scope: &mut TypeScope,
+    strings: &StringMap,
+    conversions: &mut ConversionFunctions,
+    types_output: &mut String,
+    prototypes_output: &mut String,
+    impls_output: &mut String
+) {
+    let mut declared_types = HashSet::new();
+    for (path, variant, parameter_names) in exported {
+        let (parameter_types, return_type, type_scope) = match symbols.iter().find(|s| match s {
+            IrSymbol::Procedure { path: p, variant: v, .. } => *path == *p && *variant == *v,
+            _ => false
+        }).expect("exported procedure should have been lowered") {
+            IrSymbol::Procedure { parameter_types, return_type, type_scope, .. } => (
+                parameter_types, return_type, type_scope
+            ),
+            _ => panic!("should be a procedure")
+        };
+        let exported_name = path.get_segments().iter()
+            .map(|s| strings.get(*s))
+            .collect::<Vec<&str>>()
+            .join("_");
+        let mut signature = String::new();
+        let mut body = String::new();
+        let mut call = String::new();
+        let mut cleanup = String::new();
+        let return_type = type_scope.transfer_group(*return_type, final_type_scope);
+        final_type_scope.replace_any_with_unit();
+        final_type_scope.deduplicate();
+        let (return_type_name, return_concrete_object) = emit_exported_type(
+            return_type, &format!("{}_result", exported_name), final_type_scope, strings,
+            &mut declared_types, types_output, impls_output
+        );
+        signature.push_str(&return_type_name);
+        signature.push_str(" ");
+        signature.push_str(&exported_name);
+        signature.push_str("(");
+        emit_procedure_name(path, *variant, strings, &mut call);
+        call.push_str("(");
+        let mut had_param = false;
+        for p in 0..parameter_types.len() {
+            let param_type = type_scope.transfer_group(parameter_types[p], final_type_scope);
+            final_type_scope.replace_any_with_unit();
+            final_type_scope.deduplicate();
+            if let Type::Unit = final_type_scope.group_concrete(param_type) { continue; }
+            let param_name = strings.get(parameter_names[p]);
+            let (param_type_name, param_concrete_object) = emit_exported_type(
+                param_type, &format!("{}_{}", exported_name, param_name), final_type_scope, strings,
+                &mut declared_types, types_output, impls_output
+            );
+            if had_param {
+                signature.push_str(", ");
+                call.push_str(", ");
+            }
+            had_param = true;
+            signature.push_str(&param_type_name);
+            signature.push_str(" ");
+            signature.push_str(param_name);
+            if let Some(concrete_object) = param_concrete_object {
+                let Type::ConcreteObject(concrete_object_idx) = final_type_scope.group_concrete(concrete_object)
+                    else { panic!("should be a concrete object"); };
+                let mut literal = String::new();
+                literal.push_str("(");
+                emit_type(concrete_object, final_type_scope, &mut literal);
+                literal.push_str(") {");
+                for (member_name, _) in final_type_scope.concrete_object(concrete_object_idx) {
+                    literal.push_str(" .");
+                    literal.push_str(strings.get(*member_name));
+                    literal.push_str(" = ");
+                    literal.push_str(param_name);
+                    literal.push_str(".");
+                    literal.push_str(strings.get(*member_name));
+                    literal.push_str(",");
+                }
+                literal.push_str(" }");
+                let argument = format!("gera___arg{}", p);
+                body.push_str("    ");
+                emit_type(param_type, final_type_scope, &mut body);
+                body.push_str(" ");
+                body.push_str(&argument);
+                body.push_str(" = ");
+                emit_implicit_conversion(
+                    &literal, concrete_object, param_type, conversions, final_type_scope, strings, &mut body
+                );
+                body.push_str(";\n");
+                call.push_str(&argument);
+                let mut argument_decr = String::new();
+                emit_rc_decr(&argument, param_type, final_type_scope, strings, &mut argument_decr);
+                indent(&argument_decr, &mut cleanup);
+            } else {
+                call.push_str(param_name);
+            }
+        }
+        if !had_param { signature.push_str("void"); }
+        signature.push_str(")");
+        call.push_str(")");
+        prototypes_output.push_str(&signature);
+        prototypes_output.push_str(";\n");
+        impls_output.push_str(&signature);
+        impls_output.push_str(" {\n");
+        impls_output.push_str(&body);
+        if let Type::Unit = final_type_scope.group_concrete(return_type) {
+            impls_output.push_str("    ");
+            impls_output.push_str(&call);
+            impls_output.push_str(";\n");
+            impls_output.push_str(&cleanup);
+        } else if let Some(concrete_object) = return_concrete_object {
+            let Type::ConcreteObject(concrete_object_idx) = final_type_scope.group_concrete(concrete_object)
+                else { panic!("should be a concrete object"); };
+            impls_output.push_str("    ");
+            emit_type(return_type, final_type_scope, impls_output);
+            impls_output.push_str(" gera___result = ");
+            impls_output.push_str(&call);
+            impls_output.push_str(";\n");
+            impls_output.push_str(&cleanup);
+            impls_output.push_str("    ");
+            emit_type(concrete_object, final_type_scope, impls_output);
+            impls_output.push_str(" gera___converted = ");
+            emit_implicit_conversion(
+                "gera___result", return_type, concrete_object, conversions, final_type_scope, strings,
+                impls_output
+            );
+            impls_output.push_str(";\n");
+            let mut result_rc = String::new();
+            for (member_name, member_type) in final_type_scope.concrete_object(concrete_object_idx) {
+                emit_rc_incr(
+                    &format!("gera___converted.{}", strings.get(*member_name)),
+                    *member_type, final_type_scope, strings, &mut result_rc
+                );
+            }
+            emit_rc_decr("gera___result", return_type, final_type_scope, strings, &mut result_rc);
+            indent(&result_rc, impls_output);
+            impls_output.push_str("    return (");
+            impls_output.push_str(&return_type_name);
+            impls_output.push_str(") {");
+            for (member_name, _) in final_type_scope.concrete_object(concrete_object_idx) {
+                impls_output.push_str(" .");
+                impls_output.push_str(strings.get(*member_name));
+                impls_output.push_str(" = gera___converted.");
+                impls_output.push_str(strings.get(*member_name));
+                impls_output.push_str(",");
+            }
+            impls_output.push_str(" };\n");
+        } else {
+            impls_output.push_str("    ");
+            emit_type(return_type, final_type_scope, impls_output);
+            impls_output.push_str(" gera___result = ");
+            impls_output.push_str(&call);
+            impls_output.push_str(";\n");
+            impls_output.push_str(&cleanup);
+            impls_output.push_str("    return gera___result;\n");
+        }
+        impls_output.push_str("}\n");
+    }
+}
+
 fn emit_main_function(
     main_procedure_path: &NamespacePath,
     strings: &StringMap,
@@ -1582,6 +2344,12 @@ fn emit_string_literal(value: &str, output: &mut String) {
     output.push('"');
 }
 
+fn emit_integer(value: i64, output: &mut String) {
+    // '-9223372036854775808' would be the negation of a literal that is too large for a signed integer
+    if value == i64::MIN { output.push_str(&format!("(-{} - 1)", i64::MAX)); }
+    else { output.push_str(&value.to_string()); }
+}
+
 fn emit_value(
     value: ConstantValue, value_type: TypeGroup, final_type_scope: &TypeScope, constants: &ConstantPool,
     strings: &StringMap, output: &mut String
@@ -1589,11 +2357,7 @@ fn emit_value(
     match value {
         ConstantValue::Unit => panic!("should not have to emit unit value!"),
         ConstantValue::Boolean(b) => output.push_str(if b { "1" } else { "0" }),
-        ConstantValue::Integer(i) => {
-            if i == i64::MIN {
-                output.push_str(&format!("-{} - 1", i64::MAX));
-            } else { output.push_str(&i.to_string()) }
-        }
+        ConstantValue::Integer(i) => emit_integer(i, output),
         ConstantValue::Float(f) => {
             if f == f64::INFINITY { output.push_str("(1.0 / 0.0)"); }
             else if f == f64::NEG_INFINITY { output.push_str("(-1.0 / 0.0)"); }
@@ -1611,7 +2375,7 @@ fn emit_value(
             output.push_str("(");
             emit_variants_name(variant_idx, output);
             output.push_str(") { .tag = ");
-            output.push_str(&variant_tag.0.to_string());
+            emit_variant_tag(strings.get(variant_tag), output);
             let variant_type = final_type_scope.internal_variants()[variant_idx].0.get(&variant_tag)
                 .expect("variant should exist");
             if let Type::Unit = final_type_scope.group_concrete(*variant_type) {} else {
@@ -1700,7 +2464,7 @@ fn emit_instruction(
         IrInstruction::LoadInteger { value, into } => {
             emit_variable(*into, output);
             output.push_str(" = ");
-            output.push_str(&value.to_string());
+            emit_integer(*value, output);
             output.push_str(";\n");
         }
         IrInstruction::LoadFloat { value, into } => {
@@ -1736,12 +2500,12 @@ fn emit_instruction(
             output.push_str("* object = (");
             emit_object_alloc_name(object_idx, output);
             output.push_str("*) allocation->data;\n");
-            for (member_name, member_value) in member_values {
+            for (member_name, member_value) in sorted_by_name(member_values, strings) {
                 if let Type::Unit = final_type_scope.group_concrete(variable_types[member_value.index]) { continue; }
                 let member_type = *final_type_scope.internal_objects()[object_idx].0.get(member_name)
                     .expect("member should exist");
-                output.push_str("    object->member");
-                output.push_str(&member_name.0.to_string());
+                output.push_str("    object->");
+                emit_member_name(*member_name, strings, output);
                 output.push_str(" = ");
                 let mut member_value_str = String::new();
                 emit_variable(*member_value, &mut member_value_str);
@@ -1758,10 +2522,10 @@ fn emit_instruction(
                 indent(&member_value_incr_str, output);
                 output.push_str("    ");
                 output.push_str(&into_str);
-                output.push_str(".member");
-                output.push_str(&member_name.0.to_string());
-                output.push_str(" = &object->member");
-                output.push_str(&member_name.0.to_string());
+                output.push_str(".");
+                emit_member_name(*member_name, strings, output);
+                output.push_str(" = &object->");
+                emit_member_name(*member_name, strings, output);
                 output.push_str(";\n");
             }
             output.push_str("}\n");
@@ -1841,7 +2605,7 @@ fn emit_instruction(
             output.push_str("(");
             emit_variants_name(variant_idx, output);
             output.push_str(") { .tag = ");
-            output.push_str(&name.0.to_string());
+            emit_variant_tag(strings.get(*name), output);
             if let Type::Unit = final_type_scope.group_concrete(variable_types[v.index]) {} else {
                 output.push_str(", .value = { .");
                 output.push_str(strings.get(*name));
@@ -1941,7 +2705,7 @@ fn emit_instruction(
             closure_body.push_str("typedef struct ");
             emit_closure_captures_name(closure_idx, variant, &mut closure_body);
             closure_body.push_str(" {\n");
-            for (capture_name, capture_variable) in captured {
+            for (capture_name, capture_variable) in sorted_by_name(captured, strings) {
                 let capture_type = variable_types[capture_variable.index];
                 if let Type::Unit = final_type_scope.group_concrete(capture_type) { continue; }
                 closure_body.push_str("    ");
@@ -1962,7 +2726,7 @@ fn emit_instruction(
             closure_body.push_str("* captures = (");
             emit_closure_captures_name(closure_idx, variant, &mut closure_body);
             closure_body.push_str("*) data;\n");
-            for (capture_name, capture_variable) in captured {
+            for (capture_name, capture_variable) in sorted_by_name(captured, strings) {
                 let capture_type = variable_types[capture_variable.index];
                 if let Type::Unit = final_type_scope.group_concrete(capture_type) { continue; }
                 let mut capture_rc_decr = String::new();
@@ -2044,7 +2808,7 @@ fn emit_instruction(
             output.push_str("* captures = (");
             emit_closure_captures_name(closure_idx, variant, output);
             output.push_str("*) allocation->data;\n");
-            for (capture_name, capture_value) in captured {
+            for (capture_name, capture_value) in sorted_by_name(captured, strings) {
                 if let Type::Unit = final_type_scope.group_concrete(variable_types[capture_value.index]) {
                     continue;
                 }
@@ -2090,8 +2854,8 @@ fn emit_instruction(
             output.push_str(" = ");
             access_str.push_str("(*");
             access_str.push_str(&accessed_str);
-            access_str.push_str(".member");
-            access_str.push_str(&member.0.to_string());
+            access_str.push_str(".");
+            emit_member_name(*member, strings, &mut access_str);
             access_str.push_str(")");
             emit_implicit_conversion(
                 &mut access_str, member_type, variable_types[into.index], conversions, final_type_scope,
@@ -2117,8 +2881,8 @@ fn emit_instruction(
             let mut member_str = String::new();
             member_str.push_str("(*");
             member_str.push_str(&accessed_str);
-            member_str.push_str(".member");
-            member_str.push_str(&member.0.to_string());
+            member_str.push_str(".");
+            emit_member_name(*member, strings, &mut member_str);
             member_str.push_str(")");
             emit_rc_decr(&member_str, member_type, final_type_scope, strings, output);
             output.push_str(&member_str);
@@ -2362,6 +3126,52 @@ fn emit_instruction(
             emit_variable(*x, output);
             output.push_str(";\n");
         }
+        IrInstruction::BitwiseAnd { a, b, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            emit_variable(*a, output);
+            output.push_str(" & ");
+            emit_variable(*b, output);
+            output.push_str(";\n");
+        }
+        IrInstruction::BitwiseOr { a, b, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            emit_variable(*a, output);
+            output.push_str(" | ");
+            emit_variable(*b, output);
+            output.push_str(";\n");
+        }
+        IrInstruction::BitwiseXor { a, b, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            emit_variable(*a, output);
+            output.push_str(" ^ ");
+            emit_variable(*b, output);
+            output.push_str(";\n");
+        }
+        IrInstruction::BitwiseNot { x, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ~");
+            emit_variable(*x, output);
+            output.push_str(";\n");
+        }
+        IrInstruction::LeftShift { a, b, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = gera___shift_left(");
+            emit_variable(*a, output);
+            output.push_str(", ");
+            emit_variable(*b, output);
+            output.push_str(");\n");
+        }
+        IrInstruction::RightShift { a, b, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = gera___shift_right(");
+            emit_variable(*a, output);
+            output.push_str(", ");
+            emit_variable(*b, output);
+            output.push_str(");\n");
+        }
         IrInstruction::LessThan { a, b, into } => {
             emit_variable(*into, output);
             output.push_str(" = ");
@@ -2468,7 +3278,7 @@ fn emit_instruction(
             output.push_str(".tag) {");
             for (branch_variant, branch_variable, branch_body) in branches {
                 output.push_str("\n    case ");
-                output.push_str(&branch_variant.0.to_string());
+                emit_variant_tag(strings.get(*branch_variant), output);
                 output.push_str(":\n");
                 if let Some(branch_variable) = branch_variable {
                     let branch_variable_type = variable_types[branch_variable.index];
@@ -2551,7 +3361,8 @@ fn emit_instruction(
             let returns_value = if let Type::Unit = final_type_scope.group_concrete(return_type) { false }
                 else { true };
             let mut value = String::new();
-            if let Some(backing) = external.get(path) {
+            let backing = external.get(path);
+            if let Some(backing) = backing {
                 value.push_str(strings.get(*backing));
             } else {
                 emit_procedure_name(path, *variant, strings, &mut value);
@@ -2567,14 +3378,25 @@ fn emit_instruction(
                 had_param = true;
                 let mut variable = String::new();
                 emit_variable(arguments[argument_idx], &mut variable);
+                let mut argument = String::new();
                 emit_implicit_conversion(
                     &variable,
                     variable_types[arguments[argument_idx].index],
                     param_type,
-                    conversions, final_type_scope, strings, &mut value
+                    conversions, final_type_scope, strings, &mut argument
                 );
+                if backing.is_some() {
+                    emit_erased_value(&argument, param_type, final_type_scope, &mut value);
+                } else {
+                    value.push_str(&argument);
+                }
             }
             value.push_str(")");
+            if backing.is_some() {
+                let mut restored = String::new();
+                emit_restored_value(&value, return_type, final_type_scope, &mut restored);
+                value = restored;
+            }
             if returns_value {
                 let mut into_str = String::new();
                 emit_variable(*into, &mut into_str);
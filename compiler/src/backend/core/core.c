@@ -1,9 +1,4 @@
 
-#include <geracoredeps.h>
-#include <gera.h>
-
-void gera___panic(const char* message);
-
 GeraAllocation* gera___rc_alloc(size_t size, GeraFreeHandler fh) {
     if(size == 0) { return NULL; }
     GeraAllocation* a = (GeraAllocation*) geracoredeps_malloc(
@@ -63,12 +58,49 @@ void gera___rc_unlock_write(GeraAllocation* a) {
     geracoredeps_unlock_mutex(&a->data_mutex);
 }
 
+void gera_closure_retain(GeraClosure closure) {
+    gera___rc_incr(closure.allocation);
+}
+
+void gera_closure_release(GeraClosure closure) {
+    gera___rc_decr(closure.allocation);
+}
+
+GeraString gera_string_new(const char* data) {
+    return gera___alloc_string(data);
+}
+
+void gera_string_retain(GeraString string) {
+    gera___rc_incr(string.allocation);
+}
+
+void gera_string_release(GeraString string) {
+    gera___rc_decr(string.allocation);
+}
+
+void gera_array_retain(GeraArray array) {
+    gera___rc_incr(array.allocation);
+}
+
+void gera_array_release(GeraArray array) {
+    gera___rc_decr(array.allocation);
+}
+
 double gera___float_mod(double x, double div) {
     if (div != div || x != x) { return x; }
     if (div == 0) { return (0.0f / 0.0f); }
     return x - (int) (x / div) * div;
 }
 
+gint gera___shift_left(gint x, gint n) {
+    return (gint) ((uint64_t) x << (n & 63));
+}
+
+gint gera___shift_right(gint x, gint n) {
+    n &= 63;
+    return x < 0 ? ~(~x >> n) : x >> n;
+}
+
 char gera___string_eq(GeraString a, GeraString b) {
     if(a.length_bytes != b.length_bytes) { return 0; }
     for(size_t i = 0; i < a.length_bytes; i += 1) {
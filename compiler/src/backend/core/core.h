@@ -0,0 +1,50 @@
+
+#include <geracoredeps.h>
+#include <gera.h>
+
+GeraAllocation* gera___rc_alloc(size_t size, GeraFreeHandler fh);
+void gera___rc_incr(GeraAllocation* a);
+void gera___rc_free(GeraAllocation* a);
+void gera___rc_decr(GeraAllocation* a);
+void gera___rc_lock_read(GeraAllocation* a);
+void gera___rc_unlock_read(GeraAllocation* a);
+void gera___rc_lock_write(GeraAllocation* a);
+void gera___rc_unlock_write(GeraAllocation* a);
+
+// Closures received by external procedures are only borrowed for the duration
+// of the call. Retain them to keep them around for longer, release them once
+// done, and invoke them using the 'gera_closure_call_*' procedures.
+typedef struct GeraClosure {
+    GeraAllocation* allocation;
+    void (*procedure)(void);
+} GeraClosure;
+
+void gera_closure_retain(GeraClosure closure);
+void gera_closure_release(GeraClosure closure);
+
+GeraString gera_string_new(const char* data);
+void gera_string_retain(GeraString string);
+void gera_string_release(GeraString string);
+void gera_array_retain(GeraArray array);
+void gera_array_release(GeraArray array);
+
+double gera___float_mod(double x, double div);
+gint gera___shift_left(gint x, gint n);
+gint gera___shift_right(gint x, gint n);
+char gera___string_eq(GeraString a, GeraString b);
+void gera___free_nothing(char* data, size_t size);
+void gera___panic_pre_at(const char* file, size_t line);
+void gera___panic_pre();
+void gera___panic_post();
+void gera___panic(const char* message);
+size_t gera___verify_index(gint index, size_t size, const char* file, size_t line);
+void gera___verify_integer_divisor(gint d, const char* file, size_t line);
+GeraString gera___alloc_string(const char* data);
+GeraString gera___wrap_static_string(const char* data);
+size_t gera___codepoint_size(char fb);
+GeraString gera___substring(GeraString src, gint start, gint end);
+GeraString gera___concat(GeraString a, GeraString b);
+gint gera___hash(unsigned char* data, size_t data_len);
+
+extern GeraArray GERA_ARGS;
+void gera___set_args(int argc, char** argv);
@@ -0,0 +1,87 @@
+
+local gera___array = {}
+
+local function gera___eq(a, b) -- assumes that types match
+    if type(a) ~= "table" then return a == b end
+    if getmetatable(a) == gera___array then
+        if a.n ~= b.n then return false end
+        for i = 1, a.n do
+            if not gera___eq(a[i], b[i]) then return false end
+        end
+        return true
+    end
+    for key, value in pairs(a) do -- assumes that objects have same keys
+        if not gera___eq(value, b[key]) then return false end
+    end
+    return true
+end
+
+local gera___hash = (function()
+    local object_hashes = setmetatable({}, { __mode = "k" })
+    return function(data)
+        if type(data) == "table" or type(data) == "function" then
+            local h = object_hashes[data]
+            if h == nil then
+                h = math.random(0)
+                object_hashes[data] = h
+            end
+            return h
+        end
+        if data == nil then return 0 end
+        local d = tostring(data)
+        local h = 0
+        for i = 1, #d do
+            h = string.byte(d, i) + (h << 6) + (h << 16) - h
+        end
+        return h
+    end
+end)()
+
+local gera___stack = { trace = {} }
+
+function gera___stack.push(name, file, line)
+    local trace = gera___stack.trace
+    trace[#trace + 1] = { name = name, file = file, line = line }
+end
+
+function gera___stack.pop()
+    local trace = gera___stack.trace
+    trace[#trace] = nil
+end
+
+local function gera___panic(message)
+    local err = "The program panicked: " .. message .. "\nStack trace (latest call first):\n"
+    local trace = gera___stack.trace
+    for i = #trace, 1, -1 do
+        local si = trace[i]
+        err = err .. string.format("%d %s at %s:%d\n", i - 1, si.name, si.file, si.line)
+    end
+    error(err, 0)
+end
+
+local function gera___verify_index(index, length, file, line)
+    local final_index = index
+    if index < 0 then final_index = length + index end
+    if final_index >= 0 and final_index < length then return final_index end
+    gera___stack.push("<index>", file, line)
+    gera___panic(string.format("the index %d is out of bounds for an array of length %d", index, length))
+    return -1
+end
+
+local function gera___verify_integer_divisor(d, file, line)
+    if d ~= 0 then return end
+    gera___stack.push("<division>", file, line)
+    gera___panic("integer division by zero")
+end
+
+local function gera___shift_right(x, n) -- shifts arithmetically unlike '>>'
+    n = n & 63
+    if x < 0 then return ~(~x >> n) end
+    return x >> n
+end
+
+local function gera___idiv(a, b) -- truncates like C instead of flooring
+    local q = a // b
+    if q < 0 and q * b ~= a then q = q + 1 end
+    return q
+end
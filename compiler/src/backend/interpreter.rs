@@ -11,7 +11,7 @@ use crate::util::{
     source::{HasSource, SourceRange}
 };
 use crate::frontend::{
-    ast::{TypedAstNode, HasAstNodeVariant, AstNodeVariant},
+    ast::{TypedAstNode, HasAstNodeVariant, AstNodeVariant, Pattern},
     modules::NamespacePath,
     type_checking::Symbol
 };
@@ -76,7 +76,7 @@ impl std::fmt::Debug for Value {
 
 type BuiltinProcedures = HashMap<
     NamespacePath,
-    fn(&mut Interpreter, SourceRange, &[Value], &HashMap<NamespacePath, Symbol<TypedAstNode>>, &HashMap<NamespacePath, StringIdx>, &mut StringMap) -> Result<Value, Error>
+    fn(&mut Interpreter, SourceRange, &[Value], &HashMap<NamespacePath, Symbol<TypedAstNode>>, &HashMap<NamespacePath, Option<StringIdx>>, &mut StringMap) -> Result<Value, Error>
 >;
 
 type StackFrame = Rc<RefCell<HashMap<StringIdx, Value>>>;
@@ -140,10 +140,7 @@ impl Interpreter {
             interpreter.stack.push(captured_frame);
             interpreter.stack.push(RefCell::new(HashMap::new()).into());
             loop {
-                if let Some(error) = interpreter.evaluate_nodes(&body, symbols, external_backings, strings) {
-                    return Err(error);
-                }
-                let return_value = interpreter.get_return_value();
+                let return_value = interpreter.evaluate_call_body(&body, symbols, external_backings, strings)?;
                 if let Value::Variant(tag, _) = return_value {
                     if tag == end_tag { break; }
                 } else { panic!("should return variant"); }
@@ -349,11 +346,10 @@ impl Interpreter {
         &mut self,
         nodes: &Vec<TypedAstNode>,
         symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>,
-        external_backings: &HashMap<NamespacePath, StringIdx>,
+        external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
         strings: &mut StringMap
     ) -> Option<Error> {
         for node in nodes {
-            if self.returned_value.is_some() { break; }
             if let Err(error) = self.evaluate_node(node, symbols, external_backings, strings) {
                 return Some(error);
             }
@@ -361,6 +357,38 @@ impl Interpreter {
         None
     }
 
+    fn evaluate_body(
+        &mut self,
+        nodes: &Vec<TypedAstNode>,
+        symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>,
+        external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
+        strings: &mut StringMap
+    ) -> Result<Value, Error> {
+        let mut value = Value::Unit;
+        for node in nodes {
+            value = self.evaluate_node(node, symbols, external_backings, strings)?;
+        }
+        Ok(value)
+    }
+
+    fn evaluate_call_body(
+        &mut self,
+        nodes: &Vec<TypedAstNode>,
+        symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>,
+        external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
+        strings: &mut StringMap
+    ) -> Result<Value, Error> {
+        let stack_size = self.stack.len();
+        let stack_trace_size = self.stack_trace.len();
+        if let Some(error) = self.evaluate_nodes(nodes, symbols, external_backings, strings) {
+            // returning unwinds using an error, which is only a real error if nothing returned
+            if self.returned_value.is_none() { return Err(error); }
+            self.stack.truncate(stack_size);
+            self.stack_trace.truncate(stack_trace_size);
+        }
+        Ok(self.get_return_value())
+    }
+
     pub fn stack_trace_push(&mut self, name: String, from: SourceRange, strings: &StringMap) {
         let source_line = strings.get(from.file_content())[..from.start_position()]
             .lines().collect::<Vec<&str>>().len();
@@ -411,21 +439,24 @@ impl Interpreter {
         &mut self,
         node: &TypedAstNode,
         symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>,
-        external_backings: &HashMap<NamespacePath, StringIdx>,
+        external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
         strings: &mut StringMap
     ) -> Result<Value, Error> {
         let node_source = node.source();
         match node.node_variant() {
-            AstNodeVariant::Procedure { public: _, name: _, arguments: _, body: _ } => {
+            AstNodeVariant::Procedure { public: _, name: _, arguments: _, returns: _, body: _ } => {
                 panic!("procedure should not be in the tree by now");
             }
+            AstNodeVariant::NamedArgument { name: _, value: _ } => {
+                panic!("named arguments should have been mapped by the type checker");
+            }
             AstNodeVariant::Function { arguments, body } => {
                 let captured = self.stack[self.stack.len() - 1].borrow().clone();
                 Ok(Value::Closure(
                     arguments.clone(), RefCell::new(captured).into(), body.clone()
                 ))
             }
-            AstNodeVariant::Variable { public: _, mutable: _, name, value_types: _, value } => {
+            AstNodeVariant::Variable { public: _, mutable: _, name, annotation: _, value_types: _, value } => {
                 if let Some(value) = value {
                     let value = self.evaluate_node(value, symbols, external_backings, strings)?;
                     let stack_size = self.stack.len();
@@ -433,35 +464,27 @@ impl Interpreter {
                 }
                 Ok(Value::Unit)
             }
+            AstNodeVariant::Destructuring { mutable: _, pattern, value } => {
+                let value = self.evaluate_node(value, symbols, external_backings, strings)?;
+                self.bind_pattern(pattern, value, strings)?;
+                Ok(Value::Unit)
+            }
             AstNodeVariant::CaseBranches { value, branches, else_body } => {
                 let value = self.evaluate_node(&*value, symbols, external_backings, strings)?;
-                let mut ran_branch = false;
                 for branch in branches {
                     let branch_value = self.evaluate_node(&branch.0, symbols, external_backings, strings)?;
                     if value != branch_value { continue; }
-                    if let Some(error) = self.evaluate_nodes(&branch.1, symbols, external_backings, strings) {
-                        return Err(error);
-                    };
-                    ran_branch = true;
-                    break;
-                }
-                if !ran_branch {
-                    if let Some(error) = self.evaluate_nodes(else_body, symbols, external_backings, strings) {
-                        return Err(error);
-                    };
+                    return self.evaluate_body(&branch.1, symbols, external_backings, strings);
                 }
-                Ok(Value::Unit)
+                self.evaluate_body(else_body, symbols, external_backings, strings)
             }
             AstNodeVariant::CaseConditon { condition, body, else_body } => {
                 let condition = self.evaluate_node(&*condition, symbols, external_backings, strings)?;
-                if let Some(error) = self.evaluate_nodes(
+                self.evaluate_body(
                     if condition == Value::Boolean(true) { body } else { else_body },
                     symbols,
                     external_backings, strings
-                ) {
-                    return Err(error);
-                };
-                Ok(Value::Unit)
+                )
             }
             AstNodeVariant::CaseVariant { value, branches, else_body } => {
                 let value = self.evaluate_node(&*value, symbols, external_backings, strings)?;
@@ -470,7 +493,6 @@ impl Interpreter {
                 } else {
                     panic!("value should be a variant");
                 };
-                let mut ran_branch = false;
                 for branch in branches {
                     if variant_name != branch.0 { continue; }
                     if let Some(variant_var) = &branch.1 {
@@ -478,19 +500,33 @@ impl Interpreter {
                     } else {
                         self.stack.push(RefCell::new(HashMap::new()).into());
                     }
-                    if let Some(error) = self.evaluate_nodes(&branch.2, symbols, external_backings, strings) {
-                        return Err(error);
-                    };
+                    let result = self.evaluate_body(&branch.2, symbols, external_backings, strings)?;
                     self.stack.pop();
-                    ran_branch = true;
-                    break;
+                    return Ok(result);
                 }
                 if let Some(else_body) = else_body {
-                    if !ran_branch {
-                        if let Some(error) = self.evaluate_nodes(else_body, symbols, external_backings, strings) {
-                            return Err(error);
-                        };
+                    return self.evaluate_body(else_body, symbols, external_backings, strings);
+                }
+                Ok(Value::Unit)
+            }
+            AstNodeVariant::CasePattern { value, branches, else_body } => {
+                let value = self.evaluate_node(&*value, symbols, external_backings, strings)?;
+                for (pattern, guard, body) in branches {
+                    let mut bindings = HashMap::new();
+                    if !self.match_pattern(pattern, &value, &mut bindings, symbols, external_backings, strings)? { continue; }
+                    self.stack.push(RefCell::new(bindings).into());
+                    if let Some(guard) = guard {
+                        if self.evaluate_node(guard, symbols, external_backings, strings)? != Value::Boolean(true) {
+                            self.stack.pop();
+                            continue;
+                        }
                     }
+                    let result = self.evaluate_body(body, symbols, external_backings, strings)?;
+                    self.stack.pop();
+                    return Ok(result);
+                }
+                if let Some(else_body) = else_body {
+                    return self.evaluate_body(else_body, symbols, external_backings, strings);
                 }
                 Ok(Value::Unit)
             }
@@ -504,11 +540,25 @@ impl Interpreter {
             AstNodeVariant::Return { value } => {
                 let value = self.evaluate_node(&*value, symbols, external_backings, strings)?;
                 self.returned_value = Some(value);
-                Ok(Value::Unit)
+                Err(Error::new([
+                    ErrorSection::Error(ErrorType::ConstReturns),
+                    ErrorSection::Code(node_source)
+                ].into()))
+            }
+            AstNodeVariant::Propagate { value, returned_types: _ } => {
+                let value = self.evaluate_node(&*value, symbols, external_backings, strings)?;
+                if let Value::Variant(variant_name, variant_value) = &value {
+                    if strings.get(*variant_name) == "ok" { return Ok((**variant_value).clone()); }
+                } else { panic!("value should be a variant"); }
+                self.returned_value = Some(value);
+                Err(Error::new([
+                    ErrorSection::Error(ErrorType::ConstReturns),
+                    ErrorSection::Code(node_source)
+                ].into()))
             }
             AstNodeVariant::Call { called, arguments } => {
                 if let AstNodeVariant::ModuleAccess { path } = called.node_variant() {
-                    if let Symbol::Procedure { public: _, parameter_names, parameter_types: _, returns: _, body, source: _, type_scope: _ }
+                    if let Symbol::Procedure { public: _, parameter_names, parameter_defaults: _, parameter_types: _, returns: _, body, source: _, type_scope: _ }
                         = symbols.get(path).expect("symbol should exist") {
                         self.stack_trace_push(path.display(strings), node.source(), strings);
                         let returned = if let Some(body) = body {
@@ -520,11 +570,8 @@ impl Interpreter {
                                 );
                             }
                             self.stack.push(RefCell::new(parameter_values).into());
-                            if let Some(error) = self.evaluate_nodes(body, symbols, external_backings, strings) {
-                                return Err(error);
-                            }
+                            let return_value = self.evaluate_call_body(body, symbols, external_backings, strings)?;
                             self.stack.pop();
-                            let return_value = self.get_return_value();
                             Ok(return_value)
                         } else if let Some(_) = external_backings.get(path) {
                             return Err(Error::new([
@@ -562,13 +609,10 @@ impl Interpreter {
                 self.stack.push(captures);
                 self.stack.push(RefCell::new(parameter_values).into());
                 self.stack_trace_push("<closure>".into(), node.source(), strings);
-                if let Some(error) = self.evaluate_nodes(&body, symbols, external_backings, strings) {
-                    return Err(error);
-                }
+                let return_value = self.evaluate_call_body(&body, symbols, external_backings, strings)?;
                 self.stack.pop();
                 self.stack.pop();
                 self.stack_trace.pop();
-                let return_value = self.get_return_value();
                 return Ok(return_value);
             }
             AstNodeVariant::Object { values } => {
@@ -704,6 +748,57 @@ impl Interpreter {
                     _ => panic!("value should be a number")
                 })
             }
+            AstNodeVariant::BitwiseAnd { a, b } => {
+                Ok(match (
+                    self.evaluate_node(&*a, symbols, external_backings, strings)?,
+                    self.evaluate_node(&*b, symbols, external_backings, strings)?
+                ) {
+                    (Value::Integer(a), Value::Integer(b)) => Value::Integer(a & b),
+                    _ => panic!("values should be integers")
+                })
+            }
+            AstNodeVariant::BitwiseOr { a, b } => {
+                Ok(match (
+                    self.evaluate_node(&*a, symbols, external_backings, strings)?,
+                    self.evaluate_node(&*b, symbols, external_backings, strings)?
+                ) {
+                    (Value::Integer(a), Value::Integer(b)) => Value::Integer(a | b),
+                    _ => panic!("values should be integers")
+                })
+            }
+            AstNodeVariant::BitwiseXor { a, b } => {
+                Ok(match (
+                    self.evaluate_node(&*a, symbols, external_backings, strings)?,
+                    self.evaluate_node(&*b, symbols, external_backings, strings)?
+                ) {
+                    (Value::Integer(a), Value::Integer(b)) => Value::Integer(a ^ b),
+                    _ => panic!("values should be integers")
+                })
+            }
+            AstNodeVariant::BitwiseNot { x } => {
+                Ok(match self.evaluate_node(&*x, symbols, external_backings, strings)? {
+                    Value::Integer(x) => Value::Integer(!x),
+                    _ => panic!("value should be an integer")
+                })
+            }
+            AstNodeVariant::LeftShift { a, b } => {
+                Ok(match (
+                    self.evaluate_node(&*a, symbols, external_backings, strings)?,
+                    self.evaluate_node(&*b, symbols, external_backings, strings)?
+                ) {
+                    (Value::Integer(a), Value::Integer(b)) => Value::Integer(a.wrapping_shl(b as u32)),
+                    _ => panic!("values should be integers")
+                })
+            }
+            AstNodeVariant::RightShift { a, b } => {
+                Ok(match (
+                    self.evaluate_node(&*a, symbols, external_backings, strings)?,
+                    self.evaluate_node(&*b, symbols, external_backings, strings)?
+                ) {
+                    (Value::Integer(a), Value::Integer(b)) => Value::Integer(a.wrapping_shr(b as u32)),
+                    _ => panic!("values should be integers")
+                })
+            }
             AstNodeVariant::LessThan { a, b } => {
                 Ok(match (
                     self.evaluate_node(&*a, symbols, external_backings, strings)?,
@@ -809,6 +904,9 @@ impl Interpreter {
                     Symbol::Procedure { .. } => {
                         Value::Unit
                     }
+                    Symbol::Type { .. } => {
+                        panic!("should have been rejected by the type checker!");
+                    }
                 })
             }
             AstNodeVariant::Use { paths: _ } => {
@@ -824,6 +922,92 @@ impl Interpreter {
             AstNodeVariant::Target { target: _, body: _ } => {
                 panic!("Should've been expanded!");
             }
+            AstNodeVariant::TypeAlias { public: _, name: _, nominal: _, aliased: _ } => {
+                panic!("type declarations should not be in the tree by now");
+            }
+        }
+    }
+
+    fn bind_pattern(&mut self, pattern: &Pattern<TypedAstNode>, value: Value, strings: &StringMap) -> Result<(), Error> {
+        match pattern {
+            Pattern::Variable { name, source: _, value_types: _ } => {
+                let stack_size = self.stack.len();
+                self.stack[stack_size - 1].borrow_mut().insert(*name, value);
+            }
+            Pattern::Wildcard { .. } => {}
+            Pattern::Value { .. } |
+            Pattern::Variant { .. } => panic!("the parser should only allow these patterns in 'case'"),
+            Pattern::Object { members, source: _, value_types: _ } => {
+                let member_values = if let Value::Object(member_values) = value {
+                    member_values
+                } else { panic!("destructured value should be an object"); };
+                for (member, member_pattern) in members {
+                    let member_value = member_values.borrow().get(member).expect("object should have member").clone();
+                    self.bind_pattern(member_pattern, member_value, strings)?;
+                }
+            }
+            Pattern::Array { elements, source: _, value_types: _, index_types: _ } => {
+                let element_values = if let Value::Array(element_values) = value {
+                    element_values
+                } else { panic!("destructured value should be an array"); };
+                let element_count = element_values.borrow().len();
+                for (element_index, element_pattern) in elements.iter().enumerate() {
+                    if element_index >= element_count {
+                        self.stack_trace_push("<index>".into(), element_pattern.source(), strings);
+                        return Err(self.generate_panic(
+                            &format!("array index {} is out of bounds for an array of length {}", element_index, element_count),
+                            element_pattern.source(), strings
+                        ))
+                    }
+                    let element_value = element_values.borrow()[element_index].clone();
+                    self.bind_pattern(element_pattern, element_value, strings)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn match_pattern(
+        &mut self,
+        pattern: &Pattern<TypedAstNode>,
+        value: &Value,
+        bindings: &mut HashMap<StringIdx, Value>,
+        symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>,
+        external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
+        strings: &mut StringMap
+    ) -> Result<bool, Error> {
+        match pattern {
+            Pattern::Variable { name, source: _, value_types: _ } => {
+                bindings.insert(*name, value.clone());
+                Ok(true)
+            }
+            Pattern::Wildcard { .. } => Ok(true),
+            Pattern::Value { value: matched, source: _, value_types: _ } => {
+                let matched = self.evaluate_node(matched, symbols, external_backings, strings)?;
+                Ok(*value == matched)
+            }
+            Pattern::Variant { name, payload, source: _, value_types: _ } => {
+                let (variant_name, variant_value) = if let Value::Variant(variant_name, variant_value) = value {
+                    (variant_name, variant_value)
+                } else { panic!("value should be a variant"); };
+                if variant_name != name { return Ok(false); }
+                if let Some(payload) = payload {
+                    self.match_pattern(payload, variant_value, bindings, symbols, external_backings, strings)
+                } else { Ok(true) }
+            }
+            Pattern::Object { members, source: _, value_types: _ } => {
+                let member_values = if let Value::Object(member_values) = value {
+                    member_values.clone()
+                } else { panic!("value should be an object"); };
+                for (member, member_pattern) in members {
+                    let member_value = member_values.borrow().get(member).expect("object should have member").clone();
+                    if !self.match_pattern(member_pattern, &member_value, bindings, symbols, external_backings, strings)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Pattern::Array { .. } => panic!("the parser should not allow array patterns in 'case'")
         }
     }
 
@@ -831,7 +1015,7 @@ impl Interpreter {
         &mut self,
         node: &TypedAstNode,
         symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>,
-        external_backings: &HashMap<NamespacePath, StringIdx>,
+        external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
         strings: &mut StringMap,
         value: Value
     ) -> Option<Error> {
@@ -89,6 +89,12 @@ pub enum IrInstruction {
     Divide { a: IrVariable, b: IrVariable, into: IrVariable, source: SourceRange },
     Modulo { a: IrVariable, b: IrVariable, into: IrVariable, source: SourceRange },
     Negate { x: IrVariable, into: IrVariable },
+    BitwiseAnd { a: IrVariable, b: IrVariable, into: IrVariable },
+    BitwiseOr { a: IrVariable, b: IrVariable, into: IrVariable },
+    BitwiseXor { a: IrVariable, b: IrVariable, into: IrVariable },
+    BitwiseNot { x: IrVariable, into: IrVariable },
+    LeftShift { a: IrVariable, b: IrVariable, into: IrVariable },
+    RightShift { a: IrVariable, b: IrVariable, into: IrVariable },
 
     LessThan { a: IrVariable, b: IrVariable, into: IrVariable },
     LessThanEquals { a: IrVariable, b: IrVariable, into: IrVariable },
@@ -27,7 +27,7 @@ pub fn generate_javascript(
     let mut constant_deps = String::new();
     emit_static_variables(&symbols, &types, &mut constants, strings, &mut constant_deps);
     constant_deps.push_str("\n");
-    emit_procedure_impls(&symbols, &types, &mut constants, strings, &externals, &mut constant_deps);
+    emit_procedure_impls(&symbols, &mut constants, strings, &externals, &mut constant_deps);
     output.push_str("\n");
     emit_constant_declarations(&constants, strings, &mut output);
     output.push_str("\n");
@@ -76,7 +76,7 @@ fn emit_static_variables(
                 emit_path(path, strings, output);
                 output.push_str(" = ");
                 let value = constants.insert(value, *value_type, types);
-                emit_value(&value, constants, output);
+                emit_value(&value, constants, strings, output);
                 output.push_str(";\n");
             }
             IrSymbol::ExternalVariable { .. } => {}
@@ -84,6 +84,12 @@ fn emit_static_variables(
     }
 }
 
+fn emit_variant_tag(variant_name: &str, output: &mut String) {
+    output.push_str("\"");
+    output.push_str(variant_name);
+    output.push_str("\"");
+}
+
 fn emit_variable(variable: IrVariable, output: &mut String) {
     output.push_str("local");
     output.push_str(&variable.index.to_string());
@@ -114,10 +120,10 @@ return BigInt(param0.length);
 return new Array(Number(param1)).fill(param0);
 "#)
     });
-    builtins.insert(path_from(&["core", "exhaust"], strings), |_, _, _, strings| {
-        format!("
-while(param0.call().tag == {}) {{}}
-", strings.insert("next").0)
+    builtins.insert(path_from(&["core", "exhaust"], strings), |_, _, _, _| {
+        String::from(r#"
+while(param0.call().tag === "next") {}
+"#)
     });
     builtins.insert(path_from(&["core", "panic"], strings), |_, _, _, _| {
         String::from(r#"
@@ -149,7 +155,7 @@ return "<object>";
                 let mut result = String::from("switch(param0.tag) {\n");
                 for (variant_name, _) in variant_types {
                     result.push_str("    case ");
-                    result.push_str(&variant_name.0.to_string());
+                    emit_variant_tag(strings.get(*variant_name), &mut result);
                     result.push_str(": return \"#");
                     result.push_str(strings.get(*variant_name));
                     result.push_str(" <...>\";\n");
@@ -195,19 +201,19 @@ return param0.substring(Number(start_idx), Number(end_idx));
 return param0 + param1;
 "#)
     });
-    builtins.insert(path_from(&["core", "parse_flt"], strings), |_, _, _, strings| {
-        format!("
+    builtins.insert(path_from(&["core", "parse_flt"], strings), |_, _, _, _| {
+        String::from(r#"
 const r = parseFloat(param0);
-if(isNaN(r)) {{ return {{ tag: {}, value: undefined }}; }}
-return {{ tag: {}, value: r }};
-", strings.insert("none").0, strings.insert("some").0)
+if(isNaN(r)) { return { tag: "none", value: undefined }; }
+return { tag: "some", value: r };
+"#)
     });
-    builtins.insert(path_from(&["core", "parse_int"], strings), |_, _, _, strings| {
-        format!("
+    builtins.insert(path_from(&["core", "parse_int"], strings), |_, _, _, _| {
+        String::from(r#"
 const r = parseInt(param0);
-if(isNaN(r)) {{ return {{ tag: {}, value: undefined }}; }}
-return {{ tag: {}, value: BigInt(r) }};
-", strings.insert("none").0, strings.insert("some").0)
+if(isNaN(r)) { return { tag: "none", value: undefined }; }
+return { tag: "some", value: BigInt(r) };
+"#)
     });
     builtins.insert(path_from(&["core", "string"], strings), |_, _, _, _| {
         String::from(r#"
@@ -224,7 +230,6 @@ return gera___hash(param0);
 
 fn emit_procedure_impls(
     symbols: &Vec<IrSymbol>,
-    types: &TypeScope,
     constants: &mut ConstantPool,
     strings: &mut StringMap,
     external: &HashMap<NamespacePath, StringIdx>,
@@ -233,7 +238,9 @@ fn emit_procedure_impls(
     let builtin_bodies = get_builtin_bodies(strings);
     for symbol in symbols {
         match symbol {
-            IrSymbol::Procedure { path, variant, parameter_types, return_type: _, variables, body, type_scope: _ } => {
+            IrSymbol::Procedure { path, variant, parameter_types, return_type: _, variables, body, type_scope } => {
+                let mut types = type_scope.clone();
+                types.replace_any_with_unit();
                 output.push_str("function ");
                 emit_procedure_name(path, *variant, strings, output);
                 output.push_str("(");
@@ -252,12 +259,14 @@ fn emit_procedure_impls(
                 }
                 let mut body_str = String::new();
                 emit_block(
-                    body, variables, types, constants, external, symbols, strings, &mut body_str
+                    body, variables, &types, constants, external, symbols, strings, &mut body_str
                 );
                 indent(&body_str, output);
                 output.push_str("}\n");
             }
-            IrSymbol::BuiltInProcedure { path, variant, parameter_types, return_type, type_scope: _ } => {
+            IrSymbol::BuiltInProcedure { path, variant, parameter_types, return_type, type_scope } => {
+                let mut types = type_scope.clone();
+                types.replace_any_with_unit();
                 output.push_str("function ");
                 emit_procedure_name(path, *variant, strings, output);
                 output.push_str("(");
@@ -272,7 +281,7 @@ fn emit_procedure_impls(
                 let body_str = (builtin_bodies
                     .get(path)
                     .expect("builtin should have implementation"))
-                    (parameter_types, *return_type, types, strings);
+                    (parameter_types, *return_type, &types, strings);
                 indent(&body_str, output);
                 output.push_str("}\n");
             }
@@ -302,7 +311,7 @@ fn emit_constant_declarations(constants: &ConstantPool, strings: &StringMap, out
                 output.push_str(" = [\n");
                 for value in values.iter() {
                     output.push_str("    ");
-                    emit_value(value, constants, output);
+                    emit_value(value, constants, strings, output);
                     output.push_str(",\n");
                 }
                 output.push_str("];\n");
@@ -315,7 +324,7 @@ fn emit_constant_declarations(constants: &ConstantPool, strings: &StringMap, out
                     output.push_str("    ");
                     output.push_str(strings.get(*member_name));
                     output.push_str(": ");
-                    emit_value(member_value, constants, output);
+                    emit_value(member_value, constants, strings, output);
                     output.push_str(",\n");
                 }
                 output.push_str("};\n");
@@ -371,7 +380,7 @@ fn emit_string_literal(value: &str, output: &mut String) {
 }
 
 fn emit_value(
-    value: &ConstantValue, constants: &ConstantPool, output: &mut String
+    value: &ConstantValue, constants: &ConstantPool, strings: &StringMap, output: &mut String
 ) {
     match value {
         ConstantValue::Unit => output.push_str("undefined"),
@@ -391,10 +400,10 @@ fn emit_value(
         ConstantValue::Object(o) => emit_constant_name(o.into(), output),
         ConstantValue::Variant(v) => {
             let (variant_name, variant_value, _) = constants.get_variant(*v);
-            output.push_str("{ tag = ");
-            output.push_str(&variant_name.0.to_string());
-            output.push_str(", value = ");
-            emit_value(&variant_value, constants, output);
+            output.push_str("{ tag: ");
+            emit_variant_tag(strings.get(variant_name), output);
+            output.push_str(", value: ");
+            emit_value(&variant_value, constants, strings, output);
             output.push_str("}");
         }
     }
@@ -535,7 +544,7 @@ fn emit_instruction(
         IrInstruction::LoadVariant { name, v, into } => {
             emit_variable(*into, output);
             output.push_str(" = { tag: ");
-            output.push_str(&name.0.to_string());
+            emit_variant_tag(strings.get(*name), output);
             output.push_str(", value: ");
             emit_copied_variable(*v, variable_types[v.index], types, output);
             output.push_str(" };\n");
@@ -619,7 +628,7 @@ fn emit_instruction(
             emit_variable(*into, output);
             output.push_str(" = ");
             let value = constants.insert(value, variable_types[into.index], types);
-            emit_value(&value, constants, output);
+            emit_value(&value, constants, strings, output);
             output.push_str(";\n");
         }
         IrInstruction::GetObjectMember { accessed, member, into } => {
@@ -820,6 +829,52 @@ fn emit_instruction(
             emit_variable(*x, output);
             output.push_str(";\n");
         }
+        IrInstruction::BitwiseAnd { a, b, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            emit_variable(*a, output);
+            output.push_str(" & ");
+            emit_variable(*b, output);
+            output.push_str(";\n");
+        }
+        IrInstruction::BitwiseOr { a, b, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            emit_variable(*a, output);
+            output.push_str(" | ");
+            emit_variable(*b, output);
+            output.push_str(";\n");
+        }
+        IrInstruction::BitwiseXor { a, b, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            emit_variable(*a, output);
+            output.push_str(" ^ ");
+            emit_variable(*b, output);
+            output.push_str(";\n");
+        }
+        IrInstruction::BitwiseNot { x, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ~");
+            emit_variable(*x, output);
+            output.push_str(";\n");
+        }
+        IrInstruction::LeftShift { a, b, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = BigInt.asIntN(64, ");
+            emit_variable(*a, output);
+            output.push_str(" << (");
+            emit_variable(*b, output);
+            output.push_str(" & 63n));\n");
+        }
+        IrInstruction::RightShift { a, b, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            emit_variable(*a, output);
+            output.push_str(" >> (");
+            emit_variable(*b, output);
+            output.push_str(" & 63n);\n");
+        }
         IrInstruction::LessThan { a, b, into } => {
             emit_variable(*into, output);
             output.push_str(" = ");
@@ -885,7 +940,7 @@ fn emit_instruction(
                 output.push_str(&value_str);
                 output.push_str(", ");
                 let bvalue = constants.insert(branch_value, variable_types[value.index], types);
-                emit_value(&bvalue, constants, output);
+                emit_value(&bvalue, constants, strings, output);
                 output.push_str(")) ");
                 emit_block(
                     branch_body, variable_types, types, constants, external, symbols, strings,
@@ -910,7 +965,7 @@ fn emit_instruction(
             for (branch_variant, branch_variable, branch_body) in branches {
                 let mut branch_str = String::new();
                 branch_str.push_str("case ");
-                branch_str.push_str(&branch_variant.0.to_string());
+                emit_variant_tag(strings.get(*branch_variant), &mut branch_str);
                 branch_str.push_str(": ");
                 if let Some(branch_variable) = branch_variable {
                     emit_variable(*branch_variable, &mut branch_str);
@@ -922,7 +977,7 @@ fn emit_instruction(
                     branch_body, variable_types, types, constants, external, symbols, strings,
                     &mut branch_str
                 );
-                branch_str.push_str("\n");
+                branch_str.push_str(" break;\n");
                 indent(&branch_str, output);
             }
             if else_branch.len() > 0 {
@@ -8,9 +8,9 @@ use crate::util::{
     source::{SourceRange, HasSource}
 };
 use crate::frontend::{
-    ast::{TypedAstNode, HasAstNodeVariant, AstNodeVariant},
+    ast::{TypedAstNode, HasAstNodeVariant, AstNodeVariant, Pattern},
     types::{TypeScope, TypeGroup, Type},
-    type_checking::Symbol,
+    type_checking::{Symbol, display_types},
     modules::NamespacePath
 };
 use crate::backend::{
@@ -41,11 +41,27 @@ fn enforce_valid_constant_value(value: &Value, source: SourceRange) -> Result<()
     Ok(())
 }
 
+fn enforce_external_backing(
+    path: &NamespacePath,
+    external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
+    source: SourceRange,
+    strings: &StringMap
+) -> Result<(), Error> {
+    if let Some(None) = external_backings.get(path) {
+        return Err(Error::new([
+            ErrorSection::Error(ErrorType::NoExternalBacking(path.display(strings))),
+            ErrorSection::Code(source),
+            ErrorSection::Help(String::from("Declare a backing for it in a 'target' section for the selected target"))
+        ].into()));
+    }
+    Ok(())
+}
+
 pub fn lower_typed_ast(
     strings: &mut StringMap,
     global_type_scope: &mut TypeScope,
     typed_symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>,
-    external_backings: &HashMap<NamespacePath, StringIdx>,
+    external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
     main_procedure: (&NamespacePath, &Symbol<TypedAstNode>)
 ) -> Result<Vec<IrSymbol>, Error> {
     let mut interpreter = Interpreter::new(strings);
@@ -53,6 +69,7 @@ pub fn lower_typed_ast(
     if let Symbol::Procedure {
         public: _,
         parameter_names: _,
+        parameter_defaults: _,
         parameter_types: _, returns,
         body, source: _,
         type_scope
@@ -77,7 +94,76 @@ pub fn lower_typed_ast(
             type_scope
         });
     } else { panic!("should be a procedure"); }
-    for (symbol_path, typed_symbol) in typed_symbols {
+    lower_constants(strings, typed_symbols, external_backings, &mut interpreter, &mut ir_symbols)?;
+    Ok(ir_symbols)
+}
+
+fn is_exportable(group: TypeGroup, type_scope: &TypeScope, top_level: bool) -> bool {
+    let types = type_scope.group(group).collect::<Vec<Type>>();
+    if types.len() != 1 { return false; }
+    match types[0] {
+        Type::Boolean | Type::Integer | Type::Float | Type::String => true,
+        Type::Unit => top_level,
+        Type::Array(arr) => is_exportable(type_scope.array(arr), type_scope, false),
+        Type::Object(obj) => top_level && type_scope.object(obj).0.values()
+            .all(|member_types| is_exportable(*member_types, type_scope, false)),
+        Type::Any | Type::ConcreteObject(_) | Type::Closure(_) | Type::Variants(_) => false
+    }
+}
+
+pub fn lower_typed_library(
+    strings: &mut StringMap,
+    global_type_scope: &mut TypeScope,
+    typed_symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>,
+    external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
+    exported_procedures: &[NamespacePath]
+) -> Result<(Vec<IrSymbol>, Vec<(NamespacePath, usize, Vec<StringIdx>)>), Error> {
+    let mut interpreter = Interpreter::new(strings);
+    let mut ir_symbols = Vec::new();
+    let mut exported = Vec::new();
+    for path in exported_procedures {
+        match typed_symbols.get(path) {
+            Some(Symbol::Procedure {
+                public: true, parameter_names, parameter_defaults: _, parameter_types, returns, body, source, type_scope
+            }) if body.is_some() => {
+                for types in parameter_types.iter().chain([returns]) {
+                    if is_exportable(*types, type_scope, true) { continue; }
+                    return Err(Error::new([
+                        ErrorSection::Error(ErrorType::UnexportableProcedure(
+                            path.display(strings), display_types(strings, type_scope, *types)
+                        )),
+                        ErrorSection::Code(*source),
+                        ErrorSection::Help(String::from("Exported procedures may only use scalars, strings, arrays of those and objects made up of those. Annotate any parameters or return types that could be more than one type."))
+                    ].into()));
+                }
+                let variant = IrGenerator::find_procedure(
+                    path, type_scope.clone(), global_type_scope, parameter_types.clone(), *returns,
+                    parameter_names, body, &HashMap::new(), typed_symbols, strings,
+                    external_backings, &mut interpreter, &mut ir_symbols
+                )?;
+                exported.push((path.clone(), variant, parameter_names.clone()));
+            }
+            _ => return Err(Error::new([
+                ErrorSection::Error(ErrorType::InvalidExportedProcedure(path.display(strings))),
+                ErrorSection::Help(String::from("Only public procedures that are not implemented externally may be exported."))
+            ].into()))
+        }
+    }
+    lower_constants(strings, typed_symbols, external_backings, &mut interpreter, &mut ir_symbols)?;
+    Ok((ir_symbols, exported))
+}
+
+fn lower_constants(
+    strings: &mut StringMap,
+    typed_symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>,
+    external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
+    interpreter: &mut Interpreter,
+    ir_symbols: &mut Vec<IrSymbol>
+) -> Result<(), Error> {
+    let mut typed_symbols_sorted = typed_symbols.iter()
+        .collect::<Vec<(&NamespacePath, &Symbol<TypedAstNode>)>>();
+    typed_symbols_sorted.sort_by_key(|(symbol_path, _)| symbol_path.display(strings));
+    for (symbol_path, typed_symbol) in typed_symbols_sorted {
         match typed_symbol {
             Symbol::Constant { public: _, value, value_types } => {
                 if let Some(value) = value {
@@ -92,10 +178,10 @@ pub fn lower_typed_ast(
                         value_type: *value_types,
                         value: v
                     });
-                } else {
+                } else if let Some(backing) = external_backings.get(symbol_path).expect("should be external") {
                     ir_symbols.push(IrSymbol::ExternalVariable {
                         path: symbol_path.clone(),
-                        backing: *external_backings.get(symbol_path).expect("should have backing"),
+                        backing: *backing,
                         value_type: *value_types
                     });
                 }
@@ -103,11 +189,28 @@ pub fn lower_typed_ast(
             Symbol::Procedure { .. } => {
                 // IrGenerator will lower needed procedures
             }
+            Symbol::Type { .. } => {}
         }
     }
-    Ok(ir_symbols)
+    Ok(())
+}
+
+
+struct DecisionRow<'p> {
+    patterns: Vec<Option<&'p Pattern<TypedAstNode>>>,
+    bindings: Vec<(StringIdx, IrVariable)>,
+    branch: usize
 }
 
+fn is_irrefutable(pattern: Option<&Pattern<TypedAstNode>>) -> bool {
+    match pattern {
+        None |
+        Some(Pattern::Wildcard { .. }) |
+        Some(Pattern::Variable { .. }) => true,
+        Some(Pattern::Value { value, .. }) => matches!(value.node_variant(), AstNodeVariant::UnitLiteral),
+        Some(_) => false
+    }
+}
 
 struct IrGenerator {
     instructions: Vec<Vec<IrInstruction>>,
@@ -133,7 +236,7 @@ impl IrGenerator {
         mut named_variables: HashMap<StringIdx, usize>,
         symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>,
         strings: &mut StringMap,
-        external_backings: &HashMap<NamespacePath, StringIdx>,
+        external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
         call_parameters: &(HashMap<StringIdx, usize>, Vec<TypeGroup>),
         interpreter: &mut Interpreter,
         ir_symbols: &mut Vec<IrSymbol>
@@ -149,6 +252,54 @@ impl IrGenerator {
         Ok(self.exit())
     }
 
+    fn lower_case_body(
+        &mut self,
+        nodes: &[TypedAstNode],
+        result: Option<IrVariable>,
+        captured: &HashMap<StringIdx, TypeGroup>,
+        type_scope: &mut TypeScope,
+        global_type_scope: &mut TypeScope,
+        mut named_variables: HashMap<StringIdx, usize>,
+        symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>,
+        strings: &mut StringMap,
+        external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
+        call_parameters: &(HashMap<StringIdx, usize>, Vec<TypeGroup>),
+        interpreter: &mut Interpreter,
+        ir_symbols: &mut Vec<IrSymbol>
+    ) -> Result<Vec<IrInstruction>, Error> {
+        let result = if let Some(result) = result { result } else {
+            return self.lower_nodes(
+                nodes, captured, type_scope, global_type_scope, named_variables,
+                symbols, strings, external_backings, call_parameters, interpreter,
+                ir_symbols
+            );
+        };
+        self.enter();
+        let (value, statements) = nodes.split_last().expect("grammar checker should enforce a value for each branch");
+        for node in statements {
+            self.lower_node(
+                node, None, captured, type_scope, global_type_scope, &mut named_variables,
+                symbols, strings, external_backings, call_parameters, interpreter,
+                ir_symbols
+            )?;
+        }
+        let result_var = &mut self.variables[result.index];
+        result_var.0 += 1;
+        let into = IrVariable {
+            index: result.index,
+            version: result_var.0
+        };
+        let value = self.lower_node(
+            value, Some(into), captured, type_scope, global_type_scope, &mut named_variables,
+            symbols, strings, external_backings, call_parameters, interpreter,
+            ir_symbols
+        )?.expect("should result in a value");
+        if value.index != into.index {
+            self.add(IrInstruction::Move { from: value, into });
+        }
+        Ok(self.exit())
+    }
+
     fn enter(&mut self) {
         self.instructions.push(Vec::new());
     }
@@ -235,7 +386,7 @@ impl IrGenerator {
         captured: &HashMap<StringIdx, TypeGroup>,
         symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>,
         strings: &mut StringMap,
-        external_backings: &HashMap<NamespacePath, StringIdx>,
+        external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
         interpreter: &mut Interpreter,
         ir_symbols: &mut Vec<IrSymbol>
     ) -> Result<usize, Error> {
@@ -318,10 +469,10 @@ impl IrGenerator {
                 } else {
                     panic!("procedure should exist");
                 }
-            } else if let Some(backing) = external_backings.get(path) {
+            } else if let Some(backing) = external_backings.get(path).copied().flatten() {
                 ir_symbols.push(IrSymbol::ExternalProcedure {
                     path: path.clone(),
-                    backing: *backing, 
+                    backing, 
                     parameter_types: call_parameter_types,
                     return_type: call_return_type,
                     type_scope
@@ -339,6 +490,262 @@ impl IrGenerator {
         Ok(found_variant)
     }
 
+    fn lower_pattern(
+        &mut self,
+        pattern: &Pattern<TypedAstNode>,
+        value: IrVariable,
+        named_variables: &mut HashMap<StringIdx, usize>
+    ) {
+        match pattern {
+            Pattern::Variable { name, source: _, value_types } => {
+                let var = self.allocate(value_types.expect("should have type info"));
+                named_variables.insert(*name, var.index);
+                self.add(IrInstruction::Move { from: value, into: var });
+            }
+            Pattern::Wildcard { .. } => {}
+            Pattern::Value { .. } |
+            Pattern::Variant { .. } => panic!("the parser should only allow these patterns in 'case'"),
+            Pattern::Object { members, source: _, value_types: _ } => {
+                for (member, member_pattern) in members {
+                    let into = self.allocate(member_pattern.value_types().expect("should have type info"));
+                    self.add(IrInstruction::GetObjectMember { accessed: value, member: *member, into });
+                    self.lower_pattern(member_pattern, into, named_variables);
+                }
+            }
+            Pattern::Array { elements, source: _, value_types: _, index_types } => {
+                let index_types = index_types.expect("should have type info");
+                for (element_index, element_pattern) in elements.iter().enumerate() {
+                    let index = self.allocate(index_types);
+                    self.add(IrInstruction::LoadInteger { value: element_index as i64, into: index });
+                    let into = self.allocate(element_pattern.value_types().expect("should have type info"));
+                    self.add(IrInstruction::GetArrayElement {
+                        accessed: value, index, into, source: element_pattern.source()
+                    });
+                    self.lower_pattern(element_pattern, into, named_variables);
+                }
+            }
+        }
+    }
+
+    fn lower_decision<'p>(
+        &mut self,
+        mut rows: Vec<DecisionRow<'p>>,
+        mut columns: Vec<IrVariable>,
+        branch_nodes: &[(Pattern<TypedAstNode>, Option<TypedAstNode>, Vec<TypedAstNode>)],
+        else_body: &Option<Vec<TypedAstNode>>,
+        result: Option<IrVariable>,
+        captured: &HashMap<StringIdx, TypeGroup>,
+        type_scope: &mut TypeScope,
+        global_type_scope: &mut TypeScope,
+        named_variables: &HashMap<StringIdx, usize>,
+        symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>,
+        strings: &mut StringMap,
+        external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
+        call_parameters: &(HashMap<StringIdx, usize>, Vec<TypeGroup>),
+        interpreter: &mut Interpreter,
+        ir_symbols: &mut Vec<IrSymbol>
+    ) -> Result<Vec<IrInstruction>, Error> {
+        macro_rules! lower_decision { ($rows: expr, $columns: expr) => {
+            self.lower_decision(
+                $rows, $columns, branch_nodes, else_body, result, captured, type_scope, global_type_scope,
+                named_variables, symbols, strings, external_backings, call_parameters,
+                interpreter, ir_symbols
+            )?
+        } }
+        if rows.is_empty() {
+            return if let Some(else_body) = else_body {
+                self.lower_case_body(
+                    else_body, result, captured, type_scope, global_type_scope,
+                    named_variables.clone(), symbols, strings, external_backings,
+                    call_parameters, interpreter, ir_symbols
+                )
+            } else { Ok(Vec::new()) };
+        }
+        self.enter();
+        // object patterns always match, so their members simply become new columns
+        while let Some(column) = (0..columns.len()).find(|c| rows.iter().any(|row|
+            matches!(row.patterns[*c], Some(Pattern::Object { .. }))
+        )) {
+            let mut members = Vec::new();
+            for row in &rows {
+                if let Some(Pattern::Object { members: row_members, .. }) = row.patterns[column] {
+                    for (member, member_pattern) in row_members {
+                        if members.iter().any(|(m, _)| m == member) { continue; }
+                        let into = self.allocate(member_pattern.value_types().expect("should have type info"));
+                        self.add(IrInstruction::GetObjectMember { accessed: columns[column], member: *member, into });
+                        members.push((*member, into));
+                    }
+                }
+            }
+            for row in &mut rows {
+                let pattern = row.patterns.remove(column);
+                if let Some(Pattern::Variable { name, .. }) = pattern {
+                    row.bindings.push((*name, columns[column]));
+                }
+                for (member, _) in &members {
+                    row.patterns.push(if let Some(Pattern::Object { members: row_members, .. }) = pattern {
+                        row_members.iter().find(|(m, _)| m == member).map(|(_, p)| p)
+                    } else { None });
+                }
+            }
+            columns.remove(column);
+            columns.extend(members.iter().map(|(_, into)| *into));
+        }
+        let column = (0..columns.len()).find(|c| !is_irrefutable(rows[0].patterns[*c]));
+        let column = if let Some(column) = column { column } else {
+            let row = rows.remove(0);
+            let mut branch_variables = named_variables.clone();
+            for (name, from) in row.bindings.iter().copied().chain(
+                row.patterns.iter().zip(&columns).filter_map(|(pattern, column)|
+                    if let Some(Pattern::Variable { name, .. }) = pattern {
+                        Some((*name, *column))
+                    } else { None }
+                )
+            ) {
+                let into = self.allocate(self.variables[from.index].1);
+                self.add(IrInstruction::Move { from, into });
+                branch_variables.insert(name, into.index);
+            }
+            let (_, guard, body) = &branch_nodes[row.branch];
+            let condition = if let Some(guard) = guard {
+                Some(self.lower_node(
+                    guard, None, captured, type_scope, global_type_scope, &mut branch_variables,
+                    symbols, strings, external_backings, call_parameters, interpreter, ir_symbols
+                )?.expect("should result in a value"))
+            } else { None };
+            let branch_body = self.lower_case_body(
+                body, result, captured, type_scope, global_type_scope,
+                branch_variables, symbols, strings, external_backings,
+                call_parameters, interpreter, ir_symbols
+            )?;
+            if let Some(value) = condition {
+                let mut branch_scopes = Vec::new();
+                branch_scopes.push(self.variables.iter().map(|v| v.0).collect());
+                let else_branch = lower_decision!(rows, columns);
+                branch_scopes.push(self.variables.iter().map(|v| v.0).collect());
+                self.add(IrInstruction::BranchOnValue {
+                    value,
+                    branches: vec![(Value::Boolean(true), branch_body)],
+                    else_branch
+                });
+                self.insert_phi(&branch_scopes);
+            } else {
+                for instruction in branch_body {
+                    self.add(instruction);
+                }
+            }
+            return Ok(self.exit());
+        };
+        let value = columns.remove(column);
+        let mut default_rows = Vec::new();
+        let mut tested_rows = Vec::new();
+        for mut row in rows {
+            let pattern = row.patterns.remove(column);
+            if is_irrefutable(pattern) {
+                if let Some(Pattern::Variable { name, .. }) = pattern {
+                    row.bindings.push((*name, value));
+                }
+                default_rows.push(row);
+            } else {
+                tested_rows.push((row, pattern));
+            }
+        }
+        let mut branch_scopes = Vec::new();
+        if let Some(Some(Pattern::Variant { .. })) = tested_rows.first().map(|(_, p)| *p) {
+            let mut variants: Vec<(StringIdx, Option<IrVariable>)> = Vec::new();
+            for (_, pattern) in &tested_rows {
+                if let Some(Pattern::Variant { name, payload, .. }) = pattern {
+                    let variant = if let Some(variant) = variants.iter_mut().find(|(n, _)| n == name) {
+                        variant
+                    } else {
+                        variants.push((*name, None));
+                        variants.last_mut().unwrap()
+                    };
+                    if let (None, Some(payload)) = (variant.1, payload) {
+                        variant.1 = Some(self.allocate(payload.value_types().expect("should have type info")));
+                    }
+                }
+            }
+            let mut branches = Vec::new();
+            for (variant, payload_variable) in variants {
+                let mut branch_rows = Vec::new();
+                let mut branch_columns = columns.clone();
+                branch_columns.extend(payload_variable);
+                let mut tested = tested_rows.iter().peekable();
+                let mut default = default_rows.iter().peekable();
+                loop {
+                    let take_tested = match (tested.peek(), default.peek()) {
+                        (Some((tested_row, _)), Some(default_row)) => tested_row.branch < default_row.branch,
+                        (Some(_), None) => true,
+                        (None, Some(_)) => false,
+                        (None, None) => break
+                    };
+                    let (row, payload) = if take_tested {
+                        let (row, pattern) = tested.next().unwrap();
+                        if let Some(Pattern::Variant { name, payload, .. }) = pattern {
+                            if *name != variant { continue; }
+                            (row, payload.as_deref())
+                        } else { panic!("patterns in the same column should be of the same kind"); }
+                    } else {
+                        (default.next().unwrap(), None)
+                    };
+                    let mut patterns = row.patterns.clone();
+                    if payload_variable.is_some() { patterns.push(payload); }
+                    branch_rows.push(DecisionRow { patterns, bindings: row.bindings.clone(), branch: row.branch });
+                }
+                let branch_body = lower_decision!(branch_rows, branch_columns);
+                branches.push((variant, payload_variable, branch_body));
+                branch_scopes.push(self.variables.iter().map(|v| v.0).collect());
+            }
+            let else_branch = lower_decision!(default_rows, columns);
+            branch_scopes.push(self.variables.iter().map(|v| v.0).collect());
+            self.add(IrInstruction::BranchOnVariant {
+                value,
+                branches,
+                else_branch
+            });
+        } else {
+            let mut tested_values = Vec::new();
+            for (row, pattern) in &tested_rows {
+                if let Some(Pattern::Value { value: value_node, .. }) = pattern {
+                    let row_value = interpreter.evaluate_node(value_node, symbols, external_backings, strings)?;
+                    enforce_valid_constant_value(&row_value, value_node.source())?;
+                    tested_values.push((row, row_value));
+                } else { panic!("patterns in the same column should be of the same kind"); }
+            }
+            let mut values: Vec<Value> = Vec::new();
+            for (_, row_value) in &tested_values {
+                if !values.contains(row_value) { values.push(row_value.clone()); }
+            }
+            let mut branches = Vec::new();
+            for branch_value in values {
+                let mut branch_rows: Vec<&DecisionRow> = tested_values.iter()
+                    .filter(|(_, row_value)| *row_value == branch_value)
+                    .map(|(row, _)| *row)
+                    .chain(default_rows.iter())
+                    .collect();
+                branch_rows.sort_by_key(|row| row.branch);
+                let branch_rows = branch_rows.into_iter()
+                    .map(|row| DecisionRow {
+                        patterns: row.patterns.clone(), bindings: row.bindings.clone(), branch: row.branch
+                    })
+                    .collect();
+                let branch_body = lower_decision!(branch_rows, columns.clone());
+                branches.push((branch_value, branch_body));
+                branch_scopes.push(self.variables.iter().map(|v| v.0).collect());
+            }
+            let else_branch = lower_decision!(default_rows, columns);
+            branch_scopes.push(self.variables.iter().map(|v| v.0).collect());
+            self.add(IrInstruction::BranchOnValue {
+                value,
+                branches,
+                else_branch
+            });
+        }
+        self.insert_phi(&branch_scopes);
+        Ok(self.exit())
+    }
+
     fn lower_node(
         &mut self,
         node: &TypedAstNode,
@@ -349,7 +756,7 @@ impl IrGenerator {
         named_variables: &mut HashMap<StringIdx, usize>,
         symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>,
         strings: &mut StringMap,
-        external_backings: &HashMap<NamespacePath, StringIdx>,
+        external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
         call_parameters: &(HashMap<StringIdx, usize>, Vec<TypeGroup>),
         interpreter: &mut Interpreter,
         ir_symbols: &mut Vec<IrSymbol>
@@ -363,6 +770,22 @@ impl IrGenerator {
         macro_rules! into_given_or_alloc { ($temp_type: expr) => {
             into.unwrap_or_else(|| self.allocate($temp_type))
         } }
+        macro_rules! case_result { () => {
+            if let Type::Unit = type_scope.group_concrete(node.get_types()) { None }
+            else { Some(into_given_or_alloc!(node.get_types())) }
+        } }
+        macro_rules! case_value { ($result: expr) => {
+            if let Some(result) = $result {
+                Ok(Some(IrVariable {
+                    index: result.index,
+                    version: self.variables[result.index].0
+                }))
+            } else {
+                let into = into_given_or_alloc!(node.get_types());
+                self.add(IrInstruction::LoadUnit { into });
+                Ok(Some(into))
+            }
+        } }
         match node.node_variant() {
             AstNodeVariant::Function { arguments, body } => {
                 let (parameter_types, return_type, body_captures) =
@@ -415,7 +838,7 @@ impl IrGenerator {
                 });
                 Ok(Some(into))
             }
-            AstNodeVariant::Variable { public: _, mutable: _, name, value_types, value } => {
+            AstNodeVariant::Variable { public: _, mutable: _, name, annotation: _, value_types, value } => {
                 let var = self.allocate(value_types.expect("should have type info"));
                 named_variables.insert(*name, var.index);
                 if let Some(value) = value {
@@ -425,8 +848,14 @@ impl IrGenerator {
                     Ok(None)
                 }
             }
+            AstNodeVariant::Destructuring { mutable: _, pattern, value } => {
+                let value = lower_node!(&*value, None);
+                self.lower_pattern(pattern, value, named_variables);
+                Ok(None)
+            }
             AstNodeVariant::CaseBranches { value, branches: branch_nodes, else_body } => {
                 let value = lower_node!(value, None);
+                let result = case_result!();
                 let mut branches = Vec::new();
                 let mut branch_scopes = Vec::new();
                 for branch in branch_nodes {
@@ -434,16 +863,16 @@ impl IrGenerator {
                         &branch.0, symbols, external_backings, strings
                     )?;
                     enforce_valid_constant_value(&branch_value, branch.0.source())?;
-                    let branch_body = self.lower_nodes(
-                        &branch.1, captured, type_scope, global_type_scope,
+                    let branch_body = self.lower_case_body(
+                        &branch.1, result, captured, type_scope, global_type_scope,
                         named_variables.clone(), symbols, strings, external_backings,
                         call_parameters, interpreter, ir_symbols
                     )?;
                     branches.push((branch_value, branch_body));
                     branch_scopes.push(self.variables.iter().map(|v| v.0).collect());
                 }
-                let else_branch = self.lower_nodes(
-                    &else_body, captured, type_scope, global_type_scope,
+                let else_branch = self.lower_case_body(
+                    &else_body, result, captured, type_scope, global_type_scope,
                     named_variables.clone(), symbols, strings, external_backings, call_parameters,
                     interpreter, ir_symbols
                 )?;
@@ -454,21 +883,22 @@ impl IrGenerator {
                     else_branch
                 });
                 self.insert_phi(&branch_scopes);
-                Ok(None)
+                case_value!(result)
             }
             AstNodeVariant::CaseConditon { condition, body, else_body } => {
                 let value = lower_node!(condition, None);
+                let result = case_result!();
                 let mut branch_scopes = Vec::new();
                 let branches = vec![
-                    (Value::Boolean(true), self.lower_nodes(
-                        &body, captured, type_scope, global_type_scope,
+                    (Value::Boolean(true), self.lower_case_body(
+                        &body, result, captured, type_scope, global_type_scope,
                         named_variables.clone(), symbols, strings, external_backings,
                         call_parameters, interpreter, ir_symbols
                     )?)
                 ];
                 branch_scopes.push(self.variables.iter().map(|v| v.0).collect());
-                let else_branch = self.lower_nodes(
-                    &else_body, captured, type_scope, global_type_scope,
+                let else_branch = self.lower_case_body(
+                    &else_body, result, captured, type_scope, global_type_scope,
                     named_variables.clone(), symbols, strings, external_backings, call_parameters,
                     interpreter, ir_symbols
                 )?;
@@ -479,10 +909,11 @@ impl IrGenerator {
                     else_branch
                 });
                 self.insert_phi(&branch_scopes);
-                Ok(None)
+                case_value!(result)
             }
             AstNodeVariant::CaseVariant { value, branches: branch_nodes, else_body } => {
                 let value = lower_node!(value, None);
+                let result = case_result!();
                 let mut branches = Vec::new();
                 let mut branch_scopes = Vec::new();
                 for branch in branch_nodes {
@@ -493,8 +924,8 @@ impl IrGenerator {
                         branch_variables.insert(*branch_var_variable, variant_var.index);
                         Some(variant_var)
                     } else { None };
-                    let branch_body = self.lower_nodes(
-                        &branch.2, captured, type_scope, global_type_scope,
+                    let branch_body = self.lower_case_body(
+                        &branch.2, result, captured, type_scope, global_type_scope,
                         branch_variables, symbols, strings, external_backings, call_parameters,
                         interpreter, ir_symbols
                     )?;
@@ -502,8 +933,8 @@ impl IrGenerator {
                     branch_scopes.push(self.variables.iter().map(|v| v.0).collect());
                 }
                 let else_branch = if let Some(else_body) = else_body {
-                    self.lower_nodes(
-                        &else_body, captured, type_scope, global_type_scope,
+                    self.lower_case_body(
+                        &else_body, result, captured, type_scope, global_type_scope,
                         named_variables.clone(), symbols, strings, external_backings,
                         call_parameters, interpreter, ir_symbols
                     )?
@@ -515,7 +946,25 @@ impl IrGenerator {
                     else_branch
                 });
                 self.insert_phi(&branch_scopes);
-                Ok(None)
+                case_value!(result)
+            }
+            AstNodeVariant::CasePattern { value, branches: branch_nodes, else_body } => {
+                let value = lower_node!(value, None);
+                let result = case_result!();
+                let rows = branch_nodes.iter().enumerate()
+                    .map(|(branch, (pattern, _, _))| DecisionRow {
+                        patterns: vec![Some(pattern)], bindings: Vec::new(), branch
+                    })
+                    .collect();
+                let instructions = self.lower_decision(
+                    rows, vec![value], branch_nodes, else_body, result, captured, type_scope, global_type_scope,
+                    named_variables, symbols, strings, external_backings, call_parameters,
+                    interpreter, ir_symbols
+                )?;
+                for instruction in instructions {
+                    self.add(instruction);
+                }
+                case_value!(result)
             }
             AstNodeVariant::Assignment { variable, value } => {
                 match variable.node_variant() {
@@ -555,12 +1004,36 @@ impl IrGenerator {
                 self.add(IrInstruction::Return { value });
                 Ok(None)
             }
+            AstNodeVariant::Propagate { value, returned_types } => {
+                let error_types = if let Type::Variants(variants_idx) = type_scope.group_concrete(value.get_types()) {
+                    *type_scope.variants(variants_idx).0.get(&strings.insert("err")).expect("should have an error variant")
+                } else { panic!("propagated value should be a variant"); };
+                let value = lower_node!(&*value, None);
+                let into = into_given_or_alloc!(node.get_types());
+                let error = self.allocate(error_types);
+                let returned = self.allocate(returned_types.expect("should have type info"));
+                let error_tag = strings.insert("err");
+                // no need to call 'insert_phi', as neither branch can mutate variables
+                self.add(IrInstruction::BranchOnVariant {
+                    value,
+                    branches: vec![
+                        (strings.insert("ok"), Some(into), Vec::new()),
+                        (error_tag, Some(error), vec![
+                            IrInstruction::LoadVariant { name: error_tag, v: error, into: returned },
+                            IrInstruction::Return { value: returned }
+                        ])
+                    ],
+                    else_branch: Vec::new()
+                });
+                Ok(Some(into))
+            }
             AstNodeVariant::Call { called, arguments } => {
                 if let AstNodeVariant::ModuleAccess { path } = called.node_variant() {
                     if let Symbol::Procedure {
-                        public: _, parameter_names, parameter_types, returns, body, source: _,
+                        public: _, parameter_names, parameter_defaults: _, parameter_types, returns, body, source: _,
                         type_scope: symbol_type_scope
                     } = symbols.get(path).expect("symbol should exist") {
+                        enforce_external_backing(path, external_backings, node.source(), strings)?;
                         let mut call_type_scope = symbol_type_scope.clone();
                         let mut call_parameter_types = Vec::new(); // inside of 'call_type_scope'!
                         let mut parameter_values = Vec::new();
@@ -578,7 +1051,7 @@ impl IrGenerator {
                                 lower_node!(&arguments[argument_idx], None)
                             );
                         }
-                        let call_return_type = type_scope.transfer_group(
+                        let mut call_return_type = type_scope.transfer_group(
                             node.get_types(), &mut call_type_scope
                         );
                         call_type_scope.try_merge_groups(call_return_type, *returns);
@@ -586,6 +1059,18 @@ impl IrGenerator {
                             call_return_type, type_scope
                         );
                         type_scope.try_merge_groups(og_returns, node.get_types());
+                        if external_backings.contains_key(path) {
+                            for (param_idx, declared_type) in parameter_types.iter().enumerate() {
+                                if let Type::ConcreteObject(_) = symbol_type_scope.group_concrete(*declared_type) {
+                                    call_parameter_types[param_idx] = symbol_type_scope.transfer_group(
+                                        *declared_type, &mut call_type_scope
+                                    );
+                                }
+                            }
+                            if let Type::ConcreteObject(_) = symbol_type_scope.group_concrete(*returns) {
+                                call_return_type = symbol_type_scope.transfer_group(*returns, &mut call_type_scope);
+                            }
+                        }
                         let proc_variant = IrGenerator::find_procedure(
                             path, call_type_scope, global_type_scope, call_parameter_types,
                             call_return_type, parameter_names, body, captured, symbols, strings,
@@ -749,6 +1234,47 @@ impl IrGenerator {
                 self.add(IrInstruction::Negate { x, into });
                 Ok(Some(into))
             }
+            AstNodeVariant::BitwiseAnd { a, b } => {
+                let a = lower_node!(&*a, None);
+                let b = lower_node!(&*b, None);
+                let into = into_given_or_alloc!(node.get_types());
+                self.add(IrInstruction::BitwiseAnd { a, b, into });
+                Ok(Some(into))
+            }
+            AstNodeVariant::BitwiseOr { a, b } => {
+                let a = lower_node!(&*a, None);
+                let b = lower_node!(&*b, None);
+                let into = into_given_or_alloc!(node.get_types());
+                self.add(IrInstruction::BitwiseOr { a, b, into });
+                Ok(Some(into))
+            }
+            AstNodeVariant::BitwiseXor { a, b } => {
+                let a = lower_node!(&*a, None);
+                let b = lower_node!(&*b, None);
+                let into = into_given_or_alloc!(node.get_types());
+                self.add(IrInstruction::BitwiseXor { a, b, into });
+                Ok(Some(into))
+            }
+            AstNodeVariant::BitwiseNot { x } => {
+                let x = lower_node!(&*x, None);
+                let into = into_given_or_alloc!(node.get_types());
+                self.add(IrInstruction::BitwiseNot { x, into });
+                Ok(Some(into))
+            }
+            AstNodeVariant::LeftShift { a, b } => {
+                let a = lower_node!(&*a, None);
+                let b = lower_node!(&*b, None);
+                let into = into_given_or_alloc!(node.get_types());
+                self.add(IrInstruction::LeftShift { a, b, into });
+                Ok(Some(into))
+            }
+            AstNodeVariant::RightShift { a, b } => {
+                let a = lower_node!(&*a, None);
+                let b = lower_node!(&*b, None);
+                let into = into_given_or_alloc!(node.get_types());
+                self.add(IrInstruction::RightShift { a, b, into });
+                Ok(Some(into))
+            }
             AstNodeVariant::LessThan { a, b } => {
                 let a = lower_node!(&*a, None);
                 let b = lower_node!(&*b, None);
@@ -862,6 +1388,7 @@ impl IrGenerator {
             AstNodeVariant::ModuleAccess { path } => {
                 match symbols.get(path).expect("should exist") {
                     Symbol::Constant { .. } => {
+                        enforce_external_backing(path, external_backings, node.source(), strings)?;
                         let into = into_given_or_alloc!(node.get_types());
                         self.add(IrInstruction::LoadGlobalVariable { path: path.clone(), into });
                         Ok(Some(into))
@@ -869,6 +1396,9 @@ impl IrGenerator {
                     Symbol::Procedure { .. } => {
                         panic!("should have been expanded by the type checker!");
                     }
+                    Symbol::Type { .. } => {
+                        panic!("should have been rejected by the type checker!");
+                    }
                 }
             }
             AstNodeVariant::Variant { name, value } => {
@@ -0,0 +1,1024 @@
+
+use std::collections::HashMap;
+
+use crate::backend::{
+    ir::{IrSymbol, IrInstruction, IrVariable},
+    constants::{ConstantValue, ConstantPool, ConstantPoolValue}
+};
+use crate::frontend::{
+    modules::NamespacePath,
+    types::{TypeGroup, TypeScope, Type}
+};
+use crate::util::{
+    strings::{StringMap, StringIdx},
+    source::SourceRange
+};
+
+// Lua only allows 200 locals per function, variables past this go into a table
+const MAX_LOCAL_VARIABLES: usize = 128;
+
+const LUA_KEYWORDS: &[&str] = &[
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if",
+    "in", "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while"
+];
+
+pub fn generate_lua(
+    symbols: Vec<IrSymbol>,
+    mut types: TypeScope,
+    main_procedure_path: NamespacePath,
+    strings: &mut StringMap
+) -> String {
+    types.replace_any_with_unit();
+    let mut output = String::new();
+    let externals = collect_externals(&symbols);
+    emit_core_library(&mut output);
+    output.push_str("local gera___symbols = {}\n");
+    output.push_str("local gera___constants = {}\n");
+    let mut constants = ConstantPool::new();
+    let mut constant_deps = String::new();
+    emit_static_variables(&symbols, &types, &mut constants, strings, &mut constant_deps);
+    constant_deps.push('\n');
+    emit_procedure_impls(&symbols, &mut constants, strings, &externals, &mut constant_deps);
+    output.push('\n');
+    emit_constant_declarations(&constants, strings, &mut output);
+    output.push('\n');
+    output.push_str(&constant_deps);
+    output.push('\n');
+    emit_main_function(&main_procedure_path, strings, &mut output);
+    output
+}
+
+fn collect_externals(symbols: &Vec<IrSymbol>) -> HashMap<NamespacePath, StringIdx> {
+    let mut external = HashMap::new();
+    for symbol in symbols {
+        match symbol {
+            IrSymbol::Procedure { .. } => {}
+            IrSymbol::BuiltInProcedure { .. } => {}
+            IrSymbol::Variable { .. } => {}
+            IrSymbol::ExternalProcedure { path, backing, .. } |
+            IrSymbol::ExternalVariable { path, backing, .. } => {
+                external.insert(path.clone(), *backing);
+            }
+        }
+    }
+    return external;
+}
+
+fn emit_core_library(output: &mut String) {
+    output.push_str(include_str!("./core/core.lua"));
+    output.push('\n');
+}
+
+fn emit_static_variables(
+    symbols: &Vec<IrSymbol>,
+    types: &TypeScope,
+    constants: &mut ConstantPool,
+    strings: &mut StringMap,
+    output: &mut String
+) {
+    for symbol in symbols {
+        match symbol {
+            IrSymbol::Procedure { .. } |
+            IrSymbol::ExternalProcedure { .. } |
+            IrSymbol::BuiltInProcedure { .. } => {}
+            IrSymbol::Variable { path, value_type, value } => {
+                emit_symbol_name(path, strings, output);
+                output.push_str(" = ");
+                let value = constants.insert(value, *value_type, types);
+                emit_value(&value, constants, strings, output);
+                output.push('\n');
+            }
+            IrSymbol::ExternalVariable { .. } => {}
+        }
+    }
+}
+
+fn emit_variant_tag(variant_name: &str, output: &mut String) {
+    output.push_str("\"");
+    output.push_str(variant_name);
+    output.push_str("\"");
+}
+
+fn emit_variable(variable: IrVariable, output: &mut String) {
+    if variable.index < MAX_LOCAL_VARIABLES {
+        output.push_str("local");
+        output.push_str(&variable.index.to_string());
+    } else {
+        output.push_str("locals[");
+        output.push_str(&variable.index.to_string());
+        output.push(']');
+    }
+}
+
+fn emit_variable_declarations(variables: &Vec<TypeGroup>, output: &mut String) {
+    for variable_idx in 0..variables.len().min(MAX_LOCAL_VARIABLES) {
+        output.push_str("local ");
+        emit_variable(IrVariable { index: variable_idx, version: 0 }, output);
+        output.push('\n');
+    }
+    if variables.len() > MAX_LOCAL_VARIABLES {
+        output.push_str("local locals = {}\n");
+    }
+}
+
+fn emit_parameters(parameter_types: &Vec<TypeGroup>, output: &mut String) {
+    output.push('(');
+    let mut had_param = false;
+    for p in 0..parameter_types.len() {
+        if had_param { output.push_str(", "); }
+        had_param = true;
+        output.push_str("param");
+        output.push_str(&p.to_string());
+    }
+    output.push(')');
+}
+
+fn get_builtin_bodies(strings: &mut StringMap) -> HashMap<NamespacePath, fn(&Vec<TypeGroup>, TypeGroup, &TypeScope, &mut StringMap) -> String> {
+    fn path_from(segments: &[&'static str], strings: &mut StringMap) -> NamespacePath {
+        NamespacePath::new(segments.iter().map(|s| strings.insert(s)).collect())
+    }
+    let mut builtins: HashMap<NamespacePath, fn(&Vec<TypeGroup>, TypeGroup, &TypeScope, &mut StringMap) -> String> = HashMap::new();
+    builtins.insert(path_from(&["core", "addr_eq"], strings), |_, _, _, _| {
+        String::from(r#"
+return rawequal(param0, param1)
+"#)
+    });
+    builtins.insert(path_from(&["core", "tag_eq"], strings), |_, _, _, _| {
+        String::from(r#"
+return param0.tag == param1.tag
+"#)
+    });
+    builtins.insert(path_from(&["core", "length"], strings), |param_types, _, types, _| {
+        match types.group_concrete(param_types[0]) {
+            Type::String => String::from(r#"
+return utf8.len(param0)
+"#),
+            _ => String::from(r#"
+return param0.n
+"#)
+        }
+    });
+    builtins.insert(path_from(&["core", "array"], strings), |_, _, _, _| {
+        String::from(r#"
+if param1 < 0 then
+    gera___panic(string.format("the array length %d is not valid", param1))
+end
+local result = setmetatable({ n = param1 }, gera___array)
+for i = 1, param1 do result[i] = param0 end
+return result
+"#)
+    });
+    builtins.insert(path_from(&["core", "exhaust"], strings), |_, _, _, _| {
+        String::from(r#"
+while param0().tag == "next" do end
+"#)
+    });
+    builtins.insert(path_from(&["core", "panic"], strings), |_, _, _, _| {
+        String::from(r#"
+gera___panic(param0)
+"#)
+    });
+    builtins.insert(path_from(&["core", "as_str"], strings), |param_types, _, types, strings| {
+        match types.group_concrete(param_types[0]) {
+            Type::Unit | Type::Any => String::from(r#"
+return "<unit>"
+"#),
+            Type::Boolean |
+            Type::Integer |
+            Type::Float => String::from(r#"
+return tostring(param0)
+"#),
+            Type::String => String::from(r#"
+return param0
+"#),
+            Type::Array(_) => String::from(r#"
+return "<array>"
+"#),
+            Type::Object(_) |
+            Type::ConcreteObject(_) => String::from(r#"
+return "<object>"
+"#),
+            Type::Variants(variant_idx) => {
+                let variant_types = &types.variants(variant_idx).0;
+                let mut result = String::new();
+                for (variant_name, _) in variant_types {
+                    result.push_str("if param0.tag == ");
+                    emit_variant_tag(strings.get(*variant_name), &mut result);
+                    result.push_str(" then return \"#");
+                    result.push_str(strings.get(*variant_name));
+                    result.push_str(" <...>\" end\n");
+                }
+                result
+            }
+            Type::Closure(_) => String::from(r#"
+return "<closure>"
+"#)
+        }
+    });
+    builtins.insert(path_from(&["core", "as_int"], strings), |param_types, _, types, _| {
+        match types.group_concrete(param_types[0]) {
+            Type::Float => String::from(r#"
+local truncated = param0 < 0 and math.ceil(param0) or math.floor(param0)
+return math.tointeger(truncated) or 0
+"#),
+            _ => String::from(r#"
+return param0
+"#)
+        }
+    });
+    builtins.insert(path_from(&["core", "as_flt"], strings), |_, _, _, _| {
+        String::from(r#"
+return param0 + 0.0
+"#)
+    });
+    builtins.insert(path_from(&["core", "substring"], strings), |_, _, _, _| {
+        String::from(r#"
+local length = utf8.len(param0)
+local start_idx = param1
+if param1 < 0 then start_idx = length + param1 end
+if start_idx > length then
+    gera___panic(string.format("the start index %d is out of bounds for a string of length %d", param1, length))
+end
+local end_idx = param2
+if param2 < 0 then end_idx = length + param2 end
+if end_idx > length then
+    gera___panic(string.format("the end index %d is out of bounds for a string of length %d", param2, length))
+end
+if start_idx > end_idx then
+    gera___panic(string.format("the start index %d is larger than the end index %d (length of string is %d)", param1, param2, length))
+end
+return string.sub(param0, utf8.offset(param0, start_idx + 1), utf8.offset(param0, end_idx + 1) - 1)
+"#)
+    });
+    builtins.insert(path_from(&["core", "concat"], strings), |_, _, _, _| {
+        String::from(r#"
+return param0 .. param1
+"#)
+    });
+    builtins.insert(path_from(&["core", "parse_flt"], strings), |_, _, _, _| {
+        String::from(r#"
+local r = tonumber(param0)
+if r == nil then return { tag = "none", value = nil } end
+return { tag = "some", value = r + 0.0 }
+"#)
+    });
+    builtins.insert(path_from(&["core", "parse_int"], strings), |_, _, _, _| {
+        String::from(r#"
+local r = nil
+if string.match(param0, "^[+-]?%d+$") then r = math.tointeger(tonumber(param0)) end
+if r == nil then return { tag = "none", value = nil } end
+return { tag = "some", value = r }
+"#)
+    });
+    builtins.insert(path_from(&["core", "string"], strings), |_, _, _, _| {
+        String::from(r#"
+return string.rep(param0, param1)
+"#)
+    });
+    builtins.insert(path_from(&["core", "hash"], strings), |_, _, _, _| {
+        String::from(r#"
+return gera___hash(param0)
+"#)
+    });
+    return builtins;
+}
+
+fn emit_procedure_impls(
+    symbols: &Vec<IrSymbol>,
+    constants: &mut ConstantPool,
+    strings: &mut StringMap,
+    external: &HashMap<NamespacePath, StringIdx>,
+    output: &mut String
+) {
+    let builtin_bodies = get_builtin_bodies(strings);
+    for symbol in symbols {
+        match symbol {
+            IrSymbol::Procedure { path, variant, parameter_types, return_type: _, variables, body, type_scope } => {
+                let mut types = type_scope.clone();
+                types.replace_any_with_unit();
+                output.push_str("function ");
+                emit_procedure_name(path, *variant, strings, output);
+                emit_parameters(parameter_types, output);
+                output.push('\n');
+                let mut body_str = String::new();
+                emit_variable_declarations(variables, &mut body_str);
+                emit_block(
+                    body, variables, &types, constants, external, symbols, strings, &mut body_str
+                );
+                indent(&body_str, output);
+                output.push_str("end\n");
+            }
+            IrSymbol::BuiltInProcedure { path, variant, parameter_types, return_type, type_scope } => {
+                let mut types = type_scope.clone();
+                types.replace_any_with_unit();
+                output.push_str("function ");
+                emit_procedure_name(path, *variant, strings, output);
+                emit_parameters(parameter_types, output);
+                output.push('\n');
+                let body_str = (builtin_bodies
+                    .get(path)
+                    .expect("builtin should have implementation"))
+                    (parameter_types, *return_type, &types, strings);
+                indent(&body_str, output);
+                output.push_str("end\n");
+            }
+            _ => {}
+        }
+    }
+}
+
+fn emit_constant_name(idx: usize, output: &mut String) {
+    output.push_str("gera___constants[");
+    output.push_str(&idx.to_string());
+    output.push(']');
+}
+
+fn emit_constant_declarations(constants: &ConstantPool, strings: &StringMap, output: &mut String) {
+    for vi in 0..constants.get_value_count() {
+        match constants.get_value(vi) {
+            ConstantPoolValue::String(v) => {
+                emit_constant_name(vi, output);
+                output.push_str(" = ");
+                emit_string_literal(v, output);
+                output.push('\n');
+            }
+            ConstantPoolValue::Array(values, _) => {
+                emit_constant_name(vi, output);
+                output.push_str(" = setmetatable({\n    n = ");
+                output.push_str(&values.len().to_string());
+                output.push_str(",\n");
+                for value in values.iter() {
+                    output.push_str("    ");
+                    emit_value(value, constants, strings, output);
+                    output.push_str(",\n");
+                }
+                output.push_str("}, gera___array)\n");
+            }
+            ConstantPoolValue::Object(members) => {
+                emit_constant_name(vi, output);
+                output.push_str(" = {\n");
+                for (member_name, (member_value, _)) in members {
+                    output.push_str("    ");
+                    emit_member_key(*member_name, strings, output);
+                    emit_value(member_value, constants, strings, output);
+                    output.push_str(",\n");
+                }
+                output.push_str("}\n");
+            }
+            ConstantPoolValue::Variant(_, _, _) => {}
+        }
+    }
+}
+
+fn emit_main_function(
+    main_procedure_path: &NamespacePath,
+    strings: &StringMap,
+    output: &mut String
+) {
+    output.push_str("gera___stack.push(");
+    emit_string_literal(&main_procedure_path.display(strings), output);
+    output.push_str(", \"???\", 0)\n");
+    emit_procedure_name(main_procedure_path, 0, strings, output);
+    output.push_str("()\n");
+}
+
+fn emit_path(path: &NamespacePath, strings: &StringMap, output: &mut String) {
+    output.push_str(
+        &path.get_segments()
+            .iter()
+            .map(|s| strings.get(*s)
+            .replace("_", "__"))
+            .collect::<Vec<String>>()
+            .join("_")
+    );
+}
+
+fn emit_symbol_name(path: &NamespacePath, strings: &StringMap, output: &mut String) {
+    output.push_str("gera___symbols.");
+    emit_path(path, strings, output);
+}
+
+fn emit_procedure_name(
+    path: &NamespacePath,
+    variant: usize,
+    strings: &StringMap,
+    output: &mut String
+) {
+    emit_symbol_name(path, strings, output);
+    output.push('_');
+    output.push_str(&variant.to_string());
+}
+
+fn emit_member_access(member: StringIdx, strings: &StringMap, output: &mut String) {
+    let member_name = strings.get(member);
+    if LUA_KEYWORDS.contains(&member_name) {
+        output.push_str("[\"");
+        output.push_str(member_name);
+        output.push_str("\"]");
+    } else {
+        output.push('.');
+        output.push_str(member_name);
+    }
+}
+
+fn emit_member_key(member: StringIdx, strings: &StringMap, output: &mut String) {
+    let member_name = strings.get(member);
+    if LUA_KEYWORDS.contains(&member_name) {
+        output.push_str("[\"");
+        output.push_str(member_name);
+        output.push_str("\"]");
+    } else {
+        output.push_str(member_name);
+    }
+    output.push_str(" = ");
+}
+
+fn emit_string_literal(value: &str, output: &mut String) {
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '"' => output.push_str("\\\""),
+            c if (c as u32) < 0x20 || c == '\x7F' => {
+                output.push_str(&format!("\\{:03}", c as u32));
+            }
+            c => output.push(c)
+        }
+    }
+    output.push('"');
+}
+
+fn emit_integer(value: i64, output: &mut String) {
+    if value == i64::MIN { output.push_str("math.mininteger"); }
+    else { output.push_str(&value.to_string()); }
+}
+
+fn emit_float(value: f64, output: &mut String) {
+    if value == f64::INFINITY { output.push_str("math.huge"); }
+    else if value == f64::NEG_INFINITY { output.push_str("-math.huge"); }
+    else if value.is_nan() { output.push_str("(0.0 / 0.0)"); }
+    else { output.push_str(&format!("{:?}", value)); }
+}
+
+fn emit_source_location(source: &SourceRange, strings: &StringMap, output: &mut String) {
+    emit_string_literal(strings.get(source.file_name()), output);
+    output.push_str(", ");
+    let source_line = strings.get(source.file_content()).chars()
+        .take(source.start_position())
+        .filter(|c| *c == '\n')
+        .count() + 1;
+    output.push_str(&source_line.to_string());
+}
+
+fn emit_value(
+    value: &ConstantValue, constants: &ConstantPool, strings: &StringMap, output: &mut String
+) {
+    match value {
+        ConstantValue::Unit => output.push_str("nil"),
+        ConstantValue::Boolean(b) => output.push_str(if *b { "true" } else { "false" }),
+        ConstantValue::Integer(i) => emit_integer(*i, output),
+        ConstantValue::Float(f) => emit_float(*f, output),
+        ConstantValue::String(s) => emit_constant_name(s.into(), output),
+        ConstantValue::Array(a) => emit_constant_name(a.into(), output),
+        ConstantValue::Object(o) => emit_constant_name(o.into(), output),
+        ConstantValue::Variant(v) => {
+            let (variant_name, variant_value, _) = constants.get_variant(*v);
+            output.push_str("{ tag = ");
+            emit_variant_tag(strings.get(variant_name), output);
+            output.push_str(", value = ");
+            emit_value(&variant_value, constants, strings, output);
+            output.push_str(" }");
+        }
+    }
+}
+
+fn indent(indent: &str, output: &mut String) {
+    for line in indent.lines() {
+        if line.len() == 0 { continue; }
+        output.push_str("    ");
+        output.push_str(line);
+        output.push('\n');
+    }
+}
+
+fn emit_block(
+    instructions: &Vec<IrInstruction>,
+    variable_types: &Vec<TypeGroup>,
+    types: &TypeScope,
+    constants: &mut ConstantPool,
+    external: &HashMap<NamespacePath, StringIdx>,
+    symbols: &Vec<IrSymbol>,
+    strings: &StringMap,
+    output: &mut String
+) {
+    for instruction in instructions {
+        emit_instruction(
+            instruction, variable_types, types, constants, external, symbols, strings, output
+        );
+    }
+}
+
+fn emit_indented_block(
+    instructions: &Vec<IrInstruction>,
+    variable_types: &Vec<TypeGroup>,
+    types: &TypeScope,
+    constants: &mut ConstantPool,
+    external: &HashMap<NamespacePath, StringIdx>,
+    symbols: &Vec<IrSymbol>,
+    strings: &StringMap,
+    output: &mut String
+) {
+    let mut block_str = String::new();
+    emit_block(
+        instructions, variable_types, types, constants, external, symbols, strings, &mut block_str
+    );
+    indent(&block_str, output);
+}
+
+fn emit_copied(
+    copied: &str,
+    copied_type: TypeGroup,
+    types: &TypeScope,
+    output: &mut String
+) {
+    match types.group_concrete(copied_type) {
+        Type::Any |
+        Type::Unit |
+        Type::Boolean |
+        Type::Integer |
+        Type::Float |
+        Type::String |
+        Type::Array(_) |
+        Type::Object(_) |
+        Type::ConcreteObject(_) |
+        Type::Closure(_) => {
+            output.push_str(copied);
+        }
+        Type::Variants(_) => {
+            output.push_str("{ tag = ");
+            output.push_str(copied);
+            output.push_str(".tag, value = ");
+            output.push_str(copied);
+            output.push_str(".value }");
+        }
+    }
+}
+
+fn emit_copied_variable(
+    copied: IrVariable,
+    copied_type: TypeGroup,
+    types: &TypeScope,
+    output: &mut String
+) {
+    let mut copied_str = String::new();
+    emit_variable(copied, &mut copied_str);
+    emit_copied(&copied_str, copied_type, types, output);
+}
+
+fn emit_binary_operator(a: IrVariable, operator: &str, b: IrVariable, into: IrVariable, output: &mut String) {
+    emit_variable(into, output);
+    output.push_str(" = ");
+    emit_variable(a, output);
+    output.push(' ');
+    output.push_str(operator);
+    output.push(' ');
+    emit_variable(b, output);
+    output.push('\n');
+}
+
+fn emit_instruction(
+    instruction: &IrInstruction,
+    variable_types: &Vec<TypeGroup>,
+    types: &TypeScope,
+    constants: &mut ConstantPool,
+    external: &HashMap<NamespacePath, StringIdx>,
+    symbols: &Vec<IrSymbol>,
+    strings: &StringMap,
+    output: &mut String
+) {
+    match instruction {
+        IrInstruction::LoadUnit { .. } => {}
+        IrInstruction::LoadBoolean { value, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            output.push_str(if *value { "true" } else { "false" });
+            output.push('\n');
+        }
+        IrInstruction::LoadInteger { value, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            emit_integer(*value, output);
+            output.push('\n');
+        }
+        IrInstruction::LoadFloat { value, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            emit_float(*value, output);
+            output.push('\n');
+        }
+        IrInstruction::LoadString { value, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            emit_string_literal(strings.get(*value), output);
+            output.push('\n');
+        }
+        IrInstruction::LoadObject { member_values, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = {\n");
+            for (member_name, member_value) in member_values {
+                output.push_str("    ");
+                emit_member_key(*member_name, strings, output);
+                emit_copied_variable(
+                    *member_value, variable_types[member_value.index], types, output
+                );
+                output.push_str(",\n");
+            }
+            output.push_str("}\n");
+        }
+        IrInstruction::LoadArray { element_values, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = setmetatable({\n    n = ");
+            output.push_str(&element_values.len().to_string());
+            output.push_str(",\n");
+            for element_value in element_values {
+                output.push_str("    ");
+                emit_copied_variable(
+                    *element_value, variable_types[element_value.index], types, output
+                );
+                output.push_str(",\n");
+            }
+            output.push_str("}, gera___array)\n");
+        }
+        IrInstruction::LoadVariant { name, v, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = { tag = ");
+            emit_variant_tag(strings.get(*name), output);
+            output.push_str(", value = ");
+            emit_copied_variable(*v, variable_types[v.index], types, output);
+            output.push_str(" }\n");
+        }
+        IrInstruction::LoadGlobalVariable { path, into } => {
+            if let Type::Unit = types.group_concrete(variable_types[into.index]) { return; }
+            let path_matches = |s: &&IrSymbol| match s {
+                IrSymbol::Procedure { path: p, .. } |
+                IrSymbol::ExternalProcedure { path: p, .. } |
+                IrSymbol::BuiltInProcedure { path: p, .. } |
+                IrSymbol::Variable { path: p, .. } |
+                IrSymbol::ExternalVariable { path: p, .. } => *path == *p
+            };
+            match symbols.iter().find(path_matches).expect("should exist") {
+                IrSymbol::Procedure { .. } |
+                IrSymbol::BuiltInProcedure { .. } |
+                IrSymbol::ExternalProcedure { .. } => {
+                    panic!("Should've been converted to 'IrInstruction::LoadProcedure'!");
+                }
+                IrSymbol::Variable { .. } |
+                IrSymbol::ExternalVariable { .. } => {
+                    emit_variable(*into, output);
+                    output.push_str(" = ");
+                    if let Some(backing) = external.get(path) {
+                        output.push_str(strings.get(*backing));
+                    } else {
+                        emit_symbol_name(path, strings, output);
+                    }
+                    output.push('\n');
+                }
+            }
+        }
+        IrInstruction::LoadParameter { index, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = param");
+            output.push_str(&index.to_string());
+            output.push('\n');
+        }
+        IrInstruction::LoadClosure {
+            parameter_types, return_type: _, captured, variables, body, into
+        } => {
+            output.push_str("do\n");
+            output.push_str("    local captures = { ");
+            let mut had_capture = false;
+            for (capture_name, capture_value) in captured {
+                if had_capture { output.push_str(", "); }
+                had_capture = true;
+                emit_member_key(*capture_name, strings, output);
+                emit_copied_variable(
+                    *capture_value, variable_types[capture_value.index], types, output
+                );
+            }
+            output.push_str(" }\n");
+            let mut closure_str = String::new();
+            emit_variable(*into, &mut closure_str);
+            closure_str.push_str(" = function");
+            emit_parameters(parameter_types, &mut closure_str);
+            closure_str.push('\n');
+            let mut body_str = String::new();
+            emit_variable_declarations(variables, &mut body_str);
+            emit_block(
+                body, variables, types, constants, external, symbols, strings, &mut body_str
+            );
+            indent(&body_str, &mut closure_str);
+            closure_str.push_str("end\n");
+            indent(&closure_str, output);
+            output.push_str("end\n");
+        }
+        IrInstruction::LoadValue { value, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            let value = constants.insert(value, variable_types[into.index], types);
+            emit_value(&value, constants, strings, output);
+            output.push('\n');
+        }
+        IrInstruction::GetObjectMember { accessed, member, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            let mut accessed_str = String::new();
+            emit_variable(*accessed, &mut accessed_str);
+            emit_member_access(*member, strings, &mut accessed_str);
+            emit_copied(&accessed_str, variable_types[into.index], types, output);
+            output.push('\n');
+        }
+        IrInstruction::SetObjectMember { value, accessed, member } => {
+            emit_variable(*accessed, output);
+            emit_member_access(*member, strings, output);
+            output.push_str(" = ");
+            emit_copied_variable(*value, variable_types[value.index], types, output);
+            output.push('\n');
+        }
+        IrInstruction::GetArrayElement { accessed, index, into, source } => {
+            let mut accessed_str = String::new();
+            emit_variable(*accessed, &mut accessed_str);
+            accessed_str.push_str("[gera___verify_index(");
+            emit_variable(*index, &mut accessed_str);
+            accessed_str.push_str(", ");
+            emit_variable(*accessed, &mut accessed_str);
+            accessed_str.push_str(".n, ");
+            emit_source_location(source, strings, &mut accessed_str);
+            accessed_str.push_str(") + 1]");
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            emit_copied(&accessed_str, variable_types[into.index], types, output);
+            output.push('\n');
+        }
+        IrInstruction::SetArrayElement { value, accessed, index, source } => {
+            emit_variable(*accessed, output);
+            output.push_str("[gera___verify_index(");
+            emit_variable(*index, output);
+            output.push_str(", ");
+            emit_variable(*accessed, output);
+            output.push_str(".n, ");
+            emit_source_location(source, strings, output);
+            output.push_str(") + 1] = ");
+            emit_copied_variable(*value, variable_types[value.index], types, output);
+            output.push('\n');
+        }
+        IrInstruction::GetClosureCapture { name, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = captures");
+            emit_member_access(*name, strings, output);
+            output.push('\n');
+        }
+        IrInstruction::SetClosureCapture { value, name } => {
+            output.push_str("captures");
+            emit_member_access(*name, strings, output);
+            output.push_str(" = ");
+            emit_copied_variable(*value, variable_types[value.index], types, output);
+            output.push('\n');
+        }
+        IrInstruction::Move { from, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            emit_copied_variable(*from, variable_types[from.index], types, output);
+            output.push('\n');
+        }
+        IrInstruction::Add { a, b, into } => {
+            emit_binary_operator(*a, "+", *b, *into, output);
+        }
+        IrInstruction::Subtract { a, b, into } => {
+            emit_binary_operator(*a, "-", *b, *into, output);
+        }
+        IrInstruction::Multiply { a, b, into } => {
+            emit_binary_operator(*a, "*", *b, *into, output);
+        }
+        IrInstruction::Divide { a, b, into, source } => {
+            if let Type::Integer = types.group_concrete(variable_types[a.index]) {
+                output.push_str("gera___verify_integer_divisor(");
+                emit_variable(*b, output);
+                output.push_str(", ");
+                emit_source_location(source, strings, output);
+                output.push_str(")\n");
+                emit_variable(*into, output);
+                output.push_str(" = gera___idiv(");
+                emit_variable(*a, output);
+                output.push_str(", ");
+                emit_variable(*b, output);
+                output.push_str(")\n");
+            } else {
+                emit_binary_operator(*a, "/", *b, *into, output);
+            }
+        }
+        IrInstruction::Modulo { a, b, into, source } => {
+            if let Type::Integer = types.group_concrete(variable_types[a.index]) {
+                output.push_str("gera___verify_integer_divisor(");
+                emit_variable(*b, output);
+                output.push_str(", ");
+                emit_source_location(source, strings, output);
+                output.push_str(")\n");
+            }
+            emit_variable(*into, output);
+            output.push_str(" = math.fmod(");
+            emit_variable(*a, output);
+            output.push_str(", ");
+            emit_variable(*b, output);
+            output.push_str(")\n");
+        }
+        IrInstruction::Negate { x, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = -");
+            emit_variable(*x, output);
+            output.push('\n');
+        }
+        IrInstruction::BitwiseAnd { a, b, into } => {
+            emit_binary_operator(*a, "&", *b, *into, output);
+        }
+        IrInstruction::BitwiseOr { a, b, into } => {
+            emit_binary_operator(*a, "|", *b, *into, output);
+        }
+        IrInstruction::BitwiseXor { a, b, into } => {
+            emit_binary_operator(*a, "~", *b, *into, output);
+        }
+        IrInstruction::BitwiseNot { x, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ~");
+            emit_variable(*x, output);
+            output.push('\n');
+        }
+        IrInstruction::LeftShift { a, b, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            emit_variable(*a, output);
+            output.push_str(" << (");
+            emit_variable(*b, output);
+            output.push_str(" & 63)\n");
+        }
+        IrInstruction::RightShift { a, b, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = gera___shift_right(");
+            emit_variable(*a, output);
+            output.push_str(", ");
+            emit_variable(*b, output);
+            output.push_str(")\n");
+        }
+        IrInstruction::LessThan { a, b, into } => {
+            emit_binary_operator(*a, "<", *b, *into, output);
+        }
+        IrInstruction::LessThanEquals { a, b, into } => {
+            emit_binary_operator(*a, "<=", *b, *into, output);
+        }
+        IrInstruction::GreaterThan { a, b, into } => {
+            emit_binary_operator(*a, ">", *b, *into, output);
+        }
+        IrInstruction::GreaterThanEquals { a, b, into } => {
+            emit_binary_operator(*a, ">=", *b, *into, output);
+        }
+        IrInstruction::Equals { a, b, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = gera___eq(");
+            emit_variable(*a, output);
+            output.push_str(", ");
+            emit_variable(*b, output);
+            output.push_str(")\n");
+        }
+        IrInstruction::NotEquals { a, b, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = not gera___eq(");
+            emit_variable(*a, output);
+            output.push_str(", ");
+            emit_variable(*b, output);
+            output.push_str(")\n");
+        }
+        IrInstruction::Not { x, into } => {
+            emit_variable(*into, output);
+            output.push_str(" = not ");
+            emit_variable(*x, output);
+            output.push('\n');
+        }
+        IrInstruction::BranchOnValue { value, branches, else_branch } => {
+            if branches.len() == 0 {
+                output.push_str("do\n");
+            }
+            let mut had_branch = false;
+            for (branch_value, branch_body) in branches {
+                output.push_str(if had_branch { "elseif" } else { "if" });
+                had_branch = true;
+                output.push_str(" gera___eq(");
+                emit_variable(*value, output);
+                output.push_str(", ");
+                let bvalue = constants.insert(branch_value, variable_types[value.index], types);
+                emit_value(&bvalue, constants, strings, output);
+                output.push_str(") then\n");
+                emit_indented_block(
+                    branch_body, variable_types, types, constants, external, symbols, strings,
+                    output
+                );
+            }
+            if else_branch.len() > 0 {
+                if had_branch { output.push_str("else\n"); }
+                emit_indented_block(
+                    else_branch, variable_types, types, constants, external, symbols, strings,
+                    output
+                );
+            }
+            output.push_str("end\n");
+        }
+        IrInstruction::BranchOnVariant { value, branches, else_branch } => {
+            let mut matched = String::new();
+            emit_variable(*value, &mut matched);
+            if branches.len() == 0 {
+                output.push_str("do\n");
+            }
+            let mut had_branch = false;
+            for (branch_variant, branch_variable, branch_body) in branches {
+                output.push_str(if had_branch { "elseif " } else { "if " });
+                had_branch = true;
+                output.push_str(&matched);
+                output.push_str(".tag == ");
+                emit_variant_tag(strings.get(*branch_variant), output);
+                output.push_str(" then\n");
+                if let Some(branch_variable) = branch_variable {
+                    output.push_str("    ");
+                    emit_variable(*branch_variable, output);
+                    output.push_str(" = ");
+                    output.push_str(&matched);
+                    output.push_str(".value\n");
+                }
+                emit_indented_block(
+                    branch_body, variable_types, types, constants, external, symbols, strings,
+                    output
+                );
+            }
+            if else_branch.len() > 0 {
+                if had_branch { output.push_str("else\n"); }
+                emit_indented_block(
+                    else_branch, variable_types, types, constants, external, symbols, strings,
+                    output
+                );
+            }
+            output.push_str("end\n");
+        }
+        IrInstruction::Call { path, variant, arguments, into, source } => {
+            output.push_str("gera___stack.push(");
+            emit_string_literal(&path.display(strings), output);
+            output.push_str(", ");
+            emit_source_location(source, strings, output);
+            output.push_str(")\n");
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            if let Some(backing) = external.get(path) {
+                output.push_str(strings.get(*backing));
+            } else {
+                emit_procedure_name(path, *variant, strings, output);
+            }
+            output.push('(');
+            let mut had_param = false;
+            for argument_idx in 0..arguments.len() {
+                if had_param { output.push_str(", "); }
+                had_param = true;
+                emit_copied_variable(
+                    arguments[argument_idx], variable_types[arguments[argument_idx].index], types,
+                    output
+                );
+            }
+            output.push_str(")\n");
+            output.push_str("gera___stack.pop()\n");
+        }
+        IrInstruction::CallClosure { called, arguments, into, source } => {
+            output.push_str("gera___stack.push(\"<closure>\", ");
+            emit_source_location(source, strings, output);
+            output.push_str(")\n");
+            emit_variable(*into, output);
+            output.push_str(" = ");
+            emit_variable(*called, output);
+            output.push('(');
+            let mut had_param = false;
+            for argument_idx in 0..arguments.len() {
+                if had_param { output.push_str(", "); }
+                had_param = true;
+                emit_copied_variable(
+                    arguments[argument_idx], variable_types[arguments[argument_idx].index], types,
+                    output
+                );
+            }
+            output.push_str(")\n");
+            output.push_str("gera___stack.pop()\n");
+        }
+        IrInstruction::Return { value } => {
+            // 'return' has to be the last statement of a Lua block
+            output.push_str("do return ");
+            emit_variable(*value, output);
+            output.push_str(" end\n");
+        }
+        IrInstruction::Phi { .. } => {}
+    }
+}
@@ -5,5 +5,6 @@ pub mod lowering;
 pub mod target;
 pub mod c;
 pub mod javascript;
+pub mod lua;
 pub mod symbols;
 pub mod constants;
\ No newline at end of file
@@ -14,7 +14,7 @@ use crate::util::strings::{StringMap, StringIdx};
 pub fn generate_symbols(
     type_scope: TypeScope,
     typed_symbols: HashMap<NamespacePath, Symbol<TypedAstNode>>, 
-    external_backings: HashMap<NamespacePath, StringIdx>,
+    external_backings: HashMap<NamespacePath, Option<StringIdx>>,
     strings: &mut StringMap
 ) -> String {
     let mut result = json!({
@@ -25,7 +25,8 @@ pub fn generate_symbols(
         ).collect()),
         "modules": json!({}),
         "procedures": json!({}),
-        "constants": json!({})
+        "constants": json!({}),
+        "type_aliases": json!({})
     });
     serialize_modules(
         &[], &type_scope, &typed_symbols, &external_backings, strings, &mut result
@@ -72,11 +73,16 @@ fn serialize_type(
                     type_scope.group_internal_id(*mt).into()
                 );
             }
-            json!({
+            let mut object = json!({
                 "type": "object",
                 "member_types": member_stypes,
                 "fixed": *fixed
-            })
+            });
+            if let Some((name, nominal)) = type_scope.object_name(*obj) {
+                object["name"] = serde_json::Value::String(name.display(strings));
+                object["nominal"] = serde_json::Value::Bool(*nominal);
+            }
+            object
         }
         Type::ConcreteObject(obj) => {
             let member_types = type_scope.concrete_object(*obj);
@@ -125,7 +131,7 @@ fn serialize_modules(
     element_of: &[StringIdx],
     type_scope: &TypeScope,
     typed_symbols: &HashMap<NamespacePath, Symbol<TypedAstNode>>, 
-    external_backings: &HashMap<NamespacePath, StringIdx>,
+    external_backings: &HashMap<NamespacePath, Option<StringIdx>>,
     strings: &mut StringMap,
     into: &mut serde_json::Value
 ) {
@@ -139,7 +145,8 @@ fn serialize_modules(
             let mut module = json!({
                 "modules": json!({}),
                 "procedures": json!({}),
-                "constants": json!({})
+                "constants": json!({}),
+                "type_aliases": json!({})
             });
             serialize_modules(
                 &submodule_path, type_scope, typed_symbols, external_backings, strings, &mut module
@@ -150,7 +157,7 @@ fn serialize_modules(
         // symbol is in this module
         let element_name = strings.get(element_name);
         match symbol {
-            Symbol::Procedure { public, parameter_names, parameter_types, returns, body, source: _, type_scope: _ } => {
+            Symbol::Procedure { public, parameter_names, parameter_defaults, parameter_types, returns, body, source: _, type_scope: _ } => {
                 let is_external = body.is_none() && external_backings.contains_key(symbol_path);
                 let mut procedure = json!({
                     "public": *public,
@@ -158,6 +165,7 @@ fn serialize_modules(
                     "name": element_name,
                     "parameters": serde_json::Value::Array((0..parameter_names.len()).map(|p| json!({
                         "name": strings.get(parameter_names[p]),
+                        "has_default": parameter_defaults[p].is_some(),
                         "type": serde_json::Value::Number(
                             type_scope.group_internal_id(parameter_types[p]).into()
                         )
@@ -166,10 +174,8 @@ fn serialize_modules(
                         type_scope.group_internal_id(*returns).into()
                     )
                 });
-                if is_external {
-                    procedure["backing"] = serde_json::Value::String(strings.get(
-                        *external_backings.get(symbol_path).expect("checked above")
-                    ).into());
+                if let Some(backing) = external_backings.get(symbol_path).copied().flatten().filter(|_| is_external) {
+                    procedure["backing"] = serde_json::Value::String(strings.get(backing).into());
                 }
                 into["procedures"][element_name] = procedure;
             }
@@ -180,6 +186,15 @@ fn serialize_modules(
                     "types": serde_json::Value::Number(
                         type_scope.group_internal_id(*value_types).into()
                     )
+                }),
+            Symbol::Type { public, nominal, value_types } =>
+                into["type_aliases"][element_name] = json!({
+                    "public": *public,
+                    "nominal": *nominal,
+                    "name": element_name,
+                    "types": serde_json::Value::Number(
+                        type_scope.group_internal_id(*value_types).into()
+                    )
                 })
         }
     }
@@ -11,7 +11,7 @@ use crate::util::strings::{StringMap, StringIdx};
 use crate::backend::ir::IrSymbol;
 
 pub enum CompileTarget {
-    AstConsumer(fn(TypeScope, HashMap<NamespacePath, Module<AstNode>>, HashMap<NamespacePath, StringIdx>, &mut StringMap) -> String),
-    TypedAstConsumer(fn(TypeScope, HashMap<NamespacePath, Symbol<TypedAstNode>>, HashMap<NamespacePath, StringIdx>, &mut StringMap) -> String),
+    AstConsumer(fn(TypeScope, HashMap<NamespacePath, Module<AstNode>>, HashMap<NamespacePath, Option<StringIdx>>, &mut StringMap) -> String),
+    TypedAstConsumer(fn(TypeScope, HashMap<NamespacePath, Symbol<TypedAstNode>>, HashMap<NamespacePath, Option<StringIdx>>, &mut StringMap) -> String),
     IrConsumer(fn(Vec<IrSymbol>, TypeScope, NamespacePath, &mut StringMap) -> String)
 }
\ No newline at end of file
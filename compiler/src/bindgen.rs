@@ -0,0 +1,487 @@
+
+use std::collections::HashMap;
+
+use crate::util::{
+    strings::{StringMap, StringIdx},
+    error::{Error, ErrorSection, ErrorType},
+    source::SourceRange
+};
+
+
+#[derive(Debug, Clone)]
+enum CType {
+    Unit,
+    Boolean(String),
+    Integer(String),
+    Float(String),
+    String,
+    Struct(usize)
+}
+
+struct CStruct {
+    name: String,
+    c_name: String,
+    members: Vec<(String, CType)>
+}
+
+struct CProcedure {
+    name: String,
+    parameters: Vec<CType>,
+    returns: CType
+}
+
+struct CToken {
+    content: String,
+    source: SourceRange
+}
+
+fn tokenize(file_name: StringIdx, file_content: StringIdx, strings: &StringMap) -> Result<Vec<CToken>, Error> {
+    let chars = strings.get(file_content).chars().collect::<Vec<char>>();
+    let mut tokens = Vec::new();
+    let mut line_start = true;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' { line_start = true; }
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == '#' && line_start {
+            while i < chars.len() && chars[i] != '\n' {
+                if chars[i] == '\\' { i += 1; }
+                i += 1;
+            }
+            continue;
+        }
+        line_start = false;
+        if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' { i += 1; }
+            continue;
+        }
+        if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) { i += 1; }
+            i += 2;
+            continue;
+        }
+        let start = i;
+        if c.is_ascii_alphanumeric() || c == '_' {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') { i += 1; }
+        } else if "{}();,*".contains(c) {
+            i += 1;
+        } else {
+            return Err(Error::new([
+                ErrorSection::Error(ErrorType::InvalidCharacter(c)),
+                ErrorSection::Code(SourceRange::new(file_name, file_content, start, start + 1))
+            ].into()));
+        }
+        tokens.push(CToken {
+            content: chars[start..i].iter().collect(),
+            source: SourceRange::new(file_name, file_content, start, i)
+        });
+    }
+    Ok(tokens)
+}
+
+struct HeaderParser {
+    tokens: Vec<CToken>,
+    position: usize,
+    end: SourceRange
+}
+
+impl HeaderParser {
+    fn new(file_name: StringIdx, file_content: StringIdx, strings: &StringMap) -> Result<HeaderParser, Error> {
+        let tokens = tokenize(file_name, file_content, strings)?;
+        let end = tokens.last()
+            .map(|t| t.source)
+            .unwrap_or(SourceRange::new(file_name, file_content, 0, 0));
+        Ok(HeaderParser { tokens, position: 0, end })
+    }
+
+    fn current(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(|t| t.content.as_str())
+    }
+
+    fn unexpected(&self, expected: &'static str, strings: &mut StringMap) -> Error {
+        if let Some(token) = self.tokens.get(self.position) {
+            Error::new([
+                ErrorSection::Error(ErrorType::UnexpectedToken(expected, strings.insert(&token.content))),
+                ErrorSection::Code(token.source)
+            ].into())
+        } else {
+            Error::new([
+                ErrorSection::Error(ErrorType::UnexpectedEnd(expected)),
+                ErrorSection::Code(self.end)
+            ].into())
+        }
+    }
+
+    fn expect(&mut self, content: &str, expected: &'static str, strings: &mut StringMap) -> Result<(), Error> {
+        if self.current() != Some(content) { return Err(self.unexpected(expected, strings)); }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn expect_identifier(&mut self, expected: &'static str, strings: &mut StringMap) -> Result<String, Error> {
+        match self.current() {
+            Some(name) if is_identifier(name) => {
+                let name = name.to_string();
+                self.position += 1;
+                Ok(name)
+            }
+            _ => Err(self.unexpected(expected, strings))
+        }
+    }
+
+    fn parse_header(
+        &mut self,
+        strings: &mut StringMap,
+        structs: &mut Vec<CStruct>,
+        struct_names: &mut HashMap<String, usize>,
+        procedures: &mut Vec<CProcedure>
+    ) -> Result<(), Error> {
+        while self.position < self.tokens.len() {
+            let is_struct_definition = self.current() == Some("struct")
+                && self.tokens.get(self.position + 2).map(|t| t.content.as_str()) == Some("{");
+            if self.current() == Some("typedef") {
+                self.position += 1;
+                self.expect("struct", "'struct'", strings)?;
+                let tag = if self.current() != Some("{") {
+                    Some(self.expect_identifier("the name of the struct", strings)?)
+                } else { None };
+                let members = self.parse_members(strings, struct_names)?;
+                let name_source = self.tokens.get(self.position).map(|t| t.source);
+                let name = self.expect_identifier("the name of the type", strings)?;
+                self.expect(";", "a semicolon (';')", strings)?;
+                let struct_idx = structs.len();
+                for key in tag.iter().map(|tag| format!("struct {}", tag)).chain([name.clone()]) {
+                    if struct_names.insert(key.clone(), struct_idx).is_some() {
+                        return Err(Error::new([
+                            ErrorSection::Error(ErrorType::SymbolAlreadyExists(key)),
+                            ErrorSection::Code(name_source.expect("name was parsed"))
+                        ].into()));
+                    }
+                }
+                structs.push(CStruct { name: name.clone(), c_name: name, members });
+            } else if is_struct_definition {
+                self.position += 1;
+                let name_source = self.tokens[self.position].source;
+                let name = self.expect_identifier("the name of the struct", strings)?;
+                let members = self.parse_members(strings, struct_names)?;
+                self.expect(";", "a semicolon (';')", strings)?;
+                let c_name = format!("struct {}", name);
+                if struct_names.insert(c_name.clone(), structs.len()).is_some() {
+                    return Err(Error::new([
+                        ErrorSection::Error(ErrorType::SymbolAlreadyExists(c_name)),
+                        ErrorSection::Code(name_source)
+                    ].into()));
+                }
+                structs.push(CStruct { name, c_name, members });
+            } else {
+                let returns = self.parse_type(strings, struct_names)?;
+                let name_source = self.tokens.get(self.position).map(|t| t.source);
+                let name = self.expect_identifier("the name of the function", strings)?;
+                self.expect("(", "an opening parenthesis ('(')", strings)?;
+                let mut parameters = Vec::new();
+                if self.current() == Some("void")
+                    && self.tokens.get(self.position + 1).map(|t| t.content.as_str()) == Some(")") {
+                    self.position += 1;
+                }
+                while self.current() != Some(")") {
+                    let type_start = self.position;
+                    let parameter_type = self.parse_type(strings, struct_names)?;
+                    if let CType::Unit = parameter_type { return Err(self.unsupported_type(type_start)); }
+                    parameters.push(parameter_type);
+                    if self.current().map(is_identifier).unwrap_or(false) { self.position += 1; }
+                    if self.current() == Some(",") {
+                        self.position += 1;
+                    } else if self.current() != Some(")") {
+                        return Err(self.unexpected("a comma (',') or a closing parenthesis (')')", strings));
+                    }
+                }
+                self.position += 1;
+                self.expect(";", "a semicolon (';')", strings)?;
+                if procedures.iter().any(|p| p.name == name) {
+                    return Err(Error::new([
+                        ErrorSection::Error(ErrorType::SymbolAlreadyExists(name)),
+                        ErrorSection::Code(name_source.expect("name was parsed"))
+                    ].into()));
+                }
+                procedures.push(CProcedure { name, parameters, returns });
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_members(
+        &mut self,
+        strings: &mut StringMap,
+        struct_names: &HashMap<String, usize>
+    ) -> Result<Vec<(String, CType)>, Error> {
+        self.expect("{", "an opening brace ('{')", strings)?;
+        let mut members = Vec::new();
+        loop {
+            let type_start = self.position;
+            let member_type = self.parse_type(strings, struct_names)?;
+            if let CType::Unit | CType::Struct(_) = member_type { return Err(self.unsupported_type(type_start)); }
+            loop {
+                let member_name = self.expect_identifier("the name of a member", strings)?;
+                members.push((member_name, member_type.clone()));
+                if self.current() != Some(",") { break; }
+                self.position += 1;
+            }
+            self.expect(";", "a semicolon (';')", strings)?;
+            if self.current() == Some("}") { break; }
+        }
+        self.position += 1;
+        Ok(members)
+    }
+
+    fn parse_type(&mut self, strings: &mut StringMap, struct_names: &HashMap<String, usize>) -> Result<CType, Error> {
+        while matches!(self.current(), Some("const") | Some("extern") | Some("volatile")) {
+            self.position += 1;
+        }
+        let start = self.position;
+        let mut words = Vec::new();
+        while let Some(word) = self.current() {
+            if !matches!(word, "void" | "signed" | "unsigned" | "char" | "short" | "int" | "long" | "float" | "double" | "bool" | "_Bool") {
+                break;
+            }
+            words.push(word.to_string());
+            self.position += 1;
+        }
+        let parsed = if words.len() > 0 {
+            let spelled = words.join(" ");
+            if words.iter().any(|w| w == "void") {
+                if words.len() > 1 { None } else { Some(CType::Unit) }
+            } else if words.iter().any(|w| w == "float" || w == "double") {
+                Some(CType::Float(spelled))
+            } else if words.iter().any(|w| w == "bool" || w == "_Bool") {
+                Some(CType::Boolean(spelled))
+            } else {
+                Some(CType::Integer(spelled))
+            }
+        } else {
+            let name = self.expect_identifier("a type", strings)?;
+            let name = if name == "struct" {
+                format!("struct {}", self.expect_identifier("the name of the struct", strings)?)
+            } else { name };
+            match name.as_str() {
+                "GeraString" => Some(CType::String),
+                "gbool" => Some(CType::Boolean(name)),
+                "gfloat" => Some(CType::Float(name)),
+                "gint" | "size_t" | "ssize_t" | "ptrdiff_t" | "intptr_t" | "uintptr_t" |
+                "int8_t" | "int16_t" | "int32_t" | "int64_t" |
+                "uint8_t" | "uint16_t" | "uint32_t" | "uint64_t" => Some(CType::Integer(name)),
+                _ => struct_names.get(&name).map(|s| CType::Struct(*s))
+            }
+        };
+        while self.current() == Some("const") { self.position += 1; }
+        if let (Some(parsed), false) = (parsed, self.current() == Some("*")) {
+            return Ok(parsed);
+        }
+        while self.current() == Some("*") || self.current() == Some("const") { self.position += 1; }
+        Err(self.unsupported_type(start))
+    }
+
+    fn unsupported_type(&self, start: usize) -> Error {
+        let spelled = self.tokens[start..self.position].iter()
+            .map(|t| t.content.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ");
+        Error::new([
+            ErrorSection::Error(ErrorType::UnsupportedCType(spelled)),
+            ErrorSection::Code((&self.tokens[start].source..&self.tokens[self.position - 1].source).into()),
+            ErrorSection::Help(String::from("Only scalars, 'GeraString' and structs made up of those may be used"))
+        ].into())
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    s.chars().next().map(|c| c.is_ascii_alphabetic() || c == '_').unwrap_or(false)
+}
+
+fn emit_gera_type(t: &CType, structs: &Vec<CStruct>, output: &mut String) {
+    match t {
+        CType::Unit => output.push_str("unit"),
+        CType::Boolean(_) => output.push_str("bool"),
+        CType::Integer(_) => output.push_str("int"),
+        CType::Float(_) => output.push_str("float"),
+        CType::String => output.push_str("str"),
+        CType::Struct(s) => output.push_str(&structs[*s].name)
+    }
+}
+
+fn emit_glue_type(t: &CType, structs: &Vec<CStruct>, prefix: &str, output: &mut String) {
+    match t {
+        CType::Unit => output.push_str("void"),
+        CType::Boolean(_) => output.push_str("gbool"),
+        CType::Integer(_) => output.push_str("gint"),
+        CType::Float(_) => output.push_str("gfloat"),
+        CType::String => output.push_str("GeraString"),
+        CType::Struct(s) => {
+            output.push_str(prefix);
+            output.push_str(&structs[*s].name);
+        }
+    }
+}
+
+fn emit_into_c(t: &CType, value: &str, structs: &Vec<CStruct>, prefix: &str, output: &mut String) {
+    match t {
+        CType::Unit | CType::String => output.push_str(value),
+        CType::Boolean(c_name) | CType::Integer(c_name) | CType::Float(c_name) => {
+            output.push_str(&format!("(({}) {})", c_name, value));
+        }
+        CType::Struct(s) => output.push_str(&format!("{}{}_into_c({})", prefix, structs[*s].name, value))
+    }
+}
+
+fn emit_from_c(t: &CType, value: &str, structs: &Vec<CStruct>, prefix: &str, output: &mut String) {
+    match t {
+        CType::Unit | CType::String => output.push_str(value),
+        CType::Boolean(_) | CType::Integer(_) | CType::Float(_) => {
+            output.push_str("((");
+            emit_glue_type(t, structs, prefix, output);
+            output.push_str(&format!(") {})", value));
+        }
+        CType::Struct(s) => output.push_str(&format!("{}{}_from_c({})", prefix, structs[*s].name, value))
+    }
+}
+
+fn emit_mappings(
+    module_path: &str,
+    prefix: &str,
+    structs: &Vec<CStruct>,
+    procedures: &Vec<CProcedure>,
+    output: &mut String
+) {
+    for s in structs {
+        output.push_str("type ");
+        output.push_str(&s.name);
+        output.push_str(" = { ");
+        output.push_str(&s.members.iter().map(|(member_name, member_type)| {
+            let mut member = format!("{} = ", member_name);
+            emit_gera_type(member_type, structs, &mut member);
+            member
+        }).collect::<Vec<String>>().join(", "));
+        output.push_str(" }\n");
+    }
+    if structs.len() > 0 { output.push_str("\n"); }
+    output.push_str("target c {\n");
+    for procedure in procedures {
+        output.push_str("    proc ");
+        output.push_str(module_path);
+        output.push_str("::");
+        output.push_str(&procedure.name);
+        output.push_str("(");
+        output.push_str(&procedure.parameters.iter().map(|p| {
+            let mut parameter = String::new();
+            emit_gera_type(p, structs, &mut parameter);
+            parameter
+        }).collect::<Vec<String>>().join(", "));
+        output.push_str(")");
+        if !matches!(procedure.returns, CType::Unit) {
+            output.push_str(" -> ");
+            emit_gera_type(&procedure.returns, structs, output);
+        }
+        output.push_str(" = ");
+        output.push_str(prefix);
+        output.push_str(&procedure.name);
+        output.push_str("\n");
+    }
+    output.push_str("}\n");
+}
+
+fn emit_glue(
+    headers: &[StringIdx],
+    prefix: &str,
+    structs: &Vec<CStruct>,
+    procedures: &Vec<CProcedure>,
+    strings: &StringMap,
+    output: &mut String
+) {
+    output.push_str("\n#include <geracoredeps.h>\n#include <gera.h>\n");
+    for header in headers {
+        output.push_str(&format!("#include \"{}\"\n", strings.get(*header)));
+    }
+    for s in structs {
+        let glue_name = format!("{}{}", prefix, s.name);
+        output.push_str(&format!("\ntypedef struct {} {{\n", glue_name));
+        for (member_name, member_type) in &s.members {
+            output.push_str("    ");
+            emit_glue_type(member_type, structs, prefix, output);
+            output.push_str(&format!(" {};\n", member_name));
+        }
+        output.push_str(&format!("}} {};\n", glue_name));
+        output.push_str(&format!("\nstatic {} {}_into_c({} value) {{\n", s.c_name, glue_name, glue_name));
+        output.push_str(&format!("    {} result;\n", s.c_name));
+        for (member_name, member_type) in &s.members {
+            output.push_str(&format!("    result.{} = ", member_name));
+            emit_into_c(member_type, &format!("value.{}", member_name), structs, prefix, output);
+            output.push_str(";\n");
+        }
+        output.push_str("    return result;\n}\n");
+        output.push_str(&format!("\nstatic {} {}_from_c({} value) {{\n", glue_name, glue_name, s.c_name));
+        output.push_str(&format!("    {} result;\n", glue_name));
+        for (member_name, member_type) in &s.members {
+            output.push_str(&format!("    result.{} = ", member_name));
+            emit_from_c(member_type, &format!("value.{}", member_name), structs, prefix, output);
+            output.push_str(";\n");
+        }
+        output.push_str("    return result;\n}\n");
+    }
+    for procedure in procedures {
+        output.push_str("\n");
+        emit_glue_type(&procedure.returns, structs, prefix, output);
+        output.push_str(&format!(" {}{}(", prefix, procedure.name));
+        if procedure.parameters.len() == 0 { output.push_str("void"); }
+        output.push_str(&procedure.parameters.iter().enumerate().map(|(p, parameter_type)| {
+            let mut parameter = String::new();
+            emit_glue_type(parameter_type, structs, prefix, &mut parameter);
+            parameter.push_str(&format!(" param{}", p));
+            parameter
+        }).collect::<Vec<String>>().join(", "));
+        output.push_str(") {\n    ");
+        let mut call = format!("{}(", procedure.name);
+        call.push_str(&procedure.parameters.iter().enumerate().map(|(p, parameter_type)| {
+            let mut argument = String::new();
+            emit_into_c(parameter_type, &format!("param{}", p), structs, prefix, &mut argument);
+            argument
+        }).collect::<Vec<String>>().join(", "));
+        call.push_str(")");
+        if let CType::Unit = procedure.returns {
+            output.push_str(&call);
+        } else {
+            output.push_str("return ");
+            emit_from_c(&procedure.returns, &call, structs, prefix, output);
+        }
+        output.push_str(";\n}\n");
+    }
+}
+
+pub fn generate_c_bindings(
+    headers: &[(StringIdx, StringIdx)],
+    module_path: &str,
+    strings: &mut StringMap
+) -> Result<(String, String), Vec<Error>> {
+    let mut structs = Vec::new();
+    let mut struct_names = HashMap::new();
+    let mut procedures = Vec::new();
+    let mut errors = Vec::new();
+    for (file_name, file_content) in headers {
+        let result = HeaderParser::new(*file_name, *file_content, strings)
+            .and_then(|mut parser| parser.parse_header(strings, &mut structs, &mut struct_names, &mut procedures));
+        if let Err(error) = result { errors.push(error); }
+    }
+    if errors.len() > 0 { return Err(errors); }
+    let prefix = format!("gera_{}_", module_path.replace("::", "_"));
+    let mut mappings = String::new();
+    emit_mappings(module_path, &prefix, &structs, &procedures, &mut mappings);
+    let mut glue = String::new();
+    emit_glue(
+        &headers.iter().map(|(file_name, _)| *file_name).collect::<Vec<StringIdx>>(),
+        &prefix, &structs, &procedures, strings, &mut glue
+    );
+    Ok((mappings, glue))
+}
@@ -42,6 +42,7 @@ fn register_foreign_builtin(
     typed_symbols.insert(procedure_path.clone(), Symbol::Procedure {
         public: true,
         parameter_names: parameter_names.iter().map(|p| strings.insert(p)).collect(),
+        parameter_defaults: vec![None; parameter_names.len()],
         parameter_types: parameter_types,
         returns: return_type,
         body: None,
@@ -60,7 +61,7 @@ pub fn load_builtins(
     modules: &mut HashMap<NamespacePath, Module<AstNode>>,
     global_type_scope: &mut TypeScope,
     typed_symbols: &mut HashMap<NamespacePath, Symbol<TypedAstNode>>,
-    external_backings: &mut HashMap<NamespacePath, StringIdx>
+    external_backings: &mut HashMap<NamespacePath, Option<StringIdx>>
 ) {
     load_foreign_builtins(strings, modules, typed_symbols);
     load_native_builtins(target_str, strings, modules, global_type_scope, typed_symbols, external_backings);
@@ -314,11 +315,11 @@ fn load_native_builtins(
     modules: &mut HashMap<NamespacePath, Module<AstNode>>,
     global_type_scope: &mut TypeScope,
     typed_symbols: &mut HashMap<NamespacePath, Symbol<TypedAstNode>>,
-    external_backings: &mut HashMap<NamespacePath, StringIdx>
+    external_backings: &mut HashMap<NamespacePath, Option<StringIdx>>
 ) {
     let src = strings.insert(include_str!("core.gera"));
     let file = strings.insert("<builtin>/core.gera");
-    if let Err(errors) = process_file(file, src, target_str, strings, modules, global_type_scope, typed_symbols, external_backings) {
+    if let Err(errors) = process_file(file, src, target_str, None, strings, modules, global_type_scope, typed_symbols, external_backings) {
         for error in errors {
             println!("{}", error.display(strings, false));
         }
@@ -71,15 +71,19 @@ pub trait HasAstNodeVariant<T: Clone + HasAstNodeVariant<T>> {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum AstNodeVariant<T: Clone + HasAstNodeVariant<T>> {
-    Procedure { public: bool, name: StringIdx, arguments: Vec<(StringIdx, SourceRange)>, body: Vec<T> },
+    Procedure { public: bool, name: StringIdx, arguments: Vec<(StringIdx, SourceRange, Option<TypeAnnotation>, Option<T>)>, returns: Option<(TypeAnnotation, SourceRange)>, body: Vec<T> },
     Function { arguments: Vec<(StringIdx, SourceRange)>, body: Vec<T> },
-    Variable { public: bool, mutable: bool, name: StringIdx, value_types: Option<TypeGroup>, value: Option<Box<T>> },
+    Variable { public: bool, mutable: bool, name: StringIdx, annotation: Option<TypeAnnotation>, value_types: Option<TypeGroup>, value: Option<Box<T>> },
+    Destructuring { mutable: bool, pattern: Pattern<T>, value: Box<T> },
     CaseBranches { value: Box<T>, branches: Vec<(T, Vec<T>)>, else_body: Vec<T> },
     CaseConditon { condition: Box<T>, body: Vec<T>, else_body: Vec<T> },
     CaseVariant { value: Box<T>, branches: Vec<(StringIdx, Option<(StringIdx, SourceRange, Option<TypeGroup>)>, Vec<T>)>, else_body: Option<Vec<T>> },
+    CasePattern { value: Box<T>, branches: Vec<(Pattern<T>, Option<T>, Vec<T>)>, else_body: Option<Vec<T>> },
     Assignment { variable: Box<T>, value: Box<T> },
     Return { value: Box<T> },
+    Propagate { value: Box<T>, returned_types: Option<TypeGroup> },
     Call { called: Box<T>, arguments: Vec<T> },
+    NamedArgument { name: StringIdx, value: Box<T> },
     Object { values: Vec<(StringIdx, T)> },
     Array { values: Vec<T> },
     ObjectAccess { object: Box<T>, member: StringIdx },
@@ -96,6 +100,12 @@ pub enum AstNodeVariant<T: Clone + HasAstNodeVariant<T>> {
     Divide { a: Box<T>, b: Box<T> },
     Modulo { a: Box<T>, b: Box<T> },
     Negate { x: Box<T> },
+    BitwiseAnd { a: Box<T>, b: Box<T> },
+    BitwiseOr { a: Box<T>, b: Box<T> },
+    BitwiseXor { a: Box<T>, b: Box<T> },
+    BitwiseNot { x: Box<T> },
+    LeftShift { a: Box<T>, b: Box<T> },
+    RightShift { a: Box<T>, b: Box<T> },
     LessThan { a: Box<T>, b: Box<T> },
     GreaterThan { a: Box<T>, b: Box<T> },
     LessThanEqual { a: Box<T>, b: Box<T> },
@@ -110,7 +120,184 @@ pub enum AstNodeVariant<T: Clone + HasAstNodeVariant<T>> {
     Use { paths: Vec<NamespacePath> },
     Variant { name: StringIdx, value: Box<T> },
     Static { value: Box<T> },
-    Target { target: StringIdx, body: Vec<T> }
+    Target { target: StringIdx, body: Vec<T> },
+    TypeAlias { public: bool, name: StringIdx, nominal: bool, aliased: TypeAnnotation }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeAnnotation {
+    Unit,
+    Boolean,
+    Integer,
+    Float,
+    String,
+    Named(NamespacePath, SourceRange),
+    Array(Box<TypeAnnotation>),
+    Object(Vec<(StringIdx, TypeAnnotation)>),
+    Closure(Vec<TypeAnnotation>, Box<TypeAnnotation>),
+    Variants(Vec<(StringIdx, TypeAnnotation)>, bool)
+}
+
+impl TypeAnnotation {
+    pub fn to_string(&self, strings: &StringMap) -> String {
+        match self {
+            TypeAnnotation::Unit => String::from("unit"),
+            TypeAnnotation::Boolean => String::from("bool"),
+            TypeAnnotation::Integer => String::from("int"),
+            TypeAnnotation::Float => String::from("float"),
+            TypeAnnotation::String => String::from("str"),
+            TypeAnnotation::Named(path, _) => path.display(strings),
+            TypeAnnotation::Array(element_type) => format!("[{}]", element_type.to_string(strings)),
+            TypeAnnotation::Object(members) => format!("{{ {} }}",
+                members.iter().map(|(member_name, member_type)| format!("{} = {}",
+                    strings.get(*member_name),
+                    member_type.to_string(strings)
+                )).collect::<Vec<String>>().join(", ")
+            ),
+            TypeAnnotation::Closure(parameter_types, return_type) => format!("|{}| -> {}",
+                parameter_types.iter().map(|t| t.to_string(strings)).collect::<Vec<String>>().join(", "),
+                return_type.to_string(strings)
+            ),
+            TypeAnnotation::Variants(variants, fixed) => format!("({}{})",
+                variants.iter().map(|(variant_name, variant_type)| format!("#{} {}",
+                    strings.get(*variant_name),
+                    variant_type.to_string(strings)
+                )).collect::<Vec<String>>().join(" | "),
+                if *fixed { "" } else { " | ..." }
+            )
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern<T: Clone + HasAstNodeVariant<T>> {
+    Variable { name: StringIdx, source: SourceRange, value_types: Option<TypeGroup> },
+    Wildcard { source: SourceRange, value_types: Option<TypeGroup> },
+    Value { value: Box<T>, source: SourceRange, value_types: Option<TypeGroup> },
+    Variant { name: StringIdx, payload: Option<Box<Pattern<T>>>, source: SourceRange, value_types: Option<TypeGroup> },
+    Object { members: Vec<(StringIdx, Pattern<T>)>, source: SourceRange, value_types: Option<TypeGroup> },
+    Array { elements: Vec<Pattern<T>>, source: SourceRange, value_types: Option<TypeGroup>, index_types: Option<TypeGroup> }
+}
+
+impl<T: Clone + HasAstNodeVariant<T>> Pattern<T> {
+    pub fn value_types(&self) -> Option<TypeGroup> {
+        match self {
+            Pattern::Variable { value_types, .. } |
+            Pattern::Wildcard { value_types, .. } |
+            Pattern::Value { value_types, .. } |
+            Pattern::Variant { value_types, .. } |
+            Pattern::Object { value_types, .. } |
+            Pattern::Array { value_types, .. } => *value_types
+        }
+    }
+
+    pub fn value_types_mut(&mut self) -> &mut Option<TypeGroup> {
+        match self {
+            Pattern::Variable { value_types, .. } |
+            Pattern::Wildcard { value_types, .. } |
+            Pattern::Value { value_types, .. } |
+            Pattern::Variant { value_types, .. } |
+            Pattern::Object { value_types, .. } |
+            Pattern::Array { value_types, .. } => value_types
+        }
+    }
+
+    pub fn bindings(&self) -> Vec<(StringIdx, SourceRange)> {
+        match self {
+            Pattern::Variable { name, source, .. } => vec![(*name, *source)],
+            Pattern::Wildcard { .. } |
+            Pattern::Value { .. } => Vec::new(),
+            Pattern::Variant { payload, .. } => payload.as_ref().map(|p| p.bindings()).unwrap_or_default(),
+            Pattern::Object { members, .. } => members.iter().flat_map(|(_, p)| p.bindings()).collect(),
+            Pattern::Array { elements, .. } => elements.iter().flat_map(|p| p.bindings()).collect()
+        }
+    }
+
+    pub fn values(&self) -> Vec<&T> {
+        match self {
+            Pattern::Variable { .. } |
+            Pattern::Wildcard { .. } => Vec::new(),
+            Pattern::Value { value, .. } => vec![&**value],
+            Pattern::Variant { payload, .. } => payload.as_ref().map(|p| p.values()).unwrap_or_default(),
+            Pattern::Object { members, .. } => members.iter().flat_map(|(_, p)| p.values()).collect(),
+            Pattern::Array { elements, .. } => elements.iter().flat_map(|p| p.values()).collect()
+        }
+    }
+
+    pub fn values_mut(&mut self) -> Vec<&mut T> {
+        match self {
+            Pattern::Variable { .. } |
+            Pattern::Wildcard { .. } => Vec::new(),
+            Pattern::Value { value, .. } => vec![&mut **value],
+            Pattern::Variant { payload, .. } => payload.as_mut().map(|p| p.values_mut()).unwrap_or_default(),
+            Pattern::Object { members, .. } => members.iter_mut().flat_map(|(_, p)| p.values_mut()).collect(),
+            Pattern::Array { elements, .. } => elements.iter_mut().flat_map(|p| p.values_mut()).collect()
+        }
+    }
+
+    pub fn map_values<U: Clone + HasAstNodeVariant<U>, E>(
+        self, f: &mut impl FnMut(T) -> Result<U, E>
+    ) -> Result<Pattern<U>, E> {
+        Ok(match self {
+            Pattern::Variable { name, source, value_types } => Pattern::Variable { name, source, value_types },
+            Pattern::Wildcard { source, value_types } => Pattern::Wildcard { source, value_types },
+            Pattern::Value { value, source, value_types } => Pattern::Value { value: Box::new(f(*value)?), source, value_types },
+            Pattern::Variant { name, payload, source, value_types } => Pattern::Variant {
+                name,
+                payload: match payload {
+                    Some(payload) => Some(Box::new(payload.map_values(f)?)),
+                    None => None
+                },
+                source, value_types
+            },
+            Pattern::Object { members, source, value_types } => Pattern::Object {
+                members: members.into_iter()
+                    .map(|(member_name, member_pattern)| Ok((member_name, member_pattern.map_values(f)?)))
+                    .collect::<Result<Vec<(StringIdx, Pattern<U>)>, E>>()?,
+                source, value_types
+            },
+            Pattern::Array { elements, source, value_types, index_types } => Pattern::Array {
+                elements: elements.into_iter()
+                    .map(|p| p.map_values(f))
+                    .collect::<Result<Vec<Pattern<U>>, E>>()?,
+                source, value_types, index_types
+            }
+        })
+    }
+
+    pub fn to_string(&self, strings: &StringMap) -> String {
+        match self {
+            Pattern::Variable { name, .. } => String::from(strings.get(*name)),
+            Pattern::Wildcard { .. } => String::from("_"),
+            Pattern::Value { value, .. } => value.to_string(strings),
+            Pattern::Variant { name, payload, .. } => match payload {
+                Some(payload) => format!("#{} {}", strings.get(*name), payload.to_string(strings)),
+                None => format!("#{}", strings.get(*name))
+            },
+            Pattern::Object { members, .. } => format!("{{ {} }}",
+                members.iter().map(|(member_name, member_pattern)| format!("{} = {}",
+                    strings.get(*member_name),
+                    member_pattern.to_string(strings)
+                )).collect::<Vec<String>>().join(", ")
+            ),
+            Pattern::Array { elements, .. } => format!("[{}]",
+                elements.iter().map(|p| p.to_string(strings)).collect::<Vec<String>>().join(", ")
+            )
+        }
+    }
+}
+
+impl<T: Clone + HasAstNodeVariant<T>> HasSource for Pattern<T> {
+    fn source(&self) -> SourceRange {
+        match self {
+            Pattern::Variable { source, .. } |
+            Pattern::Wildcard { source, .. } |
+            Pattern::Value { source, .. } |
+            Pattern::Variant { source, .. } |
+            Pattern::Object { source, .. } |
+            Pattern::Array { source, .. } => *source
+        }
+    }
 }
 
 fn indent(input: String, amount: usize) -> String {
@@ -118,13 +305,72 @@ fn indent(input: String, amount: usize) -> String {
 }
 
 impl<T: Clone + HasAstNodeVariant<T>> AstNodeVariant<T> {
+    /// Picks the simplest representation for a pattern match.
+    /// Patterns with bare identifiers are kept as they are, since those may either
+    /// bind a variable or name a constant until the module has been canonicalized.
+    /// Repeated values or variants are also kept, so that the unreachable branch gets reported.
+    pub fn case_pattern(value: Box<T>, branches: Vec<(Pattern<T>, Option<T>, Vec<T>)>, else_body: Option<Vec<T>>) -> AstNodeVariant<T>
+    where T: PartialEq {
+        let is_identifier = |value: &T| matches!(value.node_variant(), AstNodeVariant::VariableAccess { .. });
+        let is_value_branch = |(pattern, guard, _): &(Pattern<T>, Option<T>, Vec<T>)|
+            guard.is_none() && matches!(pattern, Pattern::Value { value, .. } if !is_identifier(value));
+        let is_variant_branch = |(pattern, guard, _): &(Pattern<T>, Option<T>, Vec<T>)|
+            guard.is_none() && match pattern {
+                Pattern::Variant { payload: None, .. } => true,
+                Pattern::Variant { payload: Some(payload), .. } =>
+                    matches!(**payload, Pattern::Variable { .. } | Pattern::Wildcard { .. }),
+                _ => false
+            };
+        let is_repeated = |index: usize| branches[..index].iter().any(|(previous, _, _)| match (previous, &branches[index].0) {
+            (Pattern::Value { value: a, .. }, Pattern::Value { value: b, .. }) => a.node_variant() == b.node_variant(),
+            (Pattern::Variant { name: a, .. }, Pattern::Variant { name: b, .. }) => a == b,
+            _ => false
+        });
+        if (0..branches.len()).any(is_repeated) {
+            AstNodeVariant::CasePattern { value, branches, else_body }
+        } else if branches.iter().all(is_value_branch) {
+            AstNodeVariant::CaseBranches {
+                value,
+                branches: branches.into_iter().map(|(pattern, _, body)| match pattern {
+                    Pattern::Value { value, .. } => (*value, body),
+                    _ => panic!("unreachable")
+                }).collect(),
+                else_body: else_body.unwrap_or_default()
+            }
+        } else if branches.iter().all(is_variant_branch) {
+            AstNodeVariant::CaseVariant {
+                value,
+                branches: branches.into_iter().map(|(pattern, _, body)| match pattern {
+                    Pattern::Variant { name, payload, .. } => (name, match payload.map(|p| *p) {
+                        Some(Pattern::Variable { name, source, .. }) => Some((name, source, None)),
+                        _ => None
+                    }, body),
+                    _ => panic!("unreachable")
+                }).collect(),
+                else_body
+            }
+        } else {
+            AstNodeVariant::CasePattern { value, branches, else_body }
+        }
+    }
+
     pub fn to_string(&self, strings: &StringMap) -> String {
         match self {
-            AstNodeVariant::Procedure { public, name, arguments, body } =>
-                format!("Procedure\n  public = {}\n  name = '{}'\n  arguments = [{}]\n  body = \n    {}",
+            AstNodeVariant::Procedure { public, name, arguments, returns, body } =>
+                format!("Procedure\n  public = {}\n  name = '{}'\n  arguments = [{}]\n  returns = {}\n  body = \n    {}",
                     public,
                     strings.get(*name),
-                    arguments.iter().map(|s| strings.get(s.0).to_string()).collect::<Vec<String>>().join(", "),
+                    arguments.iter().map(|s| {
+                        let argument = match &s.2 {
+                            Some(annotation) => format!("{}: {}", strings.get(s.0), annotation.to_string(strings)),
+                            None => strings.get(s.0).to_string()
+                        };
+                        match &s.3 {
+                            Some(default) => format!("{} = {}", argument, indent(default.to_string(strings), 4)),
+                            None => argument
+                        }
+                    }).collect::<Vec<String>>().join(", "),
+                    returns.as_ref().map(|r| r.0.to_string(strings)).unwrap_or(String::from("<none>")),
                     indent(body.iter().map(|n| n.to_string(strings)).collect::<Vec<String>>().join("\n"), 4)
                 ),
             AstNodeVariant::Function { arguments, body } =>
@@ -132,13 +378,20 @@ impl<T: Clone + HasAstNodeVariant<T>> AstNodeVariant<T> {
                     arguments.iter().map(|s| strings.get(s.0).to_string()).collect::<Vec<String>>().join(", "),
                     indent(body.iter().map(|n| n.to_string(strings)).collect::<Vec<String>>().join("\n"), 4)
                 ),
-            AstNodeVariant::Variable { public, mutable, name, value_types: _, value } => 
-                format!("Variable\n  public = {}\n  mutable = {}\n  name = '{}'\n  value = \n    {}",
+            AstNodeVariant::Variable { public, mutable, name, annotation, value_types: _, value } => 
+                format!("Variable\n  public = {}\n  mutable = {}\n  name = '{}'\n  annotation = {}\n  value = \n    {}",
                     public,
                     mutable,
                     strings.get(*name),
+                    annotation.as_ref().map(|a| a.to_string(strings)).unwrap_or(String::from("<none>")),
                     indent(value.as_ref().map(|n| n.to_string(strings)).unwrap_or(String::from("<none>")), 4)
                 ),
+            AstNodeVariant::Destructuring { mutable, pattern, value } =>
+                format!("Destructuring\n  mutable = {}\n  pattern = {}\n  value = \n    {}",
+                    mutable,
+                    pattern.to_string(strings),
+                    indent(value.to_string(strings), 4)
+                ),
             AstNodeVariant::CaseBranches { value, branches, else_body } =>
                 format!("CaseBranches\n  value = \n    {}\n  branches = \n    {}\n  else_body = \n    {}",
                     indent(value.to_string(strings), 4),
@@ -164,6 +417,16 @@ impl<T: Clone + HasAstNodeVariant<T>> AstNodeVariant<T> {
                     )).collect::<Vec<String>>().join("\n"), 4),
                     indent(else_body.as_ref().map(|else_body| else_body.iter().map(|n| n.to_string(strings)).collect::<Vec<String>>().join("\n")).unwrap_or(String::from("<none>")), 4)
                 ),
+            AstNodeVariant::CasePattern { value, branches, else_body } =>
+                format!("CasePattern\n  value = \n    {}\n  branches = \n    {}\n  else_body = \n    {}",
+                    indent(value.to_string(strings), 4),
+                    indent(branches.iter().map(|(pattern, guard, branch_body)| format!("branch\n  pattern = {}\n  guard = \n    {}\n  body = \n    {}",
+                        indent(pattern.to_string(strings), 4),
+                        indent(guard.as_ref().map(|g| g.to_string(strings)).unwrap_or(String::from("<none>")), 4),
+                        indent(branch_body.iter().map(|n| n.to_string(strings)).collect::<Vec<String>>().join("\n"), 4)
+                    )).collect::<Vec<String>>().join("\n"), 4),
+                    indent(else_body.as_ref().map(|else_body| else_body.iter().map(|n| n.to_string(strings)).collect::<Vec<String>>().join("\n")).unwrap_or(String::from("<none>")), 4)
+                ),
             AstNodeVariant::Assignment { variable, value } => 
                 format!("Assignment\n  variable = \n    {}\n  value = \n    {}",
                     indent(variable.to_string(strings), 4),
@@ -173,11 +436,20 @@ impl<T: Clone + HasAstNodeVariant<T>> AstNodeVariant<T> {
                 format!("Return\n  value = \n    {}",
                     indent(value.to_string(strings), 4)
                 ),
+            AstNodeVariant::Propagate { value, returned_types: _ } =>
+                format!("Propagate\n  value = \n    {}",
+                    indent(value.to_string(strings), 4)
+                ),
             AstNodeVariant::Call { called, arguments } =>
                 format!("Call\n  called = \n    {}\n  arguments = \n    {}",
                     indent(called.to_string(strings), 4),
                     indent(arguments.iter().map(|n| n.to_string(strings)).collect::<Vec<String>>().join("\n"), 4)
                 ),
+            AstNodeVariant::NamedArgument { name, value } =>
+                format!("NamedArgument\n  name = '{}'\n  value = \n    {}",
+                    strings.get(*name),
+                    indent(value.to_string(strings), 4)
+                ),
             AstNodeVariant::Object { values } => 
                 format!("Object\n  members = \n    {}",
                     indent(values.iter().map(|(member_name, member_value)| format!("member\n  name = '{}'\n  value = \n    {}",
@@ -234,6 +506,35 @@ impl<T: Clone + HasAstNodeVariant<T>> AstNodeVariant<T> {
                 format!("Negate\n  x = \n    {}",
                     indent(x.to_string(strings), 4),
                 ),
+            AstNodeVariant::BitwiseAnd { a, b } =>
+                format!("BitwiseAnd\n  a = \n    {}\n  b = \n    {}",
+                    indent(a.to_string(strings), 4),
+                    indent(b.to_string(strings), 4)
+                ),
+            AstNodeVariant::BitwiseOr { a, b } =>
+                format!("BitwiseOr\n  a = \n    {}\n  b = \n    {}",
+                    indent(a.to_string(strings), 4),
+                    indent(b.to_string(strings), 4)
+                ),
+            AstNodeVariant::BitwiseXor { a, b } =>
+                format!("BitwiseXor\n  a = \n    {}\n  b = \n    {}",
+                    indent(a.to_string(strings), 4),
+                    indent(b.to_string(strings), 4)
+                ),
+            AstNodeVariant::BitwiseNot { x } =>
+                format!("BitwiseNot\n  x = \n    {}",
+                    indent(x.to_string(strings), 4),
+                ),
+            AstNodeVariant::LeftShift { a, b } =>
+                format!("LeftShift\n  a = \n    {}\n  b = \n    {}",
+                    indent(a.to_string(strings), 4),
+                    indent(b.to_string(strings), 4)
+                ),
+            AstNodeVariant::RightShift { a, b } =>
+                format!("RightShift\n  a = \n    {}\n  b = \n    {}",
+                    indent(a.to_string(strings), 4),
+                    indent(b.to_string(strings), 4)
+                ),
             AstNodeVariant::LessThan { a, b } =>
                 format!("LessThan\n  a = \n    {}\n  b = \n    {}",
                     indent(a.to_string(strings), 4),
@@ -304,6 +605,13 @@ impl<T: Clone + HasAstNodeVariant<T>> AstNodeVariant<T> {
                     strings.get(*target),
                     indent(body.iter().map(|n| n.to_string(strings)).collect::<Vec<String>>().join("\n"), 4)
                 ),
+            AstNodeVariant::TypeAlias { public, name, nominal, aliased } =>
+                format!("TypeAlias\n  public = {}\n  name = '{}'\n  nominal = {}\n  aliased = {}",
+                    public,
+                    strings.get(*name),
+                    nominal,
+                    aliased.to_string(strings)
+                ),
         }
     }
 }
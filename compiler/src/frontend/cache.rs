@@ -0,0 +1,1209 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::fs;
+
+use crate::util::{
+    strings::{StringMap, StringIdx},
+    error::Error,
+    source::{HasSource, SourceRange}
+};
+use crate::frontend::{
+    ast::{AstNode, TypedAstNode, HasAstNodeVariant, AstNodeVariant, TypeAnnotation, Pattern},
+    modules::{Module, NamespacePath},
+    type_checking::{type_check_modules, Symbol},
+    types::{TypeScope, TypeGroup}
+};
+
+
+const CACHE_MAGIC: &[u8] = b"GERACACHE";
+const CACHE_FORMAT_VERSION: u32 = 4;
+
+/// 64-bit FNV-1a. Cache keys are stored on disk, so unlike the standard
+/// library's hasher the result must not change between compiler builds.
+struct KeyHasher(u64);
+
+impl KeyHasher {
+    fn new() -> KeyHasher { KeyHasher(0xcbf29ce484222325) }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) { self.write_bytes(&value.to_le_bytes()); }
+
+    fn write_str(&mut self, value: &str) {
+        self.write_u64(value.len() as u64);
+        self.write_bytes(value.as_bytes());
+    }
+
+    fn finish(&self) -> u64 { self.0 }
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleCache {
+    directory: PathBuf
+}
+
+impl ModuleCache {
+    pub fn new(directory: PathBuf) -> ModuleCache {
+        ModuleCache { directory }
+    }
+
+    fn file_key(
+        &self, file_name: StringIdx, file_content: StringIdx, target_str: &str, strings: &StringMap
+    ) -> u64 {
+        let mut hasher = KeyHasher::new();
+        hasher.write_u64(CACHE_FORMAT_VERSION as u64);
+        hasher.write_str(env!("CARGO_PKG_VERSION"));
+        hasher.write_str(target_str);
+        hasher.write_str(strings.get(file_name));
+        hasher.write_str(strings.get(file_content));
+        hasher.finish()
+    }
+
+    fn entry_path(&self, key: u64, extension: &str) -> PathBuf {
+        self.directory.join(format!("{:016x}.{}", key, extension))
+    }
+
+    fn load(&self, key: u64, extension: &str) -> Option<Vec<u8>> {
+        fs::read(self.entry_path(key, extension)).ok()
+    }
+
+    fn store(&self, key: u64, extension: &str, data: Vec<u8>) {
+        // the cache is only an optimization, so failing to write to it is not an error
+        if fs::create_dir_all(&self.directory).is_err() { return; }
+        let entry_path = self.entry_path(key, extension);
+        let temp_path = self.directory.join(format!("{:016x}.{}.{}", key, extension, std::process::id()));
+        if fs::write(&temp_path, data).is_err() { return; }
+        if fs::rename(&temp_path, &entry_path).is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+    }
+
+    pub fn load_file(
+        &self, file_name: StringIdx, file_content: StringIdx, target_str: &str, strings: &StringMap
+    ) -> Option<Vec<AstNode>> {
+        let data = self.load(self.file_key(file_name, file_content, target_str, strings), "ast")?;
+        let mut reader = CacheReader::new(&data, strings)?;
+        let nodes = read_nodes(&mut reader)?;
+        if reader.is_done() { Some(nodes) } else { None }
+    }
+
+    pub fn store_file(
+        &self, file_name: StringIdx, file_content: StringIdx, target_str: &str, nodes: &[AstNode], strings: &StringMap
+    ) {
+        let mut writer = CacheWriter::new(strings);
+        write_nodes(nodes, &mut writer);
+        if let Some(data) = writer.finish() {
+            self.store(self.file_key(file_name, file_content, target_str, strings), "ast", data);
+        }
+    }
+
+    pub fn type_check_modules(
+        &self,
+        modules: HashMap<NamespacePath, Module<AstNode>>,
+        files: &HashMap<StringIdx, StringIdx>,
+        target_str: &str,
+        strings: &StringMap,
+        type_scope: &mut TypeScope,
+        typed_symbols: &mut HashMap<NamespacePath, Symbol<TypedAstNode>>
+    ) -> Result<(), Vec<Error>> {
+        // external mappings may declare symbols in any module
+        let mut external_files = files.iter()
+            .filter(|(file_name, _)| !strings.get(**file_name).ends_with(".gera"))
+            .map(|(file_name, file_content)| (strings.get(*file_name), strings.get(*file_content)))
+            .collect::<Vec<(&str, &str)>>();
+        external_files.sort();
+        let mut external_hasher = KeyHasher::new();
+        external_hasher.write_u64(external_files.len() as u64);
+        for (file_name, file_content) in &external_files {
+            external_hasher.write_str(file_name);
+            external_hasher.write_str(file_content);
+        }
+        let external_key = external_hasher.finish();
+        // typed symbols of a module depend on the contents of all modules it (indirectly) uses
+        let module_keys = modules.iter()
+            .filter(|(_, module)| !module.is_raw())
+            .filter_map(|(module_path, module)| files.get(&module.file_name()).map(|file_content|
+                (module_path.clone(), self.file_key(module.file_name(), *file_content, target_str, strings))
+            ))
+            .collect::<HashMap<NamespacePath, u64>>();
+        let dependencies = modules.iter()
+            .map(|(module_path, module)| (module_path.clone(), module_dependencies(module, &modules)))
+            .collect::<HashMap<NamespacePath, HashSet<NamespacePath>>>();
+        let mut symbol_keys = HashMap::new();
+        for module_path in module_keys.keys() {
+            let mut encountered = HashSet::from([module_path]);
+            let mut queue = vec![module_path];
+            while let Some(current) = queue.pop() {
+                for dependency in dependencies.get(current).into_iter().flatten() {
+                    if encountered.insert(dependency) { queue.push(dependency); }
+                }
+            }
+            let mut dependency_keys = encountered.into_iter()
+                .map(|dependency| module_keys.get(dependency).copied())
+                .collect::<Vec<Option<u64>>>();
+            dependency_keys.sort();
+            let mut hasher = KeyHasher::new();
+            hasher.write_u64(CACHE_FORMAT_VERSION as u64);
+            hasher.write_str(env!("CARGO_PKG_VERSION"));
+            hasher.write_u64(external_key);
+            hasher.write_u64(dependency_keys.len() as u64);
+            for dependency_key in dependency_keys {
+                match dependency_key {
+                    Some(key) => { hasher.write_bytes(&[1]); hasher.write_u64(key); }
+                    None => hasher.write_bytes(&[0])
+                }
+            }
+            symbol_keys.insert(module_path.clone(), hasher.finish());
+        }
+        // load cached modules and type check the rest
+        let mut checked_modules = HashMap::new();
+        let mut checked_symbols = HashMap::new();
+        for (module_path, module) in modules {
+            if let Some(key) = symbol_keys.get(&module_path) {
+                if let Some(symbols) = self.load_symbols(*key, strings, type_scope) {
+                    typed_symbols.extend(symbols);
+                    continue;
+                }
+                checked_symbols.insert(module_path.clone(), module.symbol_nodes().keys().map(|symbol_name| {
+                    let mut symbol_path_segments = module_path.get_segments().clone();
+                    symbol_path_segments.push(*symbol_name);
+                    NamespacePath::new(symbol_path_segments)
+                }).collect::<Vec<NamespacePath>>());
+            }
+            checked_modules.insert(module_path, module);
+        }
+        type_check_modules(checked_modules, strings, type_scope, typed_symbols)?;
+        for (module_path, symbol_paths) in checked_symbols {
+            let symbols = symbol_paths.iter()
+                .filter_map(|symbol_path| typed_symbols.get(symbol_path).map(|symbol| (symbol_path, symbol)))
+                .collect::<Vec<(&NamespacePath, &Symbol<TypedAstNode>)>>();
+            self.store_symbols(symbol_keys[&module_path], &symbols, type_scope, strings);
+        }
+        Ok(())
+    }
+
+    fn load_symbols(
+        &self, key: u64, strings: &StringMap, global_scope: &mut TypeScope
+    ) -> Option<Vec<(NamespacePath, Symbol<TypedAstNode>)>> {
+        let data = self.load(key, "typed")?;
+        let mut reader = CacheReader::new(&data, strings)?;
+        let portable_scope = TypeScope::read_cached(&mut reader)?;
+        let mut symbols = Vec::new();
+        let mut global_groups = Vec::new();
+        for _ in 0..reader.read_usize()? {
+            let path = reader.read_path()?;
+            let symbol = match reader.read_u8()? {
+                0 => {
+                    let public = reader.read_bool()?;
+                    reader.set_scope(portable_scope.id(), portable_scope.group_count());
+                    let value_types = reader.read_group()?;
+                    // constant values are only interpreted, their types are not used (or validated)
+                    reader.set_scope(TypeScope::new().id(), usize::MAX);
+                    let value = if reader.read_bool()? { Some(TypedAstNode::read_cached(&mut reader)?) }
+                        else { None };
+                    global_groups.push(value_types);
+                    Symbol::Constant { public, value, value_types }
+                }
+                1 => {
+                    let public = reader.read_bool()?;
+                    let mut parameter_names = Vec::new();
+                    for _ in 0..reader.read_usize()? { parameter_names.push(reader.read_string()?); }
+                    let type_scope = TypeScope::read_cached(&mut reader)?;
+                    let mut parameter_types = Vec::new();
+                    for _ in 0..reader.read_usize()? { parameter_types.push(reader.read_group()?); }
+                    let returns = reader.read_group()?;
+                    let body = if reader.read_bool()? { Some(read_nodes(&mut reader)?) }
+                        else { None };
+                    let source = reader.read_source()?;
+                    let mut parameter_defaults = Vec::new();
+                    for _ in 0..parameter_names.len() {
+                        parameter_defaults.push(if reader.read_bool()? { Some(AstNode::read_cached(&mut reader)?) } else { None });
+                    }
+                    Symbol::Procedure { public, parameter_names, parameter_defaults, parameter_types, returns, body, source, type_scope }
+                }
+                2 => {
+                    let public = reader.read_bool()?;
+                    let nominal = reader.read_bool()?;
+                    reader.set_scope(portable_scope.id(), portable_scope.group_count());
+                    let value_types = reader.read_group()?;
+                    global_groups.push(value_types);
+                    Symbol::Type { public, nominal, value_types }
+                }
+                _ => return None
+            };
+            symbols.push((path, symbol));
+        }
+        if !reader.is_done() { return None; }
+        let mut global_groups = portable_scope.transfer_groups(&global_groups, global_scope).into_iter();
+        for (_, symbol) in &mut symbols {
+            match symbol {
+                Symbol::Constant { value_types, .. } |
+                Symbol::Type { value_types, .. } => {
+                    *value_types = global_groups.next().expect("should have been transferred");
+                }
+                Symbol::Procedure { .. } => {}
+            }
+        }
+        Some(symbols)
+    }
+
+    fn store_symbols(
+        &self, key: u64, symbols: &[(&NamespacePath, &Symbol<TypedAstNode>)], global_scope: &TypeScope, strings: &StringMap
+    ) {
+        let mut portable_scope = TypeScope::new();
+        let global_groups = symbols.iter().filter_map(|(_, symbol)| match symbol {
+            Symbol::Constant { value_types, .. } |
+            Symbol::Type { value_types, .. } => Some(*value_types),
+            Symbol::Procedure { .. } => None
+        }).collect::<Vec<TypeGroup>>();
+        let mut global_groups = global_scope.transfer_groups(&global_groups, &mut portable_scope).into_iter();
+        let mut writer = CacheWriter::new(strings);
+        portable_scope.write_cached(&mut writer);
+        writer.write_usize(symbols.len());
+        for (path, symbol) in symbols {
+            writer.write_path(path);
+            match symbol {
+                Symbol::Constant { public, value, value_types: _ } => {
+                    writer.write_u8(0);
+                    writer.write_bool(*public);
+                    writer.set_scope(Some(portable_scope.id()));
+                    writer.write_group(global_groups.next().expect("should have been transferred"));
+                    writer.set_scope(None);
+                    writer.write_bool(value.is_some());
+                    if let Some(value) = value { value.write_cached(&mut writer); }
+                }
+                Symbol::Procedure { public, parameter_names, parameter_defaults, parameter_types, returns, body, source, type_scope } => {
+                    writer.write_u8(1);
+                    writer.write_bool(*public);
+                    writer.write_usize(parameter_names.len());
+                    for parameter_name in parameter_names { writer.write_string(*parameter_name); }
+                    type_scope.write_cached(&mut writer);
+                    writer.write_usize(parameter_types.len());
+                    for parameter_type in parameter_types { writer.write_group(*parameter_type); }
+                    writer.write_group(*returns);
+                    writer.write_bool(body.is_some());
+                    if let Some(body) = body { write_nodes(body, &mut writer); }
+                    writer.write_source(*source);
+                    for parameter_default in parameter_defaults {
+                        writer.write_bool(parameter_default.is_some());
+                        if let Some(parameter_default) = parameter_default { parameter_default.write_cached(&mut writer); }
+                    }
+                }
+                Symbol::Type { public, nominal, value_types: _ } => {
+                    writer.write_u8(2);
+                    writer.write_bool(*public);
+                    writer.write_bool(*nominal);
+                    writer.set_scope(Some(portable_scope.id()));
+                    writer.write_group(global_groups.next().expect("should have been transferred"));
+                }
+            }
+        }
+        if let Some(data) = writer.finish() {
+            self.store(key, "typed", data);
+        }
+    }
+}
+
+fn module_dependencies(
+    module: &Module<AstNode>, modules: &HashMap<NamespacePath, Module<AstNode>>
+) -> HashSet<NamespacePath> {
+    referenced_paths(module.symbol_nodes().values()).into_iter()
+        .filter_map(|path| {
+            let segments = path.get_segments();
+            if segments.len() == 0 { return None; }
+            let module_path = NamespacePath::new(segments[..segments.len() - 1].into());
+            if modules.contains_key(&module_path) { Some(module_path) } else { None }
+        })
+        .collect()
+}
+
+/// Collects every namespace path that is mentioned in the given nodes.
+pub(crate) fn referenced_paths<'n>(
+    nodes: impl Iterator<Item = &'n AstNode>
+) -> HashSet<NamespacePath> {
+    let mut paths = HashSet::new();
+    for node in nodes { collect_node_paths(node, &mut paths); }
+    paths
+}
+
+fn collect_annotation_paths(annotation: &TypeAnnotation, paths: &mut HashSet<NamespacePath>) {
+    match annotation {
+        TypeAnnotation::Unit |
+        TypeAnnotation::Boolean |
+        TypeAnnotation::Integer |
+        TypeAnnotation::Float |
+        TypeAnnotation::String => {}
+        TypeAnnotation::Named(path, _) => { paths.insert(path.clone()); }
+        TypeAnnotation::Array(element_type) => collect_annotation_paths(element_type, paths),
+        TypeAnnotation::Object(members) |
+        TypeAnnotation::Variants(members, _) => {
+            for (_, member_type) in members { collect_annotation_paths(member_type, paths); }
+        }
+        TypeAnnotation::Closure(parameter_types, return_type) => {
+            for parameter_type in parameter_types { collect_annotation_paths(parameter_type, paths); }
+            collect_annotation_paths(return_type, paths);
+        }
+    }
+}
+
+fn collect_node_paths(node: &AstNode, paths: &mut HashSet<NamespacePath>) {
+    fn collect_nodes(nodes: &[AstNode], paths: &mut HashSet<NamespacePath>) {
+        for node in nodes { collect_node_paths(node, paths); }
+    }
+    match node.node_variant() {
+        AstNodeVariant::Procedure { arguments, returns, body, .. } => {
+            for (_, _, argument_annotation, argument_default) in arguments {
+                if let Some(argument_annotation) = argument_annotation { collect_annotation_paths(argument_annotation, paths); }
+                if let Some(argument_default) = argument_default { collect_node_paths(argument_default, paths); }
+            }
+            if let Some((returns, _)) = returns { collect_annotation_paths(returns, paths); }
+            collect_nodes(body, paths);
+        }
+        AstNodeVariant::Function { body, .. } |
+        AstNodeVariant::Target { body, .. } => collect_nodes(body, paths),
+        AstNodeVariant::Variable { annotation, value, .. } => {
+            if let Some(annotation) = annotation { collect_annotation_paths(annotation, paths); }
+            if let Some(value) = value { collect_node_paths(value, paths); }
+        }
+        AstNodeVariant::Destructuring { pattern, value, .. } => {
+            for pattern_value in pattern.values() { collect_node_paths(pattern_value, paths); }
+            collect_node_paths(value, paths);
+        }
+        AstNodeVariant::CaseBranches { value, branches, else_body } => {
+            collect_node_paths(value, paths);
+            for (branch_value, branch_body) in branches {
+                collect_node_paths(branch_value, paths);
+                collect_nodes(branch_body, paths);
+            }
+            collect_nodes(else_body, paths);
+        }
+        AstNodeVariant::CaseConditon { condition, body, else_body } => {
+            collect_node_paths(condition, paths);
+            collect_nodes(body, paths);
+            collect_nodes(else_body, paths);
+        }
+        AstNodeVariant::CaseVariant { value, branches, else_body } => {
+            collect_node_paths(value, paths);
+            for (_, _, branch_body) in branches { collect_nodes(branch_body, paths); }
+            if let Some(else_body) = else_body { collect_nodes(else_body, paths); }
+        }
+        AstNodeVariant::CasePattern { value, branches, else_body } => {
+            collect_node_paths(value, paths);
+            for (pattern, guard, branch_body) in branches {
+                for pattern_value in pattern.values() { collect_node_paths(pattern_value, paths); }
+                if let Some(guard) = guard { collect_node_paths(guard, paths); }
+                collect_nodes(branch_body, paths);
+            }
+            if let Some(else_body) = else_body { collect_nodes(else_body, paths); }
+        }
+        AstNodeVariant::Call { called, arguments } => {
+            collect_node_paths(called, paths);
+            collect_nodes(arguments, paths);
+        }
+        AstNodeVariant::Object { values } => {
+            for (_, member_value) in values { collect_node_paths(member_value, paths); }
+        }
+        AstNodeVariant::Array { values } => collect_nodes(values, paths),
+        AstNodeVariant::Return { value } |
+        AstNodeVariant::Propagate { value, .. } |
+        AstNodeVariant::NamedArgument { value, .. } |
+        AstNodeVariant::Variant { value, .. } |
+        AstNodeVariant::Static { value } |
+        AstNodeVariant::ObjectAccess { object: value, .. } |
+        AstNodeVariant::Negate { x: value } |
+        AstNodeVariant::BitwiseNot { x: value } |
+        AstNodeVariant::Not { x: value } => collect_node_paths(value, paths),
+        AstNodeVariant::Assignment { variable: a, value: b } |
+        AstNodeVariant::ArrayAccess { array: a, index: b } |
+        AstNodeVariant::Add { a, b } |
+        AstNodeVariant::Subtract { a, b } |
+        AstNodeVariant::Multiply { a, b } |
+        AstNodeVariant::Divide { a, b } |
+        AstNodeVariant::Modulo { a, b } |
+        AstNodeVariant::BitwiseAnd { a, b } |
+        AstNodeVariant::BitwiseOr { a, b } |
+        AstNodeVariant::BitwiseXor { a, b } |
+        AstNodeVariant::LeftShift { a, b } |
+        AstNodeVariant::RightShift { a, b } |
+        AstNodeVariant::LessThan { a, b } |
+        AstNodeVariant::GreaterThan { a, b } |
+        AstNodeVariant::LessThanEqual { a, b } |
+        AstNodeVariant::GreaterThanEqual { a, b } |
+        AstNodeVariant::Equals { a, b } |
+        AstNodeVariant::NotEquals { a, b } |
+        AstNodeVariant::Or { a, b } |
+        AstNodeVariant::And { a, b } => {
+            collect_node_paths(a, paths);
+            collect_node_paths(b, paths);
+        }
+        AstNodeVariant::Module { path } |
+        AstNodeVariant::ModuleAccess { path } => { paths.insert(path.clone()); }
+        AstNodeVariant::Use { paths: used_paths } => paths.extend(used_paths.iter().cloned()),
+        AstNodeVariant::TypeAlias { aliased, .. } => collect_annotation_paths(aliased, paths),
+        AstNodeVariant::VariableAccess { .. } |
+        AstNodeVariant::BooleanLiteral { .. } |
+        AstNodeVariant::IntegerLiteral { .. } |
+        AstNodeVariant::FloatLiteral { .. } |
+        AstNodeVariant::StringLiteral { .. } |
+        AstNodeVariant::UnitLiteral => {}
+    }
+}
+
+
+pub(crate) struct CacheWriter<'s> {
+    strings: &'s StringMap,
+    string_indices: HashMap<StringIdx, usize>,
+    string_table: Vec<StringIdx>,
+    data: Vec<u8>,
+    scope_id: Option<usize>,
+    valid: bool
+}
+
+impl<'s> CacheWriter<'s> {
+    fn new(strings: &'s StringMap) -> CacheWriter<'s> {
+        CacheWriter {
+            strings,
+            string_indices: HashMap::new(),
+            string_table: Vec::new(),
+            data: Vec::new(),
+            scope_id: None,
+            valid: true
+        }
+    }
+
+    fn finish(self) -> Option<Vec<u8>> {
+        // type groups that belong to a different scope can't be written
+        if !self.valid { return None; }
+        let mut content = Vec::new();
+        content.extend((self.string_table.len() as u64).to_le_bytes());
+        for string in &self.string_table {
+            let string = self.strings.get(*string).as_bytes();
+            content.extend((string.len() as u64).to_le_bytes());
+            content.extend(string);
+        }
+        content.extend(self.data);
+        let mut checksum = KeyHasher::new();
+        checksum.write_bytes(&content);
+        let mut output = Vec::from(CACHE_MAGIC);
+        output.extend(CACHE_FORMAT_VERSION.to_le_bytes());
+        output.extend(checksum.finish().to_le_bytes());
+        output.extend(content);
+        Some(output)
+    }
+
+    pub(crate) fn set_scope(&mut self, scope_id: Option<usize>) { self.scope_id = scope_id; }
+
+    pub(crate) fn write_u8(&mut self, value: u8) { self.data.push(value); }
+    pub(crate) fn write_bool(&mut self, value: bool) { self.data.push(value as u8); }
+    pub(crate) fn write_usize(&mut self, value: usize) { self.data.extend((value as u64).to_le_bytes()); }
+    pub(crate) fn write_i64(&mut self, value: i64) { self.data.extend(value.to_le_bytes()); }
+    pub(crate) fn write_f64(&mut self, value: f64) { self.data.extend(value.to_bits().to_le_bytes()); }
+
+    pub(crate) fn write_string(&mut self, value: StringIdx) {
+        let table_idx = if let Some(table_idx) = self.string_indices.get(&value) { *table_idx } else {
+            let table_idx = self.string_table.len();
+            self.string_table.push(value);
+            self.string_indices.insert(value, table_idx);
+            table_idx
+        };
+        self.write_usize(table_idx);
+    }
+
+    pub(crate) fn write_path(&mut self, path: &NamespacePath) {
+        self.write_usize(path.get_segments().len());
+        for segment in path.get_segments() { self.write_string(*segment); }
+    }
+
+    pub(crate) fn write_source(&mut self, source: SourceRange) {
+        self.write_string(source.file_name());
+        self.write_string(source.file_content());
+        self.write_usize(source.start_position());
+        self.write_usize(source.end_position());
+    }
+
+    pub(crate) fn write_group(&mut self, group: TypeGroup) {
+        match self.scope_id {
+            None => self.scope_id = Some(group.scope_id()),
+            Some(scope_id) => if scope_id != group.scope_id() { self.valid = false; }
+        }
+        self.write_usize(group.index());
+    }
+
+    pub(crate) fn write_group_map(&mut self, groups: &HashMap<StringIdx, TypeGroup>) {
+        self.write_usize(groups.len());
+        for (name, group) in groups {
+            self.write_string(*name);
+            self.write_group(*group);
+        }
+    }
+}
+
+
+pub(crate) struct CacheReader<'d> {
+    data: &'d [u8],
+    position: usize,
+    strings: Vec<StringIdx>,
+    scope_id: usize,
+    group_count: usize
+}
+
+impl<'d> CacheReader<'d> {
+    fn new(data: &'d [u8], strings: &StringMap) -> Option<CacheReader<'d>> {
+        if !data.starts_with(CACHE_MAGIC) { return None; }
+        let mut reader = CacheReader {
+            data,
+            position: CACHE_MAGIC.len(),
+            strings: Vec::new(),
+            scope_id: 0,
+            group_count: 0
+        };
+        if u32::from_le_bytes(reader.read_bytes(4)?.try_into().ok()?) != CACHE_FORMAT_VERSION { return None; }
+        // entries that were modified or only partially written are treated like missing ones
+        let expected_checksum = u64::from_le_bytes(reader.read_bytes(8)?.try_into().ok()?);
+        let mut checksum = KeyHasher::new();
+        checksum.write_bytes(&data[reader.position..]);
+        if checksum.finish() != expected_checksum { return None; }
+        for _ in 0..reader.read_usize()? {
+            let length = reader.read_usize()?;
+            let string = std::str::from_utf8(reader.read_bytes(length)?).ok()?;
+            reader.strings.push(strings.insert(string));
+        }
+        Some(reader)
+    }
+
+    fn is_done(&self) -> bool { self.position == self.data.len() }
+
+    fn read_bytes(&mut self, length: usize) -> Option<&'d [u8]> {
+        let end = self.position.checked_add(length)?;
+        let bytes = self.data.get(self.position..end)?;
+        self.position = end;
+        Some(bytes)
+    }
+
+    pub(crate) fn set_scope(&mut self, scope_id: usize, group_count: usize) {
+        self.scope_id = scope_id;
+        self.group_count = group_count;
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Option<u8> { Some(self.read_bytes(1)?[0]) }
+    pub(crate) fn read_bool(&mut self) -> Option<bool> {
+        match self.read_u8()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None
+        }
+    }
+    pub(crate) fn read_usize(&mut self) -> Option<usize> {
+        usize::try_from(u64::from_le_bytes(self.read_bytes(8)?.try_into().ok()?)).ok()
+    }
+    pub(crate) fn read_i64(&mut self) -> Option<i64> {
+        Some(i64::from_le_bytes(self.read_bytes(8)?.try_into().ok()?))
+    }
+    pub(crate) fn read_f64(&mut self) -> Option<f64> {
+        Some(f64::from_bits(u64::from_le_bytes(self.read_bytes(8)?.try_into().ok()?)))
+    }
+
+    pub(crate) fn read_string(&mut self) -> Option<StringIdx> {
+        let table_idx = self.read_usize()?;
+        self.strings.get(table_idx).copied()
+    }
+
+    pub(crate) fn read_path(&mut self) -> Option<NamespacePath> {
+        let mut segments = Vec::new();
+        for _ in 0..self.read_usize()? { segments.push(self.read_string()?); }
+        Some(NamespacePath::new(segments))
+    }
+
+    pub(crate) fn read_source(&mut self) -> Option<SourceRange> {
+        Some(SourceRange::new(self.read_string()?, self.read_string()?, self.read_usize()?, self.read_usize()?))
+    }
+
+    pub(crate) fn read_group(&mut self) -> Option<TypeGroup> {
+        let index = self.read_usize()?;
+        if index >= self.group_count { return None; }
+        Some(TypeGroup::from_index(index, self.scope_id))
+    }
+
+    pub(crate) fn read_group_map(&mut self) -> Option<HashMap<StringIdx, TypeGroup>> {
+        let mut groups = HashMap::new();
+        for _ in 0..self.read_usize()? {
+            groups.insert(self.read_string()?, self.read_group()?);
+        }
+        Some(groups)
+    }
+}
+
+
+trait CachedNode: Sized + Clone + HasAstNodeVariant<Self> + HasSource {
+    fn write_cached(&self, writer: &mut CacheWriter);
+    fn read_cached(reader: &mut CacheReader) -> Option<Self>;
+}
+
+impl CachedNode for AstNode {
+    fn write_cached(&self, writer: &mut CacheWriter) {
+        write_variant(self.node_variant(), writer);
+        writer.write_source(self.source());
+    }
+
+    fn read_cached(reader: &mut CacheReader) -> Option<AstNode> {
+        Some(AstNode::new(read_variant(reader)?, reader.read_source()?))
+    }
+}
+
+impl CachedNode for TypedAstNode {
+    fn write_cached(&self, writer: &mut CacheWriter) {
+        write_variant(self.node_variant(), writer);
+        writer.write_group(self.get_types());
+        writer.write_source(self.source());
+    }
+
+    fn read_cached(reader: &mut CacheReader) -> Option<TypedAstNode> {
+        Some(TypedAstNode::new(read_variant(reader)?, reader.read_group()?, reader.read_source()?))
+    }
+}
+
+fn write_nodes<T: CachedNode>(nodes: &[T], writer: &mut CacheWriter) {
+    writer.write_usize(nodes.len());
+    for node in nodes { node.write_cached(writer); }
+}
+
+fn read_nodes<T: CachedNode>(reader: &mut CacheReader) -> Option<Vec<T>> {
+    let mut nodes = Vec::new();
+    for _ in 0..reader.read_usize()? { nodes.push(T::read_cached(reader)?); }
+    Some(nodes)
+}
+
+fn read_boxed<T: CachedNode>(reader: &mut CacheReader) -> Option<Box<T>> {
+    Some(Box::new(T::read_cached(reader)?))
+}
+
+fn write_annotation(annotation: &TypeAnnotation, writer: &mut CacheWriter) {
+    match annotation {
+        TypeAnnotation::Unit => writer.write_u8(0),
+        TypeAnnotation::Boolean => writer.write_u8(1),
+        TypeAnnotation::Integer => writer.write_u8(2),
+        TypeAnnotation::Float => writer.write_u8(3),
+        TypeAnnotation::String => writer.write_u8(4),
+        TypeAnnotation::Named(path, source) => {
+            writer.write_u8(5);
+            writer.write_path(path);
+            writer.write_source(*source);
+        }
+        TypeAnnotation::Array(element_type) => {
+            writer.write_u8(6);
+            write_annotation(element_type, writer);
+        }
+        TypeAnnotation::Object(members) => {
+            writer.write_u8(7);
+            writer.write_usize(members.len());
+            for (member_name, member_type) in members {
+                writer.write_string(*member_name);
+                write_annotation(member_type, writer);
+            }
+        }
+        TypeAnnotation::Closure(parameter_types, return_type) => {
+            writer.write_u8(8);
+            writer.write_usize(parameter_types.len());
+            for parameter_type in parameter_types { write_annotation(parameter_type, writer); }
+            write_annotation(return_type, writer);
+        }
+        TypeAnnotation::Variants(variants, fixed) => {
+            writer.write_u8(9);
+            writer.write_usize(variants.len());
+            for (variant_name, variant_type) in variants {
+                writer.write_string(*variant_name);
+                write_annotation(variant_type, writer);
+            }
+            writer.write_bool(*fixed);
+        }
+    }
+}
+
+fn read_annotation(reader: &mut CacheReader) -> Option<TypeAnnotation> {
+    Some(match reader.read_u8()? {
+        0 => TypeAnnotation::Unit,
+        1 => TypeAnnotation::Boolean,
+        2 => TypeAnnotation::Integer,
+        3 => TypeAnnotation::Float,
+        4 => TypeAnnotation::String,
+        5 => TypeAnnotation::Named(reader.read_path()?, reader.read_source()?),
+        6 => TypeAnnotation::Array(Box::new(read_annotation(reader)?)),
+        7 => {
+            let mut members = Vec::new();
+            for _ in 0..reader.read_usize()? {
+                members.push((reader.read_string()?, read_annotation(reader)?));
+            }
+            TypeAnnotation::Object(members)
+        }
+        8 => {
+            let mut parameter_types = Vec::new();
+            for _ in 0..reader.read_usize()? { parameter_types.push(read_annotation(reader)?); }
+            TypeAnnotation::Closure(parameter_types, Box::new(read_annotation(reader)?))
+        }
+        9 => {
+            let mut variants = Vec::new();
+            for _ in 0..reader.read_usize()? {
+                variants.push((reader.read_string()?, read_annotation(reader)?));
+            }
+            TypeAnnotation::Variants(variants, reader.read_bool()?)
+        }
+        _ => return None
+    })
+}
+
+fn write_optional_annotation(annotation: &Option<TypeAnnotation>, writer: &mut CacheWriter) {
+    writer.write_bool(annotation.is_some());
+    if let Some(annotation) = annotation { write_annotation(annotation, writer); }
+}
+
+fn read_optional_annotation(reader: &mut CacheReader) -> Option<Option<TypeAnnotation>> {
+    Some(if reader.read_bool()? { Some(read_annotation(reader)?) } else { None })
+}
+
+fn write_optional_group(group: &Option<TypeGroup>, writer: &mut CacheWriter) {
+    writer.write_bool(group.is_some());
+    if let Some(group) = group { writer.write_group(*group); }
+}
+
+fn read_optional_group(reader: &mut CacheReader) -> Option<Option<TypeGroup>> {
+    Some(if reader.read_bool()? { Some(reader.read_group()?) } else { None })
+}
+
+fn write_pattern<T: CachedNode>(pattern: &Pattern<T>, writer: &mut CacheWriter) {
+    match pattern {
+        Pattern::Variable { name, source, value_types } => {
+            writer.write_u8(0);
+            writer.write_string(*name);
+            writer.write_source(*source);
+            write_optional_group(value_types, writer);
+        }
+        Pattern::Object { members, source, value_types } => {
+            writer.write_u8(1);
+            writer.write_usize(members.len());
+            for (member_name, member_pattern) in members {
+                writer.write_string(*member_name);
+                write_pattern(member_pattern, writer);
+            }
+            writer.write_source(*source);
+            write_optional_group(value_types, writer);
+        }
+        Pattern::Array { elements, source, value_types, index_types } => {
+            writer.write_u8(2);
+            writer.write_usize(elements.len());
+            for element_pattern in elements { write_pattern(element_pattern, writer); }
+            writer.write_source(*source);
+            write_optional_group(value_types, writer);
+            write_optional_group(index_types, writer);
+        }
+        Pattern::Wildcard { source, value_types } => {
+            writer.write_u8(3);
+            writer.write_source(*source);
+            write_optional_group(value_types, writer);
+        }
+        Pattern::Value { value, source, value_types } => {
+            writer.write_u8(4);
+            value.write_cached(writer);
+            writer.write_source(*source);
+            write_optional_group(value_types, writer);
+        }
+        Pattern::Variant { name, payload, source, value_types } => {
+            writer.write_u8(5);
+            writer.write_string(*name);
+            writer.write_bool(payload.is_some());
+            if let Some(payload) = payload { write_pattern(payload, writer); }
+            writer.write_source(*source);
+            write_optional_group(value_types, writer);
+        }
+    }
+}
+
+fn read_pattern<T: CachedNode>(reader: &mut CacheReader) -> Option<Pattern<T>> {
+    Some(match reader.read_u8()? {
+        0 => Pattern::Variable {
+            name: reader.read_string()?, source: reader.read_source()?, value_types: read_optional_group(reader)?
+        },
+        1 => {
+            let mut members = Vec::new();
+            for _ in 0..reader.read_usize()? {
+                members.push((reader.read_string()?, read_pattern(reader)?));
+            }
+            Pattern::Object { members, source: reader.read_source()?, value_types: read_optional_group(reader)? }
+        }
+        2 => {
+            let mut elements = Vec::new();
+            for _ in 0..reader.read_usize()? { elements.push(read_pattern(reader)?); }
+            Pattern::Array {
+                elements, source: reader.read_source()?,
+                value_types: read_optional_group(reader)?, index_types: read_optional_group(reader)?
+            }
+        }
+        3 => Pattern::Wildcard { source: reader.read_source()?, value_types: read_optional_group(reader)? },
+        4 => Pattern::Value {
+            value: read_boxed(reader)?, source: reader.read_source()?, value_types: read_optional_group(reader)?
+        },
+        5 => {
+            let name = reader.read_string()?;
+            let payload = if reader.read_bool()? { Some(Box::new(read_pattern(reader)?)) } else { None };
+            Pattern::Variant { name, payload, source: reader.read_source()?, value_types: read_optional_group(reader)? }
+        }
+        _ => return None
+    })
+}
+
+fn write_variant<T: CachedNode>(variant: &AstNodeVariant<T>, writer: &mut CacheWriter) {
+    macro_rules! write_binary { ($tag: expr, $a: expr, $b: expr) => {{
+        writer.write_u8($tag);
+        $a.write_cached(writer);
+        $b.write_cached(writer);
+    }} }
+    match variant {
+        AstNodeVariant::Procedure { public, name, arguments, returns, body } => {
+            writer.write_u8(0);
+            writer.write_bool(*public);
+            writer.write_string(*name);
+            writer.write_usize(arguments.len());
+            for (argument_name, argument_source, argument_annotation, argument_default) in arguments {
+                writer.write_string(*argument_name);
+                writer.write_source(*argument_source);
+                write_optional_annotation(argument_annotation, writer);
+                writer.write_bool(argument_default.is_some());
+                if let Some(argument_default) = argument_default { argument_default.write_cached(writer); }
+            }
+            writer.write_bool(returns.is_some());
+            if let Some((returns, returns_source)) = returns {
+                write_annotation(returns, writer);
+                writer.write_source(*returns_source);
+            }
+            write_nodes(body, writer);
+        }
+        AstNodeVariant::Function { arguments, body } => {
+            writer.write_u8(1);
+            writer.write_usize(arguments.len());
+            for (argument_name, argument_source) in arguments {
+                writer.write_string(*argument_name);
+                writer.write_source(*argument_source);
+            }
+            write_nodes(body, writer);
+        }
+        AstNodeVariant::Variable { public, mutable, name, annotation, value_types, value } => {
+            writer.write_u8(2);
+            writer.write_bool(*public);
+            writer.write_bool(*mutable);
+            writer.write_string(*name);
+            write_optional_annotation(annotation, writer);
+            write_optional_group(value_types, writer);
+            writer.write_bool(value.is_some());
+            if let Some(value) = value { value.write_cached(writer); }
+        }
+        AstNodeVariant::CaseBranches { value, branches, else_body } => {
+            writer.write_u8(3);
+            value.write_cached(writer);
+            writer.write_usize(branches.len());
+            for (branch_value, branch_body) in branches {
+                branch_value.write_cached(writer);
+                write_nodes(branch_body, writer);
+            }
+            write_nodes(else_body, writer);
+        }
+        AstNodeVariant::CaseConditon { condition, body, else_body } => {
+            writer.write_u8(4);
+            condition.write_cached(writer);
+            write_nodes(body, writer);
+            write_nodes(else_body, writer);
+        }
+        AstNodeVariant::CaseVariant { value, branches, else_body } => {
+            writer.write_u8(5);
+            value.write_cached(writer);
+            writer.write_usize(branches.len());
+            for (variant_name, variant_variable, branch_body) in branches {
+                writer.write_string(*variant_name);
+                writer.write_bool(variant_variable.is_some());
+                if let Some((variable_name, variable_source, variable_types)) = variant_variable {
+                    writer.write_string(*variable_name);
+                    writer.write_source(*variable_source);
+                    write_optional_group(variable_types, writer);
+                }
+                write_nodes(branch_body, writer);
+            }
+            writer.write_bool(else_body.is_some());
+            if let Some(else_body) = else_body { write_nodes(else_body, writer); }
+        }
+        AstNodeVariant::Assignment { variable, value } => write_binary!(6, variable, value),
+        AstNodeVariant::Return { value } => {
+            writer.write_u8(7);
+            value.write_cached(writer);
+        }
+        AstNodeVariant::Call { called, arguments } => {
+            writer.write_u8(8);
+            called.write_cached(writer);
+            write_nodes(arguments, writer);
+        }
+        AstNodeVariant::Object { values } => {
+            writer.write_u8(9);
+            writer.write_usize(values.len());
+            for (member_name, member_value) in values {
+                writer.write_string(*member_name);
+                member_value.write_cached(writer);
+            }
+        }
+        AstNodeVariant::Array { values } => {
+            writer.write_u8(10);
+            write_nodes(values, writer);
+        }
+        AstNodeVariant::ObjectAccess { object, member } => {
+            writer.write_u8(11);
+            object.write_cached(writer);
+            writer.write_string(*member);
+        }
+        AstNodeVariant::ArrayAccess { array, index } => write_binary!(12, array, index),
+        AstNodeVariant::VariableAccess { name } => {
+            writer.write_u8(13);
+            writer.write_string(*name);
+        }
+        AstNodeVariant::BooleanLiteral { value } => {
+            writer.write_u8(14);
+            writer.write_bool(*value);
+        }
+        AstNodeVariant::IntegerLiteral { value } => {
+            writer.write_u8(15);
+            writer.write_i64(*value);
+        }
+        AstNodeVariant::FloatLiteral { value } => {
+            writer.write_u8(16);
+            writer.write_f64(*value);
+        }
+        AstNodeVariant::StringLiteral { value } => {
+            writer.write_u8(17);
+            writer.write_string(*value);
+        }
+        AstNodeVariant::UnitLiteral => writer.write_u8(18),
+        AstNodeVariant::Add { a, b } => write_binary!(19, a, b),
+        AstNodeVariant::Subtract { a, b } => write_binary!(20, a, b),
+        AstNodeVariant::Multiply { a, b } => write_binary!(21, a, b),
+        AstNodeVariant::Divide { a, b } => write_binary!(22, a, b),
+        AstNodeVariant::Modulo { a, b } => write_binary!(23, a, b),
+        AstNodeVariant::Negate { x } => {
+            writer.write_u8(24);
+            x.write_cached(writer);
+        }
+        AstNodeVariant::LessThan { a, b } => write_binary!(25, a, b),
+        AstNodeVariant::GreaterThan { a, b } => write_binary!(26, a, b),
+        AstNodeVariant::LessThanEqual { a, b } => write_binary!(27, a, b),
+        AstNodeVariant::GreaterThanEqual { a, b } => write_binary!(28, a, b),
+        AstNodeVariant::Equals { a, b } => write_binary!(29, a, b),
+        AstNodeVariant::NotEquals { a, b } => write_binary!(30, a, b),
+        AstNodeVariant::Not { x } => {
+            writer.write_u8(31);
+            x.write_cached(writer);
+        }
+        AstNodeVariant::Or { a, b } => write_binary!(32, a, b),
+        AstNodeVariant::And { a, b } => write_binary!(33, a, b),
+        AstNodeVariant::Module { path } => {
+            writer.write_u8(34);
+            writer.write_path(path);
+        }
+        AstNodeVariant::ModuleAccess { path } => {
+            writer.write_u8(35);
+            writer.write_path(path);
+        }
+        AstNodeVariant::Use { paths } => {
+            writer.write_u8(36);
+            writer.write_usize(paths.len());
+            for path in paths { writer.write_path(path); }
+        }
+        AstNodeVariant::Variant { name, value } => {
+            writer.write_u8(37);
+            writer.write_string(*name);
+            value.write_cached(writer);
+        }
+        AstNodeVariant::Static { value } => {
+            writer.write_u8(38);
+            value.write_cached(writer);
+        }
+        AstNodeVariant::Target { target, body } => {
+            writer.write_u8(39);
+            writer.write_string(*target);
+            write_nodes(body, writer);
+        }
+        AstNodeVariant::TypeAlias { public, name, nominal, aliased } => {
+            writer.write_u8(40);
+            writer.write_bool(*public);
+            writer.write_string(*name);
+            writer.write_bool(*nominal);
+            write_annotation(aliased, writer);
+        }
+        AstNodeVariant::BitwiseAnd { a, b } => write_binary!(41, a, b),
+        AstNodeVariant::BitwiseOr { a, b } => write_binary!(42, a, b),
+        AstNodeVariant::BitwiseXor { a, b } => write_binary!(43, a, b),
+        AstNodeVariant::BitwiseNot { x } => {
+            writer.write_u8(44);
+            x.write_cached(writer);
+        }
+        AstNodeVariant::LeftShift { a, b } => write_binary!(45, a, b),
+        AstNodeVariant::RightShift { a, b } => write_binary!(46, a, b),
+        AstNodeVariant::Destructuring { mutable, pattern, value } => {
+            writer.write_u8(47);
+            writer.write_bool(*mutable);
+            write_pattern(pattern, writer);
+            value.write_cached(writer);
+        }
+        AstNodeVariant::CasePattern { value, branches, else_body } => {
+            writer.write_u8(48);
+            value.write_cached(writer);
+            writer.write_usize(branches.len());
+            for (pattern, guard, branch_body) in branches {
+                write_pattern(pattern, writer);
+                writer.write_bool(guard.is_some());
+                if let Some(guard) = guard { guard.write_cached(writer); }
+                write_nodes(branch_body, writer);
+            }
+            writer.write_bool(else_body.is_some());
+            if let Some(else_body) = else_body { write_nodes(else_body, writer); }
+        }
+        AstNodeVariant::Propagate { value, returned_types } => {
+            writer.write_u8(49);
+            value.write_cached(writer);
+            write_optional_group(returned_types, writer);
+        }
+        AstNodeVariant::NamedArgument { name, value } => {
+            writer.write_u8(50);
+            writer.write_string(*name);
+            value.write_cached(writer);
+        }
+    }
+}
+
+fn read_variant<T: CachedNode>(reader: &mut CacheReader) -> Option<AstNodeVariant<T>> {
+    Some(match reader.read_u8()? {
+        0 => {
+            let public = reader.read_bool()?;
+            let name = reader.read_string()?;
+            let mut arguments = Vec::new();
+            for _ in 0..reader.read_usize()? {
+                let argument_name = reader.read_string()?;
+                let argument_source = reader.read_source()?;
+                let argument_annotation = read_optional_annotation(reader)?;
+                let argument_default = if reader.read_bool()? { Some(T::read_cached(reader)?) } else { None };
+                arguments.push((argument_name, argument_source, argument_annotation, argument_default));
+            }
+            let returns = if reader.read_bool()? { Some((read_annotation(reader)?, reader.read_source()?)) } else { None };
+            AstNodeVariant::Procedure { public, name, arguments, returns, body: read_nodes(reader)? }
+        }
+        1 => {
+            let mut arguments = Vec::new();
+            for _ in 0..reader.read_usize()? {
+                arguments.push((reader.read_string()?, reader.read_source()?));
+            }
+            AstNodeVariant::Function { arguments, body: read_nodes(reader)? }
+        }
+        2 => {
+            let public = reader.read_bool()?;
+            let mutable = reader.read_bool()?;
+            let name = reader.read_string()?;
+            let annotation = read_optional_annotation(reader)?;
+            let value_types = read_optional_group(reader)?;
+            let value = if reader.read_bool()? { Some(read_boxed(reader)?) } else { None };
+            AstNodeVariant::Variable { public, mutable, name, annotation, value_types, value }
+        }
+        3 => {
+            let value = read_boxed(reader)?;
+            let mut branches = Vec::new();
+            for _ in 0..reader.read_usize()? {
+                branches.push((T::read_cached(reader)?, read_nodes(reader)?));
+            }
+            AstNodeVariant::CaseBranches { value, branches, else_body: read_nodes(reader)? }
+        }
+        4 => AstNodeVariant::CaseConditon {
+            condition: read_boxed(reader)?, body: read_nodes(reader)?, else_body: read_nodes(reader)?
+        },
+        5 => {
+            let value = read_boxed(reader)?;
+            let mut branches = Vec::new();
+            for _ in 0..reader.read_usize()? {
+                let variant_name = reader.read_string()?;
+                let variant_variable = if reader.read_bool()? {
+                    Some((reader.read_string()?, reader.read_source()?, read_optional_group(reader)?))
+                } else { None };
+                branches.push((variant_name, variant_variable, read_nodes(reader)?));
+            }
+            let else_body = if reader.read_bool()? { Some(read_nodes(reader)?) } else { None };
+            AstNodeVariant::CaseVariant { value, branches, else_body }
+        }
+        6 => AstNodeVariant::Assignment { variable: read_boxed(reader)?, value: read_boxed(reader)? },
+        7 => AstNodeVariant::Return { value: read_boxed(reader)? },
+        8 => AstNodeVariant::Call { called: read_boxed(reader)?, arguments: read_nodes(reader)? },
+        9 => {
+            let mut values = Vec::new();
+            for _ in 0..reader.read_usize()? {
+                values.push((reader.read_string()?, T::read_cached(reader)?));
+            }
+            AstNodeVariant::Object { values }
+        }
+        10 => AstNodeVariant::Array { values: read_nodes(reader)? },
+        11 => AstNodeVariant::ObjectAccess { object: read_boxed(reader)?, member: reader.read_string()? },
+        12 => AstNodeVariant::ArrayAccess { array: read_boxed(reader)?, index: read_boxed(reader)? },
+        13 => AstNodeVariant::VariableAccess { name: reader.read_string()? },
+        14 => AstNodeVariant::BooleanLiteral { value: reader.read_bool()? },
+        15 => AstNodeVariant::IntegerLiteral { value: reader.read_i64()? },
+        16 => AstNodeVariant::FloatLiteral { value: reader.read_f64()? },
+        17 => AstNodeVariant::StringLiteral { value: reader.read_string()? },
+        18 => AstNodeVariant::UnitLiteral,
+        19 => AstNodeVariant::Add { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        20 => AstNodeVariant::Subtract { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        21 => AstNodeVariant::Multiply { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        22 => AstNodeVariant::Divide { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        23 => AstNodeVariant::Modulo { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        24 => AstNodeVariant::Negate { x: read_boxed(reader)? },
+        25 => AstNodeVariant::LessThan { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        26 => AstNodeVariant::GreaterThan { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        27 => AstNodeVariant::LessThanEqual { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        28 => AstNodeVariant::GreaterThanEqual { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        29 => AstNodeVariant::Equals { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        30 => AstNodeVariant::NotEquals { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        31 => AstNodeVariant::Not { x: read_boxed(reader)? },
+        32 => AstNodeVariant::Or { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        33 => AstNodeVariant::And { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        34 => AstNodeVariant::Module { path: reader.read_path()? },
+        35 => AstNodeVariant::ModuleAccess { path: reader.read_path()? },
+        36 => {
+            let mut paths = Vec::new();
+            for _ in 0..reader.read_usize()? { paths.push(reader.read_path()?); }
+            AstNodeVariant::Use { paths }
+        }
+        37 => AstNodeVariant::Variant { name: reader.read_string()?, value: read_boxed(reader)? },
+        38 => AstNodeVariant::Static { value: read_boxed(reader)? },
+        39 => AstNodeVariant::Target { target: reader.read_string()?, body: read_nodes(reader)? },
+        40 => AstNodeVariant::TypeAlias {
+            public: reader.read_bool()?, name: reader.read_string()?,
+            nominal: reader.read_bool()?, aliased: read_annotation(reader)?
+        },
+        41 => AstNodeVariant::BitwiseAnd { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        42 => AstNodeVariant::BitwiseOr { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        43 => AstNodeVariant::BitwiseXor { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        44 => AstNodeVariant::BitwiseNot { x: read_boxed(reader)? },
+        45 => AstNodeVariant::LeftShift { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        46 => AstNodeVariant::RightShift { a: read_boxed(reader)?, b: read_boxed(reader)? },
+        47 => AstNodeVariant::Destructuring {
+            mutable: reader.read_bool()?, pattern: read_pattern(reader)?, value: read_boxed(reader)?
+        },
+        48 => {
+            let value = read_boxed(reader)?;
+            let mut branches = Vec::new();
+            for _ in 0..reader.read_usize()? {
+                let pattern = read_pattern(reader)?;
+                let guard = if reader.read_bool()? { Some(T::read_cached(reader)?) } else { None };
+                branches.push((pattern, guard, read_nodes(reader)?));
+            }
+            let else_body = if reader.read_bool()? { Some(read_nodes(reader)?) } else { None };
+            AstNodeVariant::CasePattern { value, branches, else_body }
+        }
+        49 => AstNodeVariant::Propagate { value: read_boxed(reader)?, returned_types: read_optional_group(reader)? },
+        50 => AstNodeVariant::NamedArgument { name: reader.read_string()?, value: read_boxed(reader)? },
+        _ => return None
+    })
+}
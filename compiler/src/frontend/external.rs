@@ -4,11 +4,11 @@ use std::collections::HashMap;
 use crate::{frontend::{
     tokens::Token,
     lexer::Lexer,
-    ast::{AstNode, TypedAstNode},
+    ast::{AstNode, TypedAstNode, TypeAnnotation},
     modules::{NamespacePath, Module},
-    type_checking::Symbol,
+    type_checking::{Symbol, resolve_annotation, resolve_annotation_types},
     tokens::TokenType,
-    types::{Type, TypeScope}
+    types::{Type, TypeScope, TypeGroup}
 }, util::source::SourceRange};
 use crate::util::{
     strings::{StringMap, StringIdx},
@@ -18,33 +18,47 @@ use crate::util::{
 
 pub struct ExternalMappingParser {
     current: Token,
+    previous_source: SourceRange,
     reached_end: bool   
 }
 
 impl ExternalMappingParser {
-    pub fn new(strings: &mut StringMap, lexer: &mut Lexer) -> Option<Result<ExternalMappingParser, Error>> {
+    pub fn new(strings: &StringMap, lexer: &mut Lexer) -> Option<Result<ExternalMappingParser, Error>> {
         lexer.next_token(strings)
             .map(|res| res.map(|token| ExternalMappingParser {
+                previous_source: token.source,
                 current: token,
                 reached_end: false
             }))
     }
 
-    fn try_next(&mut self, strings: &mut StringMap, lexer: &mut Lexer) -> Result<bool, Error> {
+    pub fn continue_from(current: Token) -> ExternalMappingParser {
+        ExternalMappingParser {
+            previous_source: current.source,
+            current,
+            reached_end: false
+        }
+    }
+
+    pub fn position(self) -> (Token, bool, SourceRange) { (self.current, self.reached_end, self.previous_source) }
+
+    fn try_next(&mut self, strings: &StringMap, lexer: &mut Lexer) -> Result<bool, Error> {
         match lexer.next_token(strings) {
             None => {
+                self.previous_source = self.current.source;
                 self.reached_end = true;
                 Ok(false)       
             }
             Some(Err(error)) => Err(error),
             Some(Ok(token)) => {
+                self.previous_source = self.current.source;
                 self.current = token;
                 Ok(true)
             }
         }
     }
 
-    fn expect_next(&mut self, strings: &mut StringMap, lexer: &mut Lexer, expected: &'static str) -> Result<(), Error> {
+    fn expect_next(&mut self, strings: &StringMap, lexer: &mut Lexer, expected: &'static str) -> Result<(), Error> {
         if !self.try_next(strings, lexer)? {
             Err(Error::new([
                 ErrorSection::Error(ErrorType::UnexpectedEnd(expected)),
@@ -64,150 +78,214 @@ impl ExternalMappingParser {
 
     pub fn parse_header(
         &mut self,
-        strings: &mut StringMap,
+        strings: &StringMap,
         lexer: &mut Lexer,
+        target_str: &str,
         modules: &mut HashMap<NamespacePath, Module<AstNode>>,
         global_type_scope: &mut TypeScope,
         typed_symbols: &mut HashMap<NamespacePath, Symbol<TypedAstNode>>,
-        external_backings: &mut HashMap<NamespacePath, StringIdx>
+        external_backings: &mut HashMap<NamespacePath, Option<StringIdx>>
     ) -> Result<(), Error> {
         let mut declared_types = HashMap::new();
         while !self.reached_end {
-            match strings.get(self.current.token_content) {
-                "type" => {
-                    self.expect_next(strings, lexer, "the name of the type")?;
-                    self.expect_type(&[TokenType::Identifier], "the name of the type")?;
-                    let type_name = self.current.token_content;
-                    self.expect_next(strings, lexer, "an equals-sign ('=')")?;
-                    self.expect_type(&[TokenType::Equals], "an equals-sign ('=')")?;
-                    self.expect_next(strings, lexer, "the type to give the name to")?;
-                    let type_replacement = self.parse_type(strings, lexer, global_type_scope, &declared_types)?;
-                    declared_types.insert(type_name, type_replacement);
+            if self.current.token_type != TokenType::KeywordTarget {
+                self.parse_declaration(
+                    strings, lexer, true, &mut declared_types, "'type', 'proc', 'var' or 'target'",
+                    modules, global_type_scope, typed_symbols, external_backings
+                )?;
+                continue;
+            }
+            self.expect_next(strings, lexer, "the target the section is for")?;
+            self.expect_type(&[TokenType::Identifier], "the target the section is for")?;
+            let selected = strings.get(self.current.token_content) == target_str;
+            self.expect_next(strings, lexer, "an opening brace ('{')")?;
+            self.expect_type(&[TokenType::BraceOpen], "an opening brace ('{')")?;
+            self.expect_next(strings, lexer, "a declaration or a closing brace ('}')")?;
+            let mut section_types = declared_types.clone();
+            while self.current.token_type != TokenType::BraceClose {
+                self.parse_declaration(
+                    strings, lexer, selected, &mut section_types, "'type', 'proc' or 'var'",
+                    modules, global_type_scope, typed_symbols, external_backings
+                )?;
+                if self.reached_end {
+                    return Err(Error::new([
+                        ErrorSection::Error(ErrorType::UnexpectedEnd("a closing brace ('}')")),
+                        ErrorSection::Code(self.current.source)
+                    ].into()));
                 }
-                "proc" => {
-                    let mut symbol_type_scope = TypeScope::new();
-                    let source_start = self.current.source;
-                    self.expect_next(strings, lexer, "the full path of the procedure")?;
-                    let procedure_path = self.parse_path(strings, lexer, "the full path of the procedure")?;
-                    self.expect_type(&[TokenType::ParenOpen], "an opening parenthesis ('(')")?;
-                    self.expect_next(strings, lexer, "a parameter's type or a closing parenthesis (')')")?;
-                    let mut parameters = Vec::new();
-                    while self.current.token_type != TokenType::ParenClose {
-                        let paramter_type = self.parse_type(strings, lexer, global_type_scope, &declared_types)?; 
-                        self.expect_type(&[TokenType::ParenClose, TokenType::Comma], "a comma (',') or a closing parenthesis (')')")?;
-                        if self.current.token_type == TokenType::Comma {
-                            self.expect_next(strings, lexer, "a parameter's type or a closing parenthesis (')')")?;
-                        }
-                        let global_parameter_group = global_type_scope.insert_group(&[paramter_type]);
-                        let parameter_group = global_type_scope.transfer_group(global_parameter_group, &mut symbol_type_scope);
-                        parameters.push(parameter_group);
-                    }
-                    self.expect_next(strings, lexer, "an arrow ('->') or an equals-sign ('=')")?;
-                    self.expect_type(&[TokenType::Arrow, TokenType::Equals], "an arrow ('->') or an equals-sign ('=')")?;
-                    let global_return_type = if self.current.token_type == TokenType::Arrow {
-                        self.expect_next(strings, lexer, "the return type")?;
-                        self.parse_type(strings, lexer, global_type_scope, &declared_types)?
-                    } else {
-                        Type::Unit
-                    };
-                    let global_return_type_group = global_type_scope.insert_group(&[global_return_type]);
-                    let return_type = global_type_scope.transfer_group(global_return_type_group, &mut symbol_type_scope);
-                    self.expect_type(&[TokenType::Equals], "an equals-sign ('=')")?;
-                    self.expect_next(strings, lexer, "the name of the external backing procedure")?;
-                    self.expect_type(&[TokenType::Identifier], "the name of the external backing procedure")?;
-                    let backing = self.current.token_content;
-                    let source_end = self.current.source;
-                    self.try_next(strings, lexer)?;
-                    let procedure_module_path = NamespacePath::new(procedure_path.get_segments()[0..procedure_path.get_segments().len() - 1].into());
-                    if procedure_module_path.get_segments().len() == 0 {
-                        return Err(Error::new([
-                            ErrorSection::Error(ErrorType::NoDefinedModule(
-                                "procedure",
-                                *procedure_path.get_segments()
-                                    .last()
-                                    .expect("should have at least one segment")
-                            )),
-                            ErrorSection::Code((&source_start..&source_end).into())
-                        ].into()));
-                    }
-                    if let Some(module) = modules.get_mut(&procedure_module_path) {
-                        module.insert_public(
-                            *procedure_path.get_segments()
-                                .last()
-                                .expect("should have at least one segment")
-                        );
-                    } else {
-                        let mut module = Module::new_raw(procedure_module_path.clone());
-                        module.insert_public(
-                            *procedure_path.get_segments()
-                                .last()
-                                .expect("should have at least one segment")
+            }
+            if selected { declared_types = section_types; }
+            self.try_next(strings, lexer)?;
+        }
+        Ok(())
+    }
+
+    fn parse_declaration(
+        &mut self,
+        strings: &StringMap,
+        lexer: &mut Lexer,
+        selected: bool,
+        declared_types: &mut HashMap<NamespacePath, Type>,
+        expected: &'static str,
+        modules: &mut HashMap<NamespacePath, Module<AstNode>>,
+        global_type_scope: &mut TypeScope,
+        typed_symbols: &mut HashMap<NamespacePath, Symbol<TypedAstNode>>,
+        external_backings: &mut HashMap<NamespacePath, Option<StringIdx>>
+    ) -> Result<(), Error> {
+        match strings.get(self.current.token_content) {
+            "type" => {
+                self.expect_next(strings, lexer, "the name of the type")?;
+                self.expect_type(&[TokenType::Identifier], "the name of the type")?;
+                let type_name = NamespacePath::new(vec![self.current.token_content]);
+                self.expect_next(strings, lexer, "an equals-sign ('=')")?;
+                self.expect_type(&[TokenType::Equals], "an equals-sign ('=')")?;
+                self.expect_next(strings, lexer, "the type to give the name to")?;
+                let type_replacement = self.parse_type(strings, lexer, global_type_scope, &declared_types)?;
+                declared_types.insert(type_name, type_replacement);
+            }
+            "proc" => {
+                let mut symbol_type_scope = TypeScope::new();
+                let source_start = self.current.source;
+                self.expect_next(strings, lexer, "the full path of the procedure")?;
+                let procedure_path = self.parse_path(strings, lexer, "the full path of the procedure")?;
+                let mut type_variables = HashMap::new();
+                if self.current.token_type == TokenType::LessThan {
+                    self.expect_next(strings, lexer, "the name of a type variable")?;
+                    loop {
+                        self.expect_type(&[TokenType::Identifier], "the name of a type variable")?;
+                        type_variables.insert(
+                            NamespacePath::new(vec![self.current.token_content]),
+                            symbol_type_scope.insert_group(&[Type::Any])
                         );
-                        modules.insert(procedure_module_path, module);
+                        self.expect_next(strings, lexer, "a comma (',') or a closing angle bracket ('>')")?;
+                        self.expect_type(&[TokenType::Comma, TokenType::GreaterThan], "a comma (',') or a closing angle bracket ('>')")?;
+                        if self.current.token_type == TokenType::GreaterThan { break; }
+                        self.expect_next(strings, lexer, "the name of a type variable")?;
                     }
-                    let external_str = strings.insert("<external>");
-                    typed_symbols.insert(procedure_path.clone(), Symbol::Procedure {
-                        public: true,
-                        parameter_names: parameters.iter().enumerate().map(|(i, _)| strings.insert(&i.to_string())).collect(),
-                        parameter_types: parameters,
-                        returns: return_type,
-                        body: None,
-                        source: SourceRange::new(external_str, external_str, 0, 0),
-                        type_scope: symbol_type_scope
-                    });
-                    external_backings.insert(procedure_path, backing);
+                    self.expect_next(strings, lexer, "an opening parenthesis ('(')")?;
                 }
-                "var" => {
-                    let source_start = self.current.source;
-                    self.expect_next(strings, lexer, "the full path of the constant")?;
-                    let variable_path = self.parse_path(strings, lexer, "the full path of the constant")?;
-                    let variable_type = self.parse_type(strings, lexer, global_type_scope, &declared_types)?;
-                    self.expect_type(&[TokenType::Equals], "an equals-sign ('=')")?;
-                    self.expect_next(strings, lexer, "the name of the external backing constant")?;
-                    self.expect_type(&[TokenType::Identifier], "the name of the external backing constant")?;
-                    let backing = self.current.token_content;
-                    let source_end = self.current.source;
-                    self.try_next(strings, lexer)?;
-                    let variable_module_path = NamespacePath::new(variable_path.get_segments()[0..variable_path.get_segments().len() - 1].into());
-                    if variable_module_path.get_segments().len() == 0 {
-                        return Err(Error::new([
-                            ErrorSection::Error(ErrorType::NoDefinedModule(
-                                "constant",
-                                *variable_path.get_segments()
-                                    .last()
-                                    .expect("should have at least one segment")
-                            )),
-                            ErrorSection::Code((&source_start..&source_end).into())
-                        ].into()));
+                let symbol_declared_types = declared_types.iter().map(|(type_name, declared_type)| {
+                    let global_group = global_type_scope.insert_group(&[*declared_type]);
+                    let symbol_group = global_type_scope.transfer_group(global_group, &mut symbol_type_scope);
+                    (type_name.clone(), symbol_type_scope.group_concrete(symbol_group))
+                }).collect::<HashMap<NamespacePath, Type>>();
+                self.expect_type(&[TokenType::ParenOpen], "an opening parenthesis ('(')")?;
+                self.expect_next(strings, lexer, "a parameter's type or a closing parenthesis (')')")?;
+                let mut parameters = Vec::new();
+                while self.current.token_type != TokenType::ParenClose {
+                    let parameter_group = self.parse_types(strings, lexer, &mut symbol_type_scope, &symbol_declared_types, &type_variables)?; 
+                    self.expect_type(&[TokenType::ParenClose, TokenType::Comma], "a comma (',') or a closing parenthesis (')')")?;
+                    if self.current.token_type == TokenType::Comma {
+                        self.expect_next(strings, lexer, "a parameter's type or a closing parenthesis (')')")?;
                     }
-                    if let Some(module) = modules.get_mut(&variable_module_path) {
-                        module.insert_public(
-                            *variable_path.get_segments()
+                    parameters.push(parameter_group);
+                }
+                self.expect_next(strings, lexer, "an arrow ('->') or an equals-sign ('=')")?;
+                self.expect_type(&[TokenType::Arrow, TokenType::Equals], "an arrow ('->') or an equals-sign ('=')")?;
+                let return_type = if self.current.token_type == TokenType::Arrow {
+                    self.expect_next(strings, lexer, "the return type")?;
+                    self.parse_types(strings, lexer, &mut symbol_type_scope, &symbol_declared_types, &type_variables)?
+                } else {
+                    symbol_type_scope.insert_group(&[Type::Unit])
+                };
+                self.expect_type(&[TokenType::Equals], "an equals-sign ('=')")?;
+                self.expect_next(strings, lexer, "the name of the external backing procedure")?;
+                self.expect_type(&[TokenType::Identifier], "the name of the external backing procedure")?;
+                let backing = self.current.token_content;
+                let source_end = self.current.source;
+                self.try_next(strings, lexer)?;
+                let procedure_module_path = NamespacePath::new(procedure_path.get_segments()[0..procedure_path.get_segments().len() - 1].into());
+                if procedure_module_path.get_segments().len() == 0 {
+                    return Err(Error::new([
+                        ErrorSection::Error(ErrorType::NoDefinedModule(
+                            "procedure",
+                            *procedure_path.get_segments()
                                 .last()
                                 .expect("should have at least one segment")
-                        );
-                    } else {
-                        let mut module = Module::new_raw(variable_module_path.clone());
-                        module.insert_public(
+                        )),
+                        ErrorSection::Code((&source_start..&source_end).into())
+                    ].into()));
+                }
+                if let Some(module) = modules.get_mut(&procedure_module_path) {
+                    module.insert_public(
+                        *procedure_path.get_segments()
+                            .last()
+                            .expect("should have at least one segment")
+                    );
+                } else {
+                    let mut module = Module::new_raw(procedure_module_path.clone());
+                    module.insert_public(
+                        *procedure_path.get_segments()
+                            .last()
+                            .expect("should have at least one segment")
+                    );
+                    modules.insert(procedure_module_path, module);
+                }
+                if !selected && external_backings.contains_key(&procedure_path) { return Ok(()); }
+                let external_str = strings.insert("<external>");
+                typed_symbols.insert(procedure_path.clone(), Symbol::Procedure {
+                    public: true,
+                    parameter_names: parameters.iter().enumerate().map(|(i, _)| strings.insert(&i.to_string())).collect(),
+                    parameter_defaults: vec![None; parameters.len()],
+                    parameter_types: parameters,
+                    returns: return_type,
+                    body: None,
+                    source: SourceRange::new(external_str, external_str, 0, 0),
+                    type_scope: symbol_type_scope
+                });
+                external_backings.insert(procedure_path, Some(backing).filter(|_| selected));
+            }
+            "var" => {
+                let source_start = self.current.source;
+                self.expect_next(strings, lexer, "the full path of the constant")?;
+                let variable_path = self.parse_path(strings, lexer, "the full path of the constant")?;
+                let variable_type = self.parse_type(strings, lexer, global_type_scope, &declared_types)?;
+                self.expect_type(&[TokenType::Equals], "an equals-sign ('=')")?;
+                self.expect_next(strings, lexer, "the name of the external backing constant")?;
+                self.expect_type(&[TokenType::Identifier], "the name of the external backing constant")?;
+                let backing = self.current.token_content;
+                let source_end = self.current.source;
+                self.try_next(strings, lexer)?;
+                let variable_module_path = NamespacePath::new(variable_path.get_segments()[0..variable_path.get_segments().len() - 1].into());
+                if variable_module_path.get_segments().len() == 0 {
+                    return Err(Error::new([
+                        ErrorSection::Error(ErrorType::NoDefinedModule(
+                            "constant",
                             *variable_path.get_segments()
                                 .last()
                                 .expect("should have at least one segment")
-                        );
-                        modules.insert(variable_module_path, module);
-                    }
-                    typed_symbols.insert(variable_path.clone(), Symbol::Constant {
-                        public: true,
-                        value: None,
-                        value_types: global_type_scope.insert_group(&[variable_type])
-                    });
-                    external_backings.insert(variable_path, backing);
-                }
-                _ => { 
-                    return Err(Error::new([
-                        ErrorSection::Error(ErrorType::UnexpectedToken("'type', 'proc' or 'var'", self.current.token_content)),
-                        ErrorSection::Code(self.current.source)
+                        )),
+                        ErrorSection::Code((&source_start..&source_end).into())
                     ].into()));
                 }
+                if let Some(module) = modules.get_mut(&variable_module_path) {
+                    module.insert_public(
+                        *variable_path.get_segments()
+                            .last()
+                            .expect("should have at least one segment")
+                    );
+                } else {
+                    let mut module = Module::new_raw(variable_module_path.clone());
+                    module.insert_public(
+                        *variable_path.get_segments()
+                            .last()
+                            .expect("should have at least one segment")
+                    );
+                    modules.insert(variable_module_path, module);
+                }
+                if !selected && external_backings.contains_key(&variable_path) { return Ok(()); }
+                typed_symbols.insert(variable_path.clone(), Symbol::Constant {
+                    public: true,
+                    value: None,
+                    value_types: global_type_scope.insert_group(&[variable_type])
+                });
+                external_backings.insert(variable_path, Some(backing).filter(|_| selected));
+            }
+            _ => { 
+                return Err(Error::new([
+                    ErrorSection::Error(ErrorType::UnexpectedToken(expected, self.current.token_content)),
+                    ErrorSection::Code(self.current.source)
+                ].into()));
             }
         }
         Ok(())
@@ -215,19 +293,39 @@ impl ExternalMappingParser {
 
     fn parse_type(
         &mut self,
-        strings: &mut StringMap,
+        strings: &StringMap,
         lexer: &mut Lexer,
         global_type_scope: &mut TypeScope,
-        declared_types: &HashMap<StringIdx, Type>
+        declared_types: &HashMap<NamespacePath, Type>
     ) -> Result<Type, Error> {
+        let annotation = self.parse_annotation(strings, lexer)?;
+        resolve_annotation(&annotation, global_type_scope, declared_types, true)
+    }
+
+    fn parse_types(
+        &mut self,
+        strings: &StringMap,
+        lexer: &mut Lexer,
+        type_scope: &mut TypeScope,
+        declared_types: &HashMap<NamespacePath, Type>,
+        type_variables: &HashMap<NamespacePath, TypeGroup>
+    ) -> Result<TypeGroup, Error> {
+        let annotation = self.parse_annotation(strings, lexer)?;
+        resolve_annotation_types(&annotation, type_scope, declared_types, type_variables, true)
+    }
+
+    pub fn parse_annotation(
+        &mut self,
+        strings: &StringMap,
+        lexer: &mut Lexer
+    ) -> Result<TypeAnnotation, Error> {
         match self.current.token_type {
             TokenType::Pipe | TokenType::DoublePipe => {
                 let mut arg_types = Vec::new();
                 if let TokenType::Pipe = self.current.token_type {
                     self.try_next(strings, lexer)?;
                     loop {
-                        let arg_type = self.parse_type(strings, lexer, global_type_scope, declared_types)?;
-                        arg_types.push(global_type_scope.insert_group(&[arg_type]));
+                        arg_types.push(self.parse_annotation(strings, lexer)?);
                         self.expect_type(&[TokenType::Comma, TokenType::Pipe], "a comma (',') or a pipe ('|')")?;
                         if self.current.token_type == TokenType::Pipe { break; }
                         self.try_next(strings, lexer)?;
@@ -236,32 +334,25 @@ impl ExternalMappingParser {
                 self.try_next(strings, lexer)?;
                 self.expect_type(&[TokenType::Arrow], "an arrow ('->')")?;
                 self.try_next(strings, lexer)?;
-                let return_type = self.parse_type(strings, lexer, global_type_scope, declared_types)?;
-                let return_tidx = global_type_scope.insert_group(&[return_type]);
-                Ok(Type::Closure(global_type_scope.insert_closure(
-                    arg_types,
-                    return_tidx,
-                    None
-                )))
+                let return_type = self.parse_annotation(strings, lexer)?;
+                Ok(TypeAnnotation::Closure(arg_types, Box::new(return_type)))
             }
             TokenType::Identifier | TokenType::KeywordUnit => {
                 let name = self.current.clone();
-                self.try_next(strings, lexer)?;
-                match strings.get(name.token_content) {
-                    "unit" => Ok(Type::Unit),
-                    "bool" => Ok(Type::Boolean),
-                    "int" => Ok(Type::Integer),
-                    "float" => Ok(Type::Float),
-                    "str" => Ok(Type::String),
-                    _ => if let Some(replacement) = declared_types.get(&name.token_content) {
-                        Ok(replacement.clone())
-                    } else {
-                        Err(Error::new([
-                            ErrorSection::Error(ErrorType::TypeDoesNotExist(name.token_content)),
-                            ErrorSection::Code(name.source)
-                        ].into()))
-                    }
+                let builtin = match strings.get(name.token_content) {
+                    "unit" => Some(TypeAnnotation::Unit),
+                    "bool" => Some(TypeAnnotation::Boolean),
+                    "int" => Some(TypeAnnotation::Integer),
+                    "float" => Some(TypeAnnotation::Float),
+                    "str" => Some(TypeAnnotation::String),
+                    _ => None
+                };
+                if let Some(builtin) = builtin {
+                    self.try_next(strings, lexer)?;
+                    return Ok(builtin);
                 }
+                let path = self.parse_path(strings, lexer, "the name of a type")?;
+                Ok(TypeAnnotation::Named(path, (&name.source..&self.previous_source).into()))
             }
             TokenType::BraceOpen => {
                 self.expect_next(strings, lexer, "the name of a member")?;
@@ -273,27 +364,53 @@ impl ExternalMappingParser {
                     self.expect_next(strings, lexer, "an equals-sign ('=')")?;
                     self.expect_type(&[TokenType::Equals], "an equals-sign ('=')")?;
                     self.expect_next(strings, lexer, "the type of the member")?;
-                    let member_type = self.parse_type(strings, lexer, global_type_scope, declared_types)?;
+                    let member_type = self.parse_annotation(strings, lexer)?;
                     self.expect_type(&[TokenType::BraceClose, TokenType::Comma], "a comma (',') or a closing brace ('}')")?;
                     if self.current.token_type == TokenType::Comma {
                         self.expect_next(strings, lexer, "the name of a member or a closing brace ('}')")?;
                     }
-                    members.push((member_name, global_type_scope.insert_group(&[member_type])));
+                    members.push((member_name, member_type));
+                }
+                self.try_next(strings, lexer)?;
+                Ok(TypeAnnotation::Object(members))
+            }
+            TokenType::ParenOpen => {
+                self.expect_next(strings, lexer, "a variant ('#') or '...'")?;
+                let mut variants = Vec::new();
+                let mut fixed = true;
+                loop {
+                    if self.current.token_type == TokenType::DoubleDot {
+                        self.expect_next(strings, lexer, "'...'")?;
+                        self.expect_type(&[TokenType::Dot], "'...'")?;
+                        self.expect_next(strings, lexer, "a closing parenthesis (')')")?;
+                        fixed = false;
+                        break;
+                    }
+                    self.expect_type(&[TokenType::Hashtag], "a variant ('#') or '...'")?;
+                    self.expect_next(strings, lexer, "the name of a variant")?;
+                    self.expect_type(&[TokenType::Identifier], "the name of a variant")?;
+                    let variant_name = self.current.token_content;
+                    self.expect_next(strings, lexer, "the type of the variant")?;
+                    let variant_type = self.parse_annotation(strings, lexer)?;
+                    variants.push((variant_name, variant_type));
+                    self.expect_type(&[TokenType::Pipe, TokenType::ParenClose], "a pipe ('|') or a closing parenthesis (')')")?;
+                    if self.current.token_type == TokenType::ParenClose { break; }
+                    self.expect_next(strings, lexer, "a variant ('#') or '...'")?;
                 }
+                self.expect_type(&[TokenType::ParenClose], "a closing parenthesis (')')")?;
                 self.try_next(strings, lexer)?;
-                Ok(Type::ConcreteObject(global_type_scope.insert_concrete_object(members)))
+                Ok(TypeAnnotation::Variants(variants, fixed))
             }
             TokenType::BracketOpen => {
                 self.expect_next(strings, lexer, "the type of the array's elements")?;
-                let element_type = self.parse_type(strings, lexer, global_type_scope, declared_types)?;
+                let element_type = self.parse_annotation(strings, lexer)?;
                 self.expect_type(&[TokenType::BracketClose], "a closing bracket (']')")?;
                 self.try_next(strings, lexer)?;
-                let element_tidx = global_type_scope.insert_group(&[element_type]);
-                Ok(Type::Array(global_type_scope.insert_array(element_tidx)))
+                Ok(TypeAnnotation::Array(Box::new(element_type)))
             }
             _ => {
                 Err(Error::new([
-                    ErrorSection::Error(ErrorType::UnexpectedToken("the name of a type, an opening brace ('{') or an opening bracket ('[')", self.current.token_content)),
+                    ErrorSection::Error(ErrorType::UnexpectedToken("the name of a type, an opening brace ('{'), an opening bracket ('[') or an opening parenthesis ('(')", self.current.token_content)),
                     ErrorSection::Code(self.current.source)
                 ].into()))
             }
@@ -302,7 +419,7 @@ impl ExternalMappingParser {
 
     fn parse_path(
         &mut self,
-        strings: &mut StringMap,
+        strings: &StringMap,
         lexer: &mut Lexer,
         expected: &'static str
     ) -> Result<NamespacePath, Error> {
@@ -2,7 +2,7 @@ use crate::util::{
     error::{Error, ErrorSection, ErrorType},
     source::HasSource
 };
-use crate::frontend::ast::{AstNode, HasAstNodeVariant, AstNodeVariant};
+use crate::frontend::ast::{AstNode, HasAstNodeVariant, AstNodeVariant, Pattern};
 
 
 #[derive(Clone, Copy)]
@@ -39,6 +39,46 @@ pub fn check_grammar(nodes: &[AstNode], scope: ScopeType, errors: &mut Vec<Error
     }
 }
 
+fn check_case_body(body: &[AstNode], scope: ScopeType, node: &AstNode, errors: &mut Vec<Error>) {
+    if let ScopeType::Expression = scope {
+        if let Some((value, statements)) = body.split_last() {
+            check_grammar(statements, ScopeType::Statement, errors);
+            check_grammar_singular(value, ScopeType::Expression, errors);
+        } else {
+            errors.push(Error::new([
+                ErrorSection::Error(ErrorType::InvalidContext("A 'case'-branch without a value", ScopeType::Statement.description(), scope.description())),
+                ErrorSection::Code(node.source().clone())
+            ].into()));
+        }
+    } else {
+        check_grammar(body, ScopeType::Statement, errors);
+    }
+}
+
+fn check_case_else_body(else_body: &[AstNode], scope: ScopeType, node: &AstNode, errors: &mut Vec<Error>) {
+    if let (ScopeType::Expression, true) = (scope, else_body.is_empty()) {
+        errors.push(Error::new([
+            ErrorSection::Error(ErrorType::InvalidContext("A 'case' without an 'else'-branch", ScopeType::Statement.description(), scope.description())),
+            ErrorSection::Code(node.source().clone())
+        ].into()));
+    } else {
+        check_case_body(else_body, scope, node, errors);
+    }
+}
+
+fn check_pattern_bindings(pattern: &Pattern<AstNode>, errors: &mut Vec<Error>) {
+    let mut bound = Vec::new();
+    for (name, source) in pattern.bindings() {
+        if bound.contains(&name) {
+            errors.push(Error::new([
+                ErrorSection::Error(ErrorType::DuplicatePatternBinding(name)),
+                ErrorSection::Code(source)
+            ].into()));
+        }
+        bound.push(name);
+    }
+}
+
 fn check_grammar_singular(node: &AstNode, scope: ScopeType, errors: &mut Vec<Error>) {
     macro_rules! enforce_min_scope {
         ($thing: expr, $enforced_scope: expr) => {
@@ -61,16 +101,19 @@ fn check_grammar_singular(node: &AstNode, scope: ScopeType, errors: &mut Vec<Err
         };
     }
     match node.node_variant() {
-        AstNodeVariant::Procedure { public: _, name: _, arguments, body } => {
+        AstNodeVariant::Procedure { public: _, name: _, arguments, returns: _, body } => {
             let mut args = Vec::new();
             for arg in arguments {
-                if args.contains(arg) {
+                if args.contains(&arg.0) {
                     errors.push(Error::new([
                         ErrorSection::Error(ErrorType::DuplicateFunctionParameter(arg.0)),
                         ErrorSection::Code(node.source().clone())
                     ].into()))
                 }
-                args.push(*arg);
+                args.push(arg.0);
+                if let Some(default) = &arg.3 {
+                    check_grammar_singular(default, ScopeType::Expression, errors);
+                }
             }
             enforce_min_scope!("'proc'", ScopeType::GlobalStatement);
             check_grammar(body, ScopeType::Statement, errors);
@@ -78,19 +121,19 @@ fn check_grammar_singular(node: &AstNode, scope: ScopeType, errors: &mut Vec<Err
         AstNodeVariant::Function { arguments, body } => {
             let mut args = Vec::new();
             for arg in arguments {
-                if args.contains(arg) {
+                if args.contains(&arg.0) {
                     errors.push(Error::new([
                         ErrorSection::Error(ErrorType::DuplicateFunctionParameter(arg.0)),
                         ErrorSection::Code(node.source().clone())
                     ].into()))
                 }
-                args.push(*arg);
+                args.push(arg.0);
             }
             enforce_min_scope!("'func'", ScopeType::Expression);
             enforce_max_scope!("'func'", ScopeType::Statement, ScopeType::Expression);
             check_grammar(body, ScopeType::Statement, errors);
         },
-        AstNodeVariant::Variable { public, mutable, name: _, value_types: _, value } => {
+        AstNodeVariant::Variable { public, mutable, name: _, annotation: _, value_types: _, value } => {
             enforce_min_scope!(match (*public, *mutable) {
                 (true, true) => "'pub mut var'",
                 (true, false) => "'pub var'",
@@ -104,32 +147,56 @@ fn check_grammar_singular(node: &AstNode, scope: ScopeType, errors: &mut Vec<Err
                 check_grammar_singular(&*value, ScopeType::Expression, errors);
             }
         },
+        AstNodeVariant::Destructuring { mutable, pattern, value } => {
+            let thing = if *mutable { "'mut var' with a pattern" } else { "'var' with a pattern" };
+            enforce_min_scope!(thing, ScopeType::Statement);
+            enforce_max_scope!(thing, ScopeType::Statement, ScopeType::Statement);
+            check_pattern_bindings(pattern, errors);
+            check_grammar_singular(&*value, ScopeType::Expression, errors);
+        },
         AstNodeVariant::CaseBranches { value, branches, else_body } => {
-            enforce_min_scope!("'case'", ScopeType::Statement);
+            enforce_min_scope!("'case'", ScopeType::Expression);
             enforce_max_scope!("'case'", ScopeType::Statement, ScopeType::Statement);
             check_grammar_singular(&*value, ScopeType::Expression, errors);
             for branch in branches {
                 check_grammar_singular(&branch.0, ScopeType::Expression, errors);
-                check_grammar(&branch.1, ScopeType::Statement, errors);
+                check_case_body(&branch.1, scope, node, errors);
             }
-            check_grammar(else_body, ScopeType::Statement, errors);
+            check_case_else_body(else_body, scope, node, errors);
         },
         AstNodeVariant::CaseConditon { condition, body, else_body } => {
-            enforce_min_scope!("'case'", ScopeType::Statement);
+            enforce_min_scope!("'case'", ScopeType::Expression);
             enforce_max_scope!("'case'", ScopeType::Statement, ScopeType::Statement);
             check_grammar_singular(&*condition, ScopeType::Expression, errors);
-            check_grammar(body, ScopeType::Statement, errors);
-            check_grammar(else_body, ScopeType::Statement, errors);
+            check_case_body(body, scope, node, errors);
+            check_case_else_body(else_body, scope, node, errors);
         },
         AstNodeVariant::CaseVariant { value, branches, else_body } => {
-            enforce_min_scope!("'case'", ScopeType::Statement);
+            enforce_min_scope!("'case'", ScopeType::Expression);
             enforce_max_scope!("'case'", ScopeType::Statement, ScopeType::Statement);
             check_grammar_singular(&*value, ScopeType::Expression, errors);
             for branch in branches {
-                check_grammar(&branch.2, ScopeType::Statement, errors);
+                check_case_body(&branch.2, scope, node, errors);
             }
             if let Some(else_body) = else_body {
-                check_grammar(else_body, ScopeType::Statement, errors);
+                check_case_body(else_body, scope, node, errors);
+            }
+        },
+        AstNodeVariant::CasePattern { value, branches, else_body } => {
+            enforce_min_scope!("'case'", ScopeType::Expression);
+            enforce_max_scope!("'case'", ScopeType::Statement, ScopeType::Statement);
+            check_grammar_singular(&*value, ScopeType::Expression, errors);
+            for branch in branches {
+                for pattern_value in branch.0.values() {
+                    check_grammar_singular(pattern_value, ScopeType::Expression, errors);
+                }
+                if let Some(guard) = &branch.1 {
+                    check_grammar_singular(guard, ScopeType::Expression, errors);
+                }
+                check_case_body(&branch.2, scope, node, errors);
+            }
+            if let Some(else_body) = else_body {
+                check_case_body(else_body, scope, node, errors);
             }
         },
         AstNodeVariant::Assignment { variable, value } => {
@@ -143,11 +210,32 @@ fn check_grammar_singular(node: &AstNode, scope: ScopeType, errors: &mut Vec<Err
             enforce_max_scope!("'return'", ScopeType::Statement, ScopeType::Statement);
             check_grammar_singular(&*value, ScopeType::Expression, errors);
         },
+        AstNodeVariant::Propagate { value, returned_types: _ } => {
+            enforce_min_scope!("'?'", ScopeType::Expression);
+            enforce_max_scope!("'?'", ScopeType::Statement, ScopeType::Expression);
+            check_grammar_singular(&*value, ScopeType::Expression, errors);
+        },
         AstNodeVariant::Call { called, arguments } => {
             enforce_min_scope!("Calls", ScopeType::Expression);
             enforce_max_scope!("Calls", ScopeType::Statement, ScopeType::Expression);
             check_grammar_singular(&*called, ScopeType::Expression, errors);
             check_grammar(arguments, ScopeType::Expression, errors);
+            let first_named = arguments.iter()
+                .position(|argument| matches!(argument.node_variant(), AstNodeVariant::NamedArgument { .. }));
+            if let Some(first_named) = first_named {
+                for argument in &arguments[first_named..] {
+                    if matches!(argument.node_variant(), AstNodeVariant::NamedArgument { .. }) { continue; }
+                    errors.push(Error::new([
+                        ErrorSection::Error(ErrorType::PositionalAfterNamedArgument),
+                        ErrorSection::Code(argument.source())
+                    ].into()));
+                }
+            }
+        },
+        AstNodeVariant::NamedArgument { name: _, value } => {
+            enforce_min_scope!("Named arguments", ScopeType::Expression);
+            enforce_max_scope!("Named arguments", ScopeType::Expression, ScopeType::Expression);
+            check_grammar_singular(&*value, ScopeType::Expression, errors);
         },
         AstNodeVariant::Object { values } => {
             enforce_min_scope!("Object literals", ScopeType::Expression);
@@ -211,6 +299,21 @@ fn check_grammar_singular(node: &AstNode, scope: ScopeType, errors: &mut Vec<Err
             enforce_max_scope!("Arithmetic operations", ScopeType::Statement, ScopeType::Expression);
             check_grammar_singular(&*x, ScopeType::Expression, errors);
         }
+        AstNodeVariant::BitwiseAnd { a, b } |
+        AstNodeVariant::BitwiseOr { a, b } |
+        AstNodeVariant::BitwiseXor { a, b } |
+        AstNodeVariant::LeftShift { a, b } |
+        AstNodeVariant::RightShift { a, b } => {
+            enforce_min_scope!("Bitwise operations", ScopeType::Expression);
+            enforce_max_scope!("Bitwise operations", ScopeType::Statement, ScopeType::Expression);
+            check_grammar_singular(&*a, ScopeType::Expression, errors);
+            check_grammar_singular(&*b, ScopeType::Expression, errors);
+        }
+        AstNodeVariant::BitwiseNot { x } => {
+            enforce_min_scope!("Bitwise operations", ScopeType::Expression);
+            enforce_max_scope!("Bitwise operations", ScopeType::Statement, ScopeType::Expression);
+            check_grammar_singular(&*x, ScopeType::Expression, errors);
+        }
         AstNodeVariant::LessThan { a, b } |
         AstNodeVariant::LessThanEqual { a , b } |
         AstNodeVariant::GreaterThan { a, b } |
@@ -258,5 +361,8 @@ fn check_grammar_singular(node: &AstNode, scope: ScopeType, errors: &mut Vec<Err
             enforce_min_scope!("'target'", ScopeType::Statement);
             check_grammar(body, scope, errors);
         }
+        AstNodeVariant::TypeAlias { public: _, name: _, nominal: _, aliased: _ } => {
+            enforce_min_scope!("'type'", ScopeType::GlobalStatement);
+        }
     }
 }
\ No newline at end of file
@@ -10,16 +10,18 @@ pub struct Lexer {
     file_name: StringIdx,
     file_content: StringIdx,
     source_chars: Vec<char>,
-    position: usize
+    position: usize,
+    interpolations: Vec<usize>
 }
 
 impl Lexer {
-    pub fn new(file_name: StringIdx, file_content: StringIdx, string_map: &mut StringMap) -> Lexer {
+    pub fn new(file_name: StringIdx, file_content: StringIdx, string_map: &StringMap) -> Lexer {
         Lexer {
             file_name,
             file_content,
             source_chars: string_map.get(file_content).chars().collect(),
-            position: 0
+            position: 0,
+            interpolations: Vec::new()
         }
     }
 
@@ -28,8 +30,9 @@ impl Lexer {
     fn has_next(&self) -> bool { self.position + 1 < self.source_chars.len() }
     fn peek(&self) -> char { self.source_chars[self.position + 1] }
     fn next(&mut self) { self.position += 1; }
+    fn peek_at(&self, offset: usize) -> Option<char> { self.source_chars.get(self.position + offset).copied() }
 
-    fn make_token(&self, content: &str, token_type: TokenType, string_map: &mut StringMap) -> Token {
+    fn make_token(&self, content: &str, token_type: TokenType, string_map: &StringMap) -> Token {
         Token {
             token_type,
             token_content: string_map.insert(content),
@@ -37,12 +40,193 @@ impl Lexer {
         }
     }
 
-    pub fn next_token(&mut self, string_map: &mut StringMap) -> Option<Result<Token, Error>> {
+    fn string_part(&mut self, start: usize, first: bool, string_map: &StringMap) -> Token {
+        let mut content = String::new();
+        let mut escaped = false;
+        while self.has() && (escaped || (self.current() != '"' && self.current() != '{')) {
+            let is_backslash = self.current() == '\\';
+            if escaped {
+                match self.current() {
+                    '\n' => {}
+                    'n' => content.push('\n'),
+                    'r' => content.push('\r'),
+                    'x' => 'char_parsing: {
+                        fn parse_hex_digit(d: char) -> Option<u8> {
+                            return Some(match d {
+                                '0' => 0, '1' => 1, '2' => 2,
+                                '3' => 3, '4' => 4, '5' => 5,
+                                '6' => 6, '7' => 7, '8' => 8,
+                                '9' => 9,
+                                'a' | 'A' => 10, 'b' | 'B' => 11,
+                                'c' | 'C' => 12, 'd' | 'D' => 13,
+                                'e' | 'E' => 14, 'f' | 'F' => 15,
+                                _ => return None
+                            })
+                        }
+                        let mut v: u8 = 0;
+                        self.next();
+                        if !self.has() { 
+                            content.push('x');
+                            break 'char_parsing;
+                        }
+                        let left = self.current();
+                        if let Some(d) = parse_hex_digit(left) {
+                            v += d * 16;
+                        } else {
+                            content.push('x');
+                            content.push(left);
+                            break 'char_parsing;
+                        }
+                        self.next();
+                        if !self.has() {
+                            content.push('x');
+                            content.push(left);
+                            break 'char_parsing;
+                        }
+                        let right = self.current();
+                        if let Some(d) = parse_hex_digit(right) {
+                            v += d;
+                        } else {
+                            content.push('x');
+                            content.push(left);
+                            content.push(right);
+                            break 'char_parsing;
+                        }
+                        content.push(v as char);
+                    }
+                    other => content.push(other)
+                }
+            } else if !is_backslash {
+                content.push(self.current());
+            }
+            escaped = is_backslash && !escaped;
+            self.next();
+        }
+        let interpolated = self.has() && self.current() == '{';
+        self.next();
+        let token_type = match (first, interpolated) {
+            (true, false) => TokenType::String,
+            (true, true) => TokenType::InterpolationStart,
+            (false, true) => TokenType::InterpolationMiddle,
+            (false, false) => TokenType::InterpolationEnd
+        };
+        if interpolated { self.interpolations.push(0); }
+        Token {
+            token_type,
+            token_content: string_map.insert(&content),
+            source: SourceRange::new(self.file_name, self.file_content, start, self.position)
+        }
+    }
+
+    fn raw_string(&mut self, start: usize, string_map: &StringMap) -> Token {
+        let mut raw = String::new();
+        while self.has() && !(self.current() == '"' && self.peek_at(1) == Some('"') && self.peek_at(2) == Some('"')) {
+            raw.push(self.current());
+            self.next();
+        }
+        for _ in 0..3 { if self.has() { self.next(); } }
+        let mut lines = raw.split('\n')
+            .map(|l| l.strip_suffix('\r').unwrap_or(l))
+            .collect::<Vec<&str>>();
+        if lines.len() > 1 && lines[0].trim().is_empty() { lines.remove(0); }
+        let indentation = |l: &str| l.chars().take_while(|c| *c == ' ' || *c == '\t').count();
+        let mut closing_indentation = None;
+        if lines.len() > 1 && lines[lines.len() - 1].trim().is_empty() {
+            closing_indentation = lines.pop().map(indentation);
+        }
+        let common = lines.iter()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| indentation(l))
+            .chain(closing_indentation)
+            .min()
+            .unwrap_or(0);
+        let content = lines.iter()
+            .map(|l| l.chars().skip(common.min(indentation(l))).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n");
+        Token {
+            token_type: TokenType::String,
+            token_content: string_map.insert(&content),
+            source: SourceRange::new(self.file_name, self.file_content, start, self.position)
+        }
+    }
+
+    fn number(&mut self, string_map: &StringMap) -> Result<Token, Error> {
+        let mut literal = String::new();
+        let start = self.position;
+        let radix = match (self.current(), self.peek_at(1)) {
+            ('0', Some('x')) => Some(16),
+            ('0', Some('o')) => Some(8),
+            ('0', Some('b')) => Some(2),
+            _ => None
+        };
+        if let Some(radix) = radix {
+            literal.push(self.current());
+            self.next();
+            literal.push(self.current());
+            self.next();
+            while self.has() && (self.current().is_ascii_alphanumeric() || self.current() == '_') {
+                literal.push(self.current());
+                self.next();
+            }
+            let token_content = string_map.insert(&literal);
+            let source = SourceRange::new(self.file_name, self.file_content, start, self.position);
+            let digits = literal[2..].chars().filter(|c| *c != '_').collect::<String>();
+            if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+                return Err(Error::new([
+                    ErrorSection::Error(ErrorType::InvalidNumericLiteral(token_content)),
+                    ErrorSection::Code(source)
+                ].into()));
+            }
+            return Ok(Token { token_type: TokenType::Integer, token_content, source });
+        }
+        let mut is_fraction = false;
+        while self.has() {
+            if self.current() == '.' && !is_fraction {
+                if self.has_next() && self.peek() == '.' {
+                    break;
+                }
+                is_fraction = true;
+            } else if self.current().is_ascii_digit() || self.current() == '_' {
+            } else {
+                break;
+            }
+            literal.push(self.current());
+            self.next();
+        }
+        let exponent_digit = match self.peek_at(1) {
+            Some('+' | '-') => self.peek_at(2),
+            other => other
+        };
+        if self.has() && (self.current() == 'e' || self.current() == 'E')
+            && exponent_digit.is_some_and(|c| c.is_ascii_digit()) {
+            is_fraction = true;
+            literal.push(self.current());
+            self.next();
+            literal.push(self.current());
+            self.next();
+            while self.has() && (self.current().is_ascii_digit() || self.current() == '_') {
+                literal.push(self.current());
+                self.next();
+            }
+        }
+        Ok(Token {
+            token_type: if is_fraction { TokenType::Fraction } else { TokenType::Integer },
+            token_content: string_map.insert(&literal),
+            source: SourceRange::new(self.file_name, self.file_content, start, self.position)
+        })
+    }
+
+    pub fn next_token(&mut self, string_map: &StringMap) -> Option<Result<Token, Error>> {
         while self.has() {
             match self.current() {
                 '|' => {
                     self.next();
-                    return Some(Ok(if self.has() && self.current() == '|' {
+                    return Some(Ok(if self.has_next() && self.current() == '|' && self.peek() == '|' {
+                        self.next();
+                        self.next();
+                        self.make_token("|||", TokenType::TriplePipe, string_map)
+                    } else if self.has() && self.current() == '|' {
                         self.next();
                         self.make_token("||", TokenType::DoublePipe, string_map)
                     } else if self.has() && self.current() == '>' {
@@ -107,7 +291,11 @@ impl Lexer {
                 '%' => { self.next(); return Some(Ok(self.make_token("%", TokenType::Percent, string_map))) },
                 '<' => {
                     self.next();
-                    return Some(Ok(if self.has() && self.current() == '=' {
+                    return Some(Ok(if self.has_next() && self.current() == '<' && self.peek() == '<' {
+                        self.next();
+                        self.next();
+                        self.make_token("<<<", TokenType::TripleLessThan, string_map)
+                    } else if self.has() && self.current() == '=' {
                         self.next();
                         self.make_token("<=", TokenType::LessThanEqual, string_map)
                     } else {
@@ -116,7 +304,11 @@ impl Lexer {
                 }
                 '>' => {
                     self.next();
-                    return Some(Ok(if self.has() && self.current() == '=' {
+                    return Some(Ok(if self.has_next() && self.current() == '>' && self.peek() == '>' {
+                        self.next();
+                        self.next();
+                        self.make_token(">>>", TokenType::TripleGreaterThan, string_map)
+                    } else if self.has() && self.current() == '=' {
                         self.next();
                         self.make_token(">=", TokenType::GreaterThanEqual, string_map)
                     } else {
@@ -132,94 +324,66 @@ impl Lexer {
                         self.make_token("!", TokenType::ExclamationMark, string_map)
                     }))
                 }
-                '&' => if self.has_next() && self.peek() == '&' {
+                '&' => if self.peek_at(1) == Some('&') && self.peek_at(2) == Some('&') {
+                    for _ in 0..3 { self.next(); }
+                    return Some(Ok(self.make_token("&&&", TokenType::TripleAmpersand, string_map)))
+                } else if self.has_next() && self.peek() == '&' {
                     self.next();
                     self.next();
                     return Some(Ok(self.make_token("&&", TokenType::DoubleAmpersand, string_map)))
                 }
-                ':' => if self.has_next() && self.peek() == ':' {
-                    self.next();
+                '^' => if self.peek_at(1) == Some('^') && self.peek_at(2) == Some('^') {
+                    for _ in 0..3 { self.next(); }
+                    return Some(Ok(self.make_token("^^^", TokenType::TripleCaret, string_map)))
+                }
+                '~' => if self.peek_at(1) == Some('~') && self.peek_at(2) == Some('~') {
+                    for _ in 0..3 { self.next(); }
+                    return Some(Ok(self.make_token("~~~", TokenType::TripleTilde, string_map)))
+                }
+                ':' => {
                     self.next();
-                    return Some(Ok(self.make_token("::", TokenType::DoubleColon, string_map)))
+                    return Some(Ok(if self.has() && self.current() == ':' {
+                        self.next();
+                        self.make_token("::", TokenType::DoubleColon, string_map)
+                    } else {
+                        self.make_token(":", TokenType::Colon, string_map)
+                    }))
                 }
+                '?' => { self.next(); return Some(Ok(self.make_token("?", TokenType::QuestionMark, string_map))) }
                 '#' => { self.next(); return Some(Ok(self.make_token("#", TokenType::Hashtag, string_map))) }
                 ',' => { self.next(); return Some(Ok(self.make_token(",", TokenType::Comma, string_map))) }
                 '(' => { self.next(); return Some(Ok(self.make_token("(", TokenType::ParenOpen, string_map))) }
                 ')' => { self.next(); return Some(Ok(self.make_token(")", TokenType::ParenClose, string_map))) }
                 '[' => { self.next(); return Some(Ok(self.make_token("[", TokenType::BracketOpen, string_map))) }
                 ']' => { self.next(); return Some(Ok(self.make_token("]", TokenType::BracketClose, string_map))) }
-                '{' => { self.next(); return Some(Ok(self.make_token("{", TokenType::BraceOpen, string_map))) }
-                '}' => { self.next(); return Some(Ok(self.make_token("}", TokenType::BraceClose, string_map))) }
+                '{' => {
+                    if let Some(depth) = self.interpolations.last_mut() { *depth += 1; }
+                    self.next();
+                    return Some(Ok(self.make_token("{", TokenType::BraceOpen, string_map)))
+                }
+                '}' => {
+                    match self.interpolations.last_mut() {
+                        Some(0) => {
+                            self.interpolations.pop();
+                            let start = self.position;
+                            self.next();
+                            return Some(Ok(self.string_part(start, false, string_map)));
+                        }
+                        Some(depth) => *depth -= 1,
+                        None => {}
+                    }
+                    self.next();
+                    return Some(Ok(self.make_token("}", TokenType::BraceClose, string_map)))
+                }
                 '"' => {
                     let start = self.position;
                     self.next();
-                    let mut content = String::new();
-                    let mut escaped = false;
-                    while self.has() && (escaped || self.current() != '"') {
-                        let is_backslash = self.current() == '\\';
-                        if escaped {
-                            match self.current() {
-                                '\n' => {}
-                                'n' => content.push('\n'),
-                                'r' => content.push('\r'),
-                                'x' => 'char_parsing: {
-                                    fn parse_hex_digit(d: char) -> Option<u8> {
-                                        return Some(match d {
-                                            '0' => 0, '1' => 1, '2' => 2,
-                                            '3' => 3, '4' => 4, '5' => 5,
-                                            '6' => 6, '7' => 7, '8' => 8,
-                                            '9' => 9,
-                                            'a' | 'A' => 10, 'b' | 'B' => 11,
-                                            'c' | 'C' => 12, 'd' | 'D' => 13,
-                                            'e' | 'E' => 14, 'f' | 'F' => 15,
-                                            _ => return None
-                                        })
-                                    }
-                                    let mut v: u8 = 0;
-                                    self.next();
-                                    if !self.has() { 
-                                        content.push('x');
-                                        break 'char_parsing;
-                                    }
-                                    let left = self.current();
-                                    if let Some(d) = parse_hex_digit(left) {
-                                        v += d * 16;
-                                    } else {
-                                        content.push('x');
-                                        content.push(left);
-                                        break 'char_parsing;
-                                    }
-                                    self.next();
-                                    if !self.has() {
-                                        content.push('x');
-                                        content.push(left);
-                                        break 'char_parsing;
-                                    }
-                                    let right = self.current();
-                                    if let Some(d) = parse_hex_digit(right) {
-                                        v += d;
-                                    } else {
-                                        content.push('x');
-                                        content.push(left);
-                                        content.push(right);
-                                        break 'char_parsing;
-                                    }
-                                    content.push(v as char);
-                                }
-                                other => content.push(other)
-                            }
-                        } else if !is_backslash {
-                            content.push(self.current());
-                        }
-                        escaped = is_backslash && !escaped;
+                    if self.has_next() && self.current() == '"' && self.peek() == '"' {
                         self.next();
+                        self.next();
+                        return Some(Ok(self.raw_string(start, string_map)));
                     }
-                    self.next();
-                    return Some(Ok(Token {
-                        token_type: TokenType::String,
-                        token_content: string_map.insert(&content),
-                        source: SourceRange::new(self.file_name, self.file_content, start, self.position)
-                    }));
+                    return Some(Ok(self.string_part(start, true, string_map)));
                 }
                 _ => {}
             }
@@ -228,29 +392,8 @@ impl Lexer {
                     self.next();
                 }
                 continue;
-            } else if '0' <= self.current() && self.current() <= '9' {
-                let mut literal = String::new();
-                let start = self.position;
-                let mut is_fraction = false;
-                while self.has() {
-                    if self.current() == '.' && !is_fraction {
-                        if self.has_next() && self.peek() == '.' {
-                            break;
-                        }
-                        is_fraction = true;
-                    } else if '0' <= self.current() && self.current() <= '9' {
-                    } else {
-                        break;
-                    }
-                    literal.push(self.current());
-                    self.next();
-                }
-                return Some(Ok(Token {
-                    token_type: if is_fraction { TokenType::Fraction } else { TokenType::Integer },
-                    token_content: string_map.insert(&literal),
-                    source: SourceRange::new(self.file_name, self.file_content, start, self.position)
-                }))
-
+            } else if self.current().is_ascii_digit() {
+                return Some(self.number(string_map));
             } else if !self.current().is_ascii_alphanumeric() && self.current() != '_' {
                 return Some(Err(Error::new([
                     ErrorSection::Error(ErrorType::InvalidCharacter(self.current())),
@@ -275,9 +418,12 @@ impl Lexer {
                 "true" => return Some(Ok(self.make_token("true", TokenType::KeywordTrue, string_map))),
                 "false" => return Some(Ok(self.make_token("false", TokenType::KeywordFalse, string_map))),
                 "else" => return Some(Ok(self.make_token("else", TokenType::KeywordElse, string_map))),
+                "if" => return Some(Ok(self.make_token("if", TokenType::KeywordIf, string_map))),
                 "unit" => return Some(Ok(self.make_token("unit", TokenType::KeywordUnit, string_map))),
                 "static" => return Some(Ok(self.make_token("const", TokenType::KeywordStatic, string_map))),
                 "target" => return Some(Ok(self.make_token("target", TokenType::KeywordTarget, string_map))),
+                "type" => return Some(Ok(self.make_token("type", TokenType::KeywordType, string_map))),
+                "nominal" => return Some(Ok(self.make_token("nominal", TokenType::KeywordNominal, string_map))),
                 _ => return Some(Ok(Token {
                     token_type: TokenType::Identifier,
                     token_content: string_map.insert(&identifier),
@@ -8,4 +8,5 @@ pub mod grammar_checking;
 pub mod modules;
 pub mod types;
 pub mod type_checking;
-pub mod target_macro;
\ No newline at end of file
+pub mod target_macro;
+pub mod cache;
\ No newline at end of file
@@ -4,9 +4,10 @@ use std::collections::{HashMap, HashSet};
 use crate::util::{
     strings::{StringMap, StringIdx},
     error::{Error, ErrorSection, ErrorType},
-    source::HasSource
+    source::{HasSource, SourceRange},
+    suggestions::did_you_mean
 };
-use crate::frontend::ast::{HasAstNodeVariant, AstNodeVariant};
+use crate::frontend::ast::{HasAstNodeVariant, AstNodeVariant, TypeAnnotation, Pattern};
 
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -45,7 +46,7 @@ pub struct Module<T: Clone + HasAstNodeVariant<T> + HasSource> {
     usages: Vec<NamespacePath>
 }
 
-impl<T: Clone + HasAstNodeVariant<T> + HasSource> Module<T> {
+impl<T: Clone + PartialEq + HasAstNodeVariant<T> + HasSource> Module<T> {
     pub fn new(path: NamespacePath, file_name: StringIdx, nodes: Vec<T>, strings: &mut StringMap) -> Result<Module<T>, Vec<Error>> {
         let mut new = Module {
             path,
@@ -75,8 +76,12 @@ impl<T: Clone + HasAstNodeVariant<T> + HasSource> Module<T> {
         }
     }
 
-    pub fn bake(&mut self, file_name: StringIdx, nodes: Vec<T>, strings: &StringMap) -> Result<(), Vec<Error>> {
+    pub fn bake(&mut self, file_name: StringIdx, nodes: Vec<T>, strings: &mut StringMap) -> Result<(), Vec<Error>> {
         self.file_name = Some(file_name);
+        self.usages.push(NamespacePath::new(vec![
+            strings.insert("core"),
+            strings.insert("*")
+        ]));
         let load_errors = self.load(nodes, strings);
         if load_errors.len() > 0 { Err(load_errors) } else { Ok(()) }
     }
@@ -87,8 +92,9 @@ impl<T: Clone + HasAstNodeVariant<T> + HasSource> Module<T> {
             let mut node = nodes.remove(0);
             let node_source = node.source();
             match node.node_variant_mut() {
-                AstNodeVariant::Procedure { public, name, arguments: _, body: _ } |
-                AstNodeVariant::Variable { public, mutable: _, name, value_types: _, value: _ } => {
+                AstNodeVariant::Procedure { public, name, arguments: _, returns: _, body: _ } |
+                AstNodeVariant::Variable { public, mutable: _, name, annotation: _, value_types: _, value: _ } |
+                AstNodeVariant::TypeAlias { public, name, nominal: _, aliased: _ } => {
                     let mut new_path_segments = self.path.get_segments().clone();
                     new_path_segments.push(*name);
                     let new_path = NamespacePath::new(new_path_segments);
@@ -129,6 +135,7 @@ impl<T: Clone + HasAstNodeVariant<T> + HasSource> Module<T> {
 
     pub fn file_name(&self) -> StringIdx { self.file_name.expect("file name should be set (module should not be raw)") }
     pub fn symbols(self) -> HashMap<StringIdx, T> { self.symbols }
+    pub fn symbol_nodes(&self) -> &HashMap<StringIdx, T> { &self.symbols }
 
     pub fn canonicalize(&mut self, modules: &HashMap<NamespacePath, Module<T>>, strings: &mut StringMap) -> Vec<Error> {
         self.expand_wildcards(modules, strings);
@@ -136,7 +143,7 @@ impl<T: Clone + HasAstNodeVariant<T> + HasSource> Module<T> {
         let symbol_names = self.symbols.keys().map(|s| *s).collect::<Vec<StringIdx>>();
         for symbol_name in symbol_names {
             let mut symbol = self.symbols.remove(&symbol_name).expect("key must be valid");
-            errors.append(&mut self.canonicalize_node(&mut symbol, modules, &mut HashSet::new(), strings));
+            errors.append(&mut self.canonicalize_node(&mut symbol, modules, &mut HashSet::new(), &[], strings));
             self.symbols.insert(symbol_name, symbol);
         }
         errors
@@ -170,31 +177,42 @@ impl<T: Clone + HasAstNodeVariant<T> + HasSource> Module<T> {
         }
     }
 
-    fn canonicalize_nodes(&self, nodes: &mut [T], modules: &HashMap<NamespacePath, Module<T>>, variables: &mut HashSet<StringIdx>, strings: &StringMap) -> Vec<Error> {
+    fn canonicalize_nodes(&self, nodes: &mut [T], modules: &HashMap<NamespacePath, Module<T>>, variables: &mut HashSet<StringIdx>, parameters: &[StringIdx], strings: &StringMap) -> Vec<Error> {
         let mut errors = Vec::new();
         for node in nodes {
-            errors.append(&mut self.canonicalize_node(node, modules, variables, strings));
+            errors.append(&mut self.canonicalize_node(node, modules, variables, parameters, strings));
         }
         errors
     }
 
-    fn canonicalize_node(&self, node: &mut T, modules: &HashMap<NamespacePath, Module<T>>, variables: &mut HashSet<StringIdx>, strings: &StringMap) -> Vec<Error> {
+    /// 'parameters' are the parameters of the procedure whose default values are being canonicalized.
+    fn canonicalize_node(&self, node: &mut T, modules: &HashMap<NamespacePath, Module<T>>, variables: &mut HashSet<StringIdx>, parameters: &[StringIdx], strings: &StringMap) -> Vec<Error> {
         let mut errors = Vec::new();
         macro_rules! visit_node {
-            ($node: expr) => { errors.append(&mut self.canonicalize_node($node, modules, variables, strings)) };
-            ($node: expr, $variables: expr) => { errors.append(&mut self.canonicalize_node($node, modules, $variables, strings)) }
+            ($node: expr) => { errors.append(&mut self.canonicalize_node($node, modules, variables, parameters, strings)) };
+            ($node: expr, $variables: expr) => { errors.append(&mut self.canonicalize_node($node, modules, $variables, parameters, strings)) }
         }
         macro_rules! visit_nodes {
-            ($nodes: expr) => { errors.append(&mut self.canonicalize_nodes($nodes, modules, &mut variables.clone(), strings)) };
-            ($nodes: expr, $variables: expr) => { errors.append(&mut self.canonicalize_nodes($nodes, modules, $variables, strings)) }
+            ($nodes: expr) => { errors.append(&mut self.canonicalize_nodes($nodes, modules, &mut variables.clone(), parameters, strings)) };
+            ($nodes: expr, $variables: expr) => { errors.append(&mut self.canonicalize_nodes($nodes, modules, $variables, parameters, strings)) }
         }
         let node_source = node.source();
         let node_variant = node.node_variant_mut();
         match node_variant {
-            AstNodeVariant::Procedure { public: _, name: _, arguments, body } => {
+            AstNodeVariant::Procedure { public: _, name: _, arguments, returns, body } => {
+                let parameter_names = arguments.iter().map(|argument| argument.0).collect::<Vec<StringIdx>>();
+                for (_, _, argument_annotation, argument_default) in arguments {
+                    if let Some(argument_annotation) = argument_annotation {
+                        errors.append(&mut self.canonicalize_annotation(argument_annotation, modules, strings));
+                    }
+                    if let Some(argument_default) = argument_default {
+                        errors.append(&mut self.canonicalize_node(argument_default, modules, &mut variables.clone(), &parameter_names, strings));
+                    }
+                }
                 let mut variables = variables.clone();
-                for (argument_name, _) in arguments {
-                    variables.insert(*argument_name);
+                variables.extend(parameter_names);
+                if let Some((returns, _)) = returns {
+                    errors.append(&mut self.canonicalize_annotation(returns, modules, strings));
                 }
                 visit_nodes!(body, &mut variables);
             }
@@ -205,12 +223,21 @@ impl<T: Clone + HasAstNodeVariant<T> + HasSource> Module<T> {
                 }
                 visit_nodes!(body, &mut variables);
             }
-            AstNodeVariant::Variable { public: _, mutable: _, name, value_types: _, value } => {
+            AstNodeVariant::Variable { public: _, mutable: _, name, annotation, value_types: _, value } => {
+                if let Some(annotation) = annotation {
+                    errors.append(&mut self.canonicalize_annotation(annotation, modules, strings));
+                }
                 if let Some(value) = value {
                     visit_node!(&mut **value);
                 }
                 variables.insert(*name);
             }
+            AstNodeVariant::Destructuring { mutable: _, pattern, value } => {
+                visit_node!(&mut **value);
+                for (name, _) in pattern.bindings() {
+                    variables.insert(name);
+                }
+            }
             AstNodeVariant::CaseBranches { value, branches, else_body } => {
                 visit_node!(&mut **value);
                 for branch in branches {
@@ -237,11 +264,46 @@ impl<T: Clone + HasAstNodeVariant<T> + HasSource> Module<T> {
                     visit_nodes!(else_body);
                 }
             }
+            AstNodeVariant::CasePattern { value, branches, else_body } => {
+                visit_node!(&mut **value);
+                for branch in branches {
+                    self.resolve_pattern_constants(&mut branch.0, modules, variables);
+                    let mut bound = Vec::new();
+                    for (name, source) in branch.0.bindings() {
+                        if bound.contains(&name) {
+                            errors.push(Error::new([
+                                ErrorSection::Error(ErrorType::DuplicatePatternBinding(name)),
+                                ErrorSection::Code(source)
+                            ].into()));
+                        }
+                        bound.push(name);
+                    }
+                    for pattern_value in branch.0.values_mut() {
+                        visit_node!(pattern_value);
+                    }
+                    let mut variables = variables.clone();
+                    for (name, _) in branch.0.bindings() {
+                        variables.insert(name);
+                    }
+                    if let Some(guard) = &mut branch.1 {
+                        visit_node!(guard, &mut variables);
+                    }
+                    visit_nodes!(&mut branch.2, &mut variables);
+                }
+                if let Some(else_body) = else_body {
+                    visit_nodes!(else_body);
+                }
+                if let AstNodeVariant::CasePattern { value, branches, else_body } = std::mem::replace(node_variant, AstNodeVariant::UnitLiteral) {
+                    *node_variant = AstNodeVariant::case_pattern(value, branches, else_body);
+                }
+            }
             AstNodeVariant::Assignment { variable, value } => {
                 visit_node!(&mut **variable);
                 visit_node!(&mut **value);
             }
-            AstNodeVariant::Return { value } => {
+            AstNodeVariant::Return { value } |
+            AstNodeVariant::Propagate { value, returned_types: _ } |
+            AstNodeVariant::NamedArgument { name: _, value } => {
                 visit_node!(&mut **value);
             }
             AstNodeVariant::Call { called, arguments } => {
@@ -268,7 +330,7 @@ impl<T: Clone + HasAstNodeVariant<T> + HasSource> Module<T> {
                     let mut path_segments = self.path.get_segments().clone();
                     path_segments.push(*name);
                     *node_variant = AstNodeVariant::ModuleAccess { path: NamespacePath::new(path_segments) };
-                    errors.append(&mut self.canonicalize_node(node, modules, variables, strings));
+                    errors.append(&mut self.canonicalize_node(node, modules, variables, parameters, strings));
                 } else if !variables.contains(name) {
                     let mut last_usage = None;
                     for usage in &self.usages {
@@ -277,7 +339,24 @@ impl<T: Clone + HasAstNodeVariant<T> + HasSource> Module<T> {
                     }
                     if let Some(usage) = last_usage {
                         *node_variant = AstNodeVariant::ModuleAccess { path: usage.clone() };
-                        errors.append(&mut self.canonicalize_node(node, modules, variables, strings));
+                        errors.append(&mut self.canonicalize_node(node, modules, variables, parameters, strings));
+                    } else if parameters.contains(name) {
+                        errors.push(Error::new([
+                            ErrorSection::Error(ErrorType::DefaultReferencesParameter(*name)),
+                            ErrorSection::Code(node_source)
+                        ].into()));
+                    } else {
+                        let mut sections = vec![
+                            ErrorSection::Error(ErrorType::VariableDoesNotExist(*name)),
+                            ErrorSection::Code(node_source)
+                        ];
+                        let candidates = variables.iter()
+                            .chain(self.exported.keys())
+                            .chain(self.usages.iter().map(|u| &u.segments[u.segments.len() - 1]))
+                            .map(|c| strings.get(*c));
+                        sections.extend(did_you_mean(strings.get(*name), candidates));
+                        sections.append(&mut self.missing_usage_help(*name, modules, strings));
+                        errors.push(Error::new(sections.into()));
                     }
                 }
             }
@@ -291,6 +370,11 @@ impl<T: Clone + HasAstNodeVariant<T> + HasSource> Module<T> {
             AstNodeVariant::Multiply { a, b } |
             AstNodeVariant::Divide { a, b } |
             AstNodeVariant::Modulo { a, b } |
+            AstNodeVariant::BitwiseAnd { a, b } |
+            AstNodeVariant::BitwiseOr { a, b } |
+            AstNodeVariant::BitwiseXor { a, b } |
+            AstNodeVariant::LeftShift { a, b } |
+            AstNodeVariant::RightShift { a, b } |
             AstNodeVariant::LessThan { a, b } |
             AstNodeVariant::GreaterThan { a, b } |
             AstNodeVariant::LessThanEqual { a, b } |
@@ -303,45 +387,13 @@ impl<T: Clone + HasAstNodeVariant<T> + HasSource> Module<T> {
                 visit_node!(&mut **b);
             }
             AstNodeVariant::Negate { x } |
+            AstNodeVariant::BitwiseNot { x } |
             AstNodeVariant::Not { x } => {
                 visit_node!(&mut **x);
             }
             AstNodeVariant::Module { path: _ } => {}
             AstNodeVariant::ModuleAccess { path } => {
-                let first_segment = path.segments[0];
-                let mut last_usage = None;
-                for usage in &self.usages {
-                    if usage.segments[usage.segments.len() - 1] != first_segment { continue; }
-                    last_usage = Some(usage);
-                }
-                if let Some(usage) = last_usage {
-                    let mut new_path_segments = usage.segments.clone();
-                    new_path_segments.append(&mut path.segments[1..].into());
-                    *path = NamespacePath::new(new_path_segments);
-                }
-                let module_name = NamespacePath::new(path.get_segments()[0..path.get_segments().len() - 1].into());
-                let accessed_name = path.get_segments()[path.get_segments().len() - 1];
-                let module = if let Some(module) = modules.get(&module_name) { Some(module) }
-                    else if module_name == self.path { Some(self) }
-                    else { errors.push(Error::new([
-                        ErrorSection::Error(ErrorType::ModuleDoesNotExist(module_name.display(strings))),
-                        ErrorSection::Code(node_source)
-                    ].into())); None };
-                if let Some(module) = module {
-                    if let Some(is_public) = module.exported.get(&accessed_name) {
-                        if !is_public && module.symbols.get(&accessed_name).map(|n| n.source().file_name() != node_source.file_name()).unwrap_or(false) {
-                            errors.push(Error::new([
-                                ErrorSection::Error(ErrorType::SymbolIsNotPublic(path.display(strings))),
-                                ErrorSection::Code(node_source)
-                            ].into()));
-                        }
-                    } else { 
-                        errors.push(Error::new([
-                            ErrorSection::Error(ErrorType::SymbolDoesNotExist(path.display(strings))),
-                            ErrorSection::Code(node_source)
-                        ].into()));
-                    }
-                }
+                errors.append(&mut self.canonicalize_path(path, node_source, modules, strings));
             }
             AstNodeVariant::Use { paths: _ } => {}
             AstNodeVariant::Variant { name: _, value } => {
@@ -353,6 +405,188 @@ impl<T: Clone + HasAstNodeVariant<T> + HasSource> Module<T> {
             AstNodeVariant::Target { target: _, body: _ } => {
                 panic!("Should be expanded!");
             }
+            AstNodeVariant::TypeAlias { public: _, name: _, nominal: _, aliased } => {
+                errors.append(&mut self.canonicalize_annotation(aliased, modules, strings));
+            }
+        }
+        errors
+    }
+
+    /// Identifiers in refutable patterns are parsed as values. They compare against
+    /// the constant of that name if there is one, and bind a new variable otherwise.
+    fn resolve_pattern_constants(&self, pattern: &mut Pattern<T>, modules: &HashMap<NamespacePath, Module<T>>, variables: &HashSet<StringIdx>) {
+        let binding = match pattern {
+            Pattern::Value { value, source, .. } => match value.node_variant() {
+                AstNodeVariant::VariableAccess { name } if variables.contains(name) || !self.is_constant(*name, modules) =>
+                    Some(Pattern::Variable { name: *name, source: *source, value_types: None }),
+                _ => None
+            }
+            Pattern::Variant { payload: Some(payload), .. } => {
+                self.resolve_pattern_constants(payload, modules, variables);
+                None
+            }
+            Pattern::Object { members, .. } => {
+                for (_, member_pattern) in members { self.resolve_pattern_constants(member_pattern, modules, variables); }
+                None
+            }
+            Pattern::Array { elements, .. } => {
+                for element_pattern in elements { self.resolve_pattern_constants(element_pattern, modules, variables); }
+                None
+            }
+            _ => None
+        };
+        if let Some(binding) = binding { *pattern = binding; }
+    }
+
+    fn is_constant(&self, name: StringIdx, modules: &HashMap<NamespacePath, Module<T>>) -> bool {
+        let is_constant_in = |module: &Module<T>, name: StringIdx| module.symbols.get(&name)
+            .map(|symbol| matches!(symbol.node_variant(), AstNodeVariant::Variable { .. }))
+            .unwrap_or(false);
+        if self.exported.contains_key(&name) { return is_constant_in(self, name); }
+        let last_usage = self.usages.iter().rev()
+            .find(|usage| usage.segments[usage.segments.len() - 1] == name);
+        let Some(usage) = last_usage else { return false; };
+        let module_path = NamespacePath::new(usage.segments[..usage.segments.len() - 1].into());
+        let module = if module_path == self.path { Some(self) } else { modules.get(&module_path) };
+        module.map(|module| is_constant_in(module, name)).unwrap_or(false)
+    }
+
+    fn canonicalize_path(&self, path: &mut NamespacePath, source: SourceRange, modules: &HashMap<NamespacePath, Module<T>>, strings: &StringMap) -> Vec<Error> {
+        let mut errors = Vec::new();
+        let first_segment = path.segments[0];
+        let mut last_usage = None;
+        for usage in &self.usages {
+            if usage.segments[usage.segments.len() - 1] != first_segment { continue; }
+            last_usage = Some(usage);
+        }
+        if let Some(usage) = last_usage {
+            let mut new_path_segments = usage.segments.clone();
+            new_path_segments.append(&mut path.segments[1..].into());
+            *path = NamespacePath::new(new_path_segments);
+        }
+        let module_name = NamespacePath::new(path.get_segments()[0..path.get_segments().len() - 1].into());
+        let accessed_name = path.get_segments()[path.get_segments().len() - 1];
+        let module = if let Some(module) = modules.get(&module_name) { Some(module) }
+            else if module_name == self.path { Some(self) }
+            else {
+                let mut sections = vec![
+                    ErrorSection::Error(ErrorType::ModuleDoesNotExist(module_name.display(strings))),
+                    ErrorSection::Code(source)
+                ];
+                let candidates = modules.keys().chain([&self.path]).map(|p| p.display(strings));
+                sections.extend(did_you_mean(&module_name.display(strings), candidates));
+                let mut similar_paths = modules.keys()
+                    .filter(|p| p.segments.len() > module_name.segments.len() && p.segments.ends_with(&module_name.segments))
+                    .map(|p| NamespacePath::new(p.segments[..p.segments.len() - module_name.segments.len() + 1].into()).display(strings))
+                    .collect::<Vec<String>>();
+                similar_paths.sort();
+                similar_paths.dedup();
+                for similar_path in similar_paths {
+                    sections.push(ErrorSection::Help(format!("Add 'use {}' to be able to refer to it as '{}'", similar_path, module_name.display(strings))));
+                }
+                errors.push(Error::new(sections.into()));
+                None
+            };
+        if let Some(module) = module {
+            if let Some(is_public) = module.exported.get(&accessed_name) {
+                if let Some(declaration) = module.symbols.get(&accessed_name).filter(|n| !is_public && n.source().file_name() != source.file_name()) {
+                    errors.push(Error::new([
+                        ErrorSection::Error(ErrorType::SymbolIsNotPublic(path.display(strings))),
+                        ErrorSection::Code(source),
+                        ErrorSection::Info(format!("'{}' is declared here", path.display(strings))),
+                        ErrorSection::Code(declaration.source()),
+                        ErrorSection::Help(format!("Declare '{}' with 'pub' to make it accessible from other modules", path.display(strings)))
+                    ].into()));
+                }
+            } else { 
+                let mut sections = vec![
+                    ErrorSection::Error(ErrorType::SymbolDoesNotExist(path.display(strings))),
+                    ErrorSection::Code(source)
+                ];
+                let candidates = module.exported.iter()
+                    .filter(|(_, is_public)| **is_public || module.path == self.path)
+                    .map(|(name, _)| strings.get(*name));
+                sections.extend(did_you_mean(strings.get(accessed_name), candidates));
+                errors.push(Error::new(sections.into()));
+            }
+        }
+        errors
+    }
+
+    fn missing_usage_help(&self, name: StringIdx, modules: &HashMap<NamespacePath, Module<T>>, strings: &StringMap) -> Vec<ErrorSection> {
+        let mut found = modules.iter()
+            .filter_map(|(module_path, module)| module.exported.get(&name).map(|is_public| {
+                let mut symbol_path_segments = module_path.segments.clone();
+                symbol_path_segments.push(name);
+                (NamespacePath::new(symbol_path_segments).display(strings), *is_public)
+            }))
+            .collect::<Vec<(String, bool)>>();
+        found.sort();
+        found.into_iter().map(|(symbol_path, is_public)| ErrorSection::Help(if is_public {
+            format!("'{}' exists, but is not used in this module - add 'use {}'", symbol_path, symbol_path)
+        } else {
+            format!("'{}' exists, but is not public and not used in this module - declare it with 'pub' and add 'use {}'", symbol_path, symbol_path)
+        })).collect()
+    }
+
+    fn type_candidates<'s>(&'s self, modules: &'s HashMap<NamespacePath, Module<T>>, strings: &'s StringMap) -> impl Iterator<Item = &'s str> {
+        fn is_type<T: Clone + HasAstNodeVariant<T> + HasSource>(module: &Module<T>, name: &StringIdx) -> bool {
+            module.symbols.get(name).map(|n| matches!(n.node_variant(), AstNodeVariant::TypeAlias { .. })).unwrap_or(false)
+        }
+        let declared = self.exported.keys()
+            .filter(|name| is_type(self, name));
+        let used = self.usages.iter()
+            .filter(|u| modules.get(&NamespacePath::new(u.segments[..u.segments.len() - 1].into())).map(|m| is_type(m, &u.segments[u.segments.len() - 1])).unwrap_or(false))
+            .map(|u| &u.segments[u.segments.len() - 1]);
+        ["unit", "bool", "int", "float", "str"].into_iter()
+            .chain(declared.chain(used).map(|name| strings.get(*name)))
+    }
+
+    fn canonicalize_annotation(&self, annotation: &mut TypeAnnotation, modules: &HashMap<NamespacePath, Module<T>>, strings: &StringMap) -> Vec<Error> {
+        let mut errors = Vec::new();
+        match annotation {
+            TypeAnnotation::Unit |
+            TypeAnnotation::Boolean |
+            TypeAnnotation::Integer |
+            TypeAnnotation::Float |
+            TypeAnnotation::String => {}
+            TypeAnnotation::Named(path, source) => {
+                let name = path.segments[0];
+                if path.segments.len() == 1 && self.exported.contains_key(&name) {
+                    let mut path_segments = self.path.get_segments().clone();
+                    path_segments.push(name);
+                    *path = NamespacePath::new(path_segments);
+                } else if path.segments.len() > 1 || self.usages.iter().any(|u| u.segments[u.segments.len() - 1] == name) {
+                    errors.append(&mut self.canonicalize_path(path, *source, modules, strings));
+                } else {
+                    let mut sections = vec![
+                        ErrorSection::Error(ErrorType::TypeDoesNotExist(name)),
+                        ErrorSection::Code(*source)
+                    ];
+                    sections.extend(did_you_mean(strings.get(name), self.type_candidates(modules, strings)));
+                    sections.append(&mut self.missing_usage_help(name, modules, strings));
+                    errors.push(Error::new(sections.into()));
+                }
+            }
+            TypeAnnotation::Array(element_type) => {
+                errors.append(&mut self.canonicalize_annotation(element_type, modules, strings));
+            }
+            TypeAnnotation::Object(members) => {
+                for (_, member_type) in members {
+                    errors.append(&mut self.canonicalize_annotation(member_type, modules, strings));
+                }
+            }
+            TypeAnnotation::Closure(parameter_types, return_type) => {
+                for parameter_type in parameter_types {
+                    errors.append(&mut self.canonicalize_annotation(parameter_type, modules, strings));
+                }
+                errors.append(&mut self.canonicalize_annotation(return_type, modules, strings));
+            }
+            TypeAnnotation::Variants(variants, _) => {
+                for (_, variant_type) in variants {
+                    errors.append(&mut self.canonicalize_annotation(variant_type, modules, strings));
+                }
+            }
         }
         errors
     }
@@ -6,7 +6,8 @@ use crate::util::{
 };
 use crate::frontend::{
     lexer::Lexer,
-    ast::{AstNode, AstNodeVariant, HasAstNodeVariant},
+    ast::{AstNode, AstNodeVariant, HasAstNodeVariant, TypeAnnotation, Pattern},
+    external::ExternalMappingParser,
     tokens::{TokenType, Token},
     modules::NamespacePath
 };
@@ -17,24 +18,30 @@ const PREFIX_MINUS_PRECEDENCE: Option<usize> = Some(1);
 fn get_operator_precedence(token_type: TokenType) -> Option<usize> {
     match token_type {
         TokenType::ParenOpen => Some(0),
-        TokenType::ExclamationMark => Some(1),
+        TokenType::ExclamationMark |
+        TokenType::TripleTilde => Some(1),
         TokenType::Asterisk |
         TokenType::Slash |
         TokenType::Percent => Some(2),
         TokenType::Plus |
         TokenType::Minus => Some(3),
+        TokenType::TripleLessThan |
+        TokenType::TripleGreaterThan => Some(4),
+        TokenType::TripleAmpersand => Some(5),
+        TokenType::TripleCaret => Some(6),
+        TokenType::TriplePipe => Some(7),
         TokenType::LessThan |
         TokenType::GreaterThan |
         TokenType::LessThanEqual |
-        TokenType::GreaterThanEqual => Some(4),
+        TokenType::GreaterThanEqual => Some(8),
         TokenType::DoubleEquals |
-        TokenType::NotEquals => Some(5),
-        TokenType::DoubleAmpersand => Some(6),
-        TokenType::DoublePipe => Some(7),
+        TokenType::NotEquals => Some(9),
+        TokenType::DoubleAmpersand => Some(10),
+        TokenType::DoublePipe => Some(11),
         TokenType::DoubleDot |
-        TokenType::DoubleDotEquals => Some(8),
+        TokenType::DoubleDotEquals => Some(12),
         TokenType::FunctionPipe |
-        TokenType::MemberPipe => Some(9),
+        TokenType::MemberPipe => Some(13),
         _ => None
     }
 }
@@ -46,7 +53,7 @@ pub struct Parser {
 }
 
 impl Parser {
-    pub fn new(strings: &mut StringMap, lexer: &mut Lexer) -> Option<Result<Parser, Error>> {
+    pub fn new(strings: &StringMap, lexer: &mut Lexer) -> Option<Result<Parser, Error>> {
         lexer.next_token(strings)
             .map(|res| res.map(|token| Parser {
                 current: token,
@@ -54,7 +61,7 @@ impl Parser {
             }))
     }
 
-    fn next(&mut self, strings: &mut StringMap, lexer: &mut Lexer) -> Result<bool, Error> {
+    fn next(&mut self, strings: &StringMap, lexer: &mut Lexer) -> Result<bool, Error> {
         match lexer.next_token(strings) {
             None => {
                 self.reached_end = true;
@@ -68,7 +75,126 @@ impl Parser {
         }
     }
 
-    pub fn parse_block(&mut self, strings: &mut StringMap, lexer: &mut Lexer) -> Result<Vec<AstNode>, Error> {
+    fn parse_type_annotation(&mut self, strings: &StringMap, lexer: &mut Lexer) -> Result<(TypeAnnotation, SourceRange), Error> {
+        let mut type_parser = ExternalMappingParser::continue_from(self.current.clone());
+        let annotation = type_parser.parse_annotation(strings, lexer)?;
+        let annotation_end;
+        (self.current, self.reached_end, annotation_end) = type_parser.position();
+        return Ok((annotation, annotation_end));
+    }
+
+    fn parse_pattern(&mut self, strings: &StringMap, lexer: &mut Lexer, refutable: bool) -> Result<Pattern<AstNode>, Error> {
+        macro_rules! enforce_next {
+            ($expected: expr) => {
+                if !self.next(strings, lexer)? { return Err(Error::new([
+                    ErrorSection::Error(ErrorType::UnexpectedEnd($expected)),
+                    ErrorSection::Code(self.current.source)
+                ].into())); }
+            };
+        }
+        macro_rules! enforce_current_type {
+            ($types: expr, $expected: expr) => {
+                if self.reached_end { return Err(Error::new([
+                    ErrorSection::Error(ErrorType::UnexpectedEnd($expected)),
+                    ErrorSection::Code(self.current.source)
+                ].into())); }
+                if !$types.contains(&self.current.token_type) { return Err(Error::new([
+                    ErrorSection::Error(ErrorType::UnexpectedToken($expected, self.current.token_content)),
+                    ErrorSection::Code(self.current.source)
+                ].into())); }
+            };
+        }
+        let start = self.current.source;
+        match self.current.token_type {
+            TokenType::Identifier if strings.get(self.current.token_content) == "_" => {
+                self.next(strings, lexer)?;
+                Ok(Pattern::Wildcard { source: start, value_types: None })
+            }
+            TokenType::Identifier if !refutable => {
+                let name = self.current.token_content;
+                self.next(strings, lexer)?;
+                Ok(Pattern::Variable { name, source: start, value_types: None })
+            }
+            TokenType::Hashtag if refutable => {
+                enforce_next!("the variant's name");
+                enforce_current_type!(&[TokenType::Identifier], "the variant's name");
+                let name = self.current.token_content;
+                let mut source = (&start..&self.current.source).into();
+                let has_payload = self.next(strings, lexer)? && ![
+                    TokenType::Arrow, TokenType::KeywordIf, TokenType::Comma, TokenType::BraceClose
+                ].contains(&self.current.token_type);
+                let payload = if has_payload {
+                    let payload = self.parse_pattern(strings, lexer, refutable)?;
+                    source = (&start..&payload.source()).into();
+                    Some(Box::new(payload))
+                } else { None };
+                Ok(Pattern::Variant { name, payload, source, value_types: None })
+            }
+            TokenType::BraceOpen => {
+                enforce_next!("the name of a member or a closing brace ('}')");
+                let mut members = Vec::new();
+                while self.current.token_type != TokenType::BraceClose {
+                    enforce_current_type!(&[TokenType::Identifier], "the name of a member");
+                    let member = self.current.token_content;
+                    let member_source = self.current.source;
+                    enforce_next!("an equals sign ('='), a comma (',') or a closing brace ('}')");
+                    let member_pattern = if self.current.token_type == TokenType::Equals {
+                        enforce_next!("the pattern for the member");
+                        self.parse_pattern(strings, lexer, refutable)?
+                    } else {
+                        Pattern::Variable { name: member, source: member_source, value_types: None }
+                    };
+                    members.push((member, member_pattern));
+                    enforce_current_type!(&[TokenType::Comma, TokenType::BraceClose], "a comma (',') or a closing brace ('}')");
+                    if self.current.token_type == TokenType::Comma {
+                        enforce_next!("the name of a member or a closing brace ('}')");
+                    }
+                }
+                let source = (&start..&self.current.source).into();
+                self.next(strings, lexer)?;
+                Ok(Pattern::Object { members, source, value_types: None })
+            }
+            TokenType::BracketOpen if !refutable => {
+                enforce_next!("an element pattern or a closing bracket (']')");
+                let mut elements = Vec::new();
+                while self.current.token_type != TokenType::BracketClose {
+                    elements.push(self.parse_pattern(strings, lexer, refutable)?);
+                    enforce_current_type!(&[TokenType::Comma, TokenType::BracketClose], "a comma (',') or a closing bracket (']')");
+                    if self.current.token_type == TokenType::Comma {
+                        enforce_next!("an element pattern or a closing bracket (']')");
+                    }
+                }
+                let source = (&start..&self.current.source).into();
+                self.next(strings, lexer)?;
+                Ok(Pattern::Array { elements, source, value_types: None, index_types: None })
+            }
+            _ if refutable => {
+                let expected = "a variable name, a wildcard ('_'), a variant pattern ('#'), an object pattern ('{') or a value";
+                let value = match self.current.token_type {
+                    TokenType::BracketOpen => None,
+                    _ => self.parse_expression_until(strings, lexer, &mut Vec::new(), &[
+                        TokenType::Arrow, TokenType::KeywordIf, TokenType::Comma, TokenType::BraceClose
+                    ], None)?
+                };
+                let value = match value {
+                    Some(value) => value,
+                    None => return Err(Error::new([
+                        ErrorSection::Error(ErrorType::UnexpectedToken(expected, self.current.token_content)),
+                        ErrorSection::Code(self.current.source)
+                    ].into()))
+                };
+                // identifiers become variables during canonicalization unless they name a constant
+                let source = value.source();
+                Ok(Pattern::Value { value: Box::new(value), source, value_types: None })
+            }
+            _ => Err(Error::new([
+                ErrorSection::Error(ErrorType::UnexpectedToken("a variable name, an object pattern ('{') or an array pattern ('[')", self.current.token_content)),
+                ErrorSection::Code(self.current.source)
+            ].into()))
+        }
+    }
+
+    pub fn parse_block(&mut self, strings: &StringMap, lexer: &mut Lexer) -> Result<Vec<AstNode>, Error> {
         let mut nodes = Vec::new();
         while !self.reached_end {
             match self.parse_expression(strings, lexer, &mut vec![&[TokenType::BraceClose]], None) {
@@ -80,14 +206,27 @@ impl Parser {
         Ok(nodes)
     }
 
-    fn parse_expression_until(&mut self, strings: &mut StringMap, lexer: &mut Lexer, end_at_types: &mut Vec<&[TokenType]>, until: &'static [TokenType], precedence: Option<usize>) -> Result<Option<AstNode>, Error> {
+    // 'f(name = value)' --> named argument instead of an assignment
+    fn into_named_argument(argument: AstNode) -> AstNode {
+        if let AstNodeVariant::Assignment { variable, value } = argument.node_variant() {
+            if let AstNodeVariant::VariableAccess { name } = variable.node_variant() {
+                return AstNode::new(
+                    AstNodeVariant::NamedArgument { name: *name, value: value.clone() },
+                    argument.source()
+                );
+            }
+        }
+        argument
+    }
+
+    fn parse_expression_until(&mut self, strings: &StringMap, lexer: &mut Lexer, end_at_types: &mut Vec<&[TokenType]>, until: &'static [TokenType], precedence: Option<usize>) -> Result<Option<AstNode>, Error> {
         end_at_types.push(until);
         let result = self.parse_expression(strings, lexer, end_at_types, precedence);
         end_at_types.pop();
         return result;
     }
 
-    fn parse_expression(&mut self, strings: &mut StringMap, lexer: &mut Lexer, end_at_types: &mut Vec<&[TokenType]>, precedence: Option<usize>) -> Result<Option<AstNode>, Error> {
+    fn parse_expression(&mut self, strings: &StringMap, lexer: &mut Lexer, end_at_types: &mut Vec<&[TokenType]>, precedence: Option<usize>) -> Result<Option<AstNode>, Error> {
         self.reached_end = false;
         let mut previous: Option<AstNode> = None;
         macro_rules! next {
@@ -183,6 +322,16 @@ impl Parser {
                     if !next!() { return Ok(previous); }
                     continue;
                 }
+                TokenType::QuestionMark => {
+                    let value = enforce_previous!("the value to propagate");
+                    let value_source = value.source();
+                    previous = Some(AstNode::new(
+                        AstNodeVariant::Propagate { value: Box::new(value), returned_types: None },
+                        (&value_source..&self.current.source).into()
+                    ));
+                    if !next!() { return Ok(previous); }
+                    continue;
+                }
                 TokenType::BracketOpen => {
                     if previous.is_some() {
                         let accessed = enforce_previous!("the array to access an element of");
@@ -233,7 +382,7 @@ impl Parser {
                     enforce_next!("a call parameter or a closing parenthesis (')')");
                     let mut args = vec![accessed.clone()];
                     while self.current.token_type != TokenType::ParenClose {
-                        args.push(enforce_expression!(&[TokenType::Comma, TokenType::ParenClose], None, "a call parameter"));
+                        args.push(Parser::into_named_argument(enforce_expression!(&[TokenType::Comma, TokenType::ParenClose], None, "a call parameter")));
                         enforce_current_type!(&[TokenType::Comma, TokenType::ParenClose], "a comma (',') or a closing parenthesis (')')");
                         if self.current.token_type == TokenType::Comma {
                             enforce_next!("a call parameter or a closing parenthesis (')')");
@@ -294,6 +443,26 @@ impl Parser {
                     parse_infix_operator!(Modulo, get_operator_precedence(TokenType::Percent), "the thing to divide", "the divisor");
                     continue;
                 }
+                TokenType::TripleAmpersand => {
+                    parse_infix_operator!(BitwiseAnd, get_operator_precedence(TokenType::TripleAmpersand), "the first operand", "the second operand");
+                    continue;
+                }
+                TokenType::TriplePipe => {
+                    parse_infix_operator!(BitwiseOr, get_operator_precedence(TokenType::TriplePipe), "the first operand", "the second operand");
+                    continue;
+                }
+                TokenType::TripleCaret => {
+                    parse_infix_operator!(BitwiseXor, get_operator_precedence(TokenType::TripleCaret), "the first operand", "the second operand");
+                    continue;
+                }
+                TokenType::TripleLessThan => {
+                    parse_infix_operator!(LeftShift, get_operator_precedence(TokenType::TripleLessThan), "the thing to shift", "the amount to shift by");
+                    continue;
+                }
+                TokenType::TripleGreaterThan => {
+                    parse_infix_operator!(RightShift, get_operator_precedence(TokenType::TripleGreaterThan), "the thing to shift", "the amount to shift by");
+                    continue;
+                }
                 TokenType::LessThan => {
                     parse_infix_operator!(LessThan, get_operator_precedence(TokenType::LessThan), "the first thing to compare", "the second thing to compare");
                     continue;
@@ -381,7 +550,7 @@ impl Parser {
                     enforce_next!("a call parameter or a closing parenthesis (')')");
                     let mut args = Vec::new();
                     while self.current.token_type != TokenType::ParenClose {
-                        args.push(enforce_expression!(&[TokenType::Comma, TokenType::ParenClose], None, "a call parameter"));
+                        args.push(Parser::into_named_argument(enforce_expression!(&[TokenType::Comma, TokenType::ParenClose], None, "a call parameter")));
                         enforce_current_type!(&[TokenType::Comma, TokenType::ParenClose], "a comma (',') or a closing parenthesis (')')");
                         if self.current.token_type == TokenType::Comma {
                             enforce_next!("a call parameter or a closing parenthesis (')')");
@@ -432,8 +601,71 @@ impl Parser {
                     ));
                     next!();
                 }
+                TokenType::InterpolationStart => {
+                    let start_source = self.current.source;
+                    let mut parts = Vec::new();
+                    macro_rules! push_literal_part { () => {
+                        if strings.get(self.current.token_content).len() > 0 {
+                            parts.push(AstNode::new(
+                                AstNodeVariant::StringLiteral { value: self.current.token_content },
+                                self.current.source
+                            ));
+                        }
+                    } }
+                    push_literal_part!();
+                    loop {
+                        enforce_next!("an interpolated value");
+                        let value = enforce_expression!(&[TokenType::InterpolationMiddle, TokenType::InterpolationEnd], None, "an interpolated value");
+                        let value_source = value.source();
+                        enforce_current_type!(&[TokenType::InterpolationMiddle, TokenType::InterpolationEnd], "the end of the interpolated value ('}')");
+                        parts.push(AstNode::new(
+                            AstNodeVariant::Call {
+                                called: AstNode::new(
+                                    AstNodeVariant::ModuleAccess {
+                                        path: NamespacePath::new(vec![
+                                            strings.insert("core"),
+                                            strings.insert("as_str")
+                                        ])
+                                    },
+                                    value_source
+                                ).into(),
+                                arguments: vec![value]
+                            },
+                            value_source
+                        ));
+                        push_literal_part!();
+                        if self.current.token_type == TokenType::InterpolationEnd { break; }
+                    }
+                    let source = (&start_source..&self.current.source).into();
+                    previous = parts.into_iter().reduce(|a, b| AstNode::new(
+                        AstNodeVariant::Call {
+                            called: AstNode::new(
+                                AstNodeVariant::ModuleAccess {
+                                    path: NamespacePath::new(vec![
+                                        strings.insert("core"),
+                                        strings.insert("concat")
+                                    ])
+                                },
+                                source
+                            ).into(),
+                            arguments: vec![a, b]
+                        },
+                        source
+                    ));
+                    next!();
+                }
                 TokenType::Integer => {
-                    let value = if let Ok(value) = strings.get(self.current.token_content).parse() {
+                    let literal = strings.get(self.current.token_content).replace('_', "");
+                    let (digits, radix) = match literal.get(..2) {
+                        Some("0x") => (&literal[2..], 16),
+                        Some("0o") => (&literal[2..], 8),
+                        Some("0b") => (&literal[2..], 2),
+                        _ => (&literal[..], 10)
+                    };
+                    // hexadecimal, octal and binary literals spell out the bits, so they may set the sign bit
+                    let parsed = if radix == 10 { i64::from_str_radix(digits, radix).ok() }
+                        else { u64::from_str_radix(digits, radix).ok().map(|bits| bits as i64) };
+                    let value = if let Some(value) = parsed {
                         value
                     } else { return Err(Error::new([
                         ErrorSection::Error(ErrorType::IntLiteralOverflows(self.current.token_content)),
@@ -446,7 +678,8 @@ impl Parser {
                     next!();
                 }
                 TokenType::Fraction => {
-                    let value = if let Ok(value) = strings.get(self.current.token_content).parse() {
+                    let literal = strings.get(self.current.token_content).replace('_', "");
+                    let value = if let Some(value) = literal.parse::<f64>().ok().filter(|v| v.is_finite()) {
                         value
                     } else { return Err(Error::new([
                         ErrorSection::Error(ErrorType::FloatLiteralOverflows(self.current.token_content)),
@@ -478,6 +711,16 @@ impl Parser {
                         (&source_start..&x_source).into()
                     ));
                 }
+                TokenType::TripleTilde => {
+                    let source_start = self.current.source;
+                    enforce_next!("the thing to bitwise negate");
+                    let x = enforce_expression!(&[], get_operator_precedence(TokenType::TripleTilde), "the thing to bitwise negate");
+                    let x_source = x.source();
+                    previous = Some(AstNode::new(
+                        AstNodeVariant::BitwiseNot { x: Box::new(x) },
+                        (&source_start..&x_source).into()
+                    ));
+                }
                 TokenType::ParenOpen => {
                     enforce_next!("the contained expression");
                     previous = Some(enforce_expression!(&[TokenType::ParenClose], None, "the contained expression"));
@@ -536,16 +779,26 @@ impl Parser {
                 TokenType::Pipe | TokenType::DoublePipe => {
                     let source_start = self.current.source;
                     let mut arguments = Vec::new();
+                    let mut destructured = Vec::new();
                     if TokenType::Pipe == self.current.token_type {
                         enforce_next!("a function parameter's name or a pipe ('|')");
                         loop {
-                            enforce_current_type!(&[TokenType::Identifier, TokenType::Pipe], "a function parameter's name or a pipe ('|')");
+                            enforce_current_type!(&[TokenType::Identifier, TokenType::BraceOpen, TokenType::BracketOpen, TokenType::Pipe], "a function parameter's name or a pipe ('|')");
                             match self.current.token_type {
-                                TokenType::Identifier => arguments.push((self.current.token_content, self.current.source)),
+                                TokenType::Identifier => {
+                                    arguments.push((self.current.token_content, self.current.source));
+                                    enforce_next!("a comma (',') or a pipe ('|')");
+                                }
+                                TokenType::BraceOpen | TokenType::BracketOpen => {
+                                    let pattern = self.parse_pattern(strings, lexer, false)?;
+                                    let name = strings.insert(&format!("<destructured parameter {}>", arguments.len()));
+                                    arguments.push((name, pattern.source()));
+                                    destructured.push((name, pattern));
+                                    enforce_not_reached_end!("a comma (',') or a pipe ('|')");
+                                }
                                 TokenType::Pipe => break,
                                 _ => panic!("unreachable")
                             }
-                            enforce_next!("a comma (',') or a pipe ('|')");
                             enforce_current_type!(&[TokenType::Comma, TokenType::Pipe], "a comma (',') or a pipe ('|')");
                             match self.current.token_type {
                                 TokenType::Comma => enforce_next!("a function parameter's name or a pipe ('|')"),
@@ -555,11 +808,35 @@ impl Parser {
                         }
                         enforce_current_type!(&[TokenType::Pipe], "a pipe ('|')");
                     }
+                    if !destructured.is_empty() {
+                        // destructured parameters become variables of the body, so compare them to the other parameters here
+                        let mut parameter_names = arguments.iter()
+                            .filter(|(name, _)| !destructured.iter().any(|(d, _)| d == name))
+                            .copied()
+                            .chain(destructured.iter().flat_map(|(_, pattern)| pattern.bindings()))
+                            .collect::<Vec<_>>();
+                        parameter_names.sort_by_key(|(_, source)| source.start_position());
+                        for (parameter_idx, (name, source)) in parameter_names.iter().enumerate() {
+                            if !parameter_names[..parameter_idx].iter().any(|(n, _)| n == name) { continue; }
+                            return Err(Error::new([
+                                ErrorSection::Error(ErrorType::DuplicateFunctionParameter(*name)),
+                                ErrorSection::Code(*source)
+                            ].into()));
+                        }
+                    }
                     enforce_next!("the function's body");
+                    let mut body = destructured.into_iter().map(|(name, pattern)| {
+                        let pattern_source = pattern.source();
+                        AstNode::new(AstNodeVariant::Destructuring {
+                            mutable: false,
+                            pattern,
+                            value: Box::new(AstNode::new(AstNodeVariant::VariableAccess { name }, pattern_source))
+                        }, pattern_source)
+                    }).collect::<Vec<AstNode>>();
                     if self.current.token_type == TokenType::BraceOpen {
                         enforce_next!("the function's body");
-                        let body = match self.parse_block(strings, lexer) {
-                            Ok(body) => body,
+                        match self.parse_block(strings, lexer) {
+                            Ok(block) => body.extend(block),
                             Err(error) => return Err(error)
                         };
                         enforce_not_reached_end!("a closing brace ('}')");
@@ -572,13 +849,12 @@ impl Parser {
                     } else {
                         let return_value: AstNode = enforce_expression!(&[], None, "the function's result value");
                         let return_value_source = return_value.source();
+                        body.push(AstNode::new(
+                            AstNodeVariant::Return { value: Box::new(return_value) },
+                            return_value_source
+                        ));
                         previous = Some(AstNode::new(
-                            AstNodeVariant::Function {
-                                arguments,
-                                body: vec![AstNode::new(
-                                    AstNodeVariant::Return { value: Box::new(return_value) },
-                                    return_value_source
-                                )] },
+                            AstNodeVariant::Function { arguments, body },
                             (&source_start..&return_value_source).into()
                         ));
                     }
@@ -594,12 +870,25 @@ impl Parser {
                     let mut arguments = Vec::new();
                     loop {
                         enforce_current_type!(&[TokenType::Identifier, TokenType::ParenClose], "a procedure parameter's name or a closing parenthesis (')')");
-                        match self.current.token_type {
-                            TokenType::Identifier => arguments.push((self.current.token_content, self.current.source)),
+                        let (argument_name, argument_source) = match self.current.token_type {
+                            TokenType::Identifier => (self.current.token_content, self.current.source),
                             TokenType::ParenClose => break,
                             _ => panic!("unreachable")
-                        }
-                        enforce_next!("a comma or an closing parenthesis (')')");
+                        };
+                        enforce_next!("a colon (':'), an equals sign ('='), a comma (',') or an closing parenthesis (')')");
+                        let argument_annotation = if self.current.token_type == TokenType::Colon {
+                            enforce_next!("the procedure parameter's type");
+                            let (annotation, _) = self.parse_type_annotation(strings, lexer)?;
+                            enforce_not_reached_end!("an equals sign ('='), a comma (',') or a closing parenthesis (')')");
+                            Some(annotation)
+                        } else { None };
+                        let argument_default = if self.current.token_type == TokenType::Equals {
+                            enforce_next!("the procedure parameter's default value");
+                            let default = enforce_expression!(&[TokenType::Comma, TokenType::ParenClose], None, "the procedure parameter's default value");
+                            let default_source = default.source();
+                            Some(AstNode::new(AstNodeVariant::Static { value: Box::new(default) }, default_source))
+                        } else { None };
+                        arguments.push((argument_name, argument_source, argument_annotation, argument_default));
                         enforce_current_type!(&[TokenType::Comma, TokenType::ParenClose], "a comma (',') or a closing parenthesis (')')");
                         match self.current.token_type {
                             TokenType::Comma => enforce_next!("a procedure parameter's name or a closing parenthesis (')')"),
@@ -608,7 +897,14 @@ impl Parser {
                         }
                     }
                     enforce_current_type!(&[TokenType::ParenClose], "a closing parenthesis (')')");
-                    enforce_next!("an opening brace ('{')");
+                    enforce_next!("an arrow ('->') or an opening brace ('{')");
+                    let returns = if self.current.token_type == TokenType::Arrow {
+                        enforce_next!("the procedure's return type");
+                        let annotation_start = self.current.source;
+                        let (annotation, annotation_end) = self.parse_type_annotation(strings, lexer)?;
+                        enforce_not_reached_end!("an opening brace ('{')");
+                        Some((annotation, (&annotation_start..&annotation_end).into()))
+                    } else { None };
                     enforce_current_type!(&[TokenType::BraceOpen], "an opening brace ('{')");
                     enforce_next!("the procedure's body");
                     let body = match self.parse_block(strings, lexer) {
@@ -618,7 +914,7 @@ impl Parser {
                     enforce_not_reached_end!("a closing brace ('}')");
                     enforce_current_type!(&[TokenType::BraceClose], "a closing brace ('}')");
                     previous = Some(AstNode::new(
-                        AstNodeVariant::Procedure { public: false, name, arguments, body },
+                        AstNodeVariant::Procedure { public: false, name, arguments, returns, body },
                         (&source_start..&self.current.source).into()
                     ));
                     next!();
@@ -632,21 +928,40 @@ impl Parser {
                         enforce_current_type!(&[TokenType::KeywordVariable], "'var'");
                     }
                     enforce_next!("the variable's name");
+                    if let TokenType::BraceOpen | TokenType::BracketOpen = self.current.token_type {
+                        let pattern = self.parse_pattern(strings, lexer, false)?;
+                        enforce_not_reached_end!("an equals sign ('=')");
+                        enforce_current_type!(&[TokenType::Equals], "an equals sign ('=')");
+                        enforce_next!("the destructured value");
+                        let value = enforce_expression!(&[], None, "the destructured value");
+                        let value_source = value.source();
+                        previous = Some(AstNode::new(
+                            AstNodeVariant::Destructuring { mutable, pattern, value: Box::new(value) },
+                            (&source_start..&value_source).into()
+                        ));
+                        continue;
+                    }
                     enforce_current_type!(&[TokenType::Identifier], "the variable's name");
                     let name = self.current.token_content;
-                    let name_source = self.current.source;
-                    previous = if next!() && self.current.token_type == TokenType::Equals {
+                    let mut declaration_end = self.current.source;
+                    let annotation = if next!() && self.current.token_type == TokenType::Colon {
+                        enforce_next!("the variable's type");
+                        let (annotation, annotation_end) = self.parse_type_annotation(strings, lexer)?;
+                        declaration_end = annotation_end;
+                        Some(annotation)
+                    } else { None };
+                    previous = if !self.reached_end && self.current.token_type == TokenType::Equals {
                         enforce_next!("the variable's value");
                         let value = enforce_expression!(&[], None, "the variable's value");
                         let value_source = value.source();
                         Some(AstNode::new(
-                            AstNodeVariant::Variable { public: false, mutable, name, value_types: None, value: Some(Box::new(value)) },
+                            AstNodeVariant::Variable { public: false, mutable, name, annotation, value_types: None, value: Some(Box::new(value)) },
                             (&source_start..&value_source).into()
                         ))
                     } else {
                         Some(AstNode::new(
-                            AstNodeVariant::Variable { public: false, mutable, name, value_types: None, value: None },
-                            (&source_start..&name_source).into()
+                            AstNodeVariant::Variable { public: false, mutable, name, annotation, value_types: None, value: None },
+                            (&source_start..&declaration_end).into()
                         ))
                     }
                 }
@@ -701,120 +1016,60 @@ impl Parser {
                             ));
                         }
                         TokenType::BraceOpen => {
-                            enforce_next!("a branch value");
-                            if self.current.token_type == TokenType::Hashtag {
-                                let mut branches = Vec::new();
-                                while self.current.token_type != TokenType::BraceClose {
-                                    enforce_current_type!(&[TokenType::Hashtag], "a hashtag ('#')");
-                                    enforce_next!("the variant's name");
-                                    enforce_current_type!(&[TokenType::Identifier], "the variant's name");
-                                    let branch_variant_name = self.current.token_content;
-                                    enforce_next!("the value's variable's name or an arrow ('->')");
-                                    enforce_current_type!(&[TokenType::Identifier, TokenType::Arrow], "the value's variable's name or an arrow ('->')");
-                                    let branch_variable = if self.current.token_type == TokenType::Identifier {
-                                        let branch_variable_name = self.current.token_content;
-                                        let branch_variable_source = self.current.source;
-                                        enforce_next!("an arrow ('->')");
-                                        Some((branch_variable_name, branch_variable_source, None))
-                                    } else { None };
+                            enforce_next!("a branch pattern");
+                            let mut branches = Vec::new();
+                            while self.current.token_type != TokenType::BraceClose {
+                                let pattern = self.parse_pattern(strings, lexer, true)?;
+                                enforce_not_reached_end!("an arrow ('->') or a guard ('if')");
+                                enforce_current_type!(&[TokenType::Arrow, TokenType::KeywordIf], "an arrow ('->') or a guard ('if')");
+                                let guard = if self.current.token_type == TokenType::KeywordIf {
+                                    enforce_next!("the guard of the branch");
+                                    let guard = enforce_expression!(&[TokenType::Arrow], None, "the guard of the branch");
+                                    enforce_not_reached_end!("an arrow ('->')");
                                     enforce_current_type!(&[TokenType::Arrow], "an arrow ('->')");
-                                    enforce_next!("the body of the branch");
-                                    let body = if self.current.token_type == TokenType::BraceOpen {
-                                        enforce_next!("the body of the conditional branch");
-                                        let body = match self.parse_block(strings, lexer) {
-                                            Ok(body) => body,
-                                            Err(error) => return Err(error)
-                                        };
-                                        enforce_not_reached_end!("a closing brace ('}')");
-                                        enforce_current_type!(&[TokenType::BraceClose], "a closing brace ('}')");
-                                        enforce_next!("the value for the branch or a closing brace ('}')");
-                                        body
-                                    } else {
-                                        vec![enforce_expression!(&[TokenType::BraceClose], None, "the body of the conditional branch")]
+                                    Some(guard)
+                                } else { None };
+                                enforce_next!("the body of the branch");
+                                let body = if self.current.token_type == TokenType::BraceOpen {
+                                    enforce_next!("the body of the conditional branch");
+                                    let body = match self.parse_block(strings, lexer) {
+                                        Ok(body) => body,
+                                        Err(error) => return Err(error)
                                     };
-                                    branches.push((branch_variant_name, branch_variable, body));
-                                }
-                                enforce_current_type!(&[TokenType::BraceClose], "a closing brace ('}')");
-                                let mut source_end = self.current.source;
-                                next!();
-                                let else_body = if !self.reached_end && self.current.token_type == TokenType::KeywordElse {
+                                    enforce_not_reached_end!("a closing brace ('}')");
+                                    enforce_current_type!(&[TokenType::BraceClose], "a closing brace ('}')");
+                                    enforce_next!("the pattern for the branch or a closing brace ('}')");
+                                    body
+                                } else {
+                                    vec![enforce_expression!(&[TokenType::BraceClose], None, "the body of the conditional branch")]
+                                };
+                                enforce_not_reached_end!("the pattern for the branch or a closing brace ('}')");
+                                branches.push((pattern, guard, body));
+                            }
+                            enforce_current_type!(&[TokenType::BraceClose], "a closing brace ('}')");
+                            let mut source_end = self.current.source;
+                            next!();
+                            let else_body = if !self.reached_end && self.current.token_type == TokenType::KeywordElse {
+                                enforce_next!("the body of the 'else'-branch");
+                                if self.current.token_type == TokenType::BraceOpen {
                                     enforce_next!("the body of the 'else'-branch");
-                                    if self.current.token_type == TokenType::BraceOpen {
-                                        enforce_next!("the body of the 'else'-branch");
-                                        let body = match self.parse_block(strings, lexer) {
-                                            Ok(body) => body,
-                                            Err(error) => return Err(error)
-                                        };
-                                        enforce_not_reached_end!("a closing brace ('}')");
-                                        enforce_current_type!(&[TokenType::BraceClose], "a closing brace ('}')");
-                                        source_end = self.current.source;
-                                        next!();
-                                        Some(body)
-                                    } else {
-                                        let body = enforce_expression!(&[], None, "the body of the 'else'-branch");
-                                        source_end = body.source();
-                                        Some(vec![body])
-                                    }
-                                } else { None };
-                                previous = Some(AstNode::new(
-                                    AstNodeVariant::CaseVariant { value: Box::new(value), branches, else_body },
-                                    (&source_start..&source_end).into()
-                                ));
-                            } else {
-                                let mut branches = Vec::new();
-                                while self.current.token_type != TokenType::BraceClose {
-                                    match self.parse_expression(strings, lexer, &mut vec![&[TokenType::Arrow]], None) {
-                                        Ok(None) => break,
-                                        Ok(Some(value)) => {
-                                            enforce_not_reached_end!("an arrow ('->')");
-                                            enforce_current_type!(&[TokenType::Arrow], "an arrow ('->')");
-                                            enforce_next!("the body of the branch");
-                                            let body = if self.current.token_type == TokenType::BraceOpen {
-                                                enforce_next!("the body of the conditional branch");
-                                                let body = match self.parse_block(strings, lexer) {
-                                                    Ok(body) => body,
-                                                    Err(error) => return Err(error)
-                                                };
-                                                enforce_not_reached_end!("a closing brace ('}')");
-                                                enforce_current_type!(&[TokenType::BraceClose], "a closing brace ('}')");
-                                                enforce_next!("the value for the branch or a closing brace ('}')");
-                                                body
-                                            } else {
-                                                vec![enforce_expression!(&[TokenType::BraceClose], None, "the body of the conditional branch")]
-                                            };
-                                            branches.push((value, body));
-                                            if self.current.token_type == TokenType::BraceClose { break }
-                                        },
+                                    let body = match self.parse_block(strings, lexer) {
+                                        Ok(body) => body,
                                         Err(error) => return Err(error)
-                                    }
+                                    };
+                                    enforce_not_reached_end!("a closing brace ('}')");
+                                    enforce_current_type!(&[TokenType::BraceClose], "a closing brace ('}')");
+                                    source_end = self.current.source;
+                                    next!();
+                                    Some(body)
+                                } else {
+                                    let body = enforce_expression!(&[], None, "the body of the 'else'-branch");
+                                    source_end = body.source();
+                                    Some(vec![body])
                                 }
-                                enforce_current_type!(&[TokenType::BraceClose], "a closing brace ('}')");
-                                let mut source_end = self.current.source;
-                                next!();
-                                let else_body = if !self.reached_end && self.current.token_type == TokenType::KeywordElse {
-                                    enforce_next!("the body of the 'else'-branch");
-                                    if self.current.token_type == TokenType::BraceOpen {
-                                        enforce_next!("the body of the 'else'-branch");
-                                        let body = match self.parse_block(strings, lexer) {
-                                            Ok(body) => body,
-                                            Err(error) => return Err(error)
-                                        };
-                                        enforce_not_reached_end!("a closing brace ('}')");
-                                        enforce_current_type!(&[TokenType::BraceClose], "a closing brace ('}')");
-                                        source_end = self.current.source;
-                                        next!();
-                                        body
-                                    } else {
-                                        let body = enforce_expression!(&[], None, "the body of the 'else'-branch");
-                                        source_end = body.source();
-                                        vec![body]
-                                    }
-                                } else { Vec::new() };
-                                previous = Some(AstNode::new(
-                                    AstNodeVariant::CaseBranches { value: Box::new(value), branches, else_body },
-                                    (&source_start..&source_end).into()
-                                ));
-                            }
+                            } else { None };
+                            let variant = AstNodeVariant::case_pattern(Box::new(value), branches, else_body);
+                            previous = Some(AstNode::new(variant, (&source_start..&source_end).into()));
                         }
                         _ => panic!("unreachable")
                     }
@@ -852,8 +1107,9 @@ impl Parser {
                     enforce_next!("the thing to be public");
                     let mut thing = enforce_expression!(&[], None, "the thing to be public");
                     match thing.node_variant_mut() {
-                        AstNodeVariant::Procedure { public, name: _, arguments: _, body: _ } |
-                        AstNodeVariant::Variable { public, mutable: _, name: _, value_types: _, value: _ } => if !*public {
+                        AstNodeVariant::Procedure { public, name: _, arguments: _, returns: _, body: _ } |
+                        AstNodeVariant::Variable { public, mutable: _, name: _, annotation: _, value_types: _, value: _ } |
+                        AstNodeVariant::TypeAlias { public, name: _, nominal: _, aliased: _ } => if !*public {
                             *public = true;
                             thing.replace_source((&start_source..&thing.source()).into());
                             previous = Some(thing);
@@ -875,7 +1131,7 @@ impl Parser {
                     let start_source = self.current.source;
                     enforce_next!("the things to use");
                     let mut end_source = self.current.source;
-                    fn parse_usage_paths(parser: &mut Parser, strings: &mut StringMap, lexer: &mut Lexer, end_source: &mut SourceRange) -> Result<Vec<NamespacePath>, Error> {
+                    fn parse_usage_paths(parser: &mut Parser, strings: &StringMap, lexer: &mut Lexer, end_source: &mut SourceRange) -> Result<Vec<NamespacePath>, Error> {
                         macro_rules! next { () => {
                             match parser.next(strings, lexer) {
                                 Ok(did) => did,
@@ -1006,6 +1262,24 @@ impl Parser {
                     ));
                     next!();
                 }
+                TokenType::KeywordType => {
+                    let start_source = self.current.source;
+                    enforce_next!("the type's name");
+                    enforce_current_type!(&[TokenType::Identifier], "the type's name");
+                    let name = self.current.token_content;
+                    enforce_next!("an equals-sign ('=')");
+                    enforce_current_type!(&[TokenType::Equals], "an equals-sign ('=')");
+                    enforce_next!("the aliased type or 'nominal'");
+                    let nominal = self.current.token_type == TokenType::KeywordNominal;
+                    if nominal {
+                        enforce_next!("the wrapped type");
+                    }
+                    let (aliased, aliased_end) = self.parse_type_annotation(strings, lexer)?;
+                    previous = Some(AstNode::new(
+                        AstNodeVariant::TypeAlias { public: false, name, nominal, aliased },
+                        (&start_source..&aliased_end).into()
+                    ));
+                }
                 _ => return Err(Error::new([
                     ErrorSection::Error(ErrorType::TotallyUnexpectedToken(self.current.token_content)),
                     ErrorSection::Code(self.current.source)
@@ -24,17 +24,25 @@ pub fn process_target_blocks(nodes: &mut Vec<AstNode>, target_str: &str, strings
 fn process_node(opt_node: &mut Option<&mut AstNode>, inserted_nodes: &mut Option<Vec<AstNode>>, target_str: &str, strings: &StringMap) {
     if let Some(node) = opt_node {
         match node.node_variant_mut() {
-            AstNodeVariant::Procedure { public: _, name: _, arguments: _, body } => {
+            AstNodeVariant::Procedure { public: _, name: _, arguments, returns: _, body } => {
+                for argument in arguments {
+                    if let Some(default) = &mut argument.3 {
+                        process_node(&mut Some(default), &mut None, target_str, strings);
+                    }
+                }
                 process_target_blocks(body, target_str, strings);
             },
             AstNodeVariant::Function { arguments: _, body } => {
                 process_target_blocks(body, target_str, strings);
             },
-            AstNodeVariant::Variable { public: _, mutable: _, name: _, value_types: _, value } => {
+            AstNodeVariant::Variable { public: _, mutable: _, name: _, annotation: _, value_types: _, value } => {
                 if let Some(value) = value {
                     process_node(&mut Some(&mut *value), &mut None, target_str, strings);
                 }
             },
+            AstNodeVariant::Destructuring { mutable: _, pattern: _, value } => {
+                process_node(&mut Some(&mut *value), &mut None, target_str, strings);
+            },
             AstNodeVariant::CaseBranches { value, branches, else_body } => {
                 process_node(&mut Some(&mut *value), &mut None, target_str, strings);
                 for branch in branches {
@@ -57,11 +65,28 @@ fn process_node(opt_node: &mut Option<&mut AstNode>, inserted_nodes: &mut Option
                     process_target_blocks(else_body, target_str, strings);
                 }
             },
+            AstNodeVariant::CasePattern { value, branches, else_body } => {
+                process_node(&mut Some(&mut *value), &mut None, target_str, strings);
+                for branch in branches {
+                    for pattern_value in branch.0.values_mut() {
+                        process_node(&mut Some(pattern_value), &mut None, target_str, strings);
+                    }
+                    if let Some(guard) = &mut branch.1 {
+                        process_node(&mut Some(guard), &mut None, target_str, strings);
+                    }
+                    process_target_blocks(&mut branch.2, target_str, strings);
+                }
+                if let Some(else_body) = else_body {
+                    process_target_blocks(else_body, target_str, strings);
+                }
+            },
             AstNodeVariant::Assignment { variable, value } => {
                 process_node(&mut Some(&mut *variable), &mut None, target_str, strings);
                 process_node(&mut Some(&mut *value), &mut None, target_str, strings);
             },
-            AstNodeVariant::Return { value } => {
+            AstNodeVariant::Return { value } |
+            AstNodeVariant::Propagate { value, returned_types: _ } |
+            AstNodeVariant::NamedArgument { name: _, value } => {
                 process_node(&mut Some(&mut *value), &mut None, target_str, strings);
             },
             AstNodeVariant::Call { called, arguments } => {
@@ -100,6 +125,17 @@ fn process_node(opt_node: &mut Option<&mut AstNode>, inserted_nodes: &mut Option
             AstNodeVariant::Negate { x } => {
                 process_node(&mut Some(&mut *x), &mut None, target_str, strings);
             }
+            AstNodeVariant::BitwiseAnd { a, b } |
+            AstNodeVariant::BitwiseOr { a, b } |
+            AstNodeVariant::BitwiseXor { a, b } |
+            AstNodeVariant::LeftShift { a, b } |
+            AstNodeVariant::RightShift { a, b } => {
+                process_node(&mut Some(&mut *a), &mut None, target_str, strings);
+                process_node(&mut Some(&mut *b), &mut None, target_str, strings);
+            }
+            AstNodeVariant::BitwiseNot { x } => {
+                process_node(&mut Some(&mut *x), &mut None, target_str, strings);
+            }
             AstNodeVariant::LessThan { a, b } |
             AstNodeVariant::LessThanEqual { a , b } |
             AstNodeVariant::GreaterThan { a, b } |
@@ -119,7 +155,8 @@ fn process_node(opt_node: &mut Option<&mut AstNode>, inserted_nodes: &mut Option
             }
             AstNodeVariant::Module { path: _ } |
             AstNodeVariant::ModuleAccess { path: _ } |
-            AstNodeVariant::Use { paths: _ } => {}
+            AstNodeVariant::Use { paths: _ } |
+            AstNodeVariant::TypeAlias { public: _, name: _, nominal: _, aliased: _ } => {}
             AstNodeVariant::Variant { name: _, value } => {
                 process_node(&mut Some(&mut *value), &mut None, target_str, strings);
             }
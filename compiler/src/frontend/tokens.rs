@@ -7,6 +7,9 @@ pub enum TokenType {
     Integer,
     Fraction,
     String,
+    InterpolationStart,
+    InterpolationMiddle,
+    InterpolationEnd,
     Pipe,
     Equals,
     Dot,
@@ -27,10 +30,18 @@ pub enum TokenType {
     FunctionPipe,
     MemberPipe,
     DoubleAmpersand,
+    TripleAmpersand,
+    TriplePipe,
+    TripleCaret,
+    TripleTilde,
+    TripleLessThan,
+    TripleGreaterThan,
     ExclamationMark,
+    QuestionMark,
     Hashtag,
     Comma,
     Arrow,
+    Colon,
     DoubleColon,
     ParenOpen,
     ParenClose,
@@ -49,9 +60,12 @@ pub enum TokenType {
     KeywordTrue,
     KeywordFalse,
     KeywordElse,
+    KeywordIf,
     KeywordUnit,
     KeywordStatic,
-    KeywordTarget
+    KeywordTarget,
+    KeywordType,
+    KeywordNominal
 }
 
 #[derive(Debug, Clone)]
@@ -4,11 +4,12 @@ use std::collections::{HashMap, HashSet};
 use crate::util::{
     strings::{StringMap, StringIdx},
     error::{Error, ErrorSection, ErrorType},
-    source::{HasSource, SourceRange}
+    source::{HasSource, SourceRange},
+    suggestions::did_you_mean
 };
 
 use crate::frontend::{
-    ast::{TypedAstNode, AstNode, HasAstNodeVariant, AstNodeVariant},
+    ast::{TypedAstNode, AstNode, HasAstNodeVariant, AstNodeVariant, TypeAnnotation, Pattern},
     types::{TypeScope, Type, TypeGroup},
     modules::{NamespacePath, Module}
 };
@@ -24,11 +25,17 @@ pub enum Symbol<T: Clone + HasSource + HasAstNodeVariant<T>> {
     Procedure {
         public: bool,
         parameter_names: Vec<StringIdx>, 
+        parameter_defaults: Vec<Option<AstNode>>,
         parameter_types: Vec<TypeGroup>,
         returns: TypeGroup,
         body: Option<Vec<T>>,
         source: SourceRange,
         type_scope: TypeScope
+    },
+    Type {
+        public: bool,
+        nominal: bool,
+        value_types: TypeGroup
     }
 }
 
@@ -44,6 +51,7 @@ pub fn type_check_modules(modules: HashMap<NamespacePath, Module<AstNode>>, stri
     }
     let old_symbol_paths = old_symbols.keys().map(|p| p.clone()).collect::<Vec<NamespacePath>>();
     for symbol_path in old_symbol_paths {
+        if !old_symbols.contains_key(&symbol_path) { continue; }
         if let Err(error) = type_check_symbol(
             strings,
             type_scope,
@@ -57,6 +65,79 @@ pub fn type_check_modules(modules: HashMap<NamespacePath, Module<AstNode>>, stri
         else { Ok(()) }
 }
 
+pub fn resolve_annotation(annotation: &TypeAnnotation, type_scope: &mut TypeScope, declared_types: &HashMap<NamespacePath, Type>, concrete_objects: bool) -> Result<Type, Error> {
+    let types = resolve_annotation_types(annotation, type_scope, declared_types, &HashMap::new(), concrete_objects)?;
+    Ok(type_scope.group_concrete(types))
+}
+
+pub fn resolve_annotation_types(annotation: &TypeAnnotation, type_scope: &mut TypeScope, declared_types: &HashMap<NamespacePath, Type>, type_variables: &HashMap<NamespacePath, TypeGroup>, concrete_objects: bool) -> Result<TypeGroup, Error> {
+    match annotation {
+        TypeAnnotation::Unit => Ok(type_scope.insert_group(&[Type::Unit])),
+        TypeAnnotation::Boolean => Ok(type_scope.insert_group(&[Type::Boolean])),
+        TypeAnnotation::Integer => Ok(type_scope.insert_group(&[Type::Integer])),
+        TypeAnnotation::Float => Ok(type_scope.insert_group(&[Type::Float])),
+        TypeAnnotation::String => Ok(type_scope.insert_group(&[Type::String])),
+        TypeAnnotation::Named(path, source) => if let Some(variable_types) = type_variables.get(path) {
+            Ok(*variable_types)
+        } else if let Some(replacement) = declared_types.get(path) {
+            Ok(type_scope.insert_group(&[*replacement]))
+        } else {
+            Err(Error::new([
+                ErrorSection::Error(ErrorType::TypeDoesNotExist(
+                    *path.get_segments().last().expect("should have at least one segment")
+                )),
+                ErrorSection::Code(*source)
+            ].into()))
+        }
+        TypeAnnotation::Array(element_type) => {
+            let element_tidx = resolve_annotation_types(element_type, type_scope, declared_types, type_variables, concrete_objects)?;
+            let array_type = Type::Array(type_scope.insert_array(element_tidx));
+            Ok(type_scope.insert_group(&[array_type]))
+        }
+        TypeAnnotation::Object(members) => {
+            let mut member_types = Vec::new();
+            for (member_name, member_type) in members {
+                let member_tidx = resolve_annotation_types(member_type, type_scope, declared_types, type_variables, concrete_objects)?;
+                member_types.push((*member_name, member_tidx));
+            }
+            let object_type = if concrete_objects {
+                Type::ConcreteObject(type_scope.insert_concrete_object(member_types))
+            } else {
+                Type::Object(type_scope.insert_object(member_types.into_iter().collect(), true))
+            };
+            Ok(type_scope.insert_group(&[object_type]))
+        }
+        TypeAnnotation::Closure(parameter_types, return_type) => {
+            let mut parameter_tidxs = Vec::new();
+            for parameter_type in parameter_types {
+                parameter_tidxs.push(resolve_annotation_types(parameter_type, type_scope, declared_types, type_variables, concrete_objects)?);
+            }
+            let return_tidx = resolve_annotation_types(return_type, type_scope, declared_types, type_variables, concrete_objects)?;
+            let closure_type = Type::Closure(type_scope.insert_closure(
+                parameter_tidxs,
+                return_tidx,
+                None
+            ));
+            Ok(type_scope.insert_group(&[closure_type]))
+        }
+        TypeAnnotation::Variants(variants, fixed) => {
+            let mut variant_types = HashMap::new();
+            for (variant_name, variant_type) in variants {
+                let variant_tidx = resolve_annotation_types(variant_type, type_scope, declared_types, type_variables, concrete_objects)?;
+                variant_types.insert(*variant_name, variant_tidx);
+            }
+            let variants_type = Type::Variants(type_scope.insert_variants(variant_types, *fixed));
+            Ok(type_scope.insert_group(&[variants_type]))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ReturnSource {
+    Procedure(SourceRange),
+    Annotation(SourceRange)
+}
+
 #[derive(Debug, Clone)]
 struct TypeAssertion {
     limited_to: TypeGroup,
@@ -82,6 +163,16 @@ impl TypeAssertion {
             )
         }
     }
+    fn annotation(annotation_source: SourceRange, annotated_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
+        TypeAssertion {
+            limited_to: annotated_types,
+            from: annotation_source,
+            reason: format!(
+                "This was annotated to be of type {}",
+                display_types(strings, type_scope, annotated_types)
+            )
+        }
+    }
     fn literal(literal_kind: &'static str, literal_source: SourceRange, literal_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
         TypeAssertion {
             limited_to: literal_types,
@@ -113,7 +204,13 @@ impl TypeAssertion {
             )
         }
     }
-    fn returned_values(procedure_source: SourceRange, returned_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
+    fn returned_values(return_source: ReturnSource, returned_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
+        let procedure_source = match return_source {
+            ReturnSource::Procedure(procedure_source) => procedure_source,
+            ReturnSource::Annotation(annotation_source) => return TypeAssertion::annotation(
+                annotation_source, returned_types, type_scope, strings
+            )
+        };
         TypeAssertion {
             limited_to: returned_types,
             from: procedure_source,
@@ -123,6 +220,36 @@ impl TypeAssertion {
             )
         }
     }
+    fn propagated_value(value_source: SourceRange, value_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
+        TypeAssertion {
+            limited_to: value_types,
+            from: value_source,
+            reason: format!(
+                "Used with '?' here, meaning it must be of type {}",
+                display_types(strings, type_scope, value_types)
+            )
+        }
+    }
+    fn propagated_error(value_source: SourceRange, error_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
+        TypeAssertion {
+            limited_to: error_types,
+            from: value_source,
+            reason: format!(
+                "This '?' may return a value of type {}",
+                display_types(strings, type_scope, error_types)
+            )
+        }
+    }
+    fn propagation_result(value_source: SourceRange, ok_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
+        TypeAssertion {
+            limited_to: ok_types,
+            from: value_source,
+            reason: format!(
+                "This '?' results in a value of type {}",
+                display_types(strings, type_scope, ok_types)
+            )
+        }
+    }
     fn implicit_unit_return(procedure_source: SourceRange, type_scope: &mut TypeScope, strings: &StringMap) -> TypeAssertion {
         let asserted_type = type_scope.insert_group(&[Type::Unit]);
         TypeAssertion {
@@ -185,6 +312,26 @@ impl TypeAssertion {
             )
         }
     }
+    fn bitwise_result(op_source: SourceRange, result_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
+        TypeAssertion {
+            limited_to: result_types,
+            from: op_source,
+            reason: format!(
+                "This bitwise operation results in a value of type {}",
+                display_types(strings, type_scope, result_types)
+            )
+        }
+    }
+    fn bitwise_argument(op_source: SourceRange, argument_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
+        TypeAssertion {
+            limited_to: argument_types,
+            from: op_source,
+            reason: format!(
+                "This bitwise operation requires a value of type {}",
+                display_types(strings, type_scope, argument_types)
+            )
+        }
+    }
     fn comparison_result(op_source: SourceRange, result_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
         TypeAssertion {
             limited_to: result_types,
@@ -282,6 +429,33 @@ impl TypeAssertion {
             )
         }
     }
+    fn destructured_value(value_source: SourceRange, value_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
+        TypeAssertion {
+            limited_to: value_types,
+            from: value_source,
+            reason: format!(
+                "The destructured value is of type {}",
+                display_types(strings, type_scope, value_types)
+            )
+        }
+    }
+    fn destructured_member(member_source: SourceRange, member_name: StringIdx, object_types: TypeGroup, strings: &StringMap) -> TypeAssertion {
+        TypeAssertion {
+            limited_to: object_types,
+            from: member_source,
+            reason: format!(
+                "This pattern requires the destructured value to be an object with a member '{}'",
+                strings.get(member_name)
+            )
+        }
+    }
+    fn destructured_array(pattern_source: SourceRange, array_types: TypeGroup) -> TypeAssertion {
+        TypeAssertion {
+            limited_to: array_types,
+            from: pattern_source,
+            reason: String::from("This pattern requires the destructured value to be an array")
+        }
+    }
     fn branch_variants(branch_source: SourceRange, variant_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
         TypeAssertion {
             limited_to: variant_types,
@@ -302,6 +476,36 @@ impl TypeAssertion {
             )
         }
     }
+    fn case_value(value_source: SourceRange, case_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
+        TypeAssertion {
+            limited_to: case_types,
+            from: value_source,
+            reason: format!(
+                "Used as the value of a 'case'-branch here, meaning it must be of type {}",
+                display_types(strings, type_scope, case_types)
+            )
+        }
+    }
+    fn case_result(case_source: SourceRange, case_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
+        TypeAssertion {
+            limited_to: case_types,
+            from: case_source,
+            reason: format!(
+                "This 'case' results in a value of type {}",
+                display_types(strings, type_scope, case_types)
+            )
+        }
+    }
+    fn branch_pattern(pattern_source: SourceRange, pattern_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
+        TypeAssertion {
+            limited_to: pattern_types,
+            from: pattern_source,
+            reason: format!(
+                "This pattern requires the matched value to be of type {}",
+                display_types(strings, type_scope, pattern_types)
+            )
+        }
+    }
     fn procedure_parameter(procedure_source: SourceRange, parameter_name: StringIdx, parameter_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
         TypeAssertion {
             limited_to: parameter_types,
@@ -313,6 +517,27 @@ impl TypeAssertion {
             )
         }
     }
+    fn wrapped_value(construction_source: SourceRange, type_name: &NamespacePath, wrapped_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
+        TypeAssertion {
+            limited_to: wrapped_types,
+            from: construction_source,
+            reason: format!(
+                "The type '{}' wraps a value of type {}",
+                type_name.display(strings),
+                display_types(strings, type_scope, wrapped_types)
+            )
+        }
+    }
+    fn constructed_value(construction_source: SourceRange, constructed_types: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
+        TypeAssertion {
+            limited_to: constructed_types,
+            from: construction_source,
+            reason: format!(
+                "This creates a value of type {}",
+                display_types(strings, type_scope, constructed_types)
+            )
+        }
+    }
     fn call_parameter_value(param_source: SourceRange, given_type: TypeGroup, type_scope: &TypeScope, strings: &StringMap) -> TypeAssertion {
         TypeAssertion {
             limited_to: given_type,
@@ -325,6 +550,69 @@ impl TypeAssertion {
     }
 }
 
+fn type_check_annotation(
+    strings: &StringMap,
+    global_scope: &mut TypeScope,
+    rec_procedures: &mut Vec<(NamespacePath, Vec<Vec<(TypeGroup, SourceRange)>>, TypeScope)>,
+    untyped_symbols: &mut HashMap<NamespacePath, AstNode>,
+    symbols: &mut HashMap<NamespacePath, Symbol<TypedAstNode>>,
+    annotation: &TypeAnnotation,
+    named_types: &mut HashMap<NamespacePath, TypeGroup>
+) -> Result<(), Error> {
+    match annotation {
+        TypeAnnotation::Unit |
+        TypeAnnotation::Boolean |
+        TypeAnnotation::Integer |
+        TypeAnnotation::Float |
+        TypeAnnotation::String => Ok(()),
+        TypeAnnotation::Named(path, source) => {
+            if path.get_segments().len() < 2 || named_types.contains_key(path) { return Ok(()); }
+            let is_type = match (untyped_symbols.get(path), symbols.get(path)) {
+                (Some(node), _) => if let AstNodeVariant::TypeAlias { .. } = node.node_variant() { true } else { false },
+                (None, Some(symbol)) => if let Symbol::Type { .. } = symbol { true } else { false },
+                (None, None) => return Err(Error::new([
+                    ErrorSection::Error(ErrorType::RecursiveType(path.display(strings))),
+                    ErrorSection::Code(*source)
+                ].into()))
+            };
+            if !is_type { return Err(Error::new([
+                ErrorSection::Error(ErrorType::NotAType(path.display(strings))),
+                ErrorSection::Code(*source)
+            ].into())) }
+            if let Symbol::Type { public: _, nominal: _, value_types } = type_check_symbol(strings, global_scope, rec_procedures, untyped_symbols, symbols, path)? {
+                named_types.insert(path.clone(), *value_types);
+            }
+            Ok(())
+        }
+        TypeAnnotation::Array(element_type) => type_check_annotation(strings, global_scope, rec_procedures, untyped_symbols, symbols, element_type, named_types),
+        TypeAnnotation::Object(members) => {
+            for (_, member_type) in members {
+                type_check_annotation(strings, global_scope, rec_procedures, untyped_symbols, symbols, member_type, named_types)?;
+            }
+            Ok(())
+        }
+        TypeAnnotation::Closure(parameter_types, return_type) => {
+            for parameter_type in parameter_types {
+                type_check_annotation(strings, global_scope, rec_procedures, untyped_symbols, symbols, parameter_type, named_types)?;
+            }
+            type_check_annotation(strings, global_scope, rec_procedures, untyped_symbols, symbols, return_type, named_types)
+        }
+        TypeAnnotation::Variants(variants, _) => {
+            for (_, variant_type) in variants {
+                type_check_annotation(strings, global_scope, rec_procedures, untyped_symbols, symbols, variant_type, named_types)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn declared_types_in(named_types: &HashMap<NamespacePath, TypeGroup>, global_scope: &TypeScope, type_scope: &mut TypeScope) -> HashMap<NamespacePath, Type> {
+    named_types.iter().map(|(path, types)| {
+        let types = global_scope.transfer_group(*types, type_scope);
+        (path.clone(), type_scope.group_concrete(types))
+    }).collect()
+}
+
 fn type_check_symbol<'s>(
     strings: &StringMap,
     global_scope: &mut TypeScope,
@@ -336,22 +624,63 @@ fn type_check_symbol<'s>(
     if let Some(symbol) = untyped_symbols.remove(name) {
         let symbol_source = symbol.source();
         match symbol.move_node() {
-            AstNodeVariant::Procedure { public, name: _, arguments, body } => {
+            AstNodeVariant::Procedure { public, name: _, arguments, returns: return_annotation, body } => {
                 let mut type_scope = TypeScope::new();
                 let untyped_body = body;
                 let mut argument_vars = Vec::new();
                 let mut procedure_variables = HashMap::new();
                 let mut procedure_scope_variables = HashSet::new();
+                let mut named_types = HashMap::new();
+                for annotation in arguments.iter().filter_map(|a| a.2.as_ref()).chain(return_annotation.iter().map(|r| &r.0)) {
+                    type_check_annotation(strings, global_scope, rec_procedures, untyped_symbols, symbols, annotation, &mut named_types)?;
+                }
+                let declared_types = declared_types_in(&named_types, global_scope, &mut type_scope);
                 for argument_idx in 0..arguments.len() {
-                    let arg_type = type_scope.insert_group(&[Type::Any]);
+                    let arg_type = if let Some(annotation) = &arguments[argument_idx].2 {
+                        let annotated_type = resolve_annotation(annotation, &mut type_scope, &declared_types, false)?;
+                        type_scope.insert_group(&[annotated_type])
+                    } else { type_scope.insert_group(&[Type::Any]) };
                     argument_vars.push(arg_type);
                     procedure_variables.insert(arguments[argument_idx].0, (arg_type, false, arguments[argument_idx].1));
                     procedure_scope_variables.insert(arguments[argument_idx].0);
                 }
-                let return_types = type_scope.insert_group(&[Type::Any]);
+                let (return_types, return_source) = if let Some((annotation, annotation_source)) = &return_annotation {
+                    let annotated_type = resolve_annotation(annotation, &mut type_scope, &declared_types, false)?;
+                    (type_scope.insert_group(&[annotated_type]), ReturnSource::Annotation(*annotation_source))
+                } else { (type_scope.insert_group(&[Type::Any]), ReturnSource::Procedure(symbol_source)) };
+                for argument_idx in 0..arguments.len() {
+                    if let Some(default) = &arguments[argument_idx].3 {
+                        let mut default_type_scope = type_scope.clone();
+                        let default_returns = default_type_scope.insert_group(&[Type::Any]);
+                        let limited_to = arguments[argument_idx].2.as_ref().map(|_| TypeAssertion::annotation(
+                            arguments[argument_idx].1, argument_vars[argument_idx], &default_type_scope, strings
+                        ));
+                        rec_procedures.push((NamespacePath::new(Vec::new()), vec![], default_type_scope));
+                        let default_typed = type_check_node(
+                            strings,
+                            global_scope,
+                            rec_procedures,
+                            ReturnSource::Procedure(default.source()),
+                            &mut HashMap::new(),
+                            &mut HashSet::new(),
+                            &mut HashMap::new(),
+                            &mut HashSet::new(),
+                            untyped_symbols,
+                            symbols,
+                            default.clone(),
+                            default_returns,
+                            limited_to,
+                            false,
+                            false
+                        );
+                        rec_procedures.pop();
+                        default_typed?;
+                    }
+                }
                 symbols.insert(name.clone(), Symbol::Procedure {
                     public,
                     parameter_names: arguments.iter().map(|p| p.0).collect(),
+                    parameter_defaults: arguments.iter().map(|p| p.3.clone()).collect(),
                     parameter_types: argument_vars,
                     returns: return_types,
                     body: Some(Vec::new()),
@@ -363,7 +692,7 @@ fn type_check_symbol<'s>(
                     strings,
                     global_scope,
                     rec_procedures,
-                    symbol_source,
+                    return_source,
                     &mut procedure_variables,
                     &mut procedure_scope_variables,
                     &mut HashMap::new(),
@@ -376,7 +705,7 @@ fn type_check_symbol<'s>(
                     Ok(typed_nodes) => typed_nodes,
                     Err(error) => return Err(error),
                 };
-                if let Some(Symbol::Procedure { public: _, parameter_names: _, parameter_types, returns: _, body, source, type_scope: symbol_type_scope }) = symbols.get_mut(name) {
+                if let Some(Symbol::Procedure { public: _, parameter_names: _, parameter_defaults: _, parameter_types, returns: _, body, source, type_scope: symbol_type_scope }) = symbols.get_mut(name) {
                     if let Some((_, arg_groups, mut type_scope)) = rec_procedures.pop() {
                         fn copy_arg_type_group(t: TypeGroup, mapped: &mut HashMap<usize, TypeGroup>, arg_groups: &Vec<Vec<(TypeGroup, SourceRange)>>, type_scope: &mut TypeScope) -> TypeGroup {
                             if let Some(n) = mapped.get(&type_scope.group_internal_id(t)) {
@@ -412,9 +741,11 @@ fn type_check_symbol<'s>(
                                         member_name,
                                         copy_arg_type_group(member_types, mapped, arg_groups, type_scope)
                                     )).collect();
-                                    Type::Object(type_scope.insert_object(
+                                    let name = type_scope.object_name(*obj).clone();
+                                    Type::Object(type_scope.insert_named_object(
                                         new_member_types,
-                                        fixed
+                                        fixed,
+                                        name
                                     ))
                                 }
                                 Type::ConcreteObject(obj) => {
@@ -463,7 +794,7 @@ fn type_check_symbol<'s>(
                             }
                         }
                         if !returns.1 {
-                            let assertion_a = TypeAssertion::returned_values(*source, return_types, &type_scope, strings);
+                            let assertion_a = TypeAssertion::returned_values(return_source, return_types, &type_scope, strings);
                             let assertion_b = TypeAssertion::implicit_unit_return(*source, &mut type_scope, strings);
                             assert_types(assertion_a, assertion_b, &mut type_scope)?;
                         }
@@ -472,15 +803,26 @@ fn type_check_symbol<'s>(
                     } else { panic!("procedure stack was illegally modified!"); }
                 } else { panic!("procedure was illegally modified!"); }
             }
-            AstNodeVariant::Variable { public, mutable: _, name: _, value_types: _, value } => {
-                rec_procedures.push((NamespacePath::new(Vec::new()), vec![], TypeScope::new()));
-                let return_types = global_scope.insert_group(&[Type::Any]);
+            AstNodeVariant::Variable { public, mutable: _, name: _, annotation, value_types: _, value } => {
+                let mut constant_type_scope = TypeScope::new();
+                let annotated_types = if let Some(annotation) = &annotation {
+                    let mut named_types = HashMap::new();
+                    type_check_annotation(strings, global_scope, rec_procedures, untyped_symbols, symbols, annotation, &mut named_types)?;
+                    let declared_types = declared_types_in(&named_types, global_scope, &mut constant_type_scope);
+                    let annotated_type = resolve_annotation(annotation, &mut constant_type_scope, &declared_types, false)?;
+                    Some(constant_type_scope.insert_group(&[annotated_type]))
+                } else { None };
+                let limited_to = annotated_types.map(|annotated_types|
+                    TypeAssertion::annotation(symbol_source, annotated_types, &constant_type_scope, strings)
+                );
+                let return_types = constant_type_scope.insert_group(&[Type::Any]);
+                rec_procedures.push((NamespacePath::new(Vec::new()), vec![], constant_type_scope));
                 let value_typed = if let Some(value) = value {
                     match type_check_node(
                         strings,
                         global_scope,
                         rec_procedures,
-                        symbol_source,
+                        ReturnSource::Procedure(symbol_source),
                         &mut HashMap::new(),
                         &mut HashSet::new(),
                         &mut HashMap::new(),
@@ -489,7 +831,8 @@ fn type_check_symbol<'s>(
                         symbols,
                         *value,
                         return_types,
-                        None,
+                        limited_to,
+                        false,
                         false
                     ) {
                         Ok((typed_node, _)) => typed_node,
@@ -504,6 +847,44 @@ fn type_check_symbol<'s>(
                     value_types: variable_types
                 });
             }
+            AstNodeVariant::TypeAlias { public, name: _, nominal, aliased } => {
+                let mut named_types = HashMap::new();
+                type_check_annotation(strings, global_scope, rec_procedures, untyped_symbols, symbols, &aliased, &mut named_types)?;
+                let declared_types = named_types.into_iter()
+                    .map(|(path, types)| (path, global_scope.group_concrete(types)))
+                    .collect();
+                let aliased_type = resolve_annotation(&aliased, global_scope, &declared_types, false)?;
+                let alias_type = match aliased_type {
+                    Type::Object(object) => {
+                        let aliases_nominal = match global_scope.object_name(object) {
+                            Some((_, aliased_nominal)) => *aliased_nominal,
+                            None => false
+                        };
+                        if aliases_nominal && !nominal { aliased_type } else {
+                            let (member_types, fixed) = global_scope.object(object).clone();
+                            Type::Object(global_scope.insert_named_object(
+                                member_types, fixed, Some((name.clone(), nominal))
+                            ))
+                        }
+                    }
+                    _ => if nominal {
+                        return Err(Error::new([
+                            ErrorSection::Error(ErrorType::NominalTypeNotObject(name.display(strings))),
+                            ErrorSection::Code(symbol_source),
+                            ErrorSection::Help(format!(
+                                "Only object types can be nominal, consider wrapping the value in an object like 'type {} = nominal {{ value = ... }}'",
+                                strings.get(*name.get_segments().last().unwrap())
+                            ))
+                        ].into()));
+                    } else { aliased_type }
+                };
+                let value_types = global_scope.insert_group(&[alias_type]);
+                symbols.insert(name.clone(), Symbol::Type {
+                    public,
+                    nominal,
+                    value_types
+                });
+            }
             other => panic!("Unhandled symbol type checking for {:?}!", other)
         }
     }
@@ -516,6 +897,58 @@ fn type_check_symbol<'s>(
     }
 }
 
+fn map_call_arguments(
+    strings: &StringMap,
+    path: &NamespacePath,
+    parameter_names: &Vec<StringIdx>,
+    parameter_defaults: &Vec<Option<AstNode>>,
+    arguments: Vec<AstNode>,
+    call_source: SourceRange
+) -> Result<Vec<AstNode>, Error> {
+    let argument_count = arguments.len();
+    let mut mapped_arguments: Vec<Option<AstNode>> = vec![None; parameter_names.len()];
+    let mut positional_count = 0;
+    for argument in arguments {
+        let argument_source = argument.source();
+        let (parameter_idx, value) = match argument.move_node() {
+            AstNodeVariant::NamedArgument { name, value } => {
+                if let Some(parameter_idx) = parameter_names.iter().position(|p| *p == name) {
+                    (parameter_idx, *value)
+                } else {
+                    let mut sections = vec![
+                        ErrorSection::Error(ErrorType::ParameterDoesNotExist(path.display(strings), name)),
+                        ErrorSection::Code(argument_source)
+                    ];
+                    sections.extend(did_you_mean(strings.get(name), parameter_names.iter().map(|p| strings.get(*p))));
+                    return Err(Error::new(sections.into()));
+                }
+            }
+            value => {
+                if positional_count >= parameter_names.len() { return Err(Error::new([
+                    ErrorSection::Error(ErrorType::InvalidParameterCount(path.display(strings), parameter_names.len(), argument_count)),
+                    ErrorSection::Code(call_source)
+                ].into())) }
+                positional_count += 1;
+                (positional_count - 1, AstNode::new(value, argument_source))
+            }
+        };
+        if mapped_arguments[parameter_idx].is_some() { return Err(Error::new([
+            ErrorSection::Error(ErrorType::ParameterGivenTwice(parameter_names[parameter_idx])),
+            ErrorSection::Code(argument_source)
+        ].into())) }
+        mapped_arguments[parameter_idx] = Some(value);
+    }
+    mapped_arguments.into_iter().enumerate().map(|(parameter_idx, argument)|
+        match argument.or_else(|| parameter_defaults[parameter_idx].clone()) {
+            Some(argument) => Ok(argument),
+            None => Err(Error::new([
+                ErrorSection::Error(ErrorType::MissingParameter(path.display(strings), parameter_names[parameter_idx])),
+                ErrorSection::Code(call_source)
+            ].into()))
+        }
+    ).collect()
+}
+
 type SometimesReturns = bool;
 type AlwaysReturns = bool;
 
@@ -523,7 +956,7 @@ fn type_check_nodes(
     strings: &StringMap,
     global_type_scope: &mut TypeScope,
     rec_procedures: &mut Vec<(NamespacePath, Vec<Vec<(TypeGroup, SourceRange)>>, TypeScope)>,
-    procedure_source: SourceRange,
+    return_source: ReturnSource,
     variables: &mut HashMap<StringIdx, (TypeGroup, bool, SourceRange)>,
     scope_variables: &mut HashSet<StringIdx>,
     uninitialized_variables: &mut HashMap<StringIdx, (TypeGroup, bool, SourceRange)>,
@@ -540,7 +973,7 @@ fn type_check_nodes(
             strings,
             global_type_scope,
             rec_procedures,
-            procedure_source,
+            return_source,
             variables,
             scope_variables,
             uninitialized_variables,
@@ -550,7 +983,8 @@ fn type_check_nodes(
             nodes.remove(0),
             return_types,
             None,
-            false
+            false,
+            true
         ) {
             Ok((typed_node, node_returns)) => {
                 if node_returns.0 { returns.0 = true; }
@@ -588,6 +1022,400 @@ fn assert_types(
     }
 }
 
+fn type_check_pattern(
+    strings: &StringMap,
+    type_scope: &mut TypeScope,
+    variables: &mut HashMap<StringIdx, (TypeGroup, bool, SourceRange)>,
+    scope_variables: &mut HashSet<StringIdx>,
+    pattern: Pattern<AstNode>,
+    value_types: TypeGroup,
+    value_source: SourceRange,
+    mutable: bool
+) -> Result<Pattern<TypedAstNode>, Error> {
+    match pattern {
+        Pattern::Variable { name, source, value_types: _ } => {
+            variables.insert(name, (value_types, mutable, source));
+            scope_variables.insert(name);
+            Ok(Pattern::Variable { name, source, value_types: Some(value_types) })
+        }
+        Pattern::Wildcard { source, value_types: _ } => {
+            Ok(Pattern::Wildcard { source, value_types: Some(value_types) })
+        }
+        Pattern::Value { .. } |
+        Pattern::Variant { .. } => panic!("the parser should only allow these patterns in 'case'"),
+        Pattern::Object { members, source, value_types: _ } => {
+            let mut typed_members = Vec::new();
+            for (member_name, member_pattern) in members {
+                let member_types = type_scope.insert_group(&[Type::Any]);
+                let object_tidx = type_scope.insert_object([(member_name, member_types)].into(), false);
+                let object_types = type_scope.insert_group(&[Type::Object(object_tidx)]);
+                let member_source = member_pattern.source();
+                assert_types(
+                    TypeAssertion::destructured_value(value_source, value_types, type_scope, strings),
+                    TypeAssertion::destructured_member(member_source, member_name, object_types, strings),
+                    type_scope
+                )?;
+                typed_members.push((member_name, type_check_pattern(
+                    strings, type_scope, variables, scope_variables,
+                    member_pattern, member_types, member_source, mutable
+                )?));
+            }
+            Ok(Pattern::Object { members: typed_members, source, value_types: Some(value_types) })
+        }
+        Pattern::Array { elements, source, value_types: _, index_types: _ } => {
+            let element_types = type_scope.insert_group(&[Type::Any]);
+            let array_tidx = type_scope.insert_array(element_types);
+            let array_types = type_scope.insert_group(&[Type::Array(array_tidx)]);
+            assert_types(
+                TypeAssertion::destructured_value(value_source, value_types, type_scope, strings),
+                TypeAssertion::destructured_array(source, array_types),
+                type_scope
+            )?;
+            let mut typed_elements = Vec::new();
+            for element_pattern in elements {
+                let element_source = element_pattern.source();
+                typed_elements.push(type_check_pattern(
+                    strings, type_scope, variables, scope_variables,
+                    element_pattern, element_types, element_source, mutable
+                )?);
+            }
+            let index_types = type_scope.insert_group(&[Type::Integer]);
+            Ok(Pattern::Array { elements: typed_elements, source, value_types: Some(value_types), index_types: Some(index_types) })
+        }
+    }
+}
+
+fn type_check_case_patterns(
+    strings: &StringMap,
+    type_scope: &mut TypeScope,
+    mut column: Vec<(usize, &mut Pattern<TypedAstNode>)>,
+    value_types: TypeGroup,
+    value_source: SourceRange,
+    exhaustive: bool,
+    branches_variables: &mut [(HashMap<StringIdx, (TypeGroup, bool, SourceRange)>, HashSet<StringIdx>)]
+) -> Result<(), Error> {
+    let closed = exhaustive && column.iter()
+        .all(|(_, pattern)| !matches!(pattern, Pattern::Variable { .. } | Pattern::Wildcard { .. }));
+    let mut variant_payloads: Vec<(StringIdx, TypeGroup, bool)> = Vec::new();
+    let mut variant_source = None;
+    let mut object_members: Vec<(StringIdx, TypeGroup, bool)> = Vec::new();
+    let mut object_source = None;
+    for (branch, pattern) in &mut column {
+        *pattern.value_types_mut() = Some(value_types);
+        match &**pattern {
+            Pattern::Variable { name, source, .. } => {
+                branches_variables[*branch].0.insert(*name, (value_types, false, *source));
+                branches_variables[*branch].1.insert(*name);
+            }
+            Pattern::Wildcard { .. } => {}
+            Pattern::Value { value, source, .. } => {
+                assert_types(
+                    TypeAssertion::matched_value(value_source, value_types, type_scope, strings),
+                    TypeAssertion::branch_pattern(*source, value.get_types(), type_scope, strings),
+                    type_scope
+                )?;
+            }
+            Pattern::Variant { name, payload, source, .. } => {
+                variant_source.get_or_insert(*source);
+                if let Some(variant) = variant_payloads.iter_mut().find(|(n, _, _)| n == name) {
+                    variant.2 &= payload.is_some();
+                } else {
+                    let payload_types = type_scope.insert_group(&[Type::Any]);
+                    variant_payloads.push((*name, payload_types, closed && payload.is_some()));
+                }
+            }
+            Pattern::Object { members, source, .. } => {
+                object_source.get_or_insert(*source);
+                for (member_name, _) in members {
+                    if object_members.iter().any(|(n, _, _)| n == member_name) { continue; }
+                    let member_types = type_scope.insert_group(&[Type::Any]);
+                    object_members.push((*member_name, member_types, closed));
+                }
+                for member in &mut object_members {
+                    member.2 &= members.iter().any(|(n, _)| *n == member.0);
+                }
+            }
+            Pattern::Array { .. } => panic!("the parser should not allow array patterns in 'case'")
+        }
+    }
+    if let Some(variant_source) = variant_source {
+        let variants_tidx = type_scope.insert_variants(
+            variant_payloads.iter().map(|(name, payload_types, _)| (*name, *payload_types)).collect(), closed
+        );
+        let variants_types = type_scope.insert_group(&[Type::Variants(variants_tidx)]);
+        assert_types(
+            TypeAssertion::matched_value(value_source, value_types, type_scope, strings),
+            TypeAssertion::branch_pattern(variant_source, variants_types, type_scope, strings),
+            type_scope
+        )?;
+    }
+    if let Some(object_source) = object_source {
+        let object_tidx = type_scope.insert_object(
+            object_members.iter().map(|(name, member_types, _)| (*name, *member_types)).collect(), false
+        );
+        let object_types = type_scope.insert_group(&[Type::Object(object_tidx)]);
+        assert_types(
+            TypeAssertion::matched_value(value_source, value_types, type_scope, strings),
+            TypeAssertion::branch_pattern(object_source, object_types, type_scope, strings),
+            type_scope
+        )?;
+    }
+    for (variant_name, payload_types, payload_closed) in variant_payloads {
+        let payload_column = column.iter_mut()
+            .filter_map(|(branch, pattern)| match &mut **pattern {
+                Pattern::Variant { name, payload: Some(payload), .. } if *name == variant_name => Some((*branch, &mut **payload)),
+                _ => None
+            })
+            .collect();
+        type_check_case_patterns(
+            strings, type_scope, payload_column, payload_types, value_source, payload_closed, branches_variables
+        )?;
+    }
+    for (member_name, member_types, member_closed) in object_members {
+        let member_column = column.iter_mut()
+            .filter_map(|(branch, pattern)| match &mut **pattern {
+                Pattern::Object { members, .. } => members.iter_mut()
+                    .find(|(name, _)| *name == member_name)
+                    .map(|(_, member_pattern)| (*branch, member_pattern)),
+                _ => None
+            })
+            .collect();
+        type_check_case_patterns(
+            strings, type_scope, member_column, member_types, value_source, member_closed, branches_variables
+        )?;
+    }
+    Ok(())
+}
+
+fn pattern_head<'p>(row: &[Option<&'p Pattern<TypedAstNode>>]) -> Option<&'p Pattern<TypedAstNode>> {
+    match row[0] {
+        Some(Pattern::Variable { .. }) | Some(Pattern::Wildcard { .. }) | None => None,
+        Some(Pattern::Value { value, .. }) if matches!(value.node_variant(), AstNodeVariant::UnitLiteral) => None,
+        Some(pattern) => Some(pattern)
+    }
+}
+
+fn find_unmatched_value<'p>(
+    strings: &StringMap,
+    type_scope: &TypeScope,
+    rows: Vec<Vec<Option<&'p Pattern<TypedAstNode>>>>,
+    columns: &[Option<TypeGroup>]
+) -> Option<Vec<String>> {
+    if columns.is_empty() {
+        return if rows.is_empty() { Some(Vec::new()) } else { None };
+    }
+    let column_type = columns[0]
+        .map(|group| type_scope.group(group).collect::<Vec<Type>>())
+        .filter(|types| types.len() == 1)
+        .map(|types| types[0]);
+    let mut object_members = Vec::new();
+    let mut variant_names = Vec::new();
+    let mut booleans = Vec::new();
+    for row in &rows {
+        match pattern_head(row) {
+            Some(Pattern::Object { members, .. }) => for (member_name, _) in members {
+                if !object_members.contains(member_name) { object_members.push(*member_name); }
+            }
+            Some(Pattern::Variant { name, .. }) => if !variant_names.contains(name) {
+                variant_names.push(*name);
+            }
+            Some(Pattern::Value { value, .. }) => if let AstNodeVariant::BooleanLiteral { value } = value.node_variant() {
+                if !booleans.contains(value) { booleans.push(*value); }
+            }
+            _ => {}
+        }
+    }
+    if rows.iter().any(|row| matches!(pattern_head(row), Some(Pattern::Object { .. }))) {
+        let member_types = object_members.iter().map(|member_name| match column_type {
+            Some(Type::Object(object_tidx)) => type_scope.object(object_tidx).0.get(member_name).copied(),
+            _ => None
+        });
+        let columns = member_types.chain(columns[1..].iter().copied()).collect::<Vec<Option<TypeGroup>>>();
+        let specialized = rows.iter().filter_map(|row| {
+            let mut specialized_row = match pattern_head(row) {
+                Some(Pattern::Object { members, .. }) => object_members.iter().map(|member_name| members.iter()
+                    .find(|(name, _)| name == member_name)
+                    .map(|(_, member_pattern)| member_pattern)
+                ).collect::<Vec<Option<&Pattern<TypedAstNode>>>>(),
+                Some(_) => return None,
+                None => vec![None; object_members.len()]
+            };
+            specialized_row.extend(row[1..].iter().copied());
+            Some(specialized_row)
+        }).collect();
+        let mut witness = find_unmatched_value(strings, type_scope, specialized, &columns)?;
+        let rest = witness.split_off(object_members.len());
+        let object = format!("{{ {} }}", object_members.iter().zip(witness)
+            .map(|(member_name, member_witness)| format!("{} = {}", strings.get(*member_name), member_witness))
+            .collect::<Vec<String>>().join(", ")
+        );
+        return Some([object].into_iter().chain(rest).collect());
+    }
+    let all_variant_names = match column_type {
+        Some(Type::Variants(variants_tidx)) if type_scope.variants(variants_tidx).1 => {
+            let mut names = type_scope.variants(variants_tidx).0.keys().copied().collect::<Vec<StringIdx>>();
+            names.sort_by_key(|name| strings.get(*name));
+            Some(names)
+        }
+        _ => None
+    };
+    let constructors: Option<Vec<(String, Option<StringIdx>, Option<bool>)>> = if !variant_names.is_empty() {
+        all_variant_names.as_ref()
+            .filter(|names| names.iter().all(|name| variant_names.contains(name)))
+            .map(|names| names.iter().map(|name| (format!("#{}", strings.get(*name)), Some(*name), None)).collect())
+    } else if booleans.len() == 2 {
+        Some(vec![(String::from("true"), None, Some(true)), (String::from("false"), None, Some(false))])
+    } else { None };
+    if let Some(constructors) = constructors {
+        for (constructor_str, variant_name, boolean) in constructors {
+            let mut columns = columns[1..].to_vec();
+            if let Some(variant_name) = variant_name {
+                let payload_types = match column_type {
+                    Some(Type::Variants(variants_tidx)) => type_scope.variants(variants_tidx).0.get(&variant_name).copied(),
+                    _ => None
+                };
+                columns.insert(0, payload_types);
+            }
+            let specialized = rows.iter().filter_map(|row| {
+                let mut specialized_row = match (pattern_head(row), variant_name, boolean) {
+                    (Some(Pattern::Variant { name, payload, .. }), Some(variant_name), _) if *name == variant_name =>
+                        vec![payload.as_ref().map(|payload| &**payload)],
+                    (Some(Pattern::Value { value, .. }), _, Some(boolean))
+                        if value.node_variant() == &AstNodeVariant::BooleanLiteral { value: boolean } => Vec::new(),
+                    (Some(_), _, _) => return None,
+                    (None, Some(_), _) => vec![None],
+                    (None, None, _) => Vec::new()
+                };
+                specialized_row.extend(row[1..].iter().copied());
+                Some(specialized_row)
+            }).collect();
+            if let Some(mut witness) = find_unmatched_value(strings, type_scope, specialized, &columns) {
+                if variant_name.is_some() {
+                    let payload_witness = witness.remove(0);
+                    let variant_witness = if payload_witness == "_" { constructor_str }
+                        else { format!("{} {}", constructor_str, payload_witness) };
+                    witness.insert(0, variant_witness);
+                } else {
+                    witness.insert(0, constructor_str);
+                }
+                return Some(witness);
+            }
+        }
+        return None;
+    }
+    let defaults = rows.iter()
+        .filter(|row| pattern_head(row).is_none())
+        .map(|row| row[1..].to_vec())
+        .collect();
+    let mut witness = find_unmatched_value(strings, type_scope, defaults, &columns[1..])?;
+    let missing = if let Some(all_variant_names) = &all_variant_names {
+        all_variant_names.iter()
+            .find(|name| !variant_names.contains(name))
+            .map(|name| format!("#{}", strings.get(*name)))
+    } else if booleans.len() == 1 {
+        Some(String::from(if booleans[0] { "false" } else { "true" }))
+    } else { None };
+    witness.insert(0, missing.unwrap_or(String::from("_")));
+    Some(witness)
+}
+
+enum PatternConstructor<'p> {
+    Object,
+    Variant(StringIdx),
+    Value(&'p AstNodeVariant<TypedAstNode>)
+}
+
+/// Checks if 'vector' matches any value that none of the 'rows' match.
+fn is_useful_pattern<'p>(
+    type_scope: &TypeScope,
+    rows: Vec<Vec<Option<&'p Pattern<TypedAstNode>>>>,
+    vector: Vec<Option<&'p Pattern<TypedAstNode>>>,
+    columns: &[Option<TypeGroup>]
+) -> bool {
+    if columns.is_empty() { return rows.is_empty(); }
+    let column_type = columns[0]
+        .map(|group| type_scope.group(group).collect::<Vec<Type>>())
+        .filter(|types| types.len() == 1)
+        .map(|types| types[0]);
+    let mut object_members = Vec::new();
+    let mut variant_names = Vec::new();
+    let mut booleans = Vec::new();
+    for row in rows.iter().chain([&vector]) {
+        match pattern_head(row) {
+            Some(Pattern::Object { members, .. }) => for (member_name, _) in members {
+                if !object_members.contains(member_name) { object_members.push(*member_name); }
+            }
+            Some(Pattern::Variant { name, .. }) => if !variant_names.contains(name) {
+                variant_names.push(*name);
+            }
+            Some(Pattern::Value { value, .. }) => if let AstNodeVariant::BooleanLiteral { value: boolean } = value.node_variant() {
+                if !booleans.iter().any(|(b, _)| b == boolean) { booleans.push((*boolean, value.node_variant())); }
+            }
+            _ => {}
+        }
+    }
+    let constructors = match pattern_head(&vector) {
+        Some(Pattern::Object { .. }) => vec![PatternConstructor::Object],
+        Some(Pattern::Variant { name, .. }) => vec![PatternConstructor::Variant(*name)],
+        Some(Pattern::Value { value, .. }) => vec![PatternConstructor::Value(value.node_variant())],
+        Some(_) => return true,
+        None => if rows.iter().any(|row| matches!(pattern_head(row), Some(Pattern::Object { .. }))) {
+            vec![PatternConstructor::Object]
+        } else if let Some(Type::Variants(variants_tidx)) = column_type.filter(|_| !variant_names.is_empty()) {
+            let (all_variants, closed) = type_scope.variants(variants_tidx);
+            if !*closed || !all_variants.keys().all(|name| variant_names.contains(name)) {
+                Vec::new()
+            } else {
+                variant_names.iter().map(|name| PatternConstructor::Variant(*name)).collect()
+            }
+        } else if booleans.len() == 2 {
+            booleans.iter().map(|(_, value)| PatternConstructor::Value(value)).collect()
+        } else { Vec::new() }
+    };
+    if constructors.is_empty() {
+        let defaults = rows.iter()
+            .filter(|row| pattern_head(row).is_none())
+            .map(|row| row[1..].to_vec())
+            .collect();
+        return is_useful_pattern(type_scope, defaults, vector[1..].to_vec(), &columns[1..]);
+    }
+    constructors.into_iter().any(|constructor| {
+        let specialize = |row: &Vec<Option<&'p Pattern<TypedAstNode>>>| {
+            let mut specialized_row = match (pattern_head(row), &constructor) {
+                (Some(Pattern::Object { members, .. }), PatternConstructor::Object) => object_members.iter()
+                    .map(|member_name| members.iter()
+                        .find(|(name, _)| name == member_name)
+                        .map(|(_, member_pattern)| member_pattern)
+                    ).collect(),
+                (None, PatternConstructor::Object) => vec![None; object_members.len()],
+                (Some(Pattern::Variant { name, payload, .. }), PatternConstructor::Variant(variant_name)) if name == variant_name =>
+                    vec![payload.as_ref().map(|payload| &**payload)],
+                (None, PatternConstructor::Variant(_)) => vec![None],
+                (Some(Pattern::Value { value, .. }), PatternConstructor::Value(constructor_value))
+                    if value.node_variant() == *constructor_value => Vec::new(),
+                (None, PatternConstructor::Value(_)) => Vec::new(),
+                _ => return None
+            };
+            specialized_row.extend(row[1..].iter().copied());
+            Some(specialized_row)
+        };
+        let mut specialized_columns = match &constructor {
+            PatternConstructor::Object => object_members.iter().map(|member_name| match column_type {
+                Some(Type::Object(object_tidx)) => type_scope.object(object_tidx).0.get(member_name).copied(),
+                _ => None
+            }).collect(),
+            PatternConstructor::Variant(variant_name) => vec![match column_type {
+                Some(Type::Variants(variants_tidx)) => type_scope.variants(variants_tidx).0.get(variant_name).copied(),
+                _ => None
+            }],
+            PatternConstructor::Value(_) => Vec::new()
+        };
+        specialized_columns.extend(columns[1..].iter().copied());
+        let Some(specialized_vector) = specialize(&vector) else { return false; };
+        is_useful_pattern(type_scope, rows.iter().filter_map(specialize).collect(), specialized_vector, &specialized_columns)
+    })
+}
+
 fn initalize_variables(
     strings: &StringMap,
     type_scope: &mut TypeScope,
@@ -631,7 +1459,7 @@ fn type_check_node(
     strings: &StringMap,
     global_type_scope: &mut TypeScope,
     rec_procedures: &mut Vec<(NamespacePath, Vec<Vec<(TypeGroup, SourceRange)>>, TypeScope)>,
-    procedure_source: SourceRange,
+    return_source: ReturnSource,
     variables: &mut HashMap<StringIdx, (TypeGroup, bool, SourceRange)>,
     scope_variables: &mut HashSet<StringIdx>,
     uninitialized_variables: &mut HashMap<StringIdx, (TypeGroup, bool, SourceRange)>,
@@ -641,7 +1469,8 @@ fn type_check_node(
     node: AstNode,
     return_types: TypeGroup,
     limited_to: Option<TypeAssertion>,
-    assignment: bool
+    assignment: bool,
+    statement: bool
 ) -> Result<(TypedAstNode, (SometimesReturns, AlwaysReturns)), Error> {
     let node_source = node.source();
     macro_rules! type_scope { () => { {
@@ -649,29 +1478,57 @@ fn type_check_node(
         &mut rec_procedures[idx].2
     } } }
     macro_rules! type_check_node { ($node: expr, $limited_to: expr) => {
-        match type_check_node(strings, global_type_scope, rec_procedures, procedure_source, variables, scope_variables, uninitialized_variables, captured_variables, untyped_symbols, symbols, $node, return_types, $limited_to, assignment) {
+        match type_check_node(strings, global_type_scope, rec_procedures, return_source, variables, scope_variables, uninitialized_variables, captured_variables, untyped_symbols, symbols, $node, return_types, $limited_to, assignment, false) {
             Ok(typed_node) => typed_node,
             Err(error) => return Err(error)
         }
     }; ($node: expr, $limited_to: expr, $assignment: expr) => {
-        match type_check_node(strings, global_type_scope, rec_procedures, procedure_source, variables, scope_variables, uninitialized_variables, captured_variables, untyped_symbols, symbols, $node, return_types, $limited_to, $assignment) {
+        match type_check_node(strings, global_type_scope, rec_procedures, return_source, variables, scope_variables, uninitialized_variables, captured_variables, untyped_symbols, symbols, $node, return_types, $limited_to, $assignment, false) {
             Ok(typed_node) => typed_node,
             Err(error) => return Err(error)
         }
     }; ($node: expr, $limited_to: expr, $assignment: expr, $variables: expr) => {
-        match type_check_node(strings, global_type_scope, rec_procedures, procedure_source, $variables, scope_variables, uninitialized_variables, captured_variables, untyped_symbols, symbols, $node, return_types, $limited_to, $assignment) {
+        match type_check_node(strings, global_type_scope, rec_procedures, return_source, $variables, scope_variables, uninitialized_variables, captured_variables, untyped_symbols, symbols, $node, return_types, $limited_to, $assignment, false) {
             Ok(typed_node) => typed_node,
             Err(error) => return Err(error)
         }
     } }
     macro_rules! type_check_nodes { ($nodes: expr, $variables: expr, $scope_variables: expr, $uninitialized_variables: expr) => {
-        match type_check_nodes(strings, global_type_scope, rec_procedures, procedure_source, $variables, $scope_variables, $uninitialized_variables, captured_variables, untyped_symbols, symbols, $nodes, return_types) {
+        match type_check_nodes(strings, global_type_scope, rec_procedures, return_source, $variables, $scope_variables, $uninitialized_variables, captured_variables, untyped_symbols, symbols, $nodes, return_types) {
             Ok(typed_node) => typed_node,
             Err(error) => return Err(error)
         }
     } }
+    macro_rules! case_types { () => { {
+        let case_types = type_scope!().insert_group(&[if statement { Type::Unit } else { Type::Any }]);
+        if let Some(limited_to) = limited_to {
+            assert_types(
+                TypeAssertion::case_result(node_source, case_types, type_scope!(), strings),
+                limited_to, type_scope!()
+            )?;
+        }
+        case_types
+    } } }
+    macro_rules! type_check_case_body { ($nodes: expr, $case_types: expr, $variables: expr, $scope_variables: expr, $uninitialized_variables: expr) => {
+        if statement {
+            type_check_nodes!($nodes, $variables, $scope_variables, $uninitialized_variables)
+        } else {
+            let mut nodes = $nodes;
+            let value = nodes.pop().expect("grammar checker should enforce a value for each branch");
+            let (mut typed_nodes, nodes_return) = type_check_nodes!(nodes, $variables, $scope_variables, $uninitialized_variables);
+            let value_assertion = TypeAssertion::case_value(value.source(), $case_types, type_scope!(), strings);
+            let (typed_value, value_returns) = type_check_node(
+                strings, global_type_scope, rec_procedures, return_source,
+                $variables, $scope_variables, $uninitialized_variables,
+                captured_variables, untyped_symbols, symbols, value, return_types, Some(value_assertion), false, false
+            )?;
+            typed_nodes.push(typed_value);
+            (typed_nodes, (nodes_return.0 || value_returns.0, nodes_return.1 || value_returns.1))
+        }
+    } }
     match node.move_node() {
-        AstNodeVariant::Procedure { public: _, name: _, arguments: _, body: _ } => panic!("The grammar checker failed to see a procedure inside another!"),
+        AstNodeVariant::Procedure { public: _, name: _, arguments: _, returns: _, body: _ } => panic!("The grammar checker failed to see a procedure inside another!"),
+        AstNodeVariant::TypeAlias { public: _, name: _, nominal: _, aliased: _ } => panic!("The grammar checker failed to see a type declaration inside a procedure!"),
         AstNodeVariant::Function { arguments, body } => {
             let mut closure_variables = variables.clone();
             let mut closure_scope_variables = HashSet::new();
@@ -688,7 +1545,7 @@ fn type_check_node(
                 strings,
                 global_type_scope,
                 rec_procedures,
-                procedure_source,
+                ReturnSource::Procedure(node_source),
                 &mut closure_variables,
                 &mut closure_scope_variables,
                 &mut uninitialized_variables.clone(),
@@ -708,7 +1565,7 @@ fn type_check_node(
             }
             if !returns.1 {
                 assert_types(
-                    TypeAssertion::returned_values(node_source, return_types, type_scope!(), strings),
+                    TypeAssertion::returned_values(ReturnSource::Procedure(node_source), return_types, type_scope!(), strings),
                     TypeAssertion::implicit_unit_return(node_source, type_scope!(), strings),
                     type_scope!()
                 )?;
@@ -738,10 +1595,20 @@ fn type_check_node(
                 node_source
             ), (false, false)))
         }
-        AstNodeVariant::Variable { public, mutable, name, value_types: _, value } => {
-            let value_types = type_scope!().insert_group(&[Type::Any]);
+        AstNodeVariant::Variable { public, mutable, name, annotation, value_types: _, value } => {
+            let (value_types, value_assertion) = if let Some(annotation) = &annotation {
+                let mut named_types = HashMap::new();
+                type_check_annotation(strings, global_type_scope, rec_procedures, untyped_symbols, symbols, annotation, &mut named_types)?;
+                let declared_types = declared_types_in(&named_types, global_type_scope, type_scope!());
+                let annotated_type = resolve_annotation(annotation, type_scope!(), &declared_types, false)?;
+                let annotated_types = type_scope!().insert_group(&[annotated_type]);
+                (annotated_types, TypeAssertion::annotation(node_source, annotated_types, type_scope!(), strings))
+            } else {
+                let value_types = type_scope!().insert_group(&[Type::Any]);
+                (value_types, TypeAssertion::unexplained(value_types))
+            };
             let typed_value = if let Some(value) = value {
-                let typed_value = type_check_node!(*value, Some(TypeAssertion::unexplained(value_types))).0;
+                let typed_value = type_check_node!(*value, Some(value_assertion)).0;
                 variables.insert(name, (value_types, mutable, node_source));
                 Some(Box::new(typed_value))
             } else {
@@ -753,12 +1620,26 @@ fn type_check_node(
                 public,
                 mutable,
                 name,
+                annotation,
                 value_types: Some(value_types),
                 value: typed_value
             }, type_scope!().insert_group(&[Type::Unit]), node_source), (false, false)))
         }
+        AstNodeVariant::Destructuring { mutable, pattern, value } => {
+            let typed_value = type_check_node!(*value, None).0;
+            let typed_pattern = type_check_pattern(
+                strings, type_scope!(), variables, scope_variables,
+                pattern, typed_value.get_types(), typed_value.source(), mutable
+            )?;
+            Ok((TypedAstNode::new(AstNodeVariant::Destructuring {
+                mutable,
+                pattern: typed_pattern,
+                value: Box::new(typed_value)
+            }, type_scope!().insert_group(&[Type::Unit]), node_source), (false, false)))
+        }
         AstNodeVariant::CaseBranches { value, branches, else_body } => {
             let typed_value = type_check_node!(*value, None).0;
+            let case_types = case_types!();
             let mut typed_branches = Vec::new();
             let mut branches_return = Vec::new();
             let mut branches_variables = Vec::new();
@@ -766,7 +1647,7 @@ fn type_check_node(
             for (branch_value, branch_body) in branches {
                 let mut branch_variables = variables.clone();
                 let mut branch_uninitialized_variables = uninitialized_variables.clone();
-                let (branch_body, branch_returns) = type_check_nodes!(branch_body, &mut branch_variables, &mut scope_variables.clone(), &mut branch_uninitialized_variables);
+                let (branch_body, branch_returns) = type_check_case_body!(branch_body, case_types, &mut branch_variables, &mut scope_variables.clone(), &mut branch_uninitialized_variables);
                 branches_return.push(branch_returns);
                 let matched_assertion = TypeAssertion::matched_value(node_source, typed_value.get_types(), type_scope!(), strings);
                 typed_branches.push((type_check_node!(branch_value, Some(matched_assertion), false, &mut HashMap::new()).0, branch_body));
@@ -775,7 +1656,7 @@ fn type_check_node(
             }
             let mut else_body_variables = variables.clone();
             let mut else_body_uninitialized_variables = uninitialized_variables.clone();
-            let (typed_else_body, else_returns) = type_check_nodes!(else_body, &mut else_body_variables, &mut scope_variables.clone(), &mut else_body_uninitialized_variables);
+            let (typed_else_body, else_returns) = type_check_case_body!(else_body, case_types, &mut else_body_variables, &mut scope_variables.clone(), &mut else_body_uninitialized_variables);
             branches_return.push(else_returns);
             branches_variables.push(else_body_variables);
             branches_uninitialized_variables.push(else_body_uninitialized_variables);
@@ -791,7 +1672,7 @@ fn type_check_node(
                     branches: typed_branches,
                     else_body: typed_else_body
                 },
-                type_scope!().insert_group(&[Type::Unit]), node_source),
+                case_types, node_source),
                 (branches_return.iter().find(|r| r.0).is_some(), branches_return.iter().find(|r| !r.1).is_none())
             ))
         }
@@ -799,12 +1680,13 @@ fn type_check_node(
             let boolean = type_scope!().insert_group(&[Type::Boolean]);
             let cond_assertion = TypeAssertion::condition(node_source, boolean, type_scope!(), strings);
             let typed_condition = type_check_node!(*condition, Some(cond_assertion)).0;
+            let case_types = case_types!();
             let mut body_variables = variables.clone();
             let mut body_uninitialized_variables = uninitialized_variables.clone();
-            let (typed_body, body_returns) = type_check_nodes!(body, &mut body_variables, &mut scope_variables.clone(), &mut body_uninitialized_variables);
+            let (typed_body, body_returns) = type_check_case_body!(body, case_types, &mut body_variables, &mut scope_variables.clone(), &mut body_uninitialized_variables);
             let mut else_body_variables = variables.clone();
             let mut else_body_uninitialized_variables = uninitialized_variables.clone();
-            let (typed_else_body, else_returns) = type_check_nodes!(else_body, &mut else_body_variables, &mut scope_variables.clone(), &mut else_body_uninitialized_variables);
+            let (typed_else_body, else_returns) = type_check_case_body!(else_body, case_types, &mut else_body_variables, &mut scope_variables.clone(), &mut else_body_uninitialized_variables);
             initalize_variables(
                 strings, type_scope!(), variables, uninitialized_variables,
                 &[body_variables, else_body_variables],
@@ -815,7 +1697,7 @@ fn type_check_node(
                 condition: Box::new(typed_condition),
                 body: typed_body,
                 else_body: typed_else_body
-            }, type_scope!().insert_group(&[Type::Unit]), node_source), (body_returns.0 || else_returns.0, body_returns.1 && else_returns.1)))
+            }, case_types, node_source), (body_returns.0 || else_returns.0, body_returns.1 && else_returns.1)))
         }
         AstNodeVariant::CaseVariant { value, branches, else_body } => {
             let mut typed_branches = Vec::new();
@@ -823,6 +1705,7 @@ fn type_check_node(
             let mut branches_variables = Vec::new();
             let mut branches_uninitialized_variables = Vec::new();
             let mut variant_types = HashMap::new();
+            let case_types = case_types!();
             for (branch_variant_name, branch_variant_variable, branch_body) in branches {
                 let mut branch_variables = variables.clone();
                 let branch_variant_variable_types = type_scope!().insert_group(&[Type::Any]);
@@ -832,7 +1715,7 @@ fn type_check_node(
                     branch_scope_variables.insert(branch_variant_variable.0);
                 }
                 let mut branch_uninitialized_variables = uninitialized_variables.clone();
-                let (branch_body, branch_returns) = type_check_nodes!(branch_body, &mut branch_variables, &mut branch_scope_variables, &mut branch_uninitialized_variables);
+                let (branch_body, branch_returns) = type_check_case_body!(branch_body, case_types, &mut branch_variables, &mut branch_scope_variables, &mut branch_uninitialized_variables);
                 branches_return.push(branch_returns);
                 typed_branches.push((branch_variant_name, branch_variant_variable.map(|v| (v.0, v.1, Some(branch_variant_variable_types))), branch_body));
                 branches_variables.push(branch_variables);
@@ -846,7 +1729,7 @@ fn type_check_node(
             let typed_else_body = if let Some(else_body) = else_body {
                 let mut else_body_variables = variables.clone();
                 let mut else_body_uninitialized_variables = uninitialized_variables.clone();
-                let (typed_else_body, else_returns) = type_check_nodes!(else_body, &mut else_body_variables, &mut scope_variables.clone(), &mut else_body_uninitialized_variables);
+                let (typed_else_body, else_returns) = type_check_case_body!(else_body, case_types, &mut else_body_variables, &mut scope_variables.clone(), &mut else_body_uninitialized_variables);
                 branches_variables.push(else_body_variables);
                 branches_uninitialized_variables.push(else_body_uninitialized_variables);
                 branches_return.push(else_returns);
@@ -864,7 +1747,94 @@ fn type_check_node(
                     branches: typed_branches,
                     else_body: typed_else_body
                 },
-                type_scope!().insert_group(&[Type::Unit]), node_source),
+                case_types, node_source),
+                (branches_return.iter().find(|r| r.0).is_some(), branches_return.iter().find(|r| !r.1).is_none())
+            ))
+        }
+        AstNodeVariant::CasePattern { value, branches, else_body } => {
+            let typed_value = type_check_node!(*value, None).0;
+            let case_types = case_types!();
+            let mut branch_patterns = Vec::new();
+            let mut branch_guards = Vec::new();
+            let mut branch_bodies = Vec::new();
+            for (pattern, guard, body) in branches {
+                branch_patterns.push(pattern.map_values(
+                    &mut |pattern_value| Ok(type_check_node!(pattern_value, None, false, &mut HashMap::new()).0)
+                )?);
+                branch_guards.push(guard);
+                branch_bodies.push(body);
+            }
+            let mut branches_scopes = vec![(variables.clone(), scope_variables.clone()); branch_patterns.len()];
+            type_check_case_patterns(
+                strings, type_scope!(), branch_patterns.iter_mut().enumerate().collect(),
+                typed_value.get_types(), typed_value.source(), else_body.is_none(), &mut branches_scopes
+            )?;
+            let mut typed_branches = Vec::new();
+            let mut branches_return = Vec::new();
+            let mut branches_variables = Vec::new();
+            let mut branches_uninitialized_variables = Vec::new();
+            for (((pattern, guard), body), (mut branch_variables, mut branch_scope_variables))
+                in branch_patterns.into_iter().zip(branch_guards).zip(branch_bodies).zip(branches_scopes) {
+                let mut branch_uninitialized_variables = uninitialized_variables.clone();
+                let typed_guard = if let Some(guard) = guard {
+                    let boolean = type_scope!().insert_group(&[Type::Boolean]);
+                    let guard_assertion = TypeAssertion::condition(guard.source(), boolean, type_scope!(), strings);
+                    Some(type_check_node(
+                        strings, global_type_scope, rec_procedures, return_source,
+                        &mut branch_variables, &mut branch_scope_variables, &mut branch_uninitialized_variables,
+                        captured_variables, untyped_symbols, symbols, guard, return_types, Some(guard_assertion), false, false
+                    )?.0)
+                } else { None };
+                let (typed_body, branch_returns) = type_check_case_body!(body, case_types, &mut branch_variables, &mut branch_scope_variables, &mut branch_uninitialized_variables);
+                branches_return.push(branch_returns);
+                typed_branches.push((pattern, typed_guard, typed_body));
+                branches_variables.push(branch_variables);
+                branches_uninitialized_variables.push(branch_uninitialized_variables);
+            }
+            let mut previous_rows = Vec::new();
+            for (pattern, guard, _) in &typed_branches {
+                if !is_useful_pattern(type_scope!(), previous_rows.clone(), vec![Some(pattern)], &[Some(typed_value.get_types())]) {
+                    return Err(Error::new([
+                        ErrorSection::Error(ErrorType::UnreachableBranch),
+                        ErrorSection::Code(pattern.source())
+                    ].into()));
+                }
+                if guard.is_none() { previous_rows.push(vec![Some(pattern)]); }
+            }
+            let typed_else_body = if let Some(else_body) = else_body {
+                let mut else_body_variables = variables.clone();
+                let mut else_body_uninitialized_variables = uninitialized_variables.clone();
+                let (typed_else_body, else_returns) = type_check_case_body!(else_body, case_types, &mut else_body_variables, &mut scope_variables.clone(), &mut else_body_uninitialized_variables);
+                branches_variables.push(else_body_variables);
+                branches_uninitialized_variables.push(else_body_uninitialized_variables);
+                branches_return.push(else_returns);
+                Some(typed_else_body)
+            } else {
+                let unguarded_rows = typed_branches.iter()
+                    .filter(|(_, guard, _)| guard.is_none())
+                    .map(|(pattern, _, _)| vec![Some(pattern)])
+                    .collect();
+                if let Some(witness) = find_unmatched_value(strings, type_scope!(), unguarded_rows, &[Some(typed_value.get_types())]) {
+                    return Err(Error::new([
+                        ErrorSection::Error(ErrorType::NonExhaustiveCase(witness.join(", "))),
+                        ErrorSection::Code(node_source)
+                    ].into()));
+                }
+                None
+            };
+            initalize_variables(
+                strings, type_scope!(), variables, uninitialized_variables,
+                &branches_variables,
+                &branches_uninitialized_variables,
+                &branches_return
+            )?;
+            Ok((
+                TypedAstNode::new(AstNodeVariant::CasePattern {
+                    value: Box::new(typed_value),
+                    branches: typed_branches,
+                    else_body: typed_else_body
+                },
+                case_types, node_source),
                 (branches_return.iter().find(|r| r.0).is_some(), branches_return.iter().find(|r| !r.1).is_none())
             ))
         }
@@ -878,7 +1848,7 @@ fn type_check_node(
             }, type_scope!().insert_group(&[Type::Unit]), node_source), (false, false)))
         }
         AstNodeVariant::Return { value } => {
-            let ret_vals_assertion = TypeAssertion::returned_values(procedure_source, return_types, type_scope!(), strings);
+            let ret_vals_assertion = TypeAssertion::returned_values(return_source, return_types, type_scope!(), strings);
             let typed_value = type_check_node!(
                 *value,
                 Some(ret_vals_assertion)
@@ -887,14 +1857,40 @@ fn type_check_node(
                 value: Box::new(typed_value)
             }, type_scope!().insert_group(&[Type::Unit]), node_source), (true, true)))
         }
+        AstNodeVariant::Propagate { value, returned_types: _ } => {
+            let ok_types = type_scope!().insert_group(&[Type::Any]);
+            let err_types = type_scope!().insert_group(&[Type::Any]);
+            let value_tidx = type_scope!().insert_variants(
+                [(strings.insert("ok"), ok_types), (strings.insert("err"), err_types)].into(), true
+            );
+            let value_types = type_scope!().insert_group(&[Type::Variants(value_tidx)]);
+            let value_assertion = TypeAssertion::propagated_value(node_source, value_types, type_scope!(), strings);
+            let typed_value = type_check_node!(*value, Some(value_assertion)).0;
+            let error_tidx = type_scope!().insert_variants([(strings.insert("err"), err_types)].into(), false);
+            let error_types = type_scope!().insert_group(&[Type::Variants(error_tidx)]);
+            assert_types(
+                TypeAssertion::propagated_error(node_source, error_types, type_scope!(), strings),
+                TypeAssertion::returned_values(return_source, return_types, type_scope!(), strings),
+                type_scope!()
+            )?;
+            if let Some(limited_to) = limited_to {
+                assert_types(
+                    TypeAssertion::propagation_result(node_source, ok_types, type_scope!(), strings),
+                    limited_to, type_scope!()
+                )?;
+            }
+            Ok((TypedAstNode::new(AstNodeVariant::Propagate {
+                value: Box::new(typed_value),
+                returned_types: Some(return_types)
+            }, ok_types, node_source), (true, false)))
+        }
         AstNodeVariant::Call { called, mut arguments } => {
             if let AstNodeVariant::ModuleAccess { path } = called.node_variant() {
                 match type_check_symbol(strings, global_type_scope, rec_procedures, untyped_symbols, symbols, &path).map(|s| s.clone()) {
-                    Ok(Symbol::Procedure { public: _, parameter_names, parameter_types, returns, body: _, source: _, type_scope: symbol_type_scope }) => {
-                        if arguments.len() != parameter_types.len() { return Err(Error::new([
-                            ErrorSection::Error(ErrorType::InvalidParameterCount(path.display(strings), parameter_types.len(), arguments.len())),
-                            ErrorSection::Code(node_source)
-                        ].into())) }
+                    Ok(Symbol::Procedure { public: _, parameter_names, parameter_defaults, parameter_types, returns, body: _, source: _, type_scope: symbol_type_scope }) => {
+                        let mut arguments = map_call_arguments(
+                            strings, path, &parameter_names, &parameter_defaults, arguments, node_source
+                        )?;
                         if let Some(rec_proc_idx) = rec_procedures
                                 .iter().position(|p| p.0 == *path) {
                             let mut typed_arguments = Vec::new();
@@ -924,8 +1920,12 @@ fn type_check_node(
                             }, returned_types, node_source), (false, false)));
                         } else {
                             let mut typed_arguments = Vec::new();
+                            let mut signature_types = parameter_types.clone();
+                            signature_types.push(returns);
+                            let mut signature_types = symbol_type_scope.transfer_groups(&signature_types, type_scope!());
+                            let returned_types = signature_types.pop().expect("should have the return types");
                             for argument_idx in 0..arguments.len() {
-                                let param_types = symbol_type_scope.transfer_group(parameter_types[argument_idx], type_scope!());
+                                let param_types = signature_types[argument_idx];
                                 let call_param_assertion = TypeAssertion::call_parameter(
                                     node_source, parameter_names[argument_idx],
                                     param_types,
@@ -936,7 +1936,6 @@ fn type_check_node(
                                     Some(call_param_assertion)
                                 ).0);
                             }
-                            let returned_types = symbol_type_scope.transfer_group(returns, type_scope!());
                             if let Some(limited_to) = limited_to {
                                 assert_types(
                                     TypeAssertion::call_return_value(node_source, returned_types, type_scope!(), strings),
@@ -954,6 +1953,33 @@ fn type_check_node(
                             }, returned_types, node_source), (false, false)));
                         }
                     }
+                    Ok(Symbol::Type { public: _, nominal, value_types }) => {
+                        if !nominal { return Err(Error::new([
+                            ErrorSection::Error(ErrorType::TypeUsedAsValue(path.display(strings))),
+                            ErrorSection::Code(called.source())
+                        ].into())) }
+                        if arguments.len() != 1 { return Err(Error::new([
+                            ErrorSection::Error(ErrorType::InvalidParameterCount(path.display(strings), 1, arguments.len())),
+                            ErrorSection::Code(node_source)
+                        ].into())) }
+                        let nominal_types = global_type_scope.transfer_group(value_types, type_scope!());
+                        let wrapped_types = if let Type::Object(object) = type_scope!().group_concrete(nominal_types) {
+                            let (member_types, fixed) = type_scope!().object(object).clone();
+                            let wrapped_object = type_scope!().insert_object(member_types, fixed);
+                            type_scope!().insert_group(&[Type::Object(wrapped_object)])
+                        } else { panic!("nominal types should always wrap objects!"); };
+                        let wrapped_assertion = TypeAssertion::wrapped_value(node_source, path, wrapped_types, type_scope!(), strings);
+                        let (mut typed_value, value_returns) = type_check_node!(arguments.remove(0), Some(wrapped_assertion));
+                        if let Some(limited_to) = limited_to {
+                            assert_types(
+                                TypeAssertion::constructed_value(node_source, nominal_types, type_scope!(), strings),
+                                limited_to, type_scope!()
+                            )?;
+                        }
+                        *typed_value.get_types_mut() = nominal_types;
+                        typed_value.replace_source(node_source);
+                        return Ok((typed_value, value_returns));
+                    }
                     Ok(_) => {}
                     Err(error) => return Err(error)
                 }
@@ -986,6 +2012,10 @@ fn type_check_node(
                 arguments: typed_arguments
             }, passed_return_type, node_source), (false, false)))
         }
+        AstNodeVariant::NamedArgument { name: _, value: _ } => Err(Error::new([
+            ErrorSection::Error(ErrorType::InvalidNamedArgument),
+            ErrorSection::Code(node_source)
+        ].into())),
         AstNodeVariant::Object { values } => {
             let mut member_types = HashMap::new();
             let mut typed_values = Vec::new();
@@ -1277,6 +2307,100 @@ fn type_check_node(
                 x: Box::new(x_typed),
             }, op_type, node_source), (false, false)))
         }
+        AstNodeVariant::BitwiseAnd { a, b } => {
+            let op_type = type_scope!().insert_group(&[Type::Integer]);
+            if let Some(limited_to) = limited_to {
+                assert_types(
+                    TypeAssertion::bitwise_result(node_source, op_type, type_scope!(), strings),
+                    limited_to, type_scope!()
+                )?;
+            }
+            let assertion = TypeAssertion::bitwise_argument(node_source, op_type, type_scope!(), strings);
+            let a_typed = type_check_node!(*a, Some(assertion.clone())).0;
+            let b_typed = type_check_node!(*b, Some(assertion)).0;
+            Ok((TypedAstNode::new(AstNodeVariant::BitwiseAnd {
+                a: Box::new(a_typed),
+                b: Box::new(b_typed)
+            }, op_type, node_source), (false, false)))
+        }
+        AstNodeVariant::BitwiseOr { a, b } => {
+            let op_type = type_scope!().insert_group(&[Type::Integer]);
+            if let Some(limited_to) = limited_to {
+                assert_types(
+                    TypeAssertion::bitwise_result(node_source, op_type, type_scope!(), strings),
+                    limited_to, type_scope!()
+                )?;
+            }
+            let assertion = TypeAssertion::bitwise_argument(node_source, op_type, type_scope!(), strings);
+            let a_typed = type_check_node!(*a, Some(assertion.clone())).0;
+            let b_typed = type_check_node!(*b, Some(assertion)).0;
+            Ok((TypedAstNode::new(AstNodeVariant::BitwiseOr {
+                a: Box::new(a_typed),
+                b: Box::new(b_typed)
+            }, op_type, node_source), (false, false)))
+        }
+        AstNodeVariant::BitwiseXor { a, b } => {
+            let op_type = type_scope!().insert_group(&[Type::Integer]);
+            if let Some(limited_to) = limited_to {
+                assert_types(
+                    TypeAssertion::bitwise_result(node_source, op_type, type_scope!(), strings),
+                    limited_to, type_scope!()
+                )?;
+            }
+            let assertion = TypeAssertion::bitwise_argument(node_source, op_type, type_scope!(), strings);
+            let a_typed = type_check_node!(*a, Some(assertion.clone())).0;
+            let b_typed = type_check_node!(*b, Some(assertion)).0;
+            Ok((TypedAstNode::new(AstNodeVariant::BitwiseXor {
+                a: Box::new(a_typed),
+                b: Box::new(b_typed)
+            }, op_type, node_source), (false, false)))
+        }
+        AstNodeVariant::BitwiseNot { x } => {
+            let op_type = type_scope!().insert_group(&[Type::Integer]);
+            if let Some(limited_to) = limited_to {
+                assert_types(
+                    TypeAssertion::bitwise_result(node_source, op_type, type_scope!(), strings),
+                    limited_to, type_scope!()
+                )?;
+            }
+            let assertion = TypeAssertion::bitwise_argument(node_source, op_type, type_scope!(), strings);
+            let x_typed = type_check_node!(*x, Some(assertion)).0;
+            Ok((TypedAstNode::new(AstNodeVariant::BitwiseNot {
+                x: Box::new(x_typed),
+            }, op_type, node_source), (false, false)))
+        }
+        AstNodeVariant::LeftShift { a, b } => {
+            let op_type = type_scope!().insert_group(&[Type::Integer]);
+            if let Some(limited_to) = limited_to {
+                assert_types(
+                    TypeAssertion::bitwise_result(node_source, op_type, type_scope!(), strings),
+                    limited_to, type_scope!()
+                )?;
+            }
+            let assertion = TypeAssertion::bitwise_argument(node_source, op_type, type_scope!(), strings);
+            let a_typed = type_check_node!(*a, Some(assertion.clone())).0;
+            let b_typed = type_check_node!(*b, Some(assertion)).0;
+            Ok((TypedAstNode::new(AstNodeVariant::LeftShift {
+                a: Box::new(a_typed),
+                b: Box::new(b_typed)
+            }, op_type, node_source), (false, false)))
+        }
+        AstNodeVariant::RightShift { a, b } => {
+            let op_type = type_scope!().insert_group(&[Type::Integer]);
+            if let Some(limited_to) = limited_to {
+                assert_types(
+                    TypeAssertion::bitwise_result(node_source, op_type, type_scope!(), strings),
+                    limited_to, type_scope!()
+                )?;
+            }
+            let assertion = TypeAssertion::bitwise_argument(node_source, op_type, type_scope!(), strings);
+            let a_typed = type_check_node!(*a, Some(assertion.clone())).0;
+            let b_typed = type_check_node!(*b, Some(assertion)).0;
+            Ok((TypedAstNode::new(AstNodeVariant::RightShift {
+                a: Box::new(a_typed),
+                b: Box::new(b_typed)
+            }, op_type, node_source), (false, false)))
+        }
         AstNodeVariant::LessThan { a, b } => {
             let boolean = type_scope!().insert_group(&[Type::Boolean]);
             if let Some(limited_to) = limited_to {
@@ -1444,7 +2568,7 @@ fn type_check_node(
                         path
                     }, value_types, node_source), (false, false)))
                 }
-                Ok(Symbol::Procedure { public: _, parameter_names, parameter_types: _, returns: _, body: _, source: _, type_scope: _ }) => {
+                Ok(Symbol::Procedure { public: _, parameter_names, parameter_defaults: _, parameter_types: _, returns: _, body: _, source: _, type_scope: _ }) => {
                     // 'io::println' --> '|x| io::println(x)'
                     let parameter_names = parameter_names.clone();
                     Ok(type_check_node!(AstNode::new(
@@ -1475,6 +2599,10 @@ fn type_check_node(
                         node_source
                     ), None))
                 }
+                Ok(Symbol::Type { public: _, nominal: _, value_types: _ }) => Err(Error::new([
+                    ErrorSection::Error(ErrorType::TypeUsedAsValue(path.display(strings))),
+                    ErrorSection::Code(node_source)
+                ].into())),
                 Err(error) => return Err(error)
             }
         }
@@ -1564,6 +2692,7 @@ pub fn display_types(
             Type::String => {}
             Type::Array(arr) => collect_letters(letters, type_scope.array(*arr), type_scope),
             Type::Object(obj) => {
+                if type_scope.object_name(*obj).is_some() { return; }
                 for member_types in type_scope.object(*obj).0.values().map(|t| *t).collect::<Vec<TypeGroup>>() {
                     collect_letters(letters, member_types, type_scope);
                 }
@@ -1624,6 +2753,9 @@ pub fn display_types(
                 display_types_internal(strings, type_scope, type_scope.array(*arr), letters)
             ),
             Type::Object(obj) => {
+                if let Some((name, _)) = type_scope.object_name(*obj) {
+                    return name.display(strings);
+                }
                 let (member_types, fixed) = type_scope.object(*obj);
                 format!(
                     "{{ {}{} }}",
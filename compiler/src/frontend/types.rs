@@ -1,32 +1,38 @@
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::util::strings::StringIdx;
+use crate::frontend::{modules::NamespacePath, cache::{CacheWriter, CacheReader}};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct TypeGroup(usize, usize);
-impl TypeGroup { pub fn scope_id(&self) -> usize { self.1 } }
+impl TypeGroup {
+    pub fn scope_id(&self) -> usize { self.1 }
+    pub(crate) fn index(&self) -> usize { self.0 }
+    pub(crate) fn from_index(index: usize, scope_id: usize) -> TypeGroup { TypeGroup(index, scope_id) }
+}
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ArrayType(usize);
 impl ArrayType { pub fn get_internal_id(&self) -> usize { self.0 } }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ObjectType(usize);
 impl ObjectType { pub fn get_internal_id(&self) -> usize { self.0 } }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ConcreteObjectType(usize);
 impl ConcreteObjectType { pub fn get_internal_id(&self) -> usize { self.0 } }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ClosureType(usize);
 impl ClosureType { pub fn get_internal_id(&self) -> usize { self.0 } }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct VariantsType(usize);
 impl VariantsType { pub fn get_internal_id(&self) -> usize { self.0 } }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Type {
     Any,
     Unit,
@@ -41,7 +47,17 @@ pub enum Type {
     Variants(VariantsType)
 }
 
-static mut NEXT_ID: usize = 0;
+/// Pairs of groups (identified by their scope's address and their internal id)
+/// that are assumed to be equal while comparing recursive types.
+type EncounteredGroups = ((usize, usize), (usize, usize));
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+fn sorted_members(members: HashMap<StringIdx, TypeGroup>) -> Vec<(StringIdx, TypeGroup)> {
+    let mut members = members.into_iter().collect::<Vec<(StringIdx, TypeGroup)>>();
+    members.sort_by_key(|(name, _)| name.0);
+    members
+}
 
 #[derive(Debug, Clone)]
 pub struct TypeScope {
@@ -50,6 +66,7 @@ pub struct TypeScope {
     group_types: Vec<HashSet<Type>>,
     arrays: Vec<TypeGroup>,
     objects: Vec<(HashMap<StringIdx, TypeGroup>, bool)>,
+    object_names: Vec<Option<(NamespacePath, bool)>>,
     concrete_objects: Vec<Vec<(StringIdx, TypeGroup)>>,
     closures: Vec<(
         Vec<TypeGroup>, TypeGroup, Option<HashMap<StringIdx, TypeGroup>>
@@ -59,17 +76,13 @@ pub struct TypeScope {
 
 impl TypeScope {
     pub fn new() -> TypeScope {
-        let id;
-        unsafe {
-            id = NEXT_ID;
-            NEXT_ID += 1;
-        }
         TypeScope {
-            id,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
             groups: Vec::new(),
             group_types: Vec::new(),
             arrays: Vec::new(),
             objects: Vec::new(),
+            object_names: Vec::new(),
             concrete_objects: Vec::new(),
             closures: Vec::new(),
             variants: Vec::new()
@@ -78,6 +91,8 @@ impl TypeScope {
 
     pub fn id(&self) -> usize { self.id }
 
+    pub(crate) fn group_count(&self) -> usize { self.groups.len() }
+
     pub fn internal_arrays(&self) -> &Vec<TypeGroup> { &self.arrays }
     pub fn insert_array(
         &mut self, element_type: TypeGroup
@@ -103,10 +118,17 @@ impl TypeScope {
         -> &Vec<(HashMap<StringIdx, TypeGroup>, bool)> { &self.objects }
     pub fn insert_object(
         &mut self, member_types: HashMap<StringIdx, TypeGroup>, fixed: bool
+    ) -> ObjectType {
+        return self.insert_named_object(member_types, fixed, None);
+    }
+    pub fn insert_named_object(
+        &mut self, member_types: HashMap<StringIdx, TypeGroup>, fixed: bool,
+        name: Option<(NamespacePath, bool)>
     ) -> ObjectType {
         let object_value = (member_types, fixed);
         let idx = self.objects.len();
         self.objects.push(object_value);
+        self.object_names.push(name);
         return ObjectType(idx);
     }
     pub fn insert_dedup_object(&mut self, v: (HashMap<StringIdx, TypeGroup>, bool)) -> ObjectType {
@@ -123,6 +145,11 @@ impl TypeScope {
     ) -> &(HashMap<StringIdx, TypeGroup>, bool) {
         &self.objects[object.0]
     }
+    pub fn object_name(
+        &self, object: ObjectType
+    ) -> &Option<(NamespacePath, bool)> {
+        &self.object_names[object.0]
+    }
 
     pub fn internal_concrete_objects(&self)
         -> &Vec<Vec<(StringIdx, TypeGroup)>> {
@@ -348,6 +375,21 @@ impl TypeScope {
             (Type::Object(obj_a), Type::Object(obj_b)) => {
                 let (members_a, fixed_a) = self.object(obj_a).clone();
                 let (members_b, fixed_b) = self.object(obj_b).clone();
+                let name_a = self.object_name(obj_a).clone();
+                let name_b = self.object_name(obj_b).clone();
+                let nominal_a = name_a.as_ref().filter(|(_, nominal)| *nominal);
+                let nominal_b = name_b.as_ref().filter(|(_, nominal)| *nominal);
+                let names_compatible = match (nominal_a, nominal_b) {
+                    (Some(nominal_a), Some(nominal_b)) => nominal_a == nominal_b,
+                    (Some(_), None) => name_b.is_none() && !fixed_b,
+                    (None, Some(_)) => name_a.is_none() && !fixed_a,
+                    (None, None) => true
+                };
+                if !names_compatible { return None }
+                let merged_name = nominal_a.or(nominal_b)
+                    .or(name_a.as_ref())
+                    .or(name_b.as_ref())
+                    .cloned();
                 let member_names = members_a.keys().chain(members_b.keys())
                     .map(|n| *n).collect::<HashSet<StringIdx>>();
                 let mut new_members = HashMap::new();
@@ -377,8 +419,8 @@ impl TypeScope {
                         (None, None) => panic!("Impossible!")
                     }
                 }
-                Some(Type::Object(self.insert_object(
-                    new_members, fixed_a || fixed_b
+                Some(Type::Object(self.insert_named_object(
+                    new_members, fixed_a || fixed_b, merged_name
                 )))
             }
             (Type::Closure(clo_a), Type::Closure(clo_b)) => {
@@ -453,6 +495,15 @@ impl TypeScope {
         self.transfer_group_internal(group, dest, &mut HashMap::new())
     }
 
+    pub fn transfer_groups(
+        &self, groups: &[TypeGroup], dest: &mut TypeScope
+    ) -> Vec<TypeGroup> {
+        let mut encountered = HashMap::new();
+        groups.iter()
+            .map(|group| self.transfer_group_internal(*group, dest, &mut encountered))
+            .collect()
+    }
+
     fn transfer_group_internal(
         &self, group: TypeGroup, dest: &mut TypeScope,
         encountered: &mut HashMap<usize, TypeGroup>
@@ -463,8 +514,9 @@ impl TypeScope {
         }
         let transferred_group = dest.insert_group(&[]);
         encountered.insert(internal_idx, transferred_group);
-        let transferred_types = self.group(group)
-            .collect::<Vec<Type>>().into_iter()
+        let mut types = self.group(group).collect::<Vec<Type>>();
+        types.sort();
+        let transferred_types = types.into_iter()
             .map(|t| self.transfer_type_internal(t, dest, encountered))
             .collect::<Vec<Type>>(); 
         dest.set_group_types(transferred_group, &transferred_types);
@@ -483,13 +535,13 @@ impl TypeScope {
             },
             Type::Object(obj) => {
                 let (old_members, fixed) = self.object(obj).clone();
-                let new_members = old_members.into_iter().map(|(mn, mt)| (
+                let new_members = sorted_members(old_members).into_iter().map(|(mn, mt)| (
                     mn,
                     self.transfer_group_internal(
                         mt, dest, encountered
                     )
                 )).collect();
-                Type::Object(dest.insert_object(new_members, fixed))
+                Type::Object(dest.insert_named_object(new_members, fixed, self.object_name(obj).clone()))
             }
             Type::ConcreteObject(obj) => {
                 let old_members = self.concrete_object(obj).clone();
@@ -507,7 +559,7 @@ impl TypeScope {
                     t, dest, encountered
                 )).collect();
                 let new_return_type = self.transfer_group_internal(old_return_type, dest, encountered);
-                let new_captures = old_captures.map(|c| c.into_iter().map(|(cn, ct)| (
+                let new_captures = old_captures.map(|c| sorted_members(c).into_iter().map(|(cn, ct)| (
                     cn,
                     self.transfer_group_internal(ct, dest, encountered)
                 )).collect());
@@ -519,7 +571,7 @@ impl TypeScope {
             }
             Type::Variants(var) => {
                 let (old_variants, fixed) = self.variants(var).clone();
-                let new_variants = old_variants.into_iter().map(|(vn, vt)| (
+                let new_variants = sorted_members(old_variants).into_iter().map(|(vn, vt)| (
                     vn,
                     self.transfer_group_internal(
                         vt, dest, encountered
@@ -548,12 +600,16 @@ impl TypeScope {
 
     fn internal_groups_eq(
         a: TypeGroup, a_scope: &TypeScope, b: TypeGroup, b_scope: &TypeScope,
-        encountered: &mut HashSet<(usize, usize)>
+        encountered: &mut HashSet<EncounteredGroups>
     ) -> bool {
         let a_internal = a_scope.group_internal_id(a);
         let b_internal = b_scope.group_internal_id(b);
-        if a_internal == b_internal { return true; }
-        let internal = (a_internal, b_internal);
+        // internal ids are only unique inside of a single scope (and clones share scope ids)
+        if std::ptr::eq(a_scope, b_scope) && a_internal == b_internal { return true; }
+        let internal = (
+            (a_scope as *const TypeScope as usize, a_internal),
+            (b_scope as *const TypeScope as usize, b_internal)
+        );
         if encountered.contains(&internal) { return true; }
         encountered.insert(internal);
         let mut result = true;
@@ -577,11 +633,11 @@ impl TypeScope {
 
     fn internal_types_eq(
         a: Type, a_scope: &TypeScope, b: Type, b_scope: &TypeScope,
-        encountered: &mut HashSet<(usize, usize)>
+        encountered: &mut HashSet<EncounteredGroups>
     ) -> bool {
         match (a, b) {
             (Type::Array(arr_a), Type::Array(arr_b)) => {
-                if arr_a.get_internal_id() == arr_b.get_internal_id() { return true; }
+                if std::ptr::eq(a_scope, b_scope) && arr_a.get_internal_id() == arr_b.get_internal_id() { return true; }
                 TypeScope::internal_arrays_eq(
                     a_scope.array(arr_a), a_scope,
                     b_scope.array(arr_b), b_scope,
@@ -589,7 +645,7 @@ impl TypeScope {
                 )
             }
             (Type::Object(obj_a), Type::Object(obj_b)) => {
-                if obj_a.get_internal_id() == obj_b.get_internal_id() { return true; }
+                if std::ptr::eq(a_scope, b_scope) && obj_a.get_internal_id() == obj_b.get_internal_id() { return true; }
                 TypeScope::internal_objects_eq(
                     a_scope.object(obj_a), a_scope,
                     b_scope.object(obj_b), b_scope,
@@ -597,7 +653,7 @@ impl TypeScope {
                 )
             }
             (Type::ConcreteObject(obj_a), Type::ConcreteObject(obj_b)) => {
-                if obj_a.get_internal_id() == obj_b.get_internal_id() { return true; }
+                if std::ptr::eq(a_scope, b_scope) && obj_a.get_internal_id() == obj_b.get_internal_id() { return true; }
                 TypeScope::internal_concrete_objects_eq(
                     a_scope.concrete_object(obj_a), a_scope,
                     b_scope.concrete_object(obj_b), b_scope,
@@ -605,7 +661,7 @@ impl TypeScope {
                 )
             }
             (Type::Closure(clo_a), Type::Closure(clo_b)) => {
-                if clo_a.get_internal_id() == clo_b.get_internal_id() { return true; }
+                if std::ptr::eq(a_scope, b_scope) && clo_a.get_internal_id() == clo_b.get_internal_id() { return true; }
                 TypeScope::internal_closures_eq(
                     a_scope.closure(clo_a), a_scope,
                     b_scope.closure(clo_b), b_scope,
@@ -613,7 +669,7 @@ impl TypeScope {
                 )
             }
             (Type::Variants(var_a), Type::Variants(var_b)) => {
-                if var_a.get_internal_id() == var_b.get_internal_id() { return true; }
+                if std::ptr::eq(a_scope, b_scope) && var_a.get_internal_id() == var_b.get_internal_id() { return true; }
                 TypeScope::internal_variants_eq(
                     a_scope.variants(var_a), a_scope,
                     b_scope.variants(var_b), b_scope,
@@ -628,7 +684,7 @@ impl TypeScope {
 
     fn internal_arrays_eq(
         a: TypeGroup, a_scope: &TypeScope, b: TypeGroup, b_scope: &TypeScope,
-        encountered: &mut HashSet<(usize, usize)>
+        encountered: &mut HashSet<EncounteredGroups>
     ) -> bool {
         TypeScope::internal_groups_eq(a, a_scope, b, b_scope, encountered)
     }
@@ -636,7 +692,7 @@ impl TypeScope {
     fn internal_objects_eq(
         a: &(HashMap<StringIdx, TypeGroup>, bool), a_scope: &TypeScope,
         b: &(HashMap<StringIdx, TypeGroup>, bool), b_scope: &TypeScope,
-        encountered: &mut HashSet<(usize, usize)>
+        encountered: &mut HashSet<EncounteredGroups>
     ) -> bool {
         let a = &a.0;
         let b = &b.0;
@@ -662,7 +718,7 @@ impl TypeScope {
     fn internal_concrete_objects_eq(
         a: &Vec<(StringIdx, TypeGroup)>, a_scope: &TypeScope,
         b: &Vec<(StringIdx, TypeGroup)>, b_scope: &TypeScope,
-        encountered: &mut HashSet<(usize, usize)>
+        encountered: &mut HashSet<EncounteredGroups>
     ) -> bool {
         if a.len() != b.len() { return false; }
         for member_idx in 0..a.len() {
@@ -678,7 +734,7 @@ impl TypeScope {
     fn internal_closures_eq(
         a: &(Vec<TypeGroup>, TypeGroup, Option<HashMap<StringIdx, TypeGroup>>), a_scope: &TypeScope,
         b: &(Vec<TypeGroup>, TypeGroup, Option<HashMap<StringIdx, TypeGroup>>), b_scope: &TypeScope,
-        encountered: &mut HashSet<(usize, usize)>
+        encountered: &mut HashSet<EncounteredGroups>
     ) -> bool {
         let (a_params, a_return, _) = a;
         let (b_params, b_return, _) = b;
@@ -696,7 +752,7 @@ impl TypeScope {
     fn internal_variants_eq(
         a: &(HashMap<StringIdx, TypeGroup>, bool), a_scope: &TypeScope,
         b: &(HashMap<StringIdx, TypeGroup>, bool), b_scope: &TypeScope,
-        encountered: &mut HashSet<(usize, usize)>
+        encountered: &mut HashSet<EncounteredGroups>
     ) -> bool {
         let a = &a.0;
         let b = &b.0;
@@ -759,6 +815,7 @@ impl TypeScope {
             let new_object_idx = new.objects.len();
             mapped_objects.insert(og_object_idx, new_object_idx);
             new.objects.push(self.objects[og_object_idx].clone());
+            new.object_names.push(self.object_names[og_object_idx].clone());
         }
         // deduplicate concrete objects
         let mut mapped_concrete_objects = HashMap::new();
@@ -869,3 +926,138 @@ impl TypeScope {
         }
     }
 }
+
+impl Type {
+    fn write_cached(&self, writer: &mut CacheWriter) {
+        match self {
+            Type::Any => writer.write_u8(0),
+            Type::Unit => writer.write_u8(1),
+            Type::Boolean => writer.write_u8(2),
+            Type::Integer => writer.write_u8(3),
+            Type::Float => writer.write_u8(4),
+            Type::String => writer.write_u8(5),
+            Type::Array(arr) => { writer.write_u8(6); writer.write_usize(arr.0); }
+            Type::Object(obj) => { writer.write_u8(7); writer.write_usize(obj.0); }
+            Type::ConcreteObject(obj) => { writer.write_u8(8); writer.write_usize(obj.0); }
+            Type::Closure(clo) => { writer.write_u8(9); writer.write_usize(clo.0); }
+            Type::Variants(var) => { writer.write_u8(10); writer.write_usize(var.0); }
+        }
+    }
+
+    fn read_cached(reader: &mut CacheReader) -> Option<Type> {
+        Some(match reader.read_u8()? {
+            0 => Type::Any,
+            1 => Type::Unit,
+            2 => Type::Boolean,
+            3 => Type::Integer,
+            4 => Type::Float,
+            5 => Type::String,
+            6 => Type::Array(ArrayType(reader.read_usize()?)),
+            7 => Type::Object(ObjectType(reader.read_usize()?)),
+            8 => Type::ConcreteObject(ConcreteObjectType(reader.read_usize()?)),
+            9 => Type::Closure(ClosureType(reader.read_usize()?)),
+            10 => Type::Variants(VariantsType(reader.read_usize()?)),
+            _ => return None
+        })
+    }
+}
+
+impl TypeScope {
+    pub(crate) fn write_cached(&self, writer: &mut CacheWriter) {
+        writer.set_scope(Some(self.id));
+        writer.write_usize(self.groups.len());
+        for internal_idx in &self.groups { writer.write_usize(*internal_idx); }
+        writer.write_usize(self.group_types.len());
+        for group_types in &self.group_types {
+            writer.write_usize(group_types.len());
+            for t in group_types { t.write_cached(writer); }
+        }
+        writer.write_usize(self.arrays.len());
+        for element_types in &self.arrays { writer.write_group(*element_types); }
+        writer.write_usize(self.objects.len());
+        for ((member_types, fixed), name) in self.objects.iter().zip(&self.object_names) {
+            writer.write_group_map(member_types);
+            writer.write_bool(*fixed);
+            writer.write_bool(name.is_some());
+            if let Some((path, nominal)) = name {
+                writer.write_path(path);
+                writer.write_bool(*nominal);
+            }
+        }
+        writer.write_usize(self.concrete_objects.len());
+        for member_types in &self.concrete_objects {
+            writer.write_usize(member_types.len());
+            for (member_name, member_types) in member_types {
+                writer.write_string(*member_name);
+                writer.write_group(*member_types);
+            }
+        }
+        writer.write_usize(self.closures.len());
+        for (param_types, return_types, captures) in &self.closures {
+            writer.write_usize(param_types.len());
+            for param_type in param_types { writer.write_group(*param_type); }
+            writer.write_group(*return_types);
+            writer.write_bool(captures.is_some());
+            if let Some(captures) = captures { writer.write_group_map(captures); }
+        }
+        writer.write_usize(self.variants.len());
+        for (variant_types, fixed) in &self.variants {
+            writer.write_group_map(variant_types);
+            writer.write_bool(*fixed);
+        }
+    }
+
+    pub(crate) fn read_cached(reader: &mut CacheReader) -> Option<TypeScope> {
+        let mut scope = TypeScope::new();
+        for _ in 0..reader.read_usize()? { scope.groups.push(reader.read_usize()?); }
+        reader.set_scope(scope.id, scope.groups.len());
+        for _ in 0..reader.read_usize()? {
+            let mut group_types = HashSet::new();
+            for _ in 0..reader.read_usize()? { group_types.insert(Type::read_cached(reader)?); }
+            scope.group_types.push(group_types);
+        }
+        for _ in 0..reader.read_usize()? { scope.arrays.push(reader.read_group()?); }
+        for _ in 0..reader.read_usize()? {
+            let member_types = reader.read_group_map()?;
+            let fixed = reader.read_bool()?;
+            let name = if reader.read_bool()? {
+                Some((reader.read_path()?, reader.read_bool()?))
+            } else { None };
+            scope.objects.push((member_types, fixed));
+            scope.object_names.push(name);
+        }
+        for _ in 0..reader.read_usize()? {
+            let mut member_types = Vec::new();
+            for _ in 0..reader.read_usize()? {
+                member_types.push((reader.read_string()?, reader.read_group()?));
+            }
+            scope.concrete_objects.push(member_types);
+        }
+        for _ in 0..reader.read_usize()? {
+            let mut param_types = Vec::new();
+            for _ in 0..reader.read_usize()? { param_types.push(reader.read_group()?); }
+            let return_types = reader.read_group()?;
+            let captures = if reader.read_bool()? {
+                Some(reader.read_group_map()?)
+            } else { None };
+            scope.closures.push((param_types, return_types, captures));
+        }
+        for _ in 0..reader.read_usize()? {
+            let variant_types = reader.read_group_map()?;
+            let fixed = reader.read_bool()?;
+            scope.variants.push((variant_types, fixed));
+        }
+        let internal_groups_valid = scope.groups.iter()
+            .all(|internal_idx| *internal_idx < scope.group_types.len());
+        let types_valid = scope.group_types.iter().flatten().all(|t| match t {
+            Type::Array(arr) => arr.0 < scope.arrays.len(),
+            Type::Object(obj) => obj.0 < scope.objects.len(),
+            Type::ConcreteObject(obj) => obj.0 < scope.concrete_objects.len(),
+            Type::Closure(clo) => clo.0 < scope.closures.len(),
+            Type::Variants(var) => var.0 < scope.variants.len(),
+            _ => true
+        });
+        if !internal_groups_valid || !types_valid { return None; }
+        Some(scope)
+    }
+}
@@ -3,13 +3,14 @@ pub mod backend;
 pub mod frontend;
 pub mod util;
 pub mod builtin;
+pub mod bindgen;
+pub mod session;
 
 use util::{
     strings::{StringMap, StringIdx},
     error::{Error, ErrorSection, ErrorType},
     source::HasSource
 };
-use builtin::load_builtins;
 use frontend::{
     lexer::Lexer,
     parser::Parser,
@@ -17,70 +18,75 @@ use frontend::{
     grammar_checking::{check_grammar, ScopeType},
     modules::{Module, NamespacePath},
     type_checking::{type_check_modules, Symbol}, external::ExternalMappingParser,
-    types::TypeScope, target_macro::process_target_blocks
-};
-use backend::{
-    lowering::lower_typed_ast,
-    target::CompileTarget,
-    c::generate_c,
-    javascript::generate_javascript,
-    symbols::generate_symbols
+    types::TypeScope, target_macro::process_target_blocks,
+    cache::ModuleCache
 };
+use session::Session;
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
 pub fn compile(
     strings: &mut StringMap,
     files: HashMap<StringIdx, StringIdx>,
     target_str: &str,
-    main_proc: Option<String>
+    main_proc: Option<String>,
+    cache: Option<&ModuleCache>
 ) -> Result<String, Vec<Error>> {
-    let targets: HashMap<String, CompileTarget> = HashMap::from([
-        ("c".into(), CompileTarget::IrConsumer(generate_c)),
-        ("js".into(), CompileTarget::IrConsumer(generate_javascript)),
-        ("symbols".into(), CompileTarget::TypedAstConsumer(generate_symbols))
-    ]);
-    let selected_target = targets.get(target_str).map(|t| Ok(t)).unwrap_or_else(|| Err(vec![Error::new([
-        ErrorSection::Error(ErrorType::InvalidCompileTarget(target_str.to_string())),
-        ErrorSection::Help(format!("List of available targets: {}", targets.iter().map(|t| format!("\n- {}", t.0)).collect::<Vec<String>>().join("")))
-    ].into())]))?;
-    // load builtins
-    let mut modules = HashMap::new();
-    let mut global_type_scope = TypeScope::new();
-    let mut typed_symbols = HashMap::new();
-    let mut external_backings = HashMap::new();
-    load_builtins(&target_str, strings, &mut modules, &mut global_type_scope, &mut typed_symbols, &mut external_backings);
-    // process all files
-    let mut file_process_errors = Vec::new();
-    for (file_name, file_content) in files {
-        process_file(
-            file_name, file_content, target_str,
-            strings, &mut modules, &mut global_type_scope, &mut typed_symbols, &mut external_backings
-        ).unwrap_or_else(|mut errors| file_process_errors.append(&mut errors));
-    }
-    if file_process_errors.len() > 0 { return Err(file_process_errors); }
-    //println!("parsing done");
-    // canonicalize modules
-    let module_paths = modules.keys().map(|p| p.clone()).collect::<Vec<NamespacePath>>();
-    for module_path in module_paths {
-        let mut module = modules.remove(&module_path).expect("key must be valid");
-        let canonicalization_errors = module.canonicalize(&modules, strings);
-        modules.insert(module_path, module);
-        if canonicalization_errors.len() > 0 { return Err(canonicalization_errors) }
-    }
-    //println!("canonicalization done");
-    // if target consumes AST, pass it the typed AST and return the result
-    if let CompileTarget::AstConsumer(generator) = selected_target {
-        return Ok((generator)(global_type_scope, modules, external_backings, strings));
-    }
-    // type check
-    type_check_modules(modules, &strings, &mut global_type_scope, &mut typed_symbols)?;
-    //println!("type checking done");
-    // if target consumes typed AST, pass it the typed AST and return the result
-    if let CompileTarget::TypedAstConsumer(generator) = selected_target {
-        return Ok((generator)(global_type_scope, typed_symbols, external_backings, strings));
-    }
-    // find main procedure
+    with_session(strings, files, target_str, cache, |session| session.compile(main_proc))
+}
+
+pub fn compile_with_header(
+    strings: &mut StringMap,
+    files: HashMap<StringIdx, StringIdx>,
+    target_str: &str,
+    main_proc: Option<String>,
+    cache: Option<&ModuleCache>
+) -> Result<(String, String), Vec<Error>> {
+    with_session(strings, files, target_str, cache, |session| session.compile_with_header(main_proc))
+}
+
+pub fn compile_split(
+    strings: &mut StringMap,
+    files: HashMap<StringIdx, StringIdx>,
+    target_str: &str,
+    main_proc: Option<String>,
+    cache: Option<&ModuleCache>
+) -> Result<Vec<(String, String)>, Vec<Error>> {
+    with_session(strings, files, target_str, cache, |session| session.compile_split(main_proc))
+}
+
+pub fn compile_library(
+    strings: &mut StringMap,
+    files: HashMap<StringIdx, StringIdx>,
+    target_str: &str,
+    exported_procs: Vec<String>,
+    cache: Option<&ModuleCache>
+) -> Result<(String, String), Vec<Error>> {
+    with_session(strings, files, target_str, cache, |session| session.compile_library(&exported_procs))
+}
+
+fn with_session<R>(
+    strings: &mut StringMap,
+    files: HashMap<StringIdx, StringIdx>,
+    target_str: &str,
+    cache: Option<&ModuleCache>,
+    f: impl FnOnce(&mut Session) -> R
+) -> R {
+    let mut session = Session::from_files(std::mem::replace(strings, StringMap::new()), files, target_str);
+    session.set_cache(cache.cloned());
+    let result = f(&mut session);
+    *strings = session.into_strings();
+    result
+}
+
+fn find_main_procedure<'s>(
+    strings: &mut StringMap,
+    typed_symbols: &'s HashMap<NamespacePath, Symbol<TypedAstNode>>,
+    main_proc: Option<String>,
+    target_str: &str
+) -> Result<(NamespacePath, &'s Symbol<TypedAstNode>), Vec<Error>> {
     let main_proc = main_proc.map(|p| Ok(p)).unwrap_or_else(|| Err(vec![Error::new([
             ErrorSection::Error(ErrorType::NoMainProcedureDefined(target_str.to_string()))
         ].into())]))?;
@@ -108,79 +114,39 @@ pub fn compile(
         ErrorSection::Error(ErrorType::InvalidMainProcedure(main_procedure_path.display(&strings))),
         ErrorSection::Help(String::from("The main procedure needs to be a procedure without any arguments."))
     ].into())]); };
-    // lower typed AST
-    let ir_symbols = lower_typed_ast(
-        strings, &mut global_type_scope, &typed_symbols, &external_backings,
-        (&main_procedure_path, main_procedure)
-    ).map_err(|e| vec![e])?;
-    //println!("lowering done");
-    // if target consumes IR, pass it the IR and return the result
-    if let CompileTarget::IrConsumer(generator) = selected_target {
-        return Ok((generator)(ir_symbols, global_type_scope, main_procedure_path, strings));
+    Ok((main_procedure_path, main_procedure))
+}
+
+fn type_check(
+    modules: HashMap<NamespacePath, Module<AstNode>>,
+    files: &HashMap<StringIdx, StringIdx>,
+    target_str: &str,
+    cache: Option<&ModuleCache>,
+    strings: &StringMap,
+    global_type_scope: &mut TypeScope,
+    typed_symbols: &mut HashMap<NamespacePath, Symbol<TypedAstNode>>
+) -> Result<(), Vec<Error>> {
+    if let Some(cache) = cache {
+        cache.type_check_modules(modules, files, target_str, strings, global_type_scope, typed_symbols)
+    } else {
+        type_check_modules(modules, strings, global_type_scope, typed_symbols)
     }
-    // done!
-    return Ok(String::new())
 }
 
 pub fn process_file(
     file_path: StringIdx,
     file_content: StringIdx,
     target_str: &str,
+    cache: Option<&ModuleCache>,
     strings: &mut StringMap,
     modules: &mut HashMap<NamespacePath, Module<AstNode>>,
     global_type_scope: &mut TypeScope,
     typed_symbols: &mut HashMap<NamespacePath, Symbol<TypedAstNode>>,
-    external_backings: &mut HashMap<NamespacePath, StringIdx>
+    external_backings: &mut HashMap<NamespacePath, Option<StringIdx>>
 ) -> Result<(), Vec<Error>> {
     if strings.get(file_path).ends_with(".gera") {
-        // parse the file
-        let mut lexer = Lexer::new(file_path, file_content, strings);
-        let mut parser = match Parser::new(strings, &mut lexer) {
-            None => None,
-            Some(Err(error)) => { return Err(vec![error]) },
-            Some(Ok(parser)) => Some(parser)
-        };
-        let mut nodes = if let Some(parser) = parser.as_mut() {
-            match parser.parse_block(strings, &mut lexer) {
-                Ok(tree) => tree,
-                Err(error) => return Err(vec![error])
-            }
-        } else { Vec::new() };
-        // check for grammar errors
-        let mut grammar_errors = Vec::new();
-        check_grammar(&nodes, ScopeType::GlobalStatement, &mut grammar_errors);
-        if grammar_errors.len() > 0 { return Err(grammar_errors); }
-        // expand target macrocs
-        process_target_blocks(&mut nodes, target_str, strings);
-        // put the file into a module
-        let mut module_path: NamespacePath = NamespacePath::new(Vec::new());
-        if nodes.len() == 0 || match nodes[0].node_variant() {
-            AstNodeVariant::Module { path } => {
-                module_path = path.clone();
-                false
-            }
-            _ => true
-        } { return Err(vec![Error::new([
-            ErrorSection::Error(ErrorType::ModuleDeclarationNotAtTop),
-            ErrorSection::Info(format!("In the file '{}'", strings.get(file_path))),
-        ].into())]) }
-        let module_declaration = nodes.remove(0);
-        if let Some(module) = modules.get_mut(&module_path) {
-            if !module.is_raw() {
-                return Err(vec![Error::new([
-                    ErrorSection::Error(ErrorType::ModuleAlreadyDefined(module_path.display(strings))),
-                    ErrorSection::Code(module_declaration.source()),
-                    ErrorSection::Info(format!("'{}' is already defined in the file '{}'", module_path.display(strings), strings.get(module.file_name())))
-                ].into())]);
-            }
-            module.bake(module_declaration.source().file_name(), nodes, strings)?;
-        } else {
-            modules.insert(
-                module_path.clone(),
-                Module::new(module_path.clone(), module_declaration.source().file_name(), nodes, strings)?
-            );
-        }
-        Ok(())
+        let nodes = load_or_parse_file(file_path, file_content, target_str, cache, strings)?;
+        add_file_module(nodes, file_path, strings, modules)
     } else if strings.get(file_path).ends_with(".gem") {
         // parse the file
         let mut lexer = Lexer::new(file_path, file_content, strings);
@@ -188,7 +154,7 @@ pub fn process_file(
             None => Ok(()),
             Some(Err(error)) => Err(vec![error]),
             Some(Ok(mut parser)) => if let Err(error) = 
-                    parser.parse_header(strings, &mut lexer, modules, global_type_scope, typed_symbols, external_backings) {
+                    parser.parse_header(strings, &mut lexer, target_str, modules, global_type_scope, typed_symbols, external_backings) {
                 Err(vec![error])
             } else {
                 Ok(())
@@ -201,4 +167,118 @@ pub fn process_file(
         ].into())])
     }
     
-}
\ No newline at end of file
+}
+
+fn parse_file(
+    file_path: StringIdx,
+    file_content: StringIdx,
+    target_str: &str,
+    strings: &StringMap
+) -> Result<Vec<AstNode>, Vec<Error>> {
+    // parse the file
+    let mut lexer = Lexer::new(file_path, file_content, strings);
+    let mut parser = match Parser::new(strings, &mut lexer) {
+        None => None,
+        Some(Err(error)) => { return Err(vec![error]) },
+        Some(Ok(parser)) => Some(parser)
+    };
+    let mut nodes = if let Some(parser) = parser.as_mut() {
+        match parser.parse_block(strings, &mut lexer) {
+            Ok(tree) => tree,
+            Err(error) => return Err(vec![error])
+        }
+    } else { Vec::new() };
+    // check for grammar errors
+    let mut grammar_errors = Vec::new();
+    check_grammar(&nodes, ScopeType::GlobalStatement, &mut grammar_errors);
+    if grammar_errors.len() > 0 { return Err(grammar_errors); }
+    // expand target macrocs
+    process_target_blocks(&mut nodes, target_str, strings);
+    Ok(nodes)
+}
+
+fn parse_files(
+    files: &HashMap<StringIdx, StringIdx>,
+    target_str: &str,
+    cache: Option<&ModuleCache>,
+    strings: &StringMap
+) -> HashMap<StringIdx, Result<Vec<AstNode>, Vec<Error>>> {
+    let source_files = files.iter()
+        .filter(|(file_name, _)| strings.get(**file_name).ends_with(".gera"))
+        .map(|(file_name, file_content)| (*file_name, *file_content))
+        .collect::<Vec<(StringIdx, StringIdx)>>();
+    let next_file = AtomicUsize::new(0);
+    let thread_count = thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1)
+        .min(source_files.len());
+    thread::scope(|scope| {
+        let workers = (0..thread_count).map(|_| scope.spawn(|| {
+            let mut parsed_files = Vec::new();
+            while let Some((file_name, file_content)) = source_files.get(next_file.fetch_add(1, Ordering::Relaxed)) {
+                parsed_files.push((
+                    *file_name,
+                    load_or_parse_file(*file_name, *file_content, target_str, cache, strings)
+                ));
+            }
+            parsed_files
+        })).collect::<Vec<thread::ScopedJoinHandle<Vec<(StringIdx, Result<Vec<AstNode>, Vec<Error>>)>>>>();
+        workers.into_iter()
+            .flat_map(|worker| worker.join().expect("parsing should not panic"))
+            .collect()
+    })
+}
+
+fn load_or_parse_file(
+    file_path: StringIdx,
+    file_content: StringIdx,
+    target_str: &str,
+    cache: Option<&ModuleCache>,
+    strings: &StringMap
+) -> Result<Vec<AstNode>, Vec<Error>> {
+    if let Some(nodes) = cache.and_then(|cache| cache.load_file(file_path, file_content, target_str, strings)) {
+        return Ok(nodes);
+    }
+    let nodes = parse_file(file_path, file_content, target_str, strings)?;
+    if let Some(cache) = cache {
+        cache.store_file(file_path, file_content, target_str, &nodes, strings);
+    }
+    Ok(nodes)
+}
+
+fn add_file_module(
+    mut nodes: Vec<AstNode>,
+    file_path: StringIdx,
+    strings: &mut StringMap,
+    modules: &mut HashMap<NamespacePath, Module<AstNode>>
+) -> Result<(), Vec<Error>> {
+    // put the file into a module
+    let mut module_path: NamespacePath = NamespacePath::new(Vec::new());
+    if nodes.len() == 0 || match nodes[0].node_variant() {
+        AstNodeVariant::Module { path } => {
+            module_path = path.clone();
+            false
+        }
+        _ => true
+    } { return Err(vec![Error::new([
+        ErrorSection::Error(ErrorType::ModuleDeclarationNotAtTop),
+        ErrorSection::Info(format!("In the file '{}'", strings.get(file_path))),
+    ].into())]) }
+    let module_declaration = nodes.remove(0);
+    if let Some(module) = modules.get_mut(&module_path) {
+        if !module.is_raw() {
+            return Err(vec![Error::new([
+                ErrorSection::Error(ErrorType::ModuleAlreadyDefined(module_path.display(strings))),
+                ErrorSection::Code(module_declaration.source()),
+                ErrorSection::Info(format!("'{}' is already defined in the file '{}'", module_path.display(strings), strings.get(module.file_name())))
+            ].into())]);
+        }
+        module.bake(module_declaration.source().file_name(), nodes, strings)?;
+    } else {
+        modules.insert(
+            module_path.clone(),
+            Module::new(module_path.clone(), module_declaration.source().file_name(), nodes, strings)?
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,355 @@
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::util::{
+    strings::{StringMap, StringIdx},
+    error::{Error, ErrorSection, ErrorType},
+    vfs::{FileSystem, DiskFileSystem}
+};
+use crate::builtin::load_builtins;
+use crate::frontend::{
+    ast::{AstNode, TypedAstNode, HasAstNodeVariant, AstNodeVariant},
+    modules::{Module, NamespacePath},
+    type_checking::Symbol,
+    types::TypeScope,
+    cache::{ModuleCache, referenced_paths}
+};
+use crate::backend::{
+    ir::IrSymbol,
+    lowering::{lower_typed_ast, lower_typed_library},
+    target::CompileTarget,
+    c::{generate_c, generate_c_with_header, generate_c_split, generate_c_library},
+    javascript::generate_javascript,
+    lua::generate_lua,
+    symbols::generate_symbols
+};
+use crate::{parse_files, add_file_module, process_file, type_check, find_main_procedure};
+
+
+/// The modules of a session after parsing, along with everything
+/// declared by the builtins and external mappings.
+pub struct ParsedProgram {
+    pub modules: HashMap<NamespacePath, Module<AstNode>>,
+    pub type_scope: TypeScope,
+    pub typed_symbols: HashMap<NamespacePath, Symbol<TypedAstNode>>,
+    pub external_backings: HashMap<NamespacePath, Option<StringIdx>>
+}
+
+pub struct TypedProgram {
+    pub type_scope: TypeScope,
+    pub symbols: HashMap<NamespacePath, Symbol<TypedAstNode>>,
+    pub external_backings: HashMap<NamespacePath, Option<StringIdx>>
+}
+
+pub struct LoweredProgram {
+    pub type_scope: TypeScope,
+    pub symbols: Vec<IrSymbol>,
+    pub main_procedure: NamespacePath
+}
+
+pub struct LoweredLibrary {
+    pub type_scope: TypeScope,
+    pub symbols: Vec<IrSymbol>,
+    pub exported: Vec<(NamespacePath, usize, Vec<StringIdx>)>
+}
+
+/// Owns the strings and source files of a compilation and runs
+/// its phases one by one, so that tools can stop after any of them.
+///
+/// Every file added to the session is loaded and checked, even if the main
+/// module never uses it, since the main procedure is only known once the
+/// program is lowered and libraries or the 'symbols' target have none.
+/// Search paths are the way to load only what is reachable: modules found
+/// there are added only if a loaded module uses them.
+pub struct Session {
+    strings: StringMap,
+    files: HashMap<StringIdx, StringIdx>,
+    target: String,
+    cache: Option<ModuleCache>,
+    search_paths: Vec<PathBuf>,
+    file_system: Box<dyn FileSystem>
+}
+
+impl Session {
+    pub fn new(target: &str) -> Session {
+        Session::from_files(StringMap::new(), HashMap::new(), target)
+    }
+
+    pub fn from_files(strings: StringMap, files: HashMap<StringIdx, StringIdx>, target: &str) -> Session {
+        Session {
+            strings,
+            files,
+            target: target.into(),
+            cache: None,
+            search_paths: Vec::new(),
+            file_system: Box::new(DiskFileSystem)
+        }
+    }
+
+    pub fn set_cache(&mut self, cache: Option<ModuleCache>) { self.cache = cache; }
+    pub fn set_file_system(&mut self, file_system: Box<dyn FileSystem>) { self.file_system = file_system; }
+
+    /// Modules that are used but not part of the session's files are looked up
+    /// as '<search-path>/<segments...>.gera' and '<search-path>/<segments...>.gem'.
+    pub fn add_search_path(&mut self, search_path: impl Into<PathBuf>) {
+        self.search_paths.push(search_path.into());
+    }
+
+    pub fn strings(&self) -> &StringMap { &self.strings }
+    pub fn strings_mut(&mut self) -> &mut StringMap { &mut self.strings }
+    pub fn into_strings(self) -> StringMap { self.strings }
+    pub fn target(&self) -> &str { &self.target }
+    pub fn files(&self) -> &HashMap<StringIdx, StringIdx> { &self.files }
+
+    pub fn add_file(&mut self, file_name: &str, file_content: &str) -> StringIdx {
+        let file_name = self.strings.insert(file_name);
+        let file_content = self.strings.insert(file_content);
+        self.files.insert(file_name, file_content);
+        file_name
+    }
+
+    pub fn remove_file(&mut self, file_name: &str) -> bool {
+        let file_name = self.strings.insert(file_name);
+        self.files.remove(&file_name).is_some()
+    }
+
+    /// Parses all of the session's files, then repeatedly loads the modules
+    /// they use from the search paths until no more are found.
+    pub fn parse(&mut self) -> Result<ParsedProgram, Vec<Error>> {
+        let mut modules = HashMap::new();
+        let mut type_scope = TypeScope::new();
+        let mut typed_symbols = HashMap::new();
+        let mut external_backings = HashMap::new();
+        load_builtins(&self.target, &mut self.strings, &mut modules, &mut type_scope, &mut typed_symbols, &mut external_backings);
+        let mut pending_files = self.files.clone();
+        let mut searched_modules = HashSet::new();
+        while pending_files.len() > 0 {
+            let mut parsed_files = parse_files(&pending_files, &self.target, self.cache.as_ref(), &self.strings);
+            let mut used_modules = HashSet::new();
+            let mut file_process_errors = Vec::new();
+            for (file_name, file_content) in &pending_files {
+                if let Some(parsed_file) = parsed_files.remove(file_name) {
+                    parsed_file.and_then(|nodes| {
+                        used_modules.extend(used_modules_in(&nodes, &self.strings));
+                        add_file_module(nodes, *file_name, &mut self.strings, &mut modules)
+                    })
+                } else {
+                    process_file(
+                        *file_name, *file_content, &self.target, self.cache.as_ref(),
+                        &mut self.strings, &mut modules, &mut type_scope, &mut typed_symbols, &mut external_backings
+                    )
+                }.unwrap_or_else(|mut errors| file_process_errors.append(&mut errors));
+            }
+            if file_process_errors.len() > 0 { return Err(file_process_errors); }
+            pending_files = self.find_module_files(used_modules, &modules, &mut searched_modules)?;
+        }
+        Ok(ParsedProgram { modules, type_scope, typed_symbols, external_backings })
+    }
+
+    pub fn canonicalize(&mut self, mut parsed: ParsedProgram) -> Result<ParsedProgram, Vec<Error>> {
+        let module_paths = parsed.modules.keys().map(|p| p.clone()).collect::<Vec<NamespacePath>>();
+        for module_path in module_paths {
+            let mut module = parsed.modules.remove(&module_path).expect("key must be valid");
+            let canonicalization_errors = module.canonicalize(&parsed.modules, &mut self.strings);
+            parsed.modules.insert(module_path, module);
+            if canonicalization_errors.len() > 0 { return Err(canonicalization_errors) }
+        }
+        Ok(parsed)
+    }
+
+    /// Expects the modules to already be canonicalized.
+    pub fn type_check(&mut self, canonicalized: ParsedProgram) -> Result<TypedProgram, Vec<Error>> {
+        let ParsedProgram { modules, mut type_scope, mut typed_symbols, external_backings } = canonicalized;
+        type_check(
+            modules, &self.files, &self.target, self.cache.as_ref(),
+            &self.strings, &mut type_scope, &mut typed_symbols
+        )?;
+        Ok(TypedProgram { type_scope, symbols: typed_symbols, external_backings })
+    }
+
+    pub fn lower(&mut self, typed: TypedProgram, main_proc: Option<String>) -> Result<LoweredProgram, Vec<Error>> {
+        let TypedProgram { mut type_scope, symbols: typed_symbols, external_backings } = typed;
+        let (main_procedure_path, main_procedure) = find_main_procedure(
+            &mut self.strings, &typed_symbols, main_proc, &self.target
+        )?;
+        let symbols = lower_typed_ast(
+            &mut self.strings, &mut type_scope, &typed_symbols, &external_backings,
+            (&main_procedure_path, main_procedure)
+        ).map_err(|e| vec![e])?;
+        Ok(LoweredProgram { type_scope, symbols, main_procedure: main_procedure_path })
+    }
+
+    pub fn lower_library(&mut self, typed: TypedProgram, exported_procs: &[String]) -> Result<LoweredLibrary, Vec<Error>> {
+        let TypedProgram { mut type_scope, symbols: typed_symbols, external_backings } = typed;
+        let exported_paths = exported_procs.iter()
+            .map(|p| NamespacePath::new(p.split("::").map(|e| self.strings.insert(e)).collect()))
+            .collect::<Vec<NamespacePath>>();
+        let (symbols, exported) = lower_typed_library(
+            &mut self.strings, &mut type_scope, &typed_symbols, &external_backings, &exported_paths
+        ).map_err(|e| vec![e])?;
+        Ok(LoweredLibrary { type_scope, symbols, exported })
+    }
+
+    pub fn generate(&mut self, lowered: LoweredProgram) -> Result<String, Vec<Error>> {
+        match self.selected_target()? {
+            CompileTarget::IrConsumer(generator) => Ok((generator)(
+                lowered.symbols, lowered.type_scope, lowered.main_procedure, &mut self.strings
+            )),
+            _ => Err(vec![Error::new([
+                ErrorSection::Error(ErrorType::InvalidCompileTarget(self.target.clone())),
+                ErrorSection::Help(String::from("This target does not generate code from lowered programs"))
+            ].into())])
+        }
+    }
+
+    pub fn generate_with_header(&mut self, lowered: LoweredProgram) -> Result<(String, String), Vec<Error>> {
+        self.expect_c_target("Only the 'c' target generates a header")?;
+        Ok(generate_c_with_header(lowered.symbols, lowered.type_scope, lowered.main_procedure, &mut self.strings))
+    }
+
+    pub fn generate_split(&mut self, lowered: LoweredProgram) -> Result<Vec<(String, String)>, Vec<Error>> {
+        self.expect_c_target("Only the 'c' target can be split into multiple files")?;
+        Ok(generate_c_split(lowered.symbols, lowered.type_scope, lowered.main_procedure, &mut self.strings))
+    }
+
+    pub fn generate_library(&mut self, lowered: LoweredLibrary) -> Result<(String, String), Vec<Error>> {
+        self.expect_c_target("Libraries can only be compiled for the 'c' target")?;
+        Ok(generate_c_library(lowered.symbols, lowered.type_scope, lowered.exported, &mut self.strings))
+    }
+
+    pub fn compile(&mut self, main_proc: Option<String>) -> Result<String, Vec<Error>> {
+        let selected_target = self.selected_target()?;
+        let parsed = self.parse()?;
+        let canonicalized = self.canonicalize(parsed)?;
+        // if target consumes AST, pass it the canonicalized AST and return the result
+        if let CompileTarget::AstConsumer(generator) = selected_target {
+            return Ok((generator)(canonicalized.type_scope, canonicalized.modules, canonicalized.external_backings, &mut self.strings));
+        }
+        let typed = self.type_check(canonicalized)?;
+        // if target consumes typed AST, pass it the typed AST and return the result
+        if let CompileTarget::TypedAstConsumer(generator) = selected_target {
+            return Ok((generator)(typed.type_scope, typed.symbols, typed.external_backings, &mut self.strings));
+        }
+        let lowered = self.lower(typed, main_proc)?;
+        self.generate(lowered)
+    }
+
+    pub fn compile_with_header(&mut self, main_proc: Option<String>) -> Result<(String, String), Vec<Error>> {
+        self.expect_c_target("Only the 'c' target generates a header")?;
+        let parsed = self.parse()?;
+        let canonicalized = self.canonicalize(parsed)?;
+        let typed = self.type_check(canonicalized)?;
+        let lowered = self.lower(typed, main_proc)?;
+        self.generate_with_header(lowered)
+    }
+
+    pub fn compile_split(&mut self, main_proc: Option<String>) -> Result<Vec<(String, String)>, Vec<Error>> {
+        self.expect_c_target("Only the 'c' target can be split into multiple files")?;
+        let parsed = self.parse()?;
+        let canonicalized = self.canonicalize(parsed)?;
+        let typed = self.type_check(canonicalized)?;
+        let lowered = self.lower(typed, main_proc)?;
+        self.generate_split(lowered)
+    }
+
+    pub fn compile_library(&mut self, exported_procs: &[String]) -> Result<(String, String), Vec<Error>> {
+        self.expect_c_target("Libraries can only be compiled for the 'c' target")?;
+        let parsed = self.parse()?;
+        let canonicalized = self.canonicalize(parsed)?;
+        let typed = self.type_check(canonicalized)?;
+        let lowered = self.lower_library(typed, exported_procs)?;
+        self.generate_library(lowered)
+    }
+
+    fn find_module_files(
+        &mut self,
+        used_modules: HashSet<NamespacePath>,
+        modules: &HashMap<NamespacePath, Module<AstNode>>,
+        searched_modules: &mut HashSet<NamespacePath>
+    ) -> Result<HashMap<StringIdx, StringIdx>, Vec<Error>> {
+        let mut found_files = HashMap::new();
+        for module_path in used_modules {
+            if modules.get(&module_path).map(|m| !m.is_raw()).unwrap_or(false) { continue; }
+            if !searched_modules.insert(module_path.clone()) { continue; }
+            for search_path in &self.search_paths {
+                let mut file_path = search_path.clone();
+                for segment in module_path.get_segments() { file_path.push(self.strings.get(*segment)); }
+                let mut module_files = Vec::new();
+                for path in [file_path.with_extension("gera"), file_path.with_extension("gem")] {
+                    let content = self.file_system.read_file(&path).map_err(|error| vec![Error::new([
+                        ErrorSection::Error(ErrorType::FileSystemError(error.to_string())),
+                        ErrorSection::Info(format!("While trying to read '{}'", path.display()))
+                    ].into())])?;
+                    module_files.push(content.map(|content| (path, content)));
+                }
+                if module_files.iter().all(|f| f.is_none()) { continue; }
+                for (path, content) in module_files.into_iter().flatten() {
+                    let file_name = self.strings.insert(&path.to_string_lossy());
+                    let file_content = self.strings.insert(&content);
+                    self.files.insert(file_name, file_content);
+                    found_files.insert(file_name, file_content);
+                }
+                break;
+            }
+        }
+        Ok(found_files)
+    }
+
+    fn selected_target(&self) -> Result<CompileTarget, Vec<Error>> {
+        let mut targets: HashMap<String, CompileTarget> = HashMap::from([
+            ("c".into(), CompileTarget::IrConsumer(generate_c)),
+            ("js".into(), CompileTarget::IrConsumer(generate_javascript)),
+            ("lua".into(), CompileTarget::IrConsumer(generate_lua)),
+            ("symbols".into(), CompileTarget::TypedAstConsumer(generate_symbols))
+        ]);
+        targets.remove(&self.target).map(|t| Ok(t)).unwrap_or_else(|| Err(vec![Error::new([
+            ErrorSection::Error(ErrorType::InvalidCompileTarget(self.target.clone())),
+            ErrorSection::Help(format!("List of available targets: {}", targets.iter().map(|t| format!("\n- {}", t.0)).collect::<Vec<String>>().join("")))
+        ].into())]))
+    }
+
+    fn expect_c_target(&self, help: &str) -> Result<(), Vec<Error>> {
+        if self.target == "c" { return Ok(()); }
+        Err(vec![Error::new([
+            ErrorSection::Error(ErrorType::InvalidCompileTarget(self.target.clone())),
+            ErrorSection::Help(String::from(help))
+        ].into())])
+    }
+}
+
+/// Finds all modules that may be accessed by the given file, including the
+/// ones a path could refer to after being resolved against the file's usages.
+fn used_modules_in(nodes: &[AstNode], strings: &StringMap) -> HashSet<NamespacePath> {
+    let asterisk = strings.insert("*");
+    let mut usages = Vec::new();
+    for node in nodes {
+        if let AstNodeVariant::Use { paths } = node.node_variant() { usages.extend(paths.iter().cloned()); }
+    }
+    let mut used_modules = HashSet::new();
+    let statements = nodes.iter().filter(|n| !matches!(n.node_variant(), AstNodeVariant::Module { .. }));
+    for path in referenced_paths(statements) {
+        let segments = path.get_segments();
+        let mut resolved_paths = vec![segments.clone()];
+        for usage in &usages {
+            let usage_segments = usage.get_segments();
+            let (last_segment, usage_module) = usage_segments.split_last().expect("should have at least one segment");
+            if *last_segment == asterisk {
+                resolved_paths.push([usage_module, segments].concat());
+            } else if *last_segment == segments[0] {
+                resolved_paths.push([usage_segments, &segments[1..]].concat());
+            }
+        }
+        for resolved_path in resolved_paths {
+            if let Some((last_segment, module)) = resolved_path.split_last() {
+                if *last_segment == asterisk {
+                    used_modules.insert(NamespacePath::new(module.into()));
+                    continue;
+                }
+                if module.len() > 0 { used_modules.insert(NamespacePath::new(module.into())); }
+            }
+            used_modules.insert(NamespacePath::new(resolved_path));
+        }
+    }
+    used_modules
+}
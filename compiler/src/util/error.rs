@@ -1,6 +1,8 @@
 
 use crate::util::{source::SourceRange, strings::{StringMap, StringIdx}};
 
+use serde_json::json;
+
 
 #[macro_export]
 macro_rules! style_reset { () => { "\x1b[0m" } }
@@ -29,11 +31,18 @@ pub enum ErrorType {
     MissingArgument(&'static str),
     FileSystemError(String),
     InvalidFileExtension(String),
+    InvalidDiagnosticsFormat(String),
+    InvalidErrorCode(String),
+    OverwritesForeignFile(String),
 
     // lexer errors
     InvalidCharacter(char),
     IntLiteralOverflows(StringIdx),
     FloatLiteralOverflows(StringIdx),
+    InvalidNumericLiteral(StringIdx),
+
+    // C header errors
+    UnsupportedCType(String),
 
     // external mappings parser errors
     TypeDoesNotExist(StringIdx),
@@ -50,6 +59,8 @@ pub enum ErrorType {
     // grammar checking errors
     InvalidContext(&'static str, &'static str, &'static str),
     DuplicateFunctionParameter(StringIdx),
+    DuplicatePatternBinding(StringIdx),
+    PositionalAfterNamedArgument,
 
     // module errors
     ModuleDeclarationNotAtTop,
@@ -58,6 +69,7 @@ pub enum ErrorType {
     SymbolDoesNotExist(String),
     SymbolIsNotPublic(String),
     ModuleAlreadyDefined(String),
+    DefaultReferencesParameter(StringIdx),
 
     // type errors
     NoPossibleTypes,
@@ -66,15 +78,29 @@ pub enum ErrorType {
     RecursiveConstant(String),
     InvalidParameterCount(String, usize, usize),
     VariableWithoutValue(StringIdx),
+    RecursiveType(String),
+    NotAType(String),
+    TypeUsedAsValue(String),
+    NominalTypeNotObject(String),
+    NonExhaustiveCase(String),
+    ParameterDoesNotExist(String, StringIdx),
+    ParameterGivenTwice(StringIdx),
+    MissingParameter(String, StringIdx),
+    InvalidNamedArgument,
+    UnreachableBranch,
     
     // interpreter errors
     ConstExpressionPanics,
     ConstDependsOnExternal(String),
+    ConstReturns,
     
     // ir lowering errors
     NoMainProcedureDefined(String),
     InvalidMainProcedure(String),
     ConstantClosure,
+    NoExternalBacking(String),
+    InvalidExportedProcedure(String),
+    UnexportableProcedure(String, String),
 
     // code generation
     InvalidCompileTarget(String)
@@ -82,6 +108,134 @@ pub enum ErrorType {
 }
 
 impl ErrorType {
+    /// Returns the stable code of this error type, which is explained by 'gerac --explain <code>'.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorType::ArgumentDoesNotExist(..) => "E0001",
+            ErrorType::InvalidArgumentCount(..) => "E0002",
+            ErrorType::MissingArgument(..) => "E0003",
+            ErrorType::FileSystemError(..) => "E0004",
+            ErrorType::InvalidFileExtension(..) => "E0005",
+            ErrorType::InvalidDiagnosticsFormat(..) => "E0006",
+            ErrorType::InvalidCharacter(..) => "E0007",
+            ErrorType::IntLiteralOverflows(..) => "E0008",
+            ErrorType::FloatLiteralOverflows(..) => "E0009",
+            ErrorType::UnsupportedCType(..) => "E0010",
+            ErrorType::TypeDoesNotExist(..) => "E0011",
+            ErrorType::NoDefinedModule(..) => "E0012",
+            ErrorType::MissingLeftExpr(..) => "E0013",
+            ErrorType::UnexpectedEnd(..) => "E0014",
+            ErrorType::UnexpectedToken(..) => "E0015",
+            ErrorType::TotallyUnexpectedToken(..) => "E0016",
+            ErrorType::MayNotBePublic => "E0017",
+            ErrorType::NotACall => "E0018",
+            ErrorType::InvalidContext(..) => "E0019",
+            ErrorType::DuplicateFunctionParameter(..) => "E0020",
+            ErrorType::DuplicatePatternBinding(..) => "E0056",
+            ErrorType::PositionalAfterNamedArgument => "E0055",
+            ErrorType::ModuleDeclarationNotAtTop => "E0021",
+            ErrorType::SymbolAlreadyExists(..) => "E0022",
+            ErrorType::ModuleDoesNotExist(..) => "E0023",
+            ErrorType::SymbolDoesNotExist(..) => "E0024",
+            ErrorType::SymbolIsNotPublic(..) => "E0025",
+            ErrorType::ModuleAlreadyDefined(..) => "E0026",
+            ErrorType::DefaultReferencesParameter(..) => "E0054",
+            ErrorType::NoPossibleTypes => "E0027",
+            ErrorType::VariableDoesNotExist(..) => "E0028",
+            ErrorType::ImmutableAssignmant(..) => "E0029",
+            ErrorType::RecursiveConstant(..) => "E0030",
+            ErrorType::InvalidParameterCount(..) => "E0031",
+            ErrorType::VariableWithoutValue(..) => "E0032",
+            ErrorType::RecursiveType(..) => "E0033",
+            ErrorType::NotAType(..) => "E0034",
+            ErrorType::TypeUsedAsValue(..) => "E0035",
+            ErrorType::NominalTypeNotObject(..) => "E0036",
+            ErrorType::NonExhaustiveCase(..) => "E0048",
+            ErrorType::ParameterDoesNotExist(..) => "E0050",
+            ErrorType::ParameterGivenTwice(..) => "E0051",
+            ErrorType::MissingParameter(..) => "E0052",
+            ErrorType::InvalidNamedArgument => "E0053",
+            ErrorType::UnreachableBranch => "E0057",
+            ErrorType::ConstExpressionPanics => "E0037",
+            ErrorType::ConstDependsOnExternal(..) => "E0038",
+            ErrorType::ConstReturns => "E0049",
+            ErrorType::NoMainProcedureDefined(..) => "E0039",
+            ErrorType::InvalidMainProcedure(..) => "E0040",
+            ErrorType::ConstantClosure => "E0041",
+            ErrorType::NoExternalBacking(..) => "E0042",
+            ErrorType::InvalidExportedProcedure(..) => "E0043",
+            ErrorType::UnexportableProcedure(..) => "E0044",
+            ErrorType::InvalidCompileTarget(..) => "E0045",
+            ErrorType::InvalidErrorCode(..) => "E0046",
+            ErrorType::InvalidNumericLiteral(..) => "E0047",
+            ErrorType::OverwritesForeignFile(..) => "E0058",
+        }
+    }
+
+    /// Returns the stable name of this error type used in machine-readable diagnostics.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorType::ArgumentDoesNotExist(..) => "argument_does_not_exist",
+            ErrorType::InvalidArgumentCount(..) => "invalid_argument_count",
+            ErrorType::MissingArgument(..) => "missing_argument",
+            ErrorType::FileSystemError(..) => "file_system_error",
+            ErrorType::InvalidFileExtension(..) => "invalid_file_extension",
+            ErrorType::InvalidDiagnosticsFormat(..) => "invalid_diagnostics_format",
+            ErrorType::InvalidErrorCode(..) => "invalid_error_code",
+            ErrorType::OverwritesForeignFile(..) => "overwrites_foreign_file",
+            ErrorType::InvalidCharacter(..) => "invalid_character",
+            ErrorType::IntLiteralOverflows(..) => "int_literal_overflows",
+            ErrorType::FloatLiteralOverflows(..) => "float_literal_overflows",
+            ErrorType::InvalidNumericLiteral(..) => "invalid_numeric_literal",
+            ErrorType::UnsupportedCType(..) => "unsupported_c_type",
+            ErrorType::TypeDoesNotExist(..) => "type_does_not_exist",
+            ErrorType::NoDefinedModule(..) => "no_defined_module",
+            ErrorType::MissingLeftExpr(..) => "missing_left_expr",
+            ErrorType::UnexpectedEnd(..) => "unexpected_end",
+            ErrorType::UnexpectedToken(..) => "unexpected_token",
+            ErrorType::TotallyUnexpectedToken(..) => "totally_unexpected_token",
+            ErrorType::MayNotBePublic => "may_not_be_public",
+            ErrorType::NotACall => "not_a_call",
+            ErrorType::InvalidContext(..) => "invalid_context",
+            ErrorType::DuplicateFunctionParameter(..) => "duplicate_function_parameter",
+            ErrorType::DuplicatePatternBinding(..) => "duplicate_pattern_binding",
+            ErrorType::PositionalAfterNamedArgument => "positional_after_named_argument",
+            ErrorType::ModuleDeclarationNotAtTop => "module_declaration_not_at_top",
+            ErrorType::SymbolAlreadyExists(..) => "symbol_already_exists",
+            ErrorType::ModuleDoesNotExist(..) => "module_does_not_exist",
+            ErrorType::SymbolDoesNotExist(..) => "symbol_does_not_exist",
+            ErrorType::SymbolIsNotPublic(..) => "symbol_is_not_public",
+            ErrorType::ModuleAlreadyDefined(..) => "module_already_defined",
+            ErrorType::DefaultReferencesParameter(..) => "default_references_parameter",
+            ErrorType::NoPossibleTypes => "no_possible_types",
+            ErrorType::VariableDoesNotExist(..) => "variable_does_not_exist",
+            ErrorType::ImmutableAssignmant(..) => "immutable_assignment",
+            ErrorType::RecursiveConstant(..) => "recursive_constant",
+            ErrorType::InvalidParameterCount(..) => "invalid_parameter_count",
+            ErrorType::VariableWithoutValue(..) => "variable_without_value",
+            ErrorType::RecursiveType(..) => "recursive_type",
+            ErrorType::NotAType(..) => "not_a_type",
+            ErrorType::TypeUsedAsValue(..) => "type_used_as_value",
+            ErrorType::NominalTypeNotObject(..) => "nominal_type_not_object",
+            ErrorType::NonExhaustiveCase(..) => "non_exhaustive_case",
+            ErrorType::ParameterDoesNotExist(..) => "parameter_does_not_exist",
+            ErrorType::ParameterGivenTwice(..) => "parameter_given_twice",
+            ErrorType::MissingParameter(..) => "missing_parameter",
+            ErrorType::InvalidNamedArgument => "invalid_named_argument",
+            ErrorType::UnreachableBranch => "unreachable_branch",
+            ErrorType::ConstExpressionPanics => "const_expression_panics",
+            ErrorType::ConstDependsOnExternal(..) => "const_depends_on_external",
+            ErrorType::ConstReturns => "const_returns",
+            ErrorType::NoMainProcedureDefined(..) => "no_main_procedure_defined",
+            ErrorType::InvalidMainProcedure(..) => "invalid_main_procedure",
+            ErrorType::ConstantClosure => "constant_closure",
+            ErrorType::NoExternalBacking(..) => "no_external_backing",
+            ErrorType::InvalidExportedProcedure(..) => "invalid_exported_procedure",
+            ErrorType::UnexportableProcedure(..) => "unexportable_procedure",
+            ErrorType::InvalidCompileTarget(..) => "invalid_compile_target",
+        }
+    }
+
     pub fn display(&self, strings: &StringMap, color: bool) -> String {
         match self {
             ErrorType::ArgumentDoesNotExist(got) => format!(
@@ -111,6 +265,24 @@ impl ErrorType {
                 error,
                 if color { style_dark_red!() } else { "" }
             ),
+            ErrorType::InvalidDiagnosticsFormat(format) => format!(
+                "{}'{}'{} is not a valid diagnostics format",
+                if color { style_red!() } else { "" },
+                format,
+                if color { style_dark_red!() } else { "" }
+            ),
+            ErrorType::InvalidErrorCode(code) => format!(
+                "{}'{}'{} is not a known error code",
+                if color { style_red!() } else { "" },
+                code,
+                if color { style_dark_red!() } else { "" }
+            ),
+            ErrorType::OverwritesForeignFile(path) => format!(
+                "The file {}'{}'{} already exists and was not generated by gerac, so it will not be overwritten",
+                if color { style_red!() } else { "" },
+                path,
+                if color { style_dark_red!() } else { "" }
+            ),
             ErrorType::InvalidFileExtension(file_path) => format!(
                 "The file {}'{}'{} has an extension not recognized by the compiler",
                 if color { style_red!() } else { "" },
@@ -136,6 +308,19 @@ impl ErrorType {
                 strings.get(*value),
                 if color { style_dark_red!() } else { "" }
             ),
+            ErrorType::InvalidNumericLiteral(value) => format!(
+                "The value {}'{}'{} is not a valid number",
+                if color { style_red!() } else { "" },
+                strings.get(*value),
+                if color { style_dark_red!() } else { "" }
+            ),
+
+            ErrorType::UnsupportedCType(c_type) => format!(
+                "The C type {}'{}'{} can not be used from Gera",
+                if color { style_red!() } else { "" },
+                c_type,
+                if color { style_dark_red!() } else { "" }
+            ),
 
             ErrorType::TypeDoesNotExist(name) => format!(
                 "No type with the name {}'{}'{} has been defined up to this point",
@@ -189,6 +374,15 @@ impl ErrorType {
                 strings.get(*name),
                 if color { style_dark_red!() } else { "" }
             ),
+            ErrorType::DuplicatePatternBinding(name) => format!(
+                "the variable {}'{}'{} is bound more than once in the same pattern",
+                if color { style_red!() } else { "" },
+                strings.get(*name),
+                if color { style_dark_red!() } else { "" }
+            ),
+            ErrorType::PositionalAfterNamedArgument => format!(
+                "positional arguments may not follow named arguments"
+            ),
 
             ErrorType::ModuleDeclarationNotAtTop => format!(
                 "The parent module must be declared at the top of the file"
@@ -223,6 +417,12 @@ impl ErrorType {
                 path,
                 if color { style_dark_red!() } else { "" }
             ),
+            ErrorType::DefaultReferencesParameter(name) => format!(
+                "The default value refers to the parameter {}'{}'{}, but default values can't refer to parameters",
+                if color { style_red!() } else { "" },
+                strings.get(*name),
+                if color { style_dark_red!() } else { "" }
+            ),
 
             ErrorType::NoPossibleTypes => format!(
                 "Incompatible types"
@@ -260,6 +460,66 @@ impl ErrorType {
                 strings.get(*name),
                 if color { style_dark_red!() } else { "" }
             ),
+            ErrorType::RecursiveType(name) => format!(
+                "The type {}'{}'{} is defined in terms of itself",
+                if color { style_red!() } else { "" },
+                name,
+                if color { style_dark_red!() } else { "" }
+            ),
+            ErrorType::NotAType(name) => format!(
+                "{}'{}'{} is not a type",
+                if color { style_red!() } else { "" },
+                name,
+                if color { style_dark_red!() } else { "" }
+            ),
+            ErrorType::TypeUsedAsValue(name) => format!(
+                "The type {}'{}'{} is used as a value",
+                if color { style_red!() } else { "" },
+                name,
+                if color { style_dark_red!() } else { "" }
+            ),
+            ErrorType::NominalTypeNotObject(name) => format!(
+                "The nominal type {}'{}'{} does not wrap an object type",
+                if color { style_red!() } else { "" },
+                name,
+                if color { style_dark_red!() } else { "" }
+            ),
+            ErrorType::NonExhaustiveCase(missing) => format!(
+                "The branches of this 'case' do not handle every possible value, for example {}'{}'{}",
+                if color { style_red!() } else { "" },
+                missing,
+                if color { style_dark_red!() } else { "" }
+            ),
+            ErrorType::ParameterDoesNotExist(procedure, name) => format!(
+                "{}'{}'{} does not have a parameter called {}'{}'{}",
+                if color { style_red!() } else { "" },
+                procedure,
+                if color { style_dark_red!() } else { "" },
+                if color { style_red!() } else { "" },
+                strings.get(*name),
+                if color { style_dark_red!() } else { "" }
+            ),
+            ErrorType::ParameterGivenTwice(name) => format!(
+                "The parameter {}'{}'{} was given more than one value",
+                if color { style_red!() } else { "" },
+                strings.get(*name),
+                if color { style_dark_red!() } else { "" }
+            ),
+            ErrorType::MissingParameter(procedure, name) => format!(
+                "{}'{}'{} expects a value for the parameter {}'{}'{}, which does not have a default value",
+                if color { style_red!() } else { "" },
+                procedure,
+                if color { style_dark_red!() } else { "" },
+                if color { style_red!() } else { "" },
+                strings.get(*name),
+                if color { style_dark_red!() } else { "" }
+            ),
+            ErrorType::InvalidNamedArgument => format!(
+                "Named arguments may only be used when directly calling a procedure"
+            ),
+            ErrorType::UnreachableBranch => format!(
+                "This branch can never be reached, since the branches before it already handle every value it matches"
+            ),
 
             ErrorType::ConstExpressionPanics => format!(
                 "A panic occured while evaluating a constant expression:"
@@ -270,6 +530,9 @@ impl ErrorType {
                 path,
                 if color { style_dark_red!() } else { "" }
             ),
+            ErrorType::ConstReturns => format!(
+                "A constant expression attempted to return, which is only possible inside of a procedure or closure"
+            ),
 
             ErrorType::NoMainProcedureDefined(target) => format!(
                 "The target format {}'{}'{} requires a main procedure to be defined",
@@ -286,6 +549,27 @@ impl ErrorType {
             ErrorType::ConstantClosure => format!(
                 "Closures may not be used as values for constants"
             ),
+            ErrorType::InvalidExportedProcedure(path) => format!(
+                "{}'{}'{} is not the name of a procedure that can be exported",
+                if color { style_red!() } else { "" },
+                path,
+                if color { style_dark_red!() } else { "" }
+            ),
+            ErrorType::UnexportableProcedure(path, types) => format!(
+                "The procedure {}'{}'{} can not be exported, as its signature uses the type {}'{}'{}",
+                if color { style_red!() } else { "" },
+                path,
+                if color { style_dark_red!() } else { "" },
+                if color { style_red!() } else { "" },
+                types,
+                if color { style_dark_red!() } else { "" }
+            ),
+            ErrorType::NoExternalBacking(path) => format!(
+                "The external symbol {}'{}'{} has no backing for the selected target",
+                if color { style_red!() } else { "" },
+                path,
+                if color { style_dark_red!() } else { "" }
+            ),
 
             ErrorType::InvalidCompileTarget(target) => format!(
                 "{}'{}'{} is not a valid compilation target",
@@ -311,8 +595,9 @@ impl ErrorSection {
     pub fn display(&self, strings: &StringMap, color: bool) -> String {
         match self {
             ErrorSection::Error(error_type) => format!(
-                "{}error: {}{}{}",
+                "{}error[{}]: {}{}{}",
                 if color { style_bold_dark_red!() } else { "" },
+                error_type.code(),
                 if color { style_dark_red!() } else { "" },
                 error_type.display(strings, color),
                 if color { style_reset!() } else { "" }
@@ -367,10 +652,10 @@ impl ErrorSection {
                             if line >= displayed_lines_start && line <= displayed_lines_end {
                                 if (line.max(source_start_line) - source_start_line).min(source_end_line.max(line) - line) < 2 {
                                     let mut marked = vec![false; line_content.len() - 1];
+                                    let line_start = (position + 1).saturating_sub(line_content.len());
                                     for i in source.start_position()..source.end_position() {
-                                        if i < (position.max(1) - line_content.len() + 1) { continue; }
-                                        if i >= position { continue; }
-                                        marked.insert(i - (position - line_content.len() + 1), true);
+                                        if i < line_start || i >= position { continue; }
+                                        marked[i - line_start] = true;
                                     }
                                     output.push('\n');
                                     if !marked.contains(&true) {
@@ -409,7 +694,7 @@ impl ErrorSection {
                                     if color {
                                         output.push_str(style_gray!());
                                     }
-                                    output.push_str(&format!("... ({} lines hidden)", source_end_line - source_start_line - 3 /* don't ask where '3' comes from, it just works */));
+                                    output.push_str(&format!("... ({} lines hidden)", source_end_line.saturating_sub(source_start_line + 3) /* don't ask where '3' comes from, it just works */));
                                     if color {
                                         output.push_str(style_reset!());
                                     }
@@ -451,4 +736,71 @@ impl Error {
         }
         output
     }
-}
\ No newline at end of file
+
+    pub fn to_json(&self, strings: &StringMap) -> serde_json::Value {
+        let mut code = serde_json::Value::Null;
+        let mut error_type = serde_json::Value::Null;
+        let mut message = serde_json::Value::Null;
+        let mut ranges = Vec::new();
+        let mut notes = Vec::new();
+        for section in &*self.sections {
+            match section {
+                ErrorSection::Error(t) => if error_type.is_null() {
+                    code = serde_json::Value::String(t.code().into());
+                    error_type = serde_json::Value::String(t.name().into());
+                    message = serde_json::Value::String(t.display(strings, false));
+                }
+                ErrorSection::Info(note) => notes.push(json!({ "kind": "info", "message": note })),
+                ErrorSection::Help(note) => notes.push(json!({ "kind": "help", "message": note })),
+                ErrorSection::Raw(note) => notes.push(json!({ "kind": "raw", "message": note })),
+                ErrorSection::Code(source) => {
+                    let content = strings.get(source.file_content());
+                    ranges.push(json!({
+                        "file": strings.get(source.file_name()),
+                        "start": json_position(content, source.start_position()),
+                        "end": json_position(content, source.end_position())
+                    }));
+                }
+            }
+        }
+        json!({
+            "code": code,
+            "type": error_type,
+            "message": message,
+            "ranges": ranges,
+            "notes": notes
+        })
+    }
+}
+
+
+pub const DIAGNOSTICS_SCHEMA_VERSION: u64 = 2;
+
+/// Serializes the given errors into a versioned diagnostics document.
+/// Offsets and columns in it are counted in UTF-16 code units (like in LSP),
+/// which the document states in its 'position_encoding' field.
+pub fn diagnostics_to_json(errors: &[Error], strings: &StringMap) -> serde_json::Value {
+    json!({
+        "version": DIAGNOSTICS_SCHEMA_VERSION,
+        "position_encoding": "utf-16",
+        "diagnostics": errors.iter().map(|e| e.to_json(strings)).collect::<Vec<serde_json::Value>>()
+    })
+}
+
+fn json_position(content: &str, position: usize) -> serde_json::Value {
+    let mut offset = 0usize;
+    let mut line = 1usize;
+    let mut column = 1usize;
+    let mut last_c = '\0';
+    for c in content.chars().take(position) {
+        offset += c.len_utf16();
+        if (c == '\n' && last_c != '\r') || c == '\r' {
+            line += 1;
+            column = 1;
+        } else if c != '\n' {
+            column += c.len_utf16();
+        }
+        last_c = c;
+    }
+    json!({ "offset": offset, "line": line, "column": column })
+}
@@ -0,0 +1,68 @@
+
+/// Detailed descriptions of each error code returned by 'ErrorType::code', with examples.
+const EXPLANATIONS: &[(&str, &str)] = &[
+    ("E0001", include_str!("explanations/E0001.md")),
+    ("E0002", include_str!("explanations/E0002.md")),
+    ("E0003", include_str!("explanations/E0003.md")),
+    ("E0004", include_str!("explanations/E0004.md")),
+    ("E0005", include_str!("explanations/E0005.md")),
+    ("E0006", include_str!("explanations/E0006.md")),
+    ("E0007", include_str!("explanations/E0007.md")),
+    ("E0008", include_str!("explanations/E0008.md")),
+    ("E0009", include_str!("explanations/E0009.md")),
+    ("E0010", include_str!("explanations/E0010.md")),
+    ("E0011", include_str!("explanations/E0011.md")),
+    ("E0012", include_str!("explanations/E0012.md")),
+    ("E0013", include_str!("explanations/E0013.md")),
+    ("E0014", include_str!("explanations/E0014.md")),
+    ("E0015", include_str!("explanations/E0015.md")),
+    ("E0016", include_str!("explanations/E0016.md")),
+    ("E0017", include_str!("explanations/E0017.md")),
+    ("E0018", include_str!("explanations/E0018.md")),
+    ("E0019", include_str!("explanations/E0019.md")),
+    ("E0020", include_str!("explanations/E0020.md")),
+    ("E0021", include_str!("explanations/E0021.md")),
+    ("E0022", include_str!("explanations/E0022.md")),
+    ("E0023", include_str!("explanations/E0023.md")),
+    ("E0024", include_str!("explanations/E0024.md")),
+    ("E0025", include_str!("explanations/E0025.md")),
+    ("E0026", include_str!("explanations/E0026.md")),
+    ("E0027", include_str!("explanations/E0027.md")),
+    ("E0028", include_str!("explanations/E0028.md")),
+    ("E0029", include_str!("explanations/E0029.md")),
+    ("E0030", include_str!("explanations/E0030.md")),
+    ("E0031", include_str!("explanations/E0031.md")),
+    ("E0032", include_str!("explanations/E0032.md")),
+    ("E0033", include_str!("explanations/E0033.md")),
+    ("E0034", include_str!("explanations/E0034.md")),
+    ("E0035", include_str!("explanations/E0035.md")),
+    ("E0036", include_str!("explanations/E0036.md")),
+    ("E0037", include_str!("explanations/E0037.md")),
+    ("E0038", include_str!("explanations/E0038.md")),
+    ("E0039", include_str!("explanations/E0039.md")),
+    ("E0040", include_str!("explanations/E0040.md")),
+    ("E0041", include_str!("explanations/E0041.md")),
+    ("E0042", include_str!("explanations/E0042.md")),
+    ("E0043", include_str!("explanations/E0043.md")),
+    ("E0044", include_str!("explanations/E0044.md")),
+    ("E0045", include_str!("explanations/E0045.md")),
+    ("E0046", include_str!("explanations/E0046.md")),
+    ("E0047", include_str!("explanations/E0047.md")),
+    ("E0048", include_str!("explanations/E0048.md")),
+    ("E0049", include_str!("explanations/E0049.md")),
+    ("E0050", include_str!("explanations/E0050.md")),
+    ("E0051", include_str!("explanations/E0051.md")),
+    ("E0052", include_str!("explanations/E0052.md")),
+    ("E0053", include_str!("explanations/E0053.md")),
+    ("E0054", include_str!("explanations/E0054.md")),
+    ("E0055", include_str!("explanations/E0055.md")),
+    ("E0056", include_str!("explanations/E0056.md")),
+    ("E0057", include_str!("explanations/E0057.md")),
+    ("E0058", include_str!("explanations/E0058.md")),
+];
+
+pub fn explain(code: &str) -> Option<&'static str> {
+    EXPLANATIONS.iter()
+        .find(|(c, _)| c.eq_ignore_ascii_case(code))
+        .map(|(_, explanation)| *explanation)
+}
@@ -1,4 +1,6 @@
-
 pub mod strings;
 pub mod error;
-pub mod source;
\ No newline at end of file
+pub mod source;
+pub mod vfs;
+pub mod suggestions;
+pub mod explanations;
@@ -1,30 +1,57 @@
-
 use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct StringIdx(pub usize);
 
-#[derive(Debug, Clone)]
+const FIRST_SEGMENT_SIZE: usize = 64;
+const SEGMENT_COUNT: usize = 48;
+
+type Segment = Box<[OnceLock<Box<str>>]>;
+
+/// Strings are stored in an append-only arena of segments that double in size.
+/// Segments never move once allocated and each slot is only written once, which
+/// allows 'get' to hand out references without taking the lock.
+#[derive(Debug)]
 pub struct StringMap {
-    indices: HashMap<Box<str>, StringIdx>,
-    strings: Vec<Box<str>>
+    indices: RwLock<HashMap<Box<str>, StringIdx>>,
+    segments: [OnceLock<Segment>; SEGMENT_COUNT]
+}
+
+fn segment_of(idx: usize) -> (usize, usize) {
+    let segment = (idx / FIRST_SEGMENT_SIZE + 1).ilog2() as usize;
+    (segment, idx - FIRST_SEGMENT_SIZE * ((1 << segment) - 1))
 }
 
 impl StringMap {
     pub fn new() -> StringMap {
-        StringMap { indices: HashMap::new(), strings: Vec::new() }
+        StringMap {
+            indices: RwLock::new(HashMap::new()),
+            segments: std::array::from_fn(|_| OnceLock::new())
+        }
     }
 
-    pub fn insert(&mut self, string: &str) -> StringIdx {
-        if let Some(idx) = self.indices.get(string) { return *idx; }
-        let idx = StringIdx(self.strings.len());
-        self.indices.insert(string.into(), idx);
-        self.strings.push(string.into());
+    pub fn insert(&self, string: &str) -> StringIdx {
+        if let Some(idx) = self.indices.read().expect("should not be poisoned").get(string) {
+            return *idx;
+        }
+        let mut indices = self.indices.write().expect("should not be poisoned");
+        if let Some(idx) = indices.get(string) { return *idx; }
+        let idx = StringIdx(indices.len());
+        let (segment, offset) = segment_of(idx.0);
+        self.segments.get(segment).expect("should not run out of segments")
+            .get_or_init(|| (0..FIRST_SEGMENT_SIZE << segment).map(|_| OnceLock::new()).collect())[offset]
+            .set(string.into())
+            .expect("slot should only be written once");
+        indices.insert(string.into(), idx);
         idx
     }
 
-    pub fn get<'s>(&'s self, idx: StringIdx) -> &'s str {
-        &self.strings[idx.0]
+    pub fn get(&self, idx: StringIdx) -> &str {
+        let (segment, offset) = segment_of(idx.0);
+        self.segments.get(segment)
+            .and_then(|segment| segment.get())
+            .and_then(|segment| segment[offset].get())
+            .expect("string should have been inserted")
     }
 }
-
@@ -0,0 +1,51 @@
+
+use crate::util::error::ErrorSection;
+
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Computes the optimal string alignment distance, which also counts swapping two adjacent characters as a single edit.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for i in 0..=a.len() { distances[i][0] = i; }
+    for j in 0..=b.len() { distances[0][j] = j; }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distance = (distances[i - 1][j - 1] + cost)
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// Creates a help section listing the candidates that are closest to the given name, if any are close enough.
+pub fn did_you_mean<S: AsRef<str>>(name: &str, candidates: impl IntoIterator<Item = S>) -> Option<ErrorSection> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    let mut matches = candidates.into_iter()
+        .filter_map(|c| {
+            let distance = edit_distance(name, c.as_ref());
+            if distance == 0 || distance > max_distance { return None; }
+            Some((distance, String::from(c.as_ref())))
+        })
+        .collect::<Vec<(usize, String)>>();
+    matches.sort();
+    matches.dedup_by(|a, b| a.1 == b.1);
+    matches.truncate(MAX_SUGGESTIONS);
+    let names = matches.into_iter()
+        .map(|(_, c)| format!("'{}'", c))
+        .collect::<Vec<String>>();
+    match names.len() {
+        0 => None,
+        1 => Some(ErrorSection::Help(format!("Did you mean {}?", names[0]))),
+        n => Some(ErrorSection::Help(format!(
+            "Did you mean {} or {}?", names[..n - 1].join(", "), names[n - 1]
+        )))
+    }
+}
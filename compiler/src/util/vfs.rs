@@ -0,0 +1,42 @@
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// Provides the compiler with the contents of module files found on the search paths.
+/// Files that don't exist are 'None', while files that can't be read are an error.
+pub trait FileSystem {
+    fn read_file(&self, path: &Path) -> io::Result<Option<String>>;
+}
+
+pub struct DiskFileSystem;
+
+impl FileSystem for DiskFileSystem {
+    fn read_file(&self, path: &Path) -> io::Result<Option<String>> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(Some(content)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error)
+        }
+    }
+}
+
+pub struct MemoryFileSystem {
+    files: HashMap<PathBuf, String>
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> MemoryFileSystem {
+        MemoryFileSystem { files: HashMap::new() }
+    }
+
+    pub fn add_file(&mut self, path: impl Into<PathBuf>, content: impl Into<String>) {
+        self.files.insert(path.into(), content.into());
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read_file(&self, path: &Path) -> io::Result<Option<String>> {
+        Ok(self.files.get(path).cloned())
+    }
+}
@@ -28,7 +28,7 @@ pub struct CliArgList {
 
 impl CliArgList {
     pub fn new() -> CliArgList {
-        return CliArgList { args: Vec::new() }
+        CliArgList { args: Vec::new() }
     } 
     pub fn add(mut self, arg: CliArg) -> Self {
         self.args.push(arg);
@@ -51,13 +51,13 @@ impl CliArgList {
             if arg.required {
                 output.push_str(concat!(style_dark_red!(), " (Required!)"));
             }
-            output.push_str("\n");
+            output.push('\n');
             output.push_str(style_reset!());
             output.push_str("  -> ");
             output.push_str(arg.description);
             output.push_str(style_reset!());
         }
-        return output;
+        output
     }
 }
 
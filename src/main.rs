@@ -5,11 +5,13 @@ mod cli;
 
 use cli::{CliArgs, CliArg, CliArgList};
 use compiler::util::{
-    error::{Error, ErrorSection, ErrorType},
+    error::{Error, ErrorSection, ErrorType, diagnostics_to_json},
+    explanations,
     strings::{StringMap, StringIdx}
 };
+use compiler::{frontend::cache::ModuleCache, session::Session, backend::c::GENERATED_HEADER_MARKER};
 
-use std::{process::exit, fs, env, collections::HashMap};
+use std::{process::exit, fs, env, collections::HashMap, path::{Path, PathBuf}};
 
 
 fn main() {
@@ -32,26 +34,67 @@ fn main() {
     }
 }
 
-pub fn display_errors(errors: Vec<Error>, strings: &mut StringMap, color: bool) -> String {
-    errors.into_iter()
-        .map(|e| e.display(strings, color))
-        .collect::<Vec<String>>()
-        .join("\n")
+#[derive(Debug, Clone, Copy)]
+pub enum DiagnosticsFormat {
+    Text { color: bool },
+    Json
+}
+
+pub fn display_errors(errors: Vec<Error>, strings: &mut StringMap, format: DiagnosticsFormat) -> String {
+    match format {
+        DiagnosticsFormat::Text { color } => errors.into_iter()
+            .map(|e| e.display(strings, color))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        DiagnosticsFormat::Json => diagnostics_to_json(&errors, strings).to_string()
+    }
 }
 
 pub fn do_compilation() -> Result<(), String> {
     let mut strings = StringMap::new();
     // parse cli args
     const CLI_ARG_MAIN: CliArg = CliArg::optional("m", "specifies the path of the main procedure", &["full-main-proc-path"]);
-    const CLI_ARG_TARGET: CliArg = CliArg::required("t", "specifies the target format", &["target-format ('c' / 'js')"]);
-    const CLI_ARG_OUTPUT: CliArg = CliArg::required("o", "specifies the output file", &["output-file"]);
+    const CLI_ARG_TARGET: CliArg = CliArg::required("t", "specifies the target format", &["target-format ('c' / 'js' / 'lua')"]);
+    const CLI_ARG_OUTPUT: CliArg = CliArg::required("o", "specifies the output file (or directory when splitting)", &["output-file"]);
     const CLI_ARG_DISABLE_COLOR: CliArg = CliArg::optional("c", "disables colored output", &[]);
+    const CLI_ARG_BINDINGS: CliArg = CliArg::optional("b", "generates '<output-file>.gem' and '<output-file>.c' for the given module from the given C headers", &["full-module-path"]);
+    const CLI_ARG_SPLIT: CliArg = CliArg::optional("s", "splits the C output into one file per module and shared headers, written into the output directory", &[]);
+    const CLI_ARG_CACHE: CliArg = CliArg::optional("k", "caches parsed and type checked modules in the given directory to speed up later compilations", &["cache-directory"]);
+    const CLI_ARG_SEARCH_PATHS: CliArg = CliArg::optional("I", "specifies directories in which used modules that were not given as files are searched for ('a::b' is loaded from '<dir>/a/b.gera')", &["directories"]);
+    const CLI_ARG_DIAGNOSTICS: CliArg = CliArg::optional("-diagnostics", "specifies how errors are reported, either as text or as a versioned JSON document", &["format ('text' / 'json')"]);
+    const CLI_ARG_EXPLAIN: CliArg = CliArg::optional("-explain", "prints a detailed explanation of the given error code, for example 'E0028'", &["error-code"]);
+    const CLI_ARG_HEADER: CliArg = CliArg::optional("-header", "also writes a C header declaring the program's types, procedures and closure call shims next to the output file", &[]);
+    const CLI_ARG_LIBRARY: CliArg = CliArg::optional("l", "compiles a C library exporting the given public procedures and writes its header next to the output file", &["full-proc-paths"]);
     let arg_list = CliArgList::new()
         .add(CLI_ARG_MAIN)
         .add(CLI_ARG_TARGET)
         .add(CLI_ARG_OUTPUT)
-        .add(CLI_ARG_DISABLE_COLOR);
-    let args = CliArgs::parse(&arg_list, &env::args().collect::<Vec<String>>()[1..]).map_err(|e| display_errors(vec![e], &mut strings, true))?;
+        .add(CLI_ARG_DISABLE_COLOR)
+        .add(CLI_ARG_BINDINGS)
+        .add(CLI_ARG_LIBRARY)
+        .add(CLI_ARG_HEADER)
+        .add(CLI_ARG_SPLIT)
+        .add(CLI_ARG_CACHE)
+        .add(CLI_ARG_SEARCH_PATHS)
+        .add(CLI_ARG_DIAGNOSTICS)
+        .add(CLI_ARG_EXPLAIN);
+    let env_args = env::args().collect::<Vec<String>>();
+    if env_args[1..].iter().any(|a| a == "--explain") {
+        let explain_arg_list = CliArgList::new()
+            .add(CLI_ARG_EXPLAIN);
+        let args = CliArgs::parse(&explain_arg_list, &env_args[1..]).map_err(|e| display_errors(vec![e], &mut strings, DiagnosticsFormat::Text { color: true }))?;
+        let code = args.values(CLI_ARG_EXPLAIN)
+            .expect("was given")
+            .last()
+            .expect("is required to have one value");
+        let explanation = explanations::explain(code).ok_or_else(|| display_errors(vec![Error::new([
+            ErrorSection::Error(ErrorType::InvalidErrorCode(code.clone())),
+            ErrorSection::Help(String::from("Error codes are shown in brackets after 'error', for example 'error[E0028]'"))
+        ].into())], &mut strings, DiagnosticsFormat::Text { color: true }))?;
+        print!("{}", explanation);
+        return Ok(());
+    }
+    let args = CliArgs::parse(&arg_list, &env_args[1..]).map_err(|e| display_errors(vec![e], &mut strings, DiagnosticsFormat::Text { color: true }))?;
     let target_str = args.values(CLI_ARG_TARGET)
         .expect("is required")
         .last()
@@ -66,16 +109,74 @@ pub fn do_compilation() -> Result<(), String> {
         .clone();
     let color = args.values(CLI_ARG_DISABLE_COLOR)
         .is_none();
+    let diagnostics = match args.values(CLI_ARG_DIAGNOSTICS).map(|vals| vals.last().expect("is required to have one value").as_str()) {
+        None | Some("text") => DiagnosticsFormat::Text { color },
+        Some("json") => DiagnosticsFormat::Json,
+        Some(other) => return Err(display_errors(vec![Error::new([
+            ErrorSection::Error(ErrorType::InvalidDiagnosticsFormat(other.into())),
+            ErrorSection::Help(String::from("Supported formats are 'text' and 'json'"))
+        ].into())], &mut strings, DiagnosticsFormat::Text { color }))
+    };
+    let cache = args.values(CLI_ARG_CACHE)
+        .map(|vals| ModuleCache::new(PathBuf::from(vals.last().expect("is required to have one value"))));
+    if let Some(module_path) = args.values(CLI_ARG_BINDINGS) {
+        let module_path = module_path.last().expect("is required to have one value");
+        if target_str != "c" {
+            return Err(display_errors(vec![Error::new([
+                ErrorSection::Error(ErrorType::InvalidCompileTarget(target_str)),
+                ErrorSection::Help(String::from("Bindings can only be generated for the 'c' target"))
+            ].into())], &mut strings, diagnostics));
+        }
+        let mut headers = Vec::new();
+        for file_path in args.free_values() {
+            headers.push((
+                strings.insert(file_path),
+                read_file(file_path, &mut strings).map_err(|e| display_errors(vec![e], &mut strings, diagnostics))?
+            ));
+        }
+        let (mappings, glue) = compiler::bindgen::generate_c_bindings(&headers, module_path, &mut strings).map_err(|e| display_errors(e, &mut strings, diagnostics))?;
+        write_file(&format!("{}.gem", output_file), mappings).map_err(|e| display_errors(vec![e], &mut strings, diagnostics))?;
+        write_file(&format!("{}.c", output_file), glue).map_err(|e| display_errors(vec![e], &mut strings, diagnostics))?;
+        return Ok(());
+    }
     let mut files = HashMap::new();
     for file_path in args.free_values() {
         files.insert(
             strings.insert(file_path),
-            read_file(file_path, &mut strings).map_err(|e| display_errors(vec![e], &mut strings, color))?
+            read_file(file_path, &mut strings).map_err(|e| display_errors(vec![e], &mut strings, diagnostics))?
         );
     }
-    let output = compiler::compile(&mut strings, files, &target_str, main_proc).map_err(|e| display_errors(e, &mut strings, color))?;
-    write_file(&output_file, output).map_err(|e| display_errors(vec![e], &mut strings, color))?;
-    return Ok(());
+    let mut session = Session::from_files(strings, files, &target_str);
+    session.set_cache(cache);
+    for search_path in args.values(CLI_ARG_SEARCH_PATHS).into_iter().flatten() {
+        session.add_search_path(search_path);
+    }
+    if let Some(exported_procs) = args.values(CLI_ARG_LIBRARY) {
+        let (output, header) = session.compile_library(exported_procs).map_err(|e| display_errors(e, session.strings_mut(), diagnostics))?;
+        let header_file = Path::new(&output_file).with_extension("h").to_string_lossy().to_string();
+        write_header(&header_file, header).map_err(|e| display_errors(vec![e], session.strings_mut(), diagnostics))?;
+        write_file(&output_file, output).map_err(|e| display_errors(vec![e], session.strings_mut(), diagnostics))?;
+        return Ok(());
+    }
+    if args.values(CLI_ARG_SPLIT).is_some() {
+        let output_files = session.compile_split(main_proc).map_err(|e| display_errors(e, session.strings_mut(), diagnostics))?;
+        fs::create_dir_all(&output_file).map_err(|e| display_errors(vec![Error::new([
+            ErrorSection::Error(ErrorType::FileSystemError(e.to_string())),
+            ErrorSection::Info(format!("While trying to create the directory '{}'", output_file))
+        ].into())], session.strings_mut(), diagnostics))?;
+        write_split_files(&output_file, output_files).map_err(|e| display_errors(vec![e], session.strings_mut(), diagnostics))?;
+        return Ok(());
+    }
+    if args.values(CLI_ARG_HEADER).is_some() {
+        let (output, header) = session.compile_with_header(main_proc).map_err(|e| display_errors(e, session.strings_mut(), diagnostics))?;
+        let header_file = Path::new(&output_file).with_extension("h").to_string_lossy().to_string();
+        write_header(&header_file, header).map_err(|e| display_errors(vec![e], session.strings_mut(), diagnostics))?;
+        write_file(&output_file, output).map_err(|e| display_errors(vec![e], session.strings_mut(), diagnostics))?;
+        return Ok(());
+    }
+    let output = session.compile(main_proc).map_err(|e| display_errors(e, session.strings_mut(), diagnostics))?;
+    write_file(&output_file, output).map_err(|e| display_errors(vec![e], session.strings_mut(), diagnostics))?;
+    Ok(())
 }
 
 pub fn read_file(
@@ -91,8 +192,48 @@ pub fn read_file(
 }
 
 pub fn write_file(path: &String, content: String) -> Result<(), Error> {
-    return fs::write(path, content).map_err(|error| Error::new([
+    fs::write(path, content).map_err(|error| Error::new([
         ErrorSection::Error(ErrorType::FileSystemError(error.to_string())),
         ErrorSection::Info(format!("While trying to write to '{}'", path))
     ].into()))
-}
\ No newline at end of file
+}
+
+pub fn write_header(path: &String, content: String) -> Result<(), Error> {
+    if let Ok(existing) = fs::read_to_string(path) {
+        if !existing.starts_with(GENERATED_HEADER_MARKER) {
+            return Err(Error::new([
+                ErrorSection::Error(ErrorType::OverwritesForeignFile(path.clone())),
+                ErrorSection::Help(String::from("Choose a different output file or move the existing file"))
+            ].into()));
+        }
+    }
+    write_file(path, content)
+}
+
+const SPLIT_MANIFEST_FILE: &str = ".gera-output";
+
+fn write_split_files(output_dir: &str, files: Vec<(String, String)>) -> Result<(), Error> {
+    let manifest_path = Path::new(output_dir).join(SPLIT_MANIFEST_FILE);
+    let previous_files = fs::read_to_string(&manifest_path).unwrap_or_default();
+    for stale_file in previous_files.lines() {
+        if stale_file.is_empty() || files.iter().any(|(file_name, _)| file_name == stale_file) { continue; }
+        let stale_path = Path::new(output_dir).join(stale_file);
+        if let Err(error) = fs::remove_file(&stale_path) {
+            if error.kind() == std::io::ErrorKind::NotFound { continue; }
+            return Err(Error::new([
+                ErrorSection::Error(ErrorType::FileSystemError(error.to_string())),
+                ErrorSection::Info(format!("While trying to remove '{}'", stale_path.display()))
+            ].into()));
+        }
+    }
+    let mut manifest = String::new();
+    for (file_name, content) in files {
+        manifest.push_str(&file_name);
+        manifest.push('\n');
+        let file_path = Path::new(output_dir).join(file_name).to_string_lossy().to_string();
+        if fs::read(&file_path).is_ok_and(|existing| existing == content.as_bytes()) { continue; }
+        write_file(&file_path, content)?;
+    }
+    if previous_files == manifest { return Ok(()); }
+    write_file(&manifest_path.to_string_lossy().to_string(), manifest)
+}